@@ -3,7 +3,7 @@ use quote::quote;
 use quote::ToTokens;
 use syn::{parse_macro_input, DeriveInput};
 
-#[proc_macro_derive(Crawler)]
+#[proc_macro_derive(Crawler, attributes(crawler))]
 pub fn derive_crawler(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let struct_name = &input.ident;
@@ -25,6 +25,12 @@ pub fn derive_crawler(input: TokenStream) -> TokenStream {
         let field_str = field_name.as_ref().unwrap().to_string();
         let field_type = &f.ty;
 
+        // 字段属性中的字符串预处理，在 FromStr 之前执行
+        let value = match parse_field_transforms(&f.attrs) {
+            Ok(value) => value,
+            Err(err) => return err.to_compile_error(),
+        };
+
         let conversion_logic = match analyze_field_type(field_type) {
             FieldType::Direct => {
                 // 对于直接类型，只有实现了FromStr的类型才支持缺失时使用默认值
@@ -33,14 +39,14 @@ pub fn derive_crawler(input: TokenStream) -> TokenStream {
                 if is_string_type {
                     quote! {
                         match map.get(#field_str).and_then(|v| v.first()) {
-                            Some(s) => s.clone(),
+                            Some(s) => (#value).to_string(),
                             None => String::new(),
                         }
                     }
                 } else {
                     quote! {
                         match map.get(#field_str).and_then(|v| v.first()) {
-                            Some(s) => <#field_type as std::str::FromStr>::from_str(s)
+                            Some(s) => <#field_type as std::str::FromStr>::from_str(#value)
                                 .map_err(|_| #crawler_path::CrawlerParseError::ConversionFailed(#field_str))?,
                             None => return Err(#crawler_path::CrawlerParseError::MissingField(#field_str)),
                         }
@@ -53,7 +59,7 @@ pub fn derive_crawler(input: TokenStream) -> TokenStream {
                 quote! {
                     map.get(#field_str)
                         .and_then(|v| v.first())
-                        .map(|s| <#inner_type as std::str::FromStr>::from_str(s))
+                        .map(|s| <#inner_type as std::str::FromStr>::from_str(#value))
                         .transpose()
                         .map_err(|_| #crawler_path::CrawlerParseError::ConversionFailed(#field_str))?
                 }
@@ -65,7 +71,7 @@ pub fn derive_crawler(input: TokenStream) -> TokenStream {
                     map.get(#field_str)
                         .map(|values| {
                             values.iter()
-                                .map(|s| <#inner_type as std::str::FromStr>::from_str(s))
+                                .map(|s| <#inner_type as std::str::FromStr>::from_str(#value))
                                 .collect::<Result<Vec<_>, _>>()
                         })
                         .unwrap_or(Ok(Vec::new()))
@@ -82,7 +88,7 @@ pub fn derive_crawler(input: TokenStream) -> TokenStream {
                                 Ok(None)
                             } else {
                                 values.iter()
-                                    .map(|s| <#inner_type as std::str::FromStr>::from_str(s))
+                                    .map(|s| <#inner_type as std::str::FromStr>::from_str(#value))
                                     .collect::<Result<Vec<_>, _>>()
                                     .map(Some)
                             }
@@ -112,6 +118,34 @@ pub fn derive_crawler(input: TokenStream) -> TokenStream {
     TokenStream::from(expanded)
 }
 
+/// 解析 `#[crawler(trim, lowercase, uppercase)]` 字段属性
+///
+/// 返回对 `s: &String` 按属性顺序处理后的 `&str` 表达式，没有属性时原样返回
+fn parse_field_transforms(attrs: &[syn::Attribute]) -> syn::Result<proc_macro2::TokenStream> {
+    let mut transforms = Vec::new();
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("crawler")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("trim") {
+                transforms.push(quote! { .trim().to_string() });
+            } else if meta.path.is_ident("lowercase") {
+                transforms.push(quote! { .to_lowercase() });
+            } else if meta.path.is_ident("uppercase") {
+                transforms.push(quote! { .to_uppercase() });
+            } else {
+                return Err(meta.error("unsupported crawler attribute, expected `trim`, `lowercase` or `uppercase`"));
+            }
+            Ok(())
+        })?;
+    }
+
+    if transforms.is_empty() {
+        Ok(quote! { s.as_str() })
+    } else {
+        Ok(quote! { s.to_string()#(#transforms)*.as_str() })
+    }
+}
+
 // Type analysis logic
 enum FieldType {
     Direct,       // T
@@ -4,14 +4,20 @@ use thiserror::Error;
 #[derive(Error, Debug)]
 #[allow(clippy::large_enum_variant)] // pest::error::Error is inherently large
 pub enum CrawlerErr {
-    #[error("No data found for node: {node}")]
-    NotFound { node: &'static str },
+    #[error("Resource not found (HTTP 404): {0}")]
+    NotFound(String),
+    #[error("Request failed with HTTP status {0}: {1}")]
+    HttpStatus(u16, String),
     #[error("IO error: {msg}")]
     IOError { msg: String },
     #[error("Other error: {0}")]
     OtherError(String),
     #[error("Template not found")]
     TempNotFound,
+    #[error("Template 'extends' must be a template name")]
+    TemplateExtendsInvalid,
+    #[error("Template inheritance cycle detected: {0}")]
+    TemplateExtendsCycle(String),
     #[error("Template internal information error, error cause: {0}")]
     YamlTempFormatError(#[from] serde_yaml::Error),
     #[error("Selector parse failure , {0}")]
@@ -26,12 +32,30 @@ pub enum CrawlerErr {
     DynNoValidData(String),
     #[error("Variable '${{{0}}}' does not support multiple results, currently got: {1}")]
     DynMultipleResults(String, String),
+    #[error("Variable '${{{0}[{1}]}}' index out of range, variable has {2} values")]
+    DynIndexOutOfRange(String, isize, usize),
+    #[error("Variable '${{{0}[*]}}' can only be used in transform commands")]
+    DynListNotAllowed(String),
+    #[error("Variable '${{{0}[*]}}' has {1} values but the current selection has {2} elements")]
+    DynLengthMismatch(String, usize, usize),
     #[error("parent({0}) Parent node overflow, current highest parent node: {1}")]
     ParentNodeOverflow(usize, usize),
     #[error("prev({0}) Previous sibling node overflow, current highest prev node: {1}")]
     PrevNodeOverflow(usize, usize),
     #[error("Node not found: {0}")]
     NodeNotFound(String),
+    #[error("Node '{node}' failed on {url}: {source}")]
+    NodeError {
+        node: String,
+        url: String,
+        source: Box<CrawlerErr>,
+    },
+    #[error("Request timed out: {0}")]
+    Timeout(String),
+    #[error("Response from {url} exceeds the maximum size of {limit} bytes")]
+    ResponseTooLarge { url: String, limit: usize },
+    #[error("Response from {url} has unexpected content type '{content_type}'")]
+    UnexpectedContentType { url: String, content_type: String },
     #[error("Reqwest error: {0}")]
     ReqwestError(#[from] reqwest::Error),
     #[error("Node '{0}' got incorrect number of values: {1}")]
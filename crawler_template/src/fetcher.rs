@@ -0,0 +1,248 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    io::Read,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use futures_util::future::BoxFuture;
+use reqwest::header::{HeaderMap, CONTENT_LENGTH, CONTENT_TYPE};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::CrawlerErr;
+
+/// 模板未设置时单个响应的最大字节数
+pub const DEFAULT_MAX_RESPONSE_BYTES: usize = 10 * 1024 * 1024;
+
+/// 获取页面内容的方式
+///
+/// 模板默认使用 [`ReqwestFetcher`] 发起 HTTP 请求，实现该 trait 即可替换传输方式，
+/// 或包装已有的 `Fetcher` 添加代理、缓存、重试等功能
+pub trait Fetcher: Debug + Send + Sync {
+    /// 获取 `url` 对应的页面内容，404 应返回 `CrawlerErr::NotFound`
+    fn fetch<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<String, CrawlerErr>>;
+}
+
+/// 基于 reqwest 的默认实现
+///
+/// 只接受文本类型的响应，响应体边读取边检查大小，超过上限时中止而不是整个读入内存
+#[derive(Debug, Clone)]
+pub struct ReqwestFetcher {
+    client: reqwest::Client,
+    max_response_bytes: usize,
+}
+
+impl ReqwestFetcher {
+    /// 创建带请求超时的 HTTP 客户端
+    pub fn new(timeout: Duration) -> Result<Self, CrawlerErr> {
+        Ok(Self::from_client(
+            reqwest::Client::builder().timeout(timeout).build()?,
+        ))
+    }
+
+    /// 使用已配置好的客户端，如设置了代理或默认请求头的客户端
+    pub fn from_client(client: reqwest::Client) -> Self {
+        Self {
+            client,
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+        }
+    }
+
+    /// 设置单个响应的最大字节数
+    pub fn with_max_response_bytes(mut self, max_response_bytes: usize) -> Self {
+        self.max_response_bytes = max_response_bytes;
+        self
+    }
+}
+
+impl Fetcher for ReqwestFetcher {
+    fn fetch<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<String, CrawlerErr>> {
+        Box::pin(async move {
+            let mut response = self
+                .client
+                .get(url)
+                .send()
+                .await
+                .map_err(|e| request_error(url, e))?;
+            check_status(url, response.status())?;
+            check_response_headers(url, response.headers(), self.max_response_bytes)?;
+
+            let mut body = Vec::new();
+            while let Some(chunk) = response.chunk().await.map_err(|e| request_error(url, e))? {
+                if body.len() + chunk.len() > self.max_response_bytes {
+                    return Err(too_large(url, self.max_response_bytes));
+                }
+                body.extend_from_slice(&chunk);
+            }
+            Ok(decode_body(&body, response.headers()))
+        })
+    }
+}
+
+/// 按主机（含端口）限制同时进行的请求数量
+///
+/// 克隆后共享同一组许可，在多个模板之间共享时限制的是对同一站点的总请求数
+#[derive(Debug, Clone)]
+pub struct HostLimiter {
+    limit: usize,
+    hosts: Arc<Mutex<HashMap<String, Arc<Semaphore>>>>,
+}
+
+impl HostLimiter {
+    /// 每个主机最多同时进行 `limit` 个请求，最小为 1
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit: limit.max(1),
+            hosts: Arc::default(),
+        }
+    }
+
+    /// 等待 `url` 所在主机的请求许可，无法解析主机的地址不受限制
+    pub async fn acquire(&self, url: &str) -> Result<Option<OwnedSemaphorePermit>, CrawlerErr> {
+        let Some(host) = host_key(url) else {
+            return Ok(None);
+        };
+        let semaphore = self
+            .hosts
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(host)
+            .or_insert_with(|| Arc::new(Semaphore::new(self.limit)))
+            .clone();
+        semaphore
+            .acquire_owned()
+            .await
+            .map(Some)
+            .map_err(|e| CrawlerErr::OtherError(e.to_string()))
+    }
+}
+
+fn host_key(url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let host = url.host_str()?.to_lowercase();
+    Some(match url.port_or_known_default() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host,
+    })
+}
+
+/// 预先提供的页面内容，键为页面地址，缺少的地址按 404 处理
+#[derive(Debug)]
+pub(crate) struct HtmlFetcher<'h>(pub(crate) &'h HashMap<String, String>);
+
+impl Fetcher for HtmlFetcher<'_> {
+    fn fetch<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<String, CrawlerErr>> {
+        let body = self
+            .0
+            .get(url)
+            .cloned()
+            .ok_or_else(|| CrawlerErr::NotFound(url.to_string()));
+        Box::pin(async move { body })
+    }
+}
+
+/// 将请求错误转换为爬虫错误，超时单独区分
+pub(crate) fn request_error(url: &str, err: reqwest::Error) -> CrawlerErr {
+    if err.is_timeout() {
+        CrawlerErr::Timeout(url.to_string())
+    } else {
+        CrawlerErr::ReqwestError(err)
+    }
+}
+
+/// 同步读取响应体，超过 `max_response_bytes` 时中止
+pub(crate) fn read_blocking_body(
+    url: &str,
+    response: reqwest::blocking::Response,
+    max_response_bytes: usize,
+) -> Result<String, CrawlerErr> {
+    check_response_headers(url, response.headers(), max_response_bytes)?;
+    let headers = response.headers().clone();
+
+    let mut body = Vec::new();
+    response
+        .take(max_response_bytes as u64 + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| CrawlerErr::IOError {
+            msg: format!("failed to read response from {}: {}", url, e),
+        })?;
+    if body.len() > max_response_bytes {
+        return Err(too_large(url, max_response_bytes));
+    }
+    Ok(decode_body(&body, &headers))
+}
+
+/// 读取响应体前检查内容类型与声明的长度
+///
+/// 没有 Content-Type 时按文本处理；图片、视频、压缩包等二进制内容直接报错
+fn check_response_headers(
+    url: &str,
+    headers: &HeaderMap,
+    max_response_bytes: usize,
+) -> Result<(), CrawlerErr> {
+    if let Some(content_type) = headers.get(CONTENT_TYPE).and_then(|value| value.to_str().ok()) {
+        let mime = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        let textual = mime.is_empty()
+            || mime.starts_with("text/")
+            || ["html", "xml", "json", "javascript"]
+                .iter()
+                .any(|kind| mime.contains(kind));
+        if !textual {
+            return Err(CrawlerErr::UnexpectedContentType {
+                url: url.to_string(),
+                content_type: content_type.to_string(),
+            });
+        }
+    }
+
+    let declared_length = headers
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+    if declared_length.is_some_and(|length| length > max_response_bytes as u64) {
+        return Err(too_large(url, max_response_bytes));
+    }
+    Ok(())
+}
+
+/// 按 Content-Type 中的 charset 解码响应体，未声明或无法识别时按 UTF-8 解码
+fn decode_body(body: &[u8], headers: &HeaderMap) -> String {
+    let encoding = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|content_type| {
+            content_type.split(';').find_map(|param| {
+                let (key, value) = param.split_once('=')?;
+                key.trim()
+                    .eq_ignore_ascii_case("charset")
+                    .then(|| value.trim().trim_matches('"'))
+            })
+        })
+        .and_then(|label| encoding_rs::Encoding::for_label(label.as_bytes()))
+        .unwrap_or(encoding_rs::UTF_8);
+    encoding.decode(body).0.into_owned()
+}
+
+fn too_large(url: &str, limit: usize) -> CrawlerErr {
+    CrawlerErr::ResponseTooLarge {
+        url: url.to_string(),
+        limit,
+    }
+}
+
+/// 检查响应状态码，404 表示当前站点没有该资源，其他失败状态单独报告
+pub(crate) fn check_status(url: &str, status: reqwest::StatusCode) -> Result<(), CrawlerErr> {
+    if status == reqwest::StatusCode::NOT_FOUND {
+        Err(CrawlerErr::NotFound(url.to_string()))
+    } else if !status.is_success() {
+        Err(CrawlerErr::HttpStatus(status.as_u16(), url.to_string()))
+    } else {
+        Ok(())
+    }
+}
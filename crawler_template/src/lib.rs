@@ -4,20 +4,41 @@
 use std::{
     collections::{HashMap, HashSet},
     marker::PhantomData,
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
+use crate::fetcher::{check_status, read_blocking_body, request_error, HtmlFetcher};
 use crate::script::Rule;
+use futures_util::{stream, StreamExt};
 use scraper::ElementRef;
 use script::CrawlerScript;
 use serde::{Deserialize, Deserializer};
+use tokio::sync::Semaphore;
 
 pub use crawler_template_macros::Crawler;
 pub use error::{CrawlerErr, CrawlerParseError};
+pub use fetcher::{Fetcher, HostLimiter, ReqwestFetcher, DEFAULT_MAX_RESPONSE_BYTES};
+pub use lint::{LintKind, LintWarning, RUNTIME_PARAMETERS};
 
 mod error;
+mod fetcher;
+mod lint;
 pub mod script;
 mod test;
 
+/// 模板与全局配置均未设置超时时的默认请求超时（秒）
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// 同一工作流中并发请求的默认地址数量
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// 配置了 `next_page` 但未配置 `max_pages` 时的默认最大翻页数
+pub const DEFAULT_MAX_PAGES: usize = 10;
+
+/// 节点 `source` 中表示入口页面的名称，其他名称为请求节点的名称
+pub const ENTRYPOINT_SOURCE: &str = "entrypoint";
+
 #[derive(Debug, Clone)]
 pub struct Template<T>
 where
@@ -27,6 +48,54 @@ where
     resource_type: PhantomData<T>,
     parameters: RuntimeVariable,
     workflows: Vec<WorkflowRoot>,
+    timeout: Duration,
+    /// 超时时间是否已由模板 `timeout` 或 `with_timeout` 显式指定，指定后不被默认值覆盖
+    timeout_explicit: bool,
+    /// 单个响应的最大字节数
+    max_response_bytes: usize,
+    http: ReqwestFetcher,
+    /// 自定义的页面获取方式，未设置时使用 `http`
+    fetcher: Option<Arc<dyn Fetcher>>,
+    /// 限制同时进行的请求数量，可在多个模板之间共享
+    request_limit: Option<Arc<Semaphore>>,
+    /// 限制对同一主机同时进行的请求数量，可在多个模板之间共享
+    host_limit: Option<HostLimiter>,
+    best_effort: bool,
+    concurrency: usize,
+    /// 被节点 `source` 引用的工作流，只有这些工作流的页面在爬取期间保留
+    retained_sources: HashSet<String>,
+}
+
+/// 爬取过程中获取到的页面
+#[derive(Debug, Clone, PartialEq)]
+pub struct FetchedPage {
+    pub url: String,
+    pub body: String,
+}
+
+/// 爬取失败时已收集的数据，用于排查模板失效的原因
+#[derive(Debug, Clone, Default)]
+pub struct CrawlSnapshot {
+    /// 按请求顺序排列的页面内容
+    pub pages: Vec<FetchedPage>,
+    /// 失败前已写入的运行时变量
+    pub variables: HashMap<String, Vec<String>>,
+}
+
+/// 带有爬取快照的失败结果
+#[derive(Debug)]
+pub struct CrawlFailure {
+    pub error: CrawlerErr,
+    pub snapshot: CrawlSnapshot,
+}
+
+/// 尽力模式下记录的节点失败信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeWarning {
+    /// 失败的节点名称或请求地址
+    pub node: String,
+    /// 失败原因
+    pub message: String,
 }
 
 #[derive(Debug, Clone)]
@@ -34,14 +103,69 @@ struct CrawlerNode {
     _script_raw: String,
     request: bool,
     required: bool, // 新增：是否为必需字段
+    group: bool,    // 每个元素生成一条由子节点值组成的记录
+    parallel: bool, // 分组子节点分别写入同名变量，各变量按元素一一对应
     children: Option<HashMap<String, CrawlerNode>>,
     script: CrawlerScript,
+    next_page: Option<Pagination>,
+    merge_mode: MergeMode,
+    source: Option<String>,
+}
+
+/// 节点的值写入已存在的运行时变量时的合并方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum MergeMode {
+    /// 追加到已有的值之后
+    #[default]
+    Append,
+    /// 覆盖已有的值
+    Replace,
+    /// 已有非空的值时保留已有的值
+    FirstWins,
+}
+
+impl MergeMode {
+    fn write(self, runtime_variable: &mut RuntimeVariable, name: &str, values: Vec<String>) {
+        match runtime_variable.get_mut(name) {
+            Some(existing) => match self {
+                MergeMode::Append => existing.extend(values),
+                MergeMode::Replace => *existing = values,
+                MergeMode::FirstWins => {
+                    if existing.iter().all(|value| value.trim().is_empty()) {
+                        *existing = values;
+                    }
+                }
+            },
+            None => {
+                runtime_variable.insert(name.to_string(), values);
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 struct WorkflowRoot {
     url_key: String,
     node: Vec<WorkflowNode>,
+    next_page: Option<Pagination>,
+}
+
+/// 爬取期间保留的先前工作流页面，键为工作流的 `source` 名称
+#[derive(Debug)]
+struct RetainedPages<'a> {
+    keys: &'a HashSet<String>,
+    pages: HashMap<String, Vec<String>>,
+}
+
+/// `source` 节点可用的先前页面根元素，键为工作流的 `source` 名称
+type SourceElements<'a> = HashMap<&'a str, Vec<ElementRef<'a>>>;
+
+/// 工作流的翻页配置：处理完一页后用脚本提取下一页地址，值追加到同一组运行时变量
+#[derive(Debug, Clone)]
+struct Pagination {
+    script: CrawlerScript,
+    max_pages: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -49,11 +173,27 @@ struct WorkflowNode {
     name: String,
     script: CrawlerScript,
     required: bool,
+    group: bool,
+    parallel: bool,
+    merge_mode: MergeMode,
+    /// 脚本作用于该工作流保留的页面，而不是当前页面
+    source: Option<String>,
     children: Vec<WorkflowNode>,
 }
 
 type RuntimeVariable = HashMap<String, Vec<String>>;
 
+/// `group` 节点为每个元素生成的记录，键为子节点名称
+///
+/// 记录以 JSON 对象字符串的形式存入运行时变量，字段类型可在 `FromStr` 中
+/// 通过 [`parse_record`] 还原
+pub type Record = HashMap<String, String>;
+
+/// 将运行时变量中的值解析为 `group` 节点生成的记录
+pub fn parse_record(value: &str) -> Option<Record> {
+    serde_json::from_str(value).ok()
+}
+
 pub trait CrawlerData: Sized {
     type Error;
     fn parse(map: &HashMap<String, Vec<String>>) -> Result<Self, Self::Error>;
@@ -67,6 +207,93 @@ where
         serde_yaml::from_str(yaml)
     }
 
+    /// 解析支持 `extends` 继承的模板
+    ///
+    /// `loader` 根据 `extends` 中的模板名称返回被继承模板的 YAML 内容。子模板继承
+    /// entrypoint、env 与 nodes，同名键以子模板为准，其中映射类型（env、nodes 及
+    /// 节点的 children）逐层合并
+    pub fn from_yaml_with_loader<F>(yaml: &str, loader: F) -> Result<Self, CrawlerErr>
+    where
+        F: Fn(&str) -> std::io::Result<String>,
+    {
+        let value = resolve_extends(serde_yaml::from_str(yaml)?, &loader, &mut Vec::new())?;
+        Ok(serde_yaml::from_value(value)?)
+    }
+
+    /// 覆盖模板中配置的请求超时
+    pub fn with_timeout(mut self, timeout: Duration) -> Result<Self, CrawlerErr> {
+        self.http = ReqwestFetcher::new(timeout)?.with_max_response_bytes(self.max_response_bytes);
+        self.timeout = timeout;
+        self.timeout_explicit = true;
+        Ok(self)
+    }
+
+    /// 设置默认的单个请求超时时间，已显式指定超时（如模板中的 `timeout`）时保持原设置
+    pub fn with_default_timeout(self, timeout: Duration) -> Result<Self, CrawlerErr> {
+        if self.timeout_explicit {
+            Ok(self)
+        } else {
+            self.with_timeout(timeout)
+        }
+    }
+
+    /// 获取单个请求的超时时间
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// 覆盖模板中配置的单个响应最大字节数，超过时爬取失败并返回 `CrawlerErr::ResponseTooLarge`
+    pub fn with_max_response_bytes(mut self, max_response_bytes: usize) -> Self {
+        self.http = self.http.with_max_response_bytes(max_response_bytes);
+        self.max_response_bytes = max_response_bytes;
+        self
+    }
+
+    /// 启用尽力模式：取值节点或后续请求失败时记录警告并继续，而不是中止整个爬取
+    ///
+    /// 元素节点（如匹配影片的 `required` 节点）失败仍会中止，以免把不存在的影片当作部分结果
+    pub fn with_best_effort(mut self, best_effort: bool) -> Self {
+        self.best_effort = best_effort;
+        self
+    }
+
+    /// 设置同一工作流中并发请求的地址数量（如搜索结果中的多个详情页），最小为 1
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// 使用自定义的 `Fetcher` 获取页面，替代默认的 HTTP 请求
+    ///
+    /// 超时等行为由 `fetcher` 自行处理，同步爬取仍使用默认的 HTTP 客户端
+    pub fn with_fetcher(mut self, fetcher: impl Fetcher + 'static) -> Self {
+        self.fetcher = Some(Arc::new(fetcher));
+        self
+    }
+
+    /// 限制同时进行的请求数量，所有工作流和详情页的请求都需先获取许可
+    ///
+    /// 多个模板共享同一个 `Semaphore` 时限制对所有模板生效；同步爬取不受限制
+    pub fn with_request_limit(mut self, limit: Arc<Semaphore>) -> Self {
+        self.request_limit = Some(limit);
+        self
+    }
+
+    /// 限制对同一主机同时进行的请求数量，与 `with_request_limit` 同时使用时先获取主机的许可
+    ///
+    /// 多个模板共享同一个 `HostLimiter`（克隆）时限制对所有模板生效；同步爬取不受限制
+    pub fn with_host_limit(mut self, limit: HostLimiter) -> Self {
+        self.host_limit = Some(limit);
+        self
+    }
+
+    /// 覆盖模板 `env` 中的参数，如镜像站的 `base_url`
+    pub fn with_parameter(mut self, key: &str, value: &str) -> Self {
+        self.parameters
+            .insert(key.to_string(), vec![value.to_string()]);
+        self
+    }
+
     fn get_start_parameters(&self) -> RuntimeVariable {
         self.parameters
             .iter()
@@ -78,15 +305,115 @@ where
     where
         CrawlerErr: From<<T as CrawlerData>::Error>,
     {
+        self.crawler_with_warnings(parameters)
+            .await
+            .map(|(value, _)| value)
+    }
+
+    /// 爬取数据并返回尽力模式下记录的节点失败信息，未启用尽力模式时警告列表始终为空
+    pub async fn crawler_with_warnings(
+        &self,
+        parameters: &HashMap<&str, String>,
+    ) -> Result<(T, Vec<NodeWarning>), CrawlerErr>
+    where
+        CrawlerErr: From<<T as CrawlerData>::Error>,
+    {
+        self.crawl_from(parameters, self.fetcher(), false)
+            .await
+            .map_err(|failure| failure.error)
+    }
+
+    /// 爬取数据，失败时一并返回已获取的页面与运行时变量
+    ///
+    /// 爬取期间会保留所有页面的内容，只应在需要保存失败现场时使用
+    pub async fn crawler_with_snapshot(
+        &self,
+        parameters: &HashMap<&str, String>,
+    ) -> Result<(T, Vec<NodeWarning>), CrawlFailure>
+    where
+        CrawlerErr: From<<T as CrawlerData>::Error>,
+    {
+        self.crawl_from(parameters, self.fetcher(), true).await
+    }
+
+    fn fetcher(&self) -> &dyn Fetcher {
+        match &self.fetcher {
+            Some(fetcher) => fetcher.as_ref(),
+            None => &self.http,
+        }
+    }
+
+    /// 使用预先获取的页面内容爬取数据，不发起网络请求，适合用保存的 HTML 离线测试模板
+    ///
+    /// `html_by_url` 的键为完整的页面地址，缺少的地址按 404 处理
+    pub async fn crawler_with_html(
+        &self,
+        parameters: &HashMap<&str, String>,
+        html_by_url: &HashMap<String, String>,
+    ) -> Result<T, CrawlerErr>
+    where
+        CrawlerErr: From<<T as CrawlerData>::Error>,
+    {
+        self.crawl_from(parameters, &HtmlFetcher(html_by_url), false)
+            .await
+            .map(|(value, _)| value)
+            .map_err(|failure| failure.error)
+    }
+
+    async fn crawl_from(
+        &self,
+        parameters: &HashMap<&str, String>,
+        fetcher: &dyn Fetcher,
+        collect_pages: bool,
+    ) -> Result<(T, Vec<NodeWarning>), CrawlFailure>
+    where
+        CrawlerErr: From<<T as CrawlerData>::Error>,
+    {
+        let mut warnings = Vec::new();
+        let mut pages = Vec::new();
         let mut runtime_variable = self.get_start_parameters();
 
+        match self
+            .run_workflows(
+                parameters,
+                fetcher,
+                &mut runtime_variable,
+                collect_pages.then_some(&mut pages),
+                &mut warnings,
+            )
+            .await
+        {
+            Ok(value) => Ok((value, warnings)),
+            Err(error) => Err(CrawlFailure {
+                error,
+                snapshot: CrawlSnapshot {
+                    pages,
+                    variables: runtime_variable,
+                },
+            }),
+        }
+    }
+
+    async fn run_workflows(
+        &self,
+        parameters: &HashMap<&str, String>,
+        fetcher: &dyn Fetcher,
+        runtime_variable: &mut RuntimeVariable,
+        mut pages: Option<&mut Vec<FetchedPage>>,
+        warnings: &mut Vec<NodeWarning>,
+    ) -> Result<T, CrawlerErr>
+    where
+        CrawlerErr: From<<T as CrawlerData>::Error>,
+    {
+
         for (key, value) in parameters.iter() {
             runtime_variable.insert(key.to_string(), vec![value.clone()]);
         }
+        let mut retained = RetainedPages::new(&self.retained_sources);
 
         for (index, workflow) in self.workflows.iter().enumerate() {
             let urls = if index == 0 {
-                vec![self.build_entrypoint_url(&runtime_variable)?]
+                vec![self.build_entrypoint_url(runtime_variable)?]
             } else {
                 runtime_variable
                     .get(&workflow.url_key)
@@ -100,25 +427,126 @@ where
                 break;
             }
 
-            for url in urls {
-                workflow.crawler(&url, &mut runtime_variable).await?;
+            // 多个地址并发请求，响应按地址顺序依次处理，保证运行时变量的写入不会交错
+            let mut responses = stream::iter(urls.into_iter().enumerate())
+                .map(|(order, url)| async move {
+                    let body = self.fetch(fetcher, &url).await;
+                    (order, url, body)
+                })
+                .buffer_unordered(self.concurrency)
+                .collect::<Vec<_>>()
+                .await;
+            responses.sort_by_key(|(order, _, _)| *order);
+
+            for (_, url, body) in responses {
+                let mut visited = HashSet::new();
+                let mut page = Some((url, body));
+
+                while let Some((url, body)) = page.take() {
+                    visited.insert(url.clone());
+                    let node_warnings = self.best_effort.then_some(&mut *warnings);
+                    let result = body.and_then(|body| {
+                        let next_url = workflow.process_page(
+                            &url,
+                            &body,
+                            runtime_variable,
+                            node_warnings,
+                            &retained,
+                        );
+                        retained.keep(workflow.source_key(), &body);
+                        // 只有需要保存失败现场时才保留全部页面
+                        if let Some(pages) = pages.as_deref_mut() {
+                            pages.push(FetchedPage {
+                                url: url.clone(),
+                                body,
+                            });
+                        }
+                        next_url
+                    });
+
+                    // 入口的第一页请求失败时没有任何可用数据，始终中止
+                    match result {
+                        Ok(next_url) => {
+                            // 翻页依赖当前页面的处理结果，只能依次请求
+                            if let Some(next_url) = workflow.follow_next_page(next_url, &visited) {
+                                let body = self.fetch(fetcher, &next_url).await;
+                                page = Some((next_url, body));
+                            }
+                        }
+                        Err(e) if self.best_effort && (index > 0 || visited.len() > 1) => {
+                            log::warn!("尽力模式: 请求 {} 失败，继续使用已获取的数据: {}", url, e);
+                            warnings.push(NodeWarning {
+                                node: url,
+                                message: e.to_string(),
+                            });
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
             }
         }
 
-        let value = T::parse(&runtime_variable)?;
+        let value = T::parse(runtime_variable)?;
 
         Ok(value)
     }
 
+    /// 获取请求许可后请求页面，先等待主机的许可，避免占用全局许可等待同一站点
+    async fn fetch(&self, fetcher: &dyn Fetcher, url: &str) -> Result<String, CrawlerErr> {
+        let _host_permit = match &self.host_limit {
+            Some(limit) => limit.acquire(url).await?,
+            None => None,
+        };
+        let _permit = match &self.request_limit {
+            Some(limit) => Some(
+                limit
+                    .acquire()
+                    .await
+                    .map_err(|e| CrawlerErr::OtherError(e.to_string()))?,
+            ),
+            None => None,
+        };
+        fetcher.fetch(url).await
+    }
+
     pub fn crawler_block(&self, parameters: &HashMap<&str, String>) -> Result<T, CrawlerErr>
     where
         CrawlerErr: From<<T as CrawlerData>::Error>,
     {
+        self.crawl_blocking_until(parameters, None)
+    }
+
+    /// 同步爬取数据，整个爬取超过 `deadline` 后返回 `CrawlerErr::Timeout`
+    ///
+    /// 每个请求的超时时间不超过剩余时间，因此慢速站点也不会让调用方等待超过期限太久
+    pub fn crawler_block_with_deadline(
+        &self,
+        parameters: &HashMap<&str, String>,
+        deadline: Duration,
+    ) -> Result<T, CrawlerErr>
+    where
+        CrawlerErr: From<<T as CrawlerData>::Error>,
+    {
+        self.crawl_blocking_until(parameters, Some(Instant::now() + deadline))
+    }
+
+    fn crawl_blocking_until(
+        &self,
+        parameters: &HashMap<&str, String>,
+        deadline: Option<Instant>,
+    ) -> Result<T, CrawlerErr>
+    where
+        CrawlerErr: From<<T as CrawlerData>::Error>,
+    {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(self.timeout)
+            .build()?;
         let mut runtime_variable = self.get_start_parameters();
 
         for (key, value) in parameters.iter() {
             runtime_variable.insert(key.to_string(), vec![value.clone()]);
         }
+        let mut retained = RetainedPages::new(&self.retained_sources);
 
         for (index, workflow) in self.workflows.iter().enumerate() {
             let urls = if index == 0 {
@@ -132,9 +560,30 @@ where
                     .collect::<Vec<String>>()
             };
             for url in urls {
-                workflow
-                    .crawler_blocking(&url, &mut runtime_variable)
-                    .unwrap();
+                let mut visited = HashSet::new();
+                let mut page_url = Some(url);
+
+                while let Some(url) = page_url.take() {
+                    let timeout = match deadline {
+                        Some(deadline) => deadline
+                            .checked_duration_since(Instant::now())
+                            .filter(|remaining| !remaining.is_zero())
+                            .ok_or_else(|| CrawlerErr::Timeout(url.clone()))?
+                            .min(self.timeout),
+                        None => self.timeout,
+                    };
+
+                    visited.insert(url.clone());
+                    let next_url = workflow.crawler_blocking(
+                        &client,
+                        &url,
+                        timeout,
+                        self.max_response_bytes,
+                        &mut runtime_variable,
+                        &mut retained,
+                    )?;
+                    page_url = workflow.follow_next_page(next_url, &visited);
+                }
             }
         }
 
@@ -162,48 +611,262 @@ where
     }
 }
 
+/// 递归展开模板的 `extends`，`chain` 记录当前继承链用于检测循环继承
+fn resolve_extends<F>(
+    mut value: serde_yaml::Value,
+    loader: &F,
+    chain: &mut Vec<String>,
+) -> Result<serde_yaml::Value, CrawlerErr>
+where
+    F: Fn(&str) -> std::io::Result<String>,
+{
+    let extends = match value
+        .as_mapping_mut()
+        .and_then(|mapping| mapping.remove("extends"))
+    {
+        Some(serde_yaml::Value::String(extends)) => extends,
+        Some(_) => return Err(CrawlerErr::TemplateExtendsInvalid),
+        None => return Ok(value),
+    };
+
+    if chain.contains(&extends) {
+        chain.push(extends);
+        return Err(CrawlerErr::TemplateExtendsCycle(chain.join(" -> ")));
+    }
+
+    chain.push(extends.clone());
+    let base_yaml = loader(&extends).map_err(|e| CrawlerErr::IOError {
+        msg: format!("failed to load extended template '{}': {}", extends, e),
+    })?;
+    let base = resolve_extends(serde_yaml::from_str(&base_yaml)?, loader, chain)?;
+    chain.pop();
+
+    Ok(merge_yaml(base, value))
+}
+
+/// 按节点的 `source` 调整工作流顺序，被引用的工作流先于引用它的工作流执行
+///
+/// 入口工作流始终最先执行，其余工作流按名称排序后依次取出依赖已执行的工作流
+fn order_workflows(mut workflows: Vec<WorkflowRoot>) -> Result<Vec<WorkflowRoot>, String> {
+    let mut pending = workflows.split_off(1);
+    pending.sort_by(|a, b| a.url_key.cmp(&b.url_key));
+
+    let known: HashSet<&str> = std::iter::once(ENTRYPOINT_SOURCE)
+        .chain(pending.iter().map(|workflow| workflow.url_key.as_str()))
+        .collect();
+    for workflow in workflows.iter().chain(&pending) {
+        for (node, source) in workflow.sources() {
+            if !known.contains(source) {
+                return Err(format!(
+                    "node '{}': source '{}' is neither a request node nor '{}'",
+                    node, source, ENTRYPOINT_SOURCE
+                ));
+            }
+            if workflow.url_key.is_empty() || source == workflow.source_key() {
+                return Err(format!(
+                    "node '{}': source '{}' must refer to a page fetched before the node's own page",
+                    node, source
+                ));
+            }
+        }
+    }
+
+    while !pending.is_empty() {
+        let ready = pending.iter().position(|workflow| {
+            workflow.sources().iter().all(|(_, source)| {
+                workflows.iter().any(|done| done.source_key() == *source)
+            })
+        });
+        match ready {
+            Some(index) => workflows.push(pending.remove(index)),
+            None => {
+                let names: Vec<&str> = pending.iter().map(|workflow| workflow.url_key.as_str()).collect();
+                return Err(format!(
+                    "request nodes reference each other's pages through 'source' in a cycle: {}",
+                    names.join(", ")
+                ));
+            }
+        }
+    }
+
+    Ok(workflows)
+}
+
+/// 深度合并两个 YAML 值，映射逐键合并，其他类型以 `overlay` 覆盖 `base`
+fn merge_yaml(base: serde_yaml::Value, overlay: serde_yaml::Value) -> serde_yaml::Value {
+    match (base, overlay) {
+        (serde_yaml::Value::Mapping(mut base), serde_yaml::Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                let merged = match base.remove(&key) {
+                    Some(base_value) => merge_yaml(base_value, value),
+                    None => value,
+                };
+                base.insert(key, merged);
+            }
+            serde_yaml::Value::Mapping(base)
+        }
+        (_, overlay) => overlay,
+    }
+}
+
+impl RetainedPages<'_> {
+    fn new(keys: &HashSet<String>) -> RetainedPages<'_> {
+        RetainedPages {
+            keys,
+            pages: HashMap::new(),
+        }
+    }
+
+    /// 保存工作流的页面，未被任何节点引用的工作流不保存
+    fn keep(&mut self, key: &str, body: &str) {
+        if self.keys.contains(key) {
+            self.pages.entry(key.to_string()).or_default().push(body.to_string());
+        }
+    }
+
+    fn get(&self, key: &str) -> &[String] {
+        self.pages.get(key).map_or(&[], Vec::as_slice)
+    }
+}
+
 impl WorkflowRoot {
-    async fn crawler<'a>(
-        &'a self,
-        url: &str,
-        runtime_variable: &'a mut RuntimeVariable,
-    ) -> Result<(), CrawlerErr> {
-        let root_html = {
-            let response = reqwest::get(url).await?;
-            let body = response.text().await?;
-            scraper::Html::parse_document(&body)
-        };
+    /// 节点 `source` 引用该工作流时使用的名称，入口工作流为 [`ENTRYPOINT_SOURCE`]
+    fn source_key(&self) -> &str {
+        if self.url_key.is_empty() {
+            ENTRYPOINT_SOURCE
+        } else {
+            &self.url_key
+        }
+    }
 
+    /// 工作流中的节点引用的先前工作流，不含重复
+    fn sources(&self) -> Vec<(&str, &str)> {
+        fn collect<'a>(nodes: &'a [WorkflowNode], sources: &mut Vec<(&'a str, &'a str)>) {
+            for node in nodes {
+                if let Some(source) = &node.source {
+                    sources.push((node.name.as_str(), source.as_str()));
+                }
+                collect(&node.children, sources);
+            }
+        }
+
+        let mut sources = Vec::new();
+        collect(&self.node, &mut sources);
+        sources
+    }
+
+    /// 处理一个页面，返回下一页地址
+    fn process_page(
+        &self,
+        url: &str,
+        body: &str,
+        runtime_variable: &mut RuntimeVariable,
+        mut warnings: Option<&mut Vec<NodeWarning>>,
+        retained: &RetainedPages<'_>,
+    ) -> Result<Option<String>, CrawlerErr> {
+        let root_html = scraper::Html::parse_document(body);
         let root_element_refs = vec![root_html.root_element()];
 
+        // 保留的是页面内容，解析后的文档只在处理当前页面期间存在
+        let mut keys: Vec<&str> = self.sources().into_iter().map(|(_, source)| source).collect();
+        keys.sort_unstable();
+        keys.dedup();
+        let documents: Vec<(&str, scraper::Html)> = keys
+            .into_iter()
+            .flat_map(|key| {
+                retained
+                    .get(key)
+                    .iter()
+                    .map(move |body| (key, scraper::Html::parse_document(body)))
+            })
+            .collect();
+        let mut sources = SourceElements::new();
+        for (key, document) in &documents {
+            sources.entry(*key).or_default().push(document.root_element());
+        }
+
         for node in &self.node {
-            node.process(root_element_refs.clone(), runtime_variable)?;
+            node.process_best_effort(
+                url,
+                root_element_refs.clone(),
+                runtime_variable,
+                warnings.as_deref_mut(),
+                &sources,
+            )?;
         }
 
-        Ok(())
+        Ok(self.next_page_url(root_html.root_element(), url, runtime_variable))
     }
 
-    fn crawler_blocking<'a>(
-        &'a self,
+    fn crawler_blocking(
+        &self,
+        client: &reqwest::blocking::Client,
         url: &str,
-        runtime_variable: &'a mut RuntimeVariable,
-    ) -> Result<(), CrawlerErr> {
-        let root_html = {
-            let response = reqwest::blocking::get(url)?;
-            let body = response.text()?;
-            scraper::Html::parse_document(&body)
-        };
+        timeout: Duration,
+        max_response_bytes: usize,
+        runtime_variable: &mut RuntimeVariable,
+        retained: &mut RetainedPages<'_>,
+    ) -> Result<Option<String>, CrawlerErr> {
+        let response = client
+            .get(url)
+            .timeout(timeout)
+            .send()
+            .map_err(|e| request_error(url, e))?;
+        check_status(url, response.status())?;
+        let body = read_blocking_body(url, response, max_response_bytes)?;
+
+        let next_url = self.process_page(url, &body, runtime_variable, None, retained)?;
+        retained.keep(self.source_key(), &body);
+        Ok(next_url)
+    }
 
-        let root_element_refs = vec![root_html.root_element()];
+    /// 从当前页面提取下一页地址，相对地址按当前页面地址解析，没有下一页时返回 None
+    fn next_page_url(
+        &self,
+        root: ElementRef<'_>,
+        url: &str,
+        runtime_variable: &mut RuntimeVariable,
+    ) -> Option<String> {
+        let pagination = self.next_page.as_ref()?;
+        let href = match pagination.script.get_values(vec![root], runtime_variable) {
+            Ok(values) => values.into_iter().map(|v| v.trim().to_string()).find(|v| !v.is_empty()),
+            Err(e) => {
+                log::debug!("页面 {} 未找到下一页链接: {}", url, e);
+                None
+            }
+        }?;
 
-        for node in &self.node {
-            node.process(root_element_refs.clone(), runtime_variable)?;
+        match reqwest::Url::parse(url).and_then(|base| base.join(&href)) {
+            Ok(next_url) => Some(next_url.to_string()),
+            Err(e) => {
+                log::warn!("页面 {} 的下一页地址 '{}' 无效: {}", url, href, e);
+                None
+            }
         }
+    }
 
-        Ok(())
+    /// 判断是否继续翻页：已抓取过的地址或达到 `max_pages` 时停止
+    fn follow_next_page(&self, next_url: Option<String>, visited: &HashSet<String>) -> Option<String> {
+        let pagination = self.next_page.as_ref()?;
+        let next_url = next_url?;
+
+        if visited.contains(&next_url) {
+            log::debug!("下一页 {} 已抓取过，停止翻页", next_url);
+            return None;
+        }
+        if visited.len() >= pagination.max_pages {
+            log::info!("已达到最大翻页数 {}，停止翻页", pagination.max_pages);
+            return None;
+        }
+
+        Some(next_url)
     }
 
-    fn new(url_key: &str, node: HashMap<String, CrawlerNode>) -> Self {
+    fn new(
+        url_key: &str,
+        node: HashMap<String, CrawlerNode>,
+        next_page: Option<Pagination>,
+    ) -> Self {
         let node = node
             .into_iter()
             .map(|node| node.into())
@@ -211,16 +874,84 @@ impl WorkflowRoot {
         WorkflowRoot {
             url_key: url_key.to_string(),
             node,
+            next_page,
         }
     }
 }
 
+impl Pagination {
+    /// 根据 `next_page` 与 `max_pages` 创建翻页配置，未配置 `next_page` 时返回 None
+    fn new(next_page: Option<String>, max_pages: Option<usize>) -> Result<Option<Self>, String> {
+        let Some(next_page) = next_page else {
+            return match max_pages {
+                Some(_) => Err("'max_pages' requires 'next_page'".to_string()),
+                None => Ok(None),
+            };
+        };
+
+        let script = CrawlerScript::new(&next_page).map_err(|e| script_error(&next_page, e))?;
+        if script.rule != Rule::value_access {
+            return Err("'next_page' must be a value script producing the next page url".to_string());
+        }
+
+        let max_pages = max_pages.unwrap_or(DEFAULT_MAX_PAGES);
+        if max_pages == 0 {
+            return Err("'max_pages' must be at least 1".to_string());
+        }
+
+        Ok(Some(Pagination { script, max_pages }))
+    }
+}
+
 impl WorkflowNode {
+    #[cfg(test)]
     fn process(
         &self,
         root_element_refs: Vec<ElementRef<'_>>,
         runtime_variable: &mut RuntimeVariable,
     ) -> Result<(), CrawlerErr> {
+        self.process_with("", root_element_refs, runtime_variable, None, &SourceElements::new())
+    }
+
+    /// `warnings` 为 `Some` 时处于尽力模式，取值节点的失败记录为警告而不向上传播
+    fn process_best_effort<'a>(
+        &self,
+        url: &str,
+        root_element_refs: Vec<ElementRef<'a>>,
+        runtime_variable: &mut RuntimeVariable,
+        warnings: Option<&mut Vec<NodeWarning>>,
+        sources: &SourceElements<'a>,
+    ) -> Result<(), CrawlerErr> {
+        match warnings {
+            Some(warnings) if self.script.rule == Rule::value_access => {
+                if let Err(e) = self.process_with(url, root_element_refs, runtime_variable, None, sources) {
+                    log::warn!("尽力模式: 节点 '{}' 处理失败，已跳过: {}", self.name, e);
+                    warnings.push(NodeWarning {
+                        node: self.name.clone(),
+                        message: e.to_string(),
+                    });
+                }
+                Ok(())
+            }
+            warnings => self.process_with(url, root_element_refs, runtime_variable, warnings, sources),
+        }
+    }
+
+    /// `url` 为当前页面地址，脚本执行失败时与节点名一起附加到错误中
+    fn process_with<'a>(
+        &self,
+        url: &str,
+        root_element_refs: Vec<ElementRef<'a>>,
+        runtime_variable: &mut RuntimeVariable,
+        mut warnings: Option<&mut Vec<NodeWarning>>,
+        sources: &SourceElements<'a>,
+    ) -> Result<(), CrawlerErr> {
+        // `source` 节点作用于先前工作流保留的页面
+        let root_element_refs = match &self.source {
+            Some(source) => sources.get(source.as_str()).cloned().unwrap_or_default(),
+            None => root_element_refs,
+        };
+
         log::debug!("处理节点 '{}', required={}, 输入元素数量={}", 
             self.name, self.required, root_element_refs.len());
         
@@ -230,6 +961,10 @@ impl WorkflowNode {
             return Err(CrawlerErr::Custom(format!("DATA_NOT_FOUND: {}", error_msg)));
         }
         
+        if self.group {
+            return self.process_group(url, root_element_refs, runtime_variable);
+        }
+
         match self.script.rule {
             Rule::element_access => {
                 let elements = match self
@@ -243,10 +978,15 @@ impl WorkflowNode {
                     }
                     Err(e) => {
                         log::error!("必需字段 '{}' (element_access) 处理失败: {}", self.name, e);
-                        return Err(e);
+                        return Err(self.node_error(url, e));
                     }
                 };
 
+                if elements.is_empty() && self.script.is_optional() {
+                    log::debug!("可选节点 '{}' 未找到任何匹配的元素，跳过子节点", self.name);
+                    return Ok(());
+                }
+
                 if elements.is_empty() && self.required {
                     let error_msg = format!("必需节点 '{}' 未找到任何匹配的元素", self.name);
                     log::error!("{}", error_msg);
@@ -254,7 +994,13 @@ impl WorkflowNode {
                 }
 
                 for node in &self.children {
-                    node.process(elements.clone(), runtime_variable)?;
+                    node.process_best_effort(
+                        url,
+                        elements.clone(),
+                        runtime_variable,
+                        warnings.as_deref_mut(),
+                        sources,
+                    )?;
                 }
             }
             Rule::value_access => {
@@ -262,27 +1008,41 @@ impl WorkflowNode {
                     Ok(values) => values,
                     Err(e) if !self.required => {
                         log::debug!("非必需字段处理失败，使用默认空字符串: {}", e);
-                        vec![String::new()]
+                        if self.script.is_optional() {
+                            vec![]
+                        } else {
+                            vec![String::new()]
+                        }
                     }
                     Err(e) => {
                         log::error!("必需字段 '{}' (value_access) 处理失败: {}", self.name, e);
-                        return Err(e);
+                        return Err(self.node_error(url, e));
                     }
                 };
 
+                if self.script.is_optional() && values.iter().all(|v| v.trim().is_empty()) {
+                    log::debug!("可选节点 '{}' 没有值，不写入变量", self.name);
+                    return Ok(());
+                }
+
                 if self.required && (values.is_empty() || values.iter().all(|v| v.trim().is_empty())) {
                     let error_msg = format!("必需节点 '{}' 未获取到有效的值", self.name);
                     log::error!("{}", error_msg);
                     return Err(CrawlerErr::Custom(format!("DATA_NOT_FOUND: {}", error_msg)));
                 }
 
-                if !runtime_variable.contains_key(&self.name) {
-                    runtime_variable.insert(self.name.clone(), values.clone());
+                let targets = node_targets(&self.name);
+                if targets.len() > 1 {
+                    // `a,b: ...attrs("x","y")` 按位置将成对的值分别写入各个变量
+                    let mut target_values = vec![Vec::new(); targets.len()];
+                    for (index, value) in values.into_iter().enumerate() {
+                        target_values[index % targets.len()].push(value);
+                    }
+                    for (target, values) in targets.iter().zip(target_values) {
+                        self.merge_mode.write(runtime_variable, target, values);
+                    }
                 } else {
-                    runtime_variable
-                        .get_mut(&self.name)
-                        .unwrap()
-                        .extend(values.clone());
+                    self.merge_mode.write(runtime_variable, &self.name, values);
                 }
             }
             _ => {}
@@ -290,6 +1050,86 @@ impl WorkflowNode {
 
         Ok(())
     }
+
+    /// 为脚本执行错误附加节点名与页面地址
+    fn node_error(&self, url: &str, source: CrawlerErr) -> CrawlerErr {
+        CrawlerErr::NodeError {
+            node: self.name.clone(),
+            url: url.to_string(),
+            source: Box::new(source),
+        }
+    }
+
+    /// 处理 `group` 节点：每个匹配元素生成一条记录，保证同一元素的多个值保持对应
+    ///
+    /// `parallel` 分组不写入记录，而是把每个子节点的值写入以子节点命名的变量，
+    /// 元素缺少的值以空字符串占位，使各变量的同一下标对应同一元素
+    fn process_group(
+        &self,
+        url: &str,
+        root_element_refs: Vec<ElementRef<'_>>,
+        runtime_variable: &mut RuntimeVariable,
+    ) -> Result<(), CrawlerErr> {
+        let elements = match self.script.get_elements(root_element_refs, runtime_variable) {
+            Ok(elements) => elements,
+            Err(e) if !self.required => {
+                log::debug!("非必需分组 '{}' 处理失败，使用空元素列表: {}", self.name, e);
+                vec![]
+            }
+            Err(e) => return Err(self.node_error(url, e)),
+        };
+
+        if elements.is_empty() && self.script.is_optional() {
+            log::debug!("可选分组 '{}' 未找到任何匹配的元素", self.name);
+            return Ok(());
+        }
+
+        let mut records = Vec::new();
+        let mut columns: Vec<Vec<String>> = vec![Vec::new(); self.children.len()];
+        let mut produced = 0;
+        'elements: for element in elements {
+            let mut record = Record::new();
+            for child in &self.children {
+                let value = child
+                    .script
+                    .get_values(vec![element], runtime_variable)
+                    .ok()
+                    .and_then(|values| values.into_iter().next())
+                    .unwrap_or_default();
+
+                // 必需字段为空的元素不生成记录
+                if child.required && value.trim().is_empty() {
+                    log::debug!("分组 '{}' 的元素缺少必需字段 '{}'，跳过", self.name, child.name);
+                    continue 'elements;
+                }
+                record.insert(child.name.clone(), value);
+            }
+            if self.parallel {
+                for (column, child) in columns.iter_mut().zip(&self.children) {
+                    column.push(record.remove(&child.name).unwrap_or_default());
+                }
+            } else {
+                records.push(serde_json::to_string(&record).map_err(|e| CrawlerErr::OtherError(e.to_string()))?);
+            }
+            produced += 1;
+        }
+
+        if produced == 0 && self.required {
+            let error_msg = format!("必需分组 '{}' 未生成任何记录", self.name);
+            log::error!("{}", error_msg);
+            return Err(CrawlerErr::Custom(format!("DATA_NOT_FOUND: {}", error_msg)));
+        }
+
+        if self.parallel {
+            for (column, child) in columns.into_iter().zip(&self.children) {
+                self.merge_mode.write(runtime_variable, &child.name, column);
+            }
+        } else {
+            self.merge_mode.write(runtime_variable, &self.name, records);
+        }
+
+        Ok(())
+    }
 }
 
 impl CrawlerNode {
@@ -324,8 +1164,18 @@ where
         #[derive(Deserialize, Clone)]
         struct TemplateData {
             entrypoint: String,
+            #[serde(deserialize_with = "deserialize_nodes")]
             nodes: HashMap<String, CrawlerNode>,
             env: Option<RuntimeVariable>,
+            /// 单个请求的超时时间（秒）
+            #[serde(alias = "request_timeout_seconds")]
+            timeout: Option<u64>,
+            /// 入口页面的下一页地址脚本
+            next_page: Option<String>,
+            /// 入口页面的最大翻页数
+            max_pages: Option<usize>,
+            /// 单个响应的最大字节数
+            max_response_bytes: Option<usize>,
         }
 
         fn check_tree_keys_unique(nodes: &HashMap<String, CrawlerNode>) -> Result<(), String> {
@@ -342,19 +1192,47 @@ where
                     return Err(format!("Duplicate key '{}' found in tree", key));
                 }
 
-                if let Some(children) = &node.children {
+                // 普通分组的子节点只是记录中的字段，不写入运行时变量；
+                // parallel 分组的子节点写入同名变量，与其他节点一样不能重名
+                if let Some(children) = node.children.as_ref().filter(|_| !node.group || node.parallel) {
                     check_node_keys(children, seen_keys)?;
                 }
             }
             Ok(())
         }
 
+        fn check_node_targets(nodes: &HashMap<String, CrawlerNode>) -> Result<(), String> {
+            for (key, node) in nodes {
+                let targets = node_targets(key);
+                if targets.len() > 1
+                    && (node.script.rule != Rule::value_access
+                        || node.script.value_arity() != targets.len())
+                {
+                    return Err(format!(
+                        "Node '{}' has {} targets but its script produces {} values per element",
+                        key,
+                        targets.len(),
+                        node.script.value_arity()
+                    ));
+                }
+
+                if let Some(children) = &node.children {
+                    check_node_targets(children)?;
+                }
+            }
+            Ok(())
+        }
+
         let data = TemplateData::deserialize(deserializer)?;
 
         check_tree_keys_unique(&data.nodes)
             .map_err(|e| serde::de::Error::custom(format!("Duplicate key error: {}", e)))?;
 
-        let root_node = WorkflowRoot::new("", data.nodes.clone());
+        check_node_targets(&data.nodes).map_err(serde::de::Error::custom)?;
+
+        let next_page =
+            Pagination::new(data.next_page, data.max_pages).map_err(serde::de::Error::custom)?;
+        let root_node = WorkflowRoot::new("", data.nodes.clone(), next_page);
 
         let mut workflow = vec![root_node];
 
@@ -372,12 +1250,35 @@ where
         }
 
         collect_requested_nodes(&data.nodes, &mut workflow);
+        let workflow = order_workflows(workflow).map_err(serde::de::Error::custom)?;
+        let retained_sources = workflow
+            .iter()
+            .flat_map(|workflow| workflow.sources())
+            .map(|(_, source)| source.to_string())
+            .collect();
+
+        let timeout =
+            Duration::from_secs(data.timeout.unwrap_or(DEFAULT_REQUEST_TIMEOUT_SECS));
+        let max_response_bytes = data.max_response_bytes.unwrap_or(DEFAULT_MAX_RESPONSE_BYTES);
+        let http = ReqwestFetcher::new(timeout)
+            .map_err(serde::de::Error::custom)?
+            .with_max_response_bytes(max_response_bytes);
 
         Ok(Template {
             entrypoint: data.entrypoint,
             parameters: data.env.unwrap_or_default(),
             workflows: workflow,
             resource_type: PhantomData,
+            timeout,
+            timeout_explicit: data.timeout.is_some(),
+            max_response_bytes,
+            http,
+            fetcher: None,
+            request_limit: None,
+            host_limit: None,
+            best_effort: false,
+            concurrency: DEFAULT_CONCURRENCY,
+            retained_sources,
         })
     }
 }
@@ -388,35 +1289,62 @@ impl<'de> Deserialize<'de> for CrawlerNode {
         D: Deserializer<'de>,
     {
         #[derive(Deserialize)]
-        #[serde(untagged)]
-        enum CrawlerNodeData {
-            Complex {
-                script: String,
-                #[serde(default = "crate::default_false")]
-                request: bool,
-                #[serde(default = "crate::default_false")]
-                required: bool,
-                #[serde(default)]
-                children: Option<HashMap<String, CrawlerNode>>,
-            },
-            Simple(String),
+        struct ComplexNodeData {
+            script: String,
+            #[serde(default = "crate::default_false")]
+            request: bool,
+            #[serde(default = "crate::default_false")]
+            required: bool,
+            #[serde(default = "crate::default_false")]
+            group: bool,
+            #[serde(default = "crate::default_false")]
+            parallel: bool,
+            #[serde(default, deserialize_with = "deserialize_optional_nodes")]
+            children: Option<HashMap<String, CrawlerNode>>,
+            #[serde(default)]
+            next_page: Option<String>,
+            #[serde(default)]
+            max_pages: Option<usize>,
+            #[serde(default)]
+            merge_mode: MergeMode,
+            #[serde(default)]
+            source: Option<String>,
         }
 
-        let data = CrawlerNodeData::deserialize(deserializer)?;
+        // 不使用 untagged 枚举，以免子节点的错误信息被 "did not match any variant" 吞掉
+        let (script_raw, request, required, group, parallel, children, next_page, max_pages, merge_mode, source) =
+            match serde_yaml::Value::deserialize(deserializer)? {
+                serde_yaml::Value::String(script) => {
+                    (script, false, false, false, false, None, None, None, MergeMode::default(), None)
+                }
+                value => {
+                    let data =
+                        ComplexNodeData::deserialize(value).map_err(serde::de::Error::custom)?;
+                    (
+                        data.script,
+                        data.request,
+                        data.required,
+                        data.group,
+                        data.parallel,
+                        data.children,
+                        data.next_page,
+                        data.max_pages,
+                        data.merge_mode,
+                        data.source,
+                    )
+                }
+            };
 
-        let (script_raw, request, required, children) = match data {
-            CrawlerNodeData::Complex {
-                script,
-                request,
-                required,
-                children,
-            } => (script, request, required, children),
-            CrawlerNodeData::Simple(script) => (script, false, false, None),
-        };
+        let next_page = Pagination::new(next_page, max_pages).map_err(serde::de::Error::custom)?;
+        if next_page.is_some() && !request {
+            return Err(serde::de::Error::custom(
+                "'next_page' is only allowed on request nodes",
+            ));
+        }
 
         let script = match CrawlerScript::new(&script_raw) {
             Ok(script) => script,
-            Err(e) => return Err(serde::de::Error::custom(e.to_string())),
+            Err(e) => return Err(serde::de::Error::custom(script_error(&script_raw, e))),
         };
 
         if script.rule == Rule::value_access
@@ -428,16 +1356,114 @@ impl<'de> Deserialize<'de> for CrawlerNode {
             ));
         }
 
+        if parallel && !group {
+            return Err(serde::de::Error::custom("'parallel' requires 'group: true'"));
+        }
+
+        if group {
+            if script.rule != Rule::element_access || request {
+                return Err(serde::de::Error::custom(
+                    "Group node must select elements and cannot be a request node",
+                ));
+            }
+            let invalid_child = children.iter().flatten().find(|(name, child)| {
+                child.script.rule != Rule::value_access
+                    || child.request
+                    || child.group
+                    || child.source.is_some()
+                    || matches!(child.children.as_ref(), Some(c) if !c.is_empty())
+                    || node_targets(name).len() > 1
+            });
+            if let Some((name, _)) = invalid_child {
+                return Err(serde::de::Error::custom(format!(
+                    "Group child '{}' must be a single value script without children",
+                    name
+                )));
+            }
+        }
+
         Ok(CrawlerNode {
             _script_raw: script_raw,
             request,
             required,
+            group,
+            parallel,
             children,
             script,
+            next_page,
+            merge_mode,
+            source,
         })
     }
 }
 
+/// 将脚本解析错误整理为单行信息，包含脚本内的行列位置与出错处的内容
+fn script_error(script: &str, err: CrawlerErr) -> String {
+    let CrawlerErr::ScriptParseError(err) = err else {
+        return err.to_string();
+    };
+
+    let offset = match err.location {
+        pest::error::InputLocation::Pos(offset) | pest::error::InputLocation::Span((offset, _)) => {
+            offset
+        }
+    };
+    // 跳过出错位置前的空白，使行列指向实际出错的内容
+    let rest = script.get(offset..).unwrap_or_default();
+    let start = offset + (rest.len() - rest.trim_start().len());
+    let before = &script[..start];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+
+    let token: String = script[start..]
+        .chars()
+        .take_while(|c| !c.is_whitespace())
+        .take(32)
+        .collect();
+    let near = if token.is_empty() {
+        "end of script".to_string()
+    } else {
+        format!("'{}'", token)
+    };
+
+    format!(
+        "script parse error at line {}, column {} near {}: {}",
+        line,
+        column,
+        near,
+        err.variant.message()
+    )
+}
+
+/// 逐个反序列化节点，出错时在错误信息中带上节点名称
+fn deserialize_nodes<'de, D>(deserializer: D) -> Result<HashMap<String, CrawlerNode>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    HashMap::<String, serde_yaml::Value>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(name, value)| {
+            CrawlerNode::deserialize(value)
+                .map(|node| (name.clone(), node))
+                .map_err(|e| serde::de::Error::custom(format!("node '{}': {}", name, e)))
+        })
+        .collect()
+}
+
+fn deserialize_optional_nodes<'de, D>(
+    deserializer: D,
+) -> Result<Option<HashMap<String, CrawlerNode>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_nodes(deserializer).map(Some)
+}
+
+/// 节点名称可用逗号分隔多个变量名，用于接收 `attrs` 产生的成对值
+fn node_targets(name: &str) -> Vec<&str> {
+    name.split(',').map(str::trim).collect()
+}
+
 type WorkflowNodeWithName = (String, CrawlerNode);
 
 impl From<WorkflowNodeWithName> for WorkflowRoot {
@@ -449,6 +1475,7 @@ impl From<WorkflowNodeWithName> for WorkflowRoot {
                     .map(|node| node.into())
                     .collect::<Vec<WorkflowNode>>()
             }),
+            next_page: node.1.next_page.clone(),
         }
     }
 }
@@ -459,6 +1486,10 @@ impl From<WorkflowNodeWithName> for WorkflowNode {
             name: node.0,
             script: node.1.script.clone(),
             required: node.1.required,
+            group: node.1.group,
+            parallel: node.1.parallel,
+            merge_mode: node.1.merge_mode,
+            source: node.1.source.clone(),
             children: node.1.children.clone().map_or(vec![], |c| {
                 if node.1.request {
                     vec![]
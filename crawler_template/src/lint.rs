@@ -0,0 +1,125 @@
+use std::{collections::HashSet, fmt};
+
+use crate::{node_targets, script::Rule, CrawlerData, Template, WorkflowNode};
+
+/// 调用方爬取时传入的参数，入口地址中可以直接引用
+pub const RUNTIME_PARAMETERS: &[&str] = &["crawl_name", "original_filename", "parent_dir", "grandparent_dir"];
+
+/// 模板检查发现的问题类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintKind {
+    /// 请求节点的地址变量没有由前面的取值节点产生，该请求及之后的工作流不会执行
+    UnreachableRequest,
+    /// 入口地址引用的变量既不在 `env` 中也不是调用方传入的参数
+    UnknownEntrypointParameter,
+    /// 取值节点与 `env` 中的参数同名，爬取时会覆盖或追加到参数
+    EnvCollision,
+    /// 请求节点没有子节点，请求到的页面不会被使用
+    EmptyRequest,
+}
+
+/// 模板检查发现的问题
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintWarning {
+    pub kind: LintKind,
+    /// 相关的节点名称或入口地址变量
+    pub node: String,
+    pub message: String,
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "node '{}': {}", self.node, self.message)
+    }
+}
+
+impl<T> Template<T>
+where
+    T: CrawlerData + Default + Send,
+{
+    /// 静态检查模板中不会生效的配置，不发起任何请求
+    ///
+    /// 检查请求节点的地址变量是否由前面的工作流产生、入口地址的变量是否有来源、
+    /// 取值节点是否与 `env` 参数同名，以及请求节点是否没有子节点
+    pub fn lint(&self) -> Vec<LintWarning> {
+        let mut warnings = Vec::new();
+
+        for name in entrypoint_parameters(&self.entrypoint) {
+            if !self.parameters.contains_key(name) && !RUNTIME_PARAMETERS.contains(&name) {
+                warnings.push(LintWarning {
+                    kind: LintKind::UnknownEntrypointParameter,
+                    node: name.to_string(),
+                    message: format!(
+                        "entrypoint variable '${{{}}}' is not defined in env and is not one of the runtime parameters ({})",
+                        name,
+                        RUNTIME_PARAMETERS.join(", ")
+                    ),
+                });
+            }
+        }
+
+        let mut produced: HashSet<String> = self.parameters.keys().cloned().collect();
+        for (index, workflow) in self.workflows.iter().enumerate() {
+            if index > 0 {
+                if !produced.contains(&workflow.url_key) {
+                    warnings.push(LintWarning {
+                        kind: LintKind::UnreachableRequest,
+                        node: workflow.url_key.clone(),
+                        message: "request node never produces url values, its workflow and all following workflows will not run".to_string(),
+                    });
+                }
+                if workflow.node.is_empty() {
+                    warnings.push(LintWarning {
+                        kind: LintKind::EmptyRequest,
+                        node: workflow.url_key.clone(),
+                        message: "request node has no children, the fetched page is never used"
+                            .to_string(),
+                    });
+                }
+            }
+
+            let mut outputs = Vec::new();
+            collect_outputs(&workflow.node, &mut outputs);
+            for name in outputs {
+                if self.parameters.contains_key(&name) {
+                    warnings.push(LintWarning {
+                        kind: LintKind::EnvCollision,
+                        node: name.clone(),
+                        message: "value node has the same name as an env parameter".to_string(),
+                    });
+                }
+                produced.insert(name);
+            }
+        }
+
+        warnings
+    }
+}
+
+/// 入口地址中 `${name}` 引用的变量名
+fn entrypoint_parameters(entrypoint: &str) -> Vec<&str> {
+    entrypoint
+        .split("${")
+        .skip(1)
+        .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+        .collect()
+}
+
+/// 收集节点树中写入运行时变量的名称
+fn collect_outputs(nodes: &[WorkflowNode], outputs: &mut Vec<String>) {
+    for node in nodes {
+        if node.group {
+            if node.parallel {
+                outputs.extend(node.children.iter().map(|child| child.name.clone()));
+            } else {
+                outputs.push(node.name.clone());
+            }
+            continue;
+        }
+
+        if node.script.rule == Rule::value_access {
+            outputs.extend(node_targets(&node.name).into_iter().map(str::to_string));
+        }
+        collect_outputs(&node.children, outputs);
+    }
+}
@@ -1,4 +1,6 @@
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Display};
+use std::sync::{Arc, Mutex};
 
 use pest::Parser;
 use pest_derive::Parser;
@@ -18,12 +20,21 @@ pub struct CrawlerScript {
     pub(crate) rule: Rule,
 }
 
+/// 动态参数编译结果缓存的最大数量，超过时清空
+const COMPILED_CACHE_CAPACITY: usize = 64;
+
 #[derive(Debug, Clone)]
 enum Command {
-    Selector(Param),
+    Selector(Compiled<Selector>),
     Parent(usize),
     Prev(usize),
     Nth(usize),
+    /// 只保留前 n 个元素或值
+    Take(usize),
+    /// 只保留第 n 个元素或值（从 0 开始），超出范围时没有结果
+    Index(usize),
+    /// 标记结果允许为空，不改变元素和值
+    Optional,
     Replace(Param, Param),
     Uppercase,
     Lowercase,
@@ -31,19 +42,96 @@ enum Command {
     Prepend(Param),
     Append(Param),
     Delete(Param),
-    RegexMatch(Param),
+    RegexMatch(Compiled<Regex>),
     Equals(Param),
     Html,
     Attr(Param),
+    /// 依次读取每个元素的多个属性，每个元素产生与属性数量相同的值
+    Attrs(Vec<Param>),
     Val,
-    RegexExtract(Param),
-    RegexReplace(Param, Param),
+    /// 将当前选中的元素替换为一个值：元素数量
+    Count,
+    RegexExtract(Compiled<Regex>),
+    RegexReplace(Compiled<Regex>, Param),
+    /// 去除重复的值，保留第一次出现的顺序
+    Dedup,
+    /// 解码值中的 HTML 实体，如 `&amp;` -> `&`
+    HtmlDecode,
+    /// 将连续的空白字符（包括不换行空格）合并为一个空格，并去除首尾空白
+    NormalizeSpace,
+    /// `if_matches` 链：每个值替换为第一个匹配的正则对应的值，都不匹配时使用 `otherwise` 的值，
+    /// 没有 `otherwise` 时保持不变
+    IfMatches(Vec<(Compiled<Regex>, Param)>, Option<Param>),
+    /// 值为空或只有空白时替换为默认值
+    IfEmpty(Param),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 enum Param {
     StaticStr(String),
+    /// `${var}` - 变量必须只有一个值
     DynamicStr(String),
+    /// `${var[0]}` / `${var[-1]}` - 按下标取变量中的某个值，负数从末尾开始
+    DynamicIndex(String, isize),
+    /// `${var[*]}` - 变量的全部值，在转换命令中与当前元素逐一配对
+    DynamicList(String),
+    /// `"div[data-id='${var}']"` - 字符串中嵌入变量，各部分求值后拼接
+    Interpolated(Vec<Param>),
+}
+
+/// 选择器、正则等需要编译的参数
+///
+/// 静态参数在解析脚本时编译一次；动态参数在执行时按解析后的字符串编译，
+/// 编译结果在脚本的所有克隆之间共享缓存。静态参数编译失败时脚本解析失败，便于在加载模板时发现错误
+#[derive(Debug, Clone)]
+struct Compiled<T> {
+    param: Param,
+    compiled: Option<T>,
+    cache: Arc<Mutex<HashMap<String, T>>>,
+    compile: fn(&str) -> Result<T, CrawlerErr>,
+}
+
+impl<T: Clone> Compiled<T> {
+    fn new(param: Param, compile: fn(&str) -> Result<T, CrawlerErr>) -> Result<Self, CrawlerErr> {
+        let compiled = match &param {
+            Param::StaticStr(value) => Some(compile(value)?),
+            _ => None,
+        };
+
+        Ok(Self {
+            param,
+            compiled,
+            cache: Arc::new(Mutex::new(HashMap::new())),
+            compile,
+        })
+    }
+
+    fn get(&self, runtime_variable: &RuntimeVariable) -> Result<T, CrawlerErr> {
+        if let Some(compiled) = &self.compiled {
+            return Ok(compiled.clone());
+        }
+
+        let value = self.param.get_value(runtime_variable)?;
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(compiled) = cache.get(&value) {
+            return Ok(compiled.clone());
+        }
+
+        let compiled = (self.compile)(&value)?;
+        if cache.len() >= COMPILED_CACHE_CAPACITY {
+            cache.clear();
+        }
+        cache.insert(value, compiled.clone());
+        Ok(compiled)
+    }
+}
+
+fn compile_selector(selector: &str) -> Result<Selector, CrawlerErr> {
+    Selector::parse(selector).map_err(|err| CrawlerErr::SelectorError(format!("'{}': {}", selector, err)))
+}
+
+fn compile_regex(regex: &str) -> Result<Regex, CrawlerErr> {
+    Regex::new(regex).map_err(CrawlerErr::from)
 }
 
 impl Param {
@@ -54,13 +142,7 @@ impl Param {
         match self {
             Param::StaticStr(param) => Ok(param.to_string()),
             Param::DynamicStr(param) => {
-                let values = runtime_variable
-                    .get(param)
-                    .ok_or_else(|| CrawlerErr::FieldNotFound(param.to_string()))?;
-
-                if values.is_empty() {
-                    return Err(CrawlerErr::DynNoValidData(param.to_string()));
-                }
+                let values = Self::get_variable(param, runtime_variable)?;
 
                 if values.len() > 1 {
                     return Err(CrawlerErr::DynMultipleResults(
@@ -75,7 +157,107 @@ impl Param {
                     Err(CrawlerErr::DynNotYetInitialised(param.to_string()))
                 }
             }
+            Param::DynamicIndex(param, index) => {
+                let values = Self::get_variable(param, runtime_variable)?;
+
+                let position = if *index < 0 {
+                    values.len().checked_sub(index.unsigned_abs())
+                } else {
+                    Some(*index as usize)
+                };
+
+                position
+                    .and_then(|position| values.get(position))
+                    .cloned()
+                    .ok_or_else(|| {
+                        CrawlerErr::DynIndexOutOfRange(param.to_string(), *index, values.len())
+                    })
+            }
+            Param::DynamicList(param) => Err(CrawlerErr::DynListNotAllowed(param.to_string())),
+            Param::Interpolated(parts) => parts
+                .iter()
+                .map(|part| part.get_value(runtime_variable))
+                .collect(),
+        }
+    }
+
+    /// 将静态字符串中的 `${var}` / `${var[0]}` 拆分为动态参数，没有变量时保持静态
+    fn interpolate(self) -> Param {
+        let Param::StaticStr(value) = &self else {
+            return self;
+        };
+
+        let mut parts = Vec::new();
+        let mut rest = value.as_str();
+        while let Some(start) = rest.find("${") {
+            let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+                break;
+            };
+            let variable = &rest[start + 2..end];
+            let param = match variable.strip_suffix(']').and_then(|v| v.split_once('[')) {
+                Some((name, "*")) => Param::DynamicList(name.to_string()),
+                Some((name, index)) => match index.parse() {
+                    Ok(index) => Param::DynamicIndex(name.to_string(), index),
+                    Err(_) => Param::StaticStr(rest[start..=end].to_string()),
+                },
+                None => Param::DynamicStr(variable.to_string()),
+            };
+
+            if start > 0 {
+                parts.push(Param::StaticStr(rest[..start].to_string()));
+            }
+            parts.push(param);
+            rest = &rest[end + 1..];
+        }
+
+        if parts.is_empty() {
+            return self;
+        }
+        if !rest.is_empty() {
+            parts.push(Param::StaticStr(rest.to_string()));
+        }
+        Param::Interpolated(parts)
+    }
+
+    /// 获取与当前元素逐一配对的参数值
+    ///
+    /// `${var[*]}` 的值数量必须与元素数量一致，其他参数的单个值会被复用到每个元素
+    pub(crate) fn get_values(
+        &self,
+        runtime_variable: &RuntimeVariable,
+        count: usize,
+    ) -> Result<Vec<String>, CrawlerErr> {
+        match self {
+            Param::DynamicList(param) => {
+                let values = Self::get_variable(param, runtime_variable)?;
+
+                if values.len() != count {
+                    return Err(CrawlerErr::DynLengthMismatch(
+                        param.to_string(),
+                        values.len(),
+                        count,
+                    ));
+                }
+
+                Ok(values.clone())
+            }
+            _ => Ok(vec![self.get_value(runtime_variable)?; count]),
+        }
+    }
+
+    fn get_variable<'a>(
+        param: &str,
+        runtime_variable: &'a RuntimeVariable,
+    ) -> Result<&'a Vec<String>, CrawlerErr> {
+        let values = runtime_variable
+            .get(param)
+            .ok_or_else(|| CrawlerErr::FieldNotFound(param.to_string()))?;
+
+        if values.is_empty() {
+            return Err(CrawlerErr::DynNoValidData(param.to_string()));
         }
+
+        Ok(values)
     }
 }
 
@@ -108,7 +290,7 @@ impl CrawlerScript {
                 Rule::condition_rule => {
                     commands.append(&mut get_commands(parse_condition_rule, pair)?)
                 }
-                Rule::accessor_rule => {
+                Rule::accessor_rule | Rule::value_accessor_rule => {
                     commands.append(&mut get_commands(parse_accessor_rule, pair)?)
                 }
                 _ => {}
@@ -127,18 +309,18 @@ impl CrawlerScript {
         root_element_ref: Vec<ElementRef<'a>>,
         runtime_variable: &mut RuntimeVariable,
     ) -> Result<Vec<(String, ElementRef<'a>)>, CrawlerErr> {
+        // 包含 count() 时没有匹配的元素也要继续执行，以便得到 "0"
+        let counted = self.commands.iter().any(|command| matches!(command, Command::Count));
+        let root = root_element_ref.first().copied();
         let mut element_values: Vec<(String, ElementRef)> = root_element_ref
             .into_iter()
             .map(|element| (String::new(), element))
             .collect();
 
-        for command in self.commands.clone() {
+        for command in &self.commands {
             match command {
                 Command::Selector(selector) => {
-                    let selector = selector.get_value(runtime_variable)?;
-
-                    let selector = Selector::parse(&selector)
-                        .map_err(|err| CrawlerErr::SelectorError(err.to_string()))?;
+                    let selector = selector.get(runtime_variable)?;
 
                     element_values = element_values
                         .into_iter()
@@ -150,11 +332,11 @@ impl CrawlerScript {
                         })
                         .collect();
 
-                    if element_values.is_empty() {
+                    if element_values.is_empty() && !counted {
                         return Ok(vec![]);
                     }
                 }
-                Command::Parent(index) => {
+                &Command::Parent(index) => {
                     for element_value in element_values.iter_mut() {
                         let mut r_parent = element_value.1;
                         for erg in 0..index {
@@ -168,7 +350,7 @@ impl CrawlerScript {
                         element_value.1 = r_parent;
                     }
                 }
-                Command::Prev(index) => {
+                &Command::Prev(index) => {
                     for element_value in element_values.iter_mut() {
                         let prev_siblings = element_value
                             .1
@@ -185,7 +367,7 @@ impl CrawlerScript {
                         element_value.1 = prev_siblings[index - 1];
                     }
                 }
-                Command::Nth(index) => {
+                &Command::Nth(index) => {
                     for element_value in element_values.iter_mut() {
                         let next_siblings = element_value
                             .1
@@ -202,6 +384,20 @@ impl CrawlerScript {
                         element_value.1 = next_siblings[index - 1];
                     }
                 }
+                &Command::Take(count) => {
+                    element_values.truncate(count);
+                }
+                Command::Optional => {}
+                &Command::Index(index) => {
+                    if index >= element_values.len() {
+                        if !counted {
+                            return Ok(vec![]);
+                        }
+                        element_values.clear();
+                    } else {
+                        element_values = vec![element_values.swap_remove(index)];
+                    }
+                }
                 Command::Html => {
                     element_values.iter_mut().for_each(|element_values| {
                         element_values.0 = element_values.1.html().to_string();
@@ -213,17 +409,39 @@ impl CrawlerScript {
                         value.0 = value.1.value().attr(&attr).unwrap_or("").to_string();
                     });
                 }
+                Command::Attrs(attrs) => {
+                    let attrs = attrs
+                        .iter()
+                        .map(|attr| attr.get_value(runtime_variable))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    element_values = element_values
+                        .into_iter()
+                        .flat_map(|(_, element)| {
+                            attrs.iter().map(move |attr| {
+                                (element.value().attr(attr).unwrap_or("").to_string(), element)
+                            })
+                        })
+                        .collect();
+                }
                 Command::Val => {
                     element_values.iter_mut().for_each(|value| {
                         value.0 = value.1.text().collect();
                     });
                 }
+                Command::Count => {
+                    let Some(element) = element_values.first().map(|value| value.1).or(root) else {
+                        return Ok(vec![]);
+                    };
+                    element_values = vec![(element_values.len().to_string(), element)];
+                }
                 Command::Replace(from, to) => {
-                    let from = from.get_value(runtime_variable)?;
-                    let to = to.get_value(runtime_variable)?;
-                    element_values.iter_mut().for_each(|element_value| {
-                        element_value.0 = element_value.0.replace(&from, &to);
-                    });
+                    let from = from.get_values(runtime_variable, element_values.len())?;
+                    let to = to.get_values(runtime_variable, element_values.len())?;
+                    element_values.iter_mut().zip(from.iter().zip(to.iter())).for_each(
+                        |(element_value, (from, to))| {
+                            element_value.0 = element_value.0.replace(from, to);
+                        },
+                    );
                 }
                 Command::Uppercase => {
                     element_values.iter_mut().for_each(|element_value| {
@@ -235,37 +453,36 @@ impl CrawlerScript {
                         element_value.0 = element_value.0.to_lowercase();
                     });
                 }
-                Command::Insert(index, param) => {
-                    let param = param.get_value(runtime_variable)?;
+                &Command::Insert(index, ref param) => {
+                    let params = param.get_values(runtime_variable, element_values.len())?;
 
-                    element_values.iter_mut().for_each(|(value, _)| {
-                        value.insert_str(index, &param);
+                    element_values.iter_mut().zip(params.iter()).for_each(|((value, _), param)| {
+                        value.insert_str(index, param);
                     });
                 }
                 Command::Prepend(param) => {
-                    let param = param.get_value(runtime_variable)?;
-                    element_values.iter_mut().for_each(|(value, _)| {
-                        value.insert_str(0, &param);
+                    let params = param.get_values(runtime_variable, element_values.len())?;
+                    element_values.iter_mut().zip(params.iter()).for_each(|((value, _), param)| {
+                        value.insert_str(0, param);
                     });
                 }
 
                 Command::Append(param) => {
-                    let param = param.get_value(runtime_variable)?;
+                    let params = param.get_values(runtime_variable, element_values.len())?;
 
-                    element_values.iter_mut().for_each(|(value, _)| {
-                        value.push_str(&param);
+                    element_values.iter_mut().zip(params.iter()).for_each(|((value, _), param)| {
+                        value.push_str(param);
                     });
                 }
                 Command::Delete(param) => {
-                    let param = param.get_value(runtime_variable)?;
+                    let params = param.get_values(runtime_variable, element_values.len())?;
 
-                    element_values.iter_mut().for_each(|element_value| {
-                        element_value.0 = element_value.0.replace(&param, "");
+                    element_values.iter_mut().zip(params.iter()).for_each(|(element_value, param)| {
+                        element_value.0 = element_value.0.replace(param, "");
                     });
                 }
-                Command::RegexExtract(param) => {
-                    let regex = Regex::new(&param.get_value(runtime_variable)?)
-                        .map_err(CrawlerErr::from)?;
+                Command::RegexExtract(regex) => {
+                    let regex = regex.get(runtime_variable)?;
                     element_values.iter_mut().for_each(|element_value| {
                         element_value.0 = regex
                             .find_iter(&element_value.0)
@@ -273,30 +490,72 @@ impl CrawlerScript {
                             .collect();
                     });
                 }
-                Command::RegexMatch(param) => {
-                    let regex = Regex::new(&param.get_value(runtime_variable)?)
-                        .map_err(CrawlerErr::from)?;
+                Command::RegexMatch(regex) => {
+                    let regex = regex.get(runtime_variable)?;
                     element_values.retain(|value| regex.is_match(&value.0));
 
-                    if element_values.is_empty() {
+                    if element_values.is_empty() && !counted {
                         return Ok(vec![]);
                     }
                 }
-                Command::RegexReplace(param, replace) => {
-                    let regex = Regex::new(&param.get_value(runtime_variable)?)
-                        .map_err(CrawlerErr::from)?;
+                Command::RegexReplace(regex, replace) => {
+                    let regex = regex.get(runtime_variable)?;
 
-                    let replace = replace.get_value(runtime_variable)?;
+                    let replaces = replace.get_values(runtime_variable, element_values.len())?;
+                    element_values.iter_mut().zip(replaces.iter()).for_each(|(element_value, replace)| {
+                        element_value.0 = regex.replace_all(&element_value.0, replace).to_string();
+                    });
+                }
+                Command::HtmlDecode => {
+                    element_values.iter_mut().for_each(|element_value| {
+                        element_value.0 = decode_html_entities(&element_value.0);
+                    });
+                }
+                Command::NormalizeSpace => {
                     element_values.iter_mut().for_each(|element_value| {
-                        element_value.0 = regex.replace_all(&element_value.0, &replace).to_string();
+                        element_value.0 = normalize_space(&element_value.0);
+                    });
+                }
+                Command::IfMatches(branches, otherwise) => {
+                    let len = element_values.len();
+                    let branches = branches
+                        .iter()
+                        .map(|(regex, value)| Ok((regex.get(runtime_variable)?, value.get_values(runtime_variable, len)?)))
+                        .collect::<Result<Vec<_>, CrawlerErr>>()?;
+                    let otherwise = otherwise
+                        .as_ref()
+                        .map(|value| value.get_values(runtime_variable, len))
+                        .transpose()?;
+
+                    for (index, element_value) in element_values.iter_mut().enumerate() {
+                        let matched = branches
+                            .iter()
+                            .find(|(regex, _)| regex.is_match(&element_value.0))
+                            .map(|(_, values)| &values[index])
+                            .or(otherwise.as_ref().map(|values| &values[index]));
+                        if let Some(value) = matched {
+                            element_value.0 = value.clone();
+                        }
+                    }
+                }
+                Command::IfEmpty(param) => {
+                    let params = param.get_values(runtime_variable, element_values.len())?;
+                    element_values.iter_mut().zip(params.iter()).for_each(|(element_value, param)| {
+                        if element_value.0.trim().is_empty() {
+                            element_value.0 = param.clone();
+                        }
                     });
                 }
+                Command::Dedup => {
+                    let mut seen = HashSet::new();
+                    element_values.retain(|value| seen.insert(value.0.clone()));
+                }
                 Command::Equals(param) => {
                     let param = param.get_value(runtime_variable)?;
 
                     element_values.retain(|value| value.0 == param);
 
-                    if element_values.is_empty() {
+                    if element_values.is_empty() && !counted {
                         return Ok(vec![]);
                     }
                 }
@@ -306,6 +565,24 @@ impl CrawlerScript {
         Ok(element_values)
     }
 
+    /// 脚本是否带有 `optional()`，结果为空时节点不写入变量
+    pub(crate) fn is_optional(&self) -> bool {
+        self.commands
+            .iter()
+            .any(|command| matches!(command, Command::Optional))
+    }
+
+    /// 每个元素产生的值的数量，`attrs` 为属性数量，其他访问器为 1
+    pub(crate) fn value_arity(&self) -> usize {
+        self.commands
+            .iter()
+            .find_map(|command| match command {
+                Command::Attrs(attrs) => Some(attrs.len()),
+                _ => None,
+            })
+            .unwrap_or(1)
+    }
+
     pub(crate) fn get_values(
         &self,
         root_element_ref: Vec<ElementRef<'_>>,
@@ -354,13 +631,35 @@ fn parse_transform_rule(pair: pest::iterators::Pair<Rule>) -> Result<Command, Cr
         Rule::delete => Ok(Command::Delete(get_pair_param(&pair))),
         Rule::regex_extract => {
             let pattern = get_pair_param(&pair);
-            Ok(Command::RegexExtract(pattern))
+            Ok(Command::RegexExtract(Compiled::new(pattern, compile_regex)?))
         }
         Rule::regex_replace => {
             let regex_str = get_pair_param_with_index(&pair, 0);
             let replace_str = get_pair_param_with_index(&pair, 1);
-            Ok(Command::RegexReplace(regex_str, replace_str))
+            Ok(Command::RegexReplace(Compiled::new(regex_str, compile_regex)?, replace_str))
         }
+        Rule::dedup => Ok(Command::Dedup),
+        Rule::html_decode => Ok(Command::HtmlDecode),
+        Rule::normalize_space => Ok(Command::NormalizeSpace),
+        Rule::if_chain => {
+            let mut branches = Vec::new();
+            let mut otherwise = None;
+            for branch in pair.into_inner() {
+                match branch.as_rule() {
+                    Rule::if_matches => {
+                        let pattern = get_pair_param_with_index(&branch, 0);
+                        let value = get_pair_param_with_index(&branch, 1);
+                        branches.push((Compiled::new(pattern, compile_regex)?, value));
+                    }
+                    _ => otherwise = Some(get_pair_param(&branch)),
+                }
+            }
+            Ok(Command::IfMatches(branches, otherwise))
+        }
+        Rule::if_empty => Ok(Command::IfEmpty(get_pair_param(&pair))),
+        Rule::take | Rule::first => Ok(parse_take(pair)),
+        Rule::index => Ok(Command::Index(parse_digit(pair))),
+        Rule::optional => Ok(Command::Optional),
         _ => Err(CrawlerErr::UnsupportedTransformRule),
     }
 }
@@ -368,9 +667,9 @@ fn parse_transform_rule(pair: pest::iterators::Pair<Rule>) -> Result<Command, Cr
 fn parse_selector_rule(pair: pest::iterators::Pair<Rule>) -> Result<Command, CrawlerErr> {
     match pair.as_rule() {
         Rule::selector => {
-            let param = get_pair_param(&pair);
+            let param = get_pair_param(&pair).interpolate();
 
-            Ok(Command::Selector(param))
+            Ok(Command::Selector(Compiled::new(param, compile_selector)?))
         }
         Rule::parent => {
             let index = pair.into_inner().as_str().parse().unwrap_or(1);
@@ -384,17 +683,106 @@ fn parse_selector_rule(pair: pest::iterators::Pair<Rule>) -> Result<Command, Cra
             let index = pair.into_inner().as_str().parse().unwrap_or(1);
             Ok(Command::Nth(index))
         }
+        Rule::take | Rule::first => Ok(parse_take(pair)),
+        Rule::index => Ok(Command::Index(parse_digit(pair))),
+        Rule::optional => Ok(Command::Optional),
         _ => Err(CrawlerErr::UnsupportedSelectorRule),
     }
 }
 
+/// 解码 HTML 实体，命名实体使用 HTML5 的实体表，无法识别的 `&` 原样保留
+fn decode_html_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        match decode_entity(rest) {
+            Some((chars, len)) => {
+                decoded.extend(chars);
+                rest = &rest[len..];
+            }
+            None => decoded.push('&'),
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// 解码 `&` 之后的一个实体，返回解码出的字符与消耗的长度
+///
+/// 数字实体的分号可省略，无效码点解码为 U+FFFD；命名实体取最长匹配，
+/// 只有实体表中的旧式实体（如 `&amp`、`&copy`）允许省略分号
+fn decode_entity(text: &str) -> Option<(Vec<char>, usize)> {
+    if let Some(number) = text.strip_prefix('#') {
+        let (prefix, radix) = match number.as_bytes().first() {
+            Some(b'x' | b'X') => (1, 16),
+            _ => (0, 10),
+        };
+        let digits = number[prefix..]
+            .bytes()
+            .take_while(|byte| (*byte as char).is_digit(radix))
+            .count();
+        if digits == 0 {
+            return None;
+        }
+        let c = u32::from_str_radix(&number[prefix..prefix + digits], radix)
+            .ok()
+            .and_then(char::from_u32)
+            .filter(|c| *c != '\0')
+            .unwrap_or(char::REPLACEMENT_CHARACTER);
+        let len = 1 + prefix + digits;
+        let len = if text[len..].starts_with(';') { len + 1 } else { len };
+        return Some((vec![c], len));
+    }
+
+    let name_len = text.bytes().take_while(u8::is_ascii_alphanumeric).count();
+    let with_semicolon = text[name_len..].starts_with(';').then_some(name_len + 1);
+    with_semicolon
+        .into_iter()
+        .chain((1..=name_len).rev())
+        .find_map(|len| match markup5ever::data::NAMED_ENTITIES.get(&text[..len]) {
+            // 实体表中的前缀项码点为 0
+            Some(&(first, second)) if first != 0 => {
+                let chars = [first, second]
+                    .into_iter()
+                    .filter(|code| *code != 0)
+                    .filter_map(char::from_u32)
+                    .collect();
+                Some((chars, len))
+            }
+            _ => None,
+        })
+}
+
+/// `char::is_whitespace` 包含不换行空格 `\u{a0}`
+fn normalize_space(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `first()` 等同于 `take(1)`
+fn parse_take(pair: pest::iterators::Pair<Rule>) -> Command {
+    match pair.as_rule() {
+        Rule::take => Command::Take(parse_digit(pair)),
+        _ => Command::Take(1),
+    }
+}
+
+fn parse_digit(pair: pest::iterators::Pair<Rule>) -> usize {
+    pair.into_inner().as_str().trim().parse().unwrap_or(0)
+}
+
 fn parse_condition_rule(pair: pest::iterators::Pair<Rule>) -> Result<Command, CrawlerErr> {
     match pair.as_rule() {
         Rule::equals => Ok(Command::Equals(get_pair_param(&pair))),
         Rule::regex_match => {
             let pattern = get_pair_param(&pair);
 
-            Ok(Command::RegexMatch(pattern))
+            Ok(Command::RegexMatch(Compiled::new(pattern, compile_regex)?))
         }
         _ => Err(CrawlerErr::UnsupportedSelectorRule),
     }
@@ -404,7 +792,13 @@ fn parse_accessor_rule(pair: pest::iterators::Pair<Rule>) -> Result<Command, Cra
     match pair.as_rule() {
         Rule::html => Ok(Command::Html),
         Rule::attr => Ok(Command::Attr(get_pair_param(&pair))),
+        Rule::attrs => Ok(Command::Attrs(
+            (0..pair.clone().into_inner().count())
+                .map(|index| get_pair_param_with_index(&pair, index))
+                .collect(),
+        )),
         Rule::val => Ok(Command::Val),
+        Rule::count => Ok(Command::Count),
         _ => Err(CrawlerErr::UnsupportedSelectorRule),
     }
 }
@@ -424,18 +818,26 @@ fn get_pair_param_with_index(pair: &pest::iterators::Pair<Rule>, index: usize) -
     pair.clone()
         .into_inner()
         .nth(index)
-        .map_or(Param::StaticStr(String::new()), |inner_pair| {
-            let pair_str = inner_pair
-                .clone()
-                .into_inner()
-                .map(|p| p.as_str())
-                .collect::<String>();
-
-            match inner_pair.as_rule() {
-                Rule::param => Param::StaticStr(pair_str),
-                Rule::dynamic_param => Param::DynamicStr(pair_str),
-                _ => panic!("Unexpected rule type"),
+        .map_or(Param::StaticStr(String::new()), |inner_pair| match inner_pair.as_rule() {
+            Rule::param => Param::StaticStr(
+                inner_pair
+                    .into_inner()
+                    .map(|p| p.as_str())
+                    .collect::<String>(),
+            ),
+            Rule::dynamic_param => {
+                let mut inner = inner_pair.into_inner();
+                let name = inner.next().map_or("", |p| p.as_str()).to_string();
+
+                match inner.next() {
+                    Some(index) if index.as_rule() == Rule::dynamic_index_all => {
+                        Param::DynamicList(name)
+                    }
+                    Some(index) => Param::DynamicIndex(name, index.as_str().parse().unwrap_or(0)),
+                    None => Param::DynamicStr(name),
+                }
             }
+            _ => panic!("Unexpected rule type"),
         })
 }
 
@@ -472,10 +874,13 @@ impl Display for CrawlerScript {
 impl Display for Command {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Command::Selector(param) => write!(f, "selector({})", param),
+            Command::Selector(selector) => write!(f, "selector({})", selector.param),
             Command::Parent(param) => write!(f, "parent({})", param),
             Command::Prev(param) => write!(f, "prev({})", param),
             Command::Nth(param) => write!(f, "nth({})", param),
+            Command::Take(param) => write!(f, "take({})", param),
+            Command::Index(param) => write!(f, "index({})", param),
+            Command::Optional => write!(f, "optional()"),
             Command::Replace(param1, param2) => {
                 write!(f, "replace(from:{}, to:{})", param1, param2)
             }
@@ -487,15 +892,40 @@ impl Display for Command {
             Command::Prepend(param) => write!(f, "prepend({})", param),
             Command::Append(param) => write!(f, "append({})", param),
             Command::Delete(param) => write!(f, "delete({})", param),
-            Command::RegexExtract(param) => write!(f, "regex_extract({})", param),
-            Command::RegexMatch(param) => write!(f, "regex_match({})", param),
-            Command::RegexReplace(param1, param2) => {
-                write!(f, "regex_replace(reg:{}, replace:{})", param1, param2)
+            Command::RegexExtract(regex) => write!(f, "regex_extract({})", regex.param),
+            Command::RegexMatch(regex) => write!(f, "regex_match({})", regex.param),
+            Command::RegexReplace(regex, replace) => {
+                write!(f, "regex_replace(reg:{}, replace:{})", regex.param, replace)
             }
             Command::Equals(param) => write!(f, "equal({})", param),
             Command::Html => write!(f, "html()"),
             Command::Attr(param) => write!(f, "attr({})", param),
+            Command::Attrs(params) => write!(
+                f,
+                "attrs({})",
+                params
+                    .iter()
+                    .map(|param| param.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
             Command::Val => write!(f, "val()"),
+            Command::Count => write!(f, "count()"),
+            Command::Dedup => write!(f, "dedup()"),
+            Command::HtmlDecode => write!(f, "html_decode()"),
+            Command::NormalizeSpace => write!(f, "normalize_space()"),
+            Command::IfMatches(branches, otherwise) => {
+                let mut branches = branches
+                    .iter()
+                    .map(|(regex, value)| format!("if_matches(reg:{}, value:{})", regex.param, value));
+                write!(f, "{}", branches.next().unwrap_or_default())?;
+                branches.try_for_each(|branch| write!(f, ".{}", branch))?;
+                match otherwise {
+                    Some(value) => write!(f, ".otherwise({})", value),
+                    None => Ok(()),
+                }
+            }
+            Command::IfEmpty(param) => write!(f, "if_empty({})", param),
         }
     }
 }
@@ -505,6 +935,9 @@ impl Display for Param {
         match self {
             Param::StaticStr(param) => write!(f, "{}", param),
             Param::DynamicStr(param) => write!(f, "${{{}}}", param),
+            Param::DynamicIndex(param, index) => write!(f, "${{{}[{}]}}", param, index),
+            Param::DynamicList(param) => write!(f, "${{{}[*]}}", param),
+            Param::Interpolated(parts) => parts.iter().try_for_each(|part| write!(f, "{}", part)),
         }
     }
 }
@@ -522,8 +955,8 @@ mod tests {
         assert_eq!(crawler_script.commands.len(), 1);
 
         match &crawler_script.commands[0] {
-            Command::Selector(selector_str) => {
-                assert_eq!(*selector_str, Param::StaticStr("div.content".to_string()));
+            Command::Selector(selector) => {
+                assert_eq!(selector.param, Param::StaticStr("div.content".to_string()));
             }
             _ => panic!("Unexpected command type"),
         }
@@ -538,8 +971,8 @@ mod tests {
         assert_eq!(crawler_script.commands.len(), 3);
 
         match &crawler_script.commands[0] {
-            Command::Selector(selector_str) => {
-                assert_eq!(*selector_str, Param::StaticStr("div.content".to_owned()));
+            Command::Selector(selector) => {
+                assert_eq!(selector.param, Param::StaticStr("div.content".to_owned()));
             }
             _ => panic!("Unexpected first command type"),
         }
@@ -568,8 +1001,8 @@ mod tests {
         assert_eq!(crawler_script.commands.len(), 3);
 
         match &crawler_script.commands[0] {
-            Command::Selector(selector_str) => {
-                assert_eq!(*selector_str, Param::StaticStr("div.content".to_owned()));
+            Command::Selector(selector) => {
+                assert_eq!(selector.param, Param::StaticStr("div.content".to_owned()));
             }
             _ => panic!("Unexpected first command type"),
         }
@@ -594,8 +1027,8 @@ mod tests {
         assert_eq!(crawler_script.commands.len(), 4);
 
         match &crawler_script.commands[0] {
-            Command::Selector(selector_str) => {
-                assert_eq!(*selector_str, Param::StaticStr("div.content".to_owned()));
+            Command::Selector(selector) => {
+                assert_eq!(selector.param, Param::StaticStr("div.content".to_owned()));
             }
             _ => panic!("Unexpected first command type"),
         }
@@ -620,4 +1053,470 @@ mod tests {
             _ => panic!("Unexpected fourth command type"),
         }
     }
+
+    fn runtime_variable_with(name: &str, values: &[&str]) -> RuntimeVariable {
+        let mut runtime_variable = RuntimeVariable::new();
+        runtime_variable.insert(
+            name.to_string(),
+            values.iter().map(|value| value.to_string()).collect(),
+        );
+        runtime_variable
+    }
+
+    #[test]
+    fn test_compiled_scripts_on_large_document() {
+        let items = (0..2000)
+            .map(|index| {
+                format!(
+                    r#"<div class="item {}"><span class="id">ID-{:04}</span><a href="/v/{}">Title {}</a></div>"#,
+                    if index % 2 == 0 { "even" } else { "odd" },
+                    index,
+                    index,
+                    index
+                )
+            })
+            .collect::<String>();
+        let html = scraper::Html::parse_document(&format!("<html><body>{}</body></html>", items));
+        let root = vec![html.root_element()];
+
+        let extract = CrawlerScript::new(r#"selector(".item .id").val().regex_extract("[0-9]+")"#).unwrap();
+        let filter = CrawlerScript::new(r#"selector(".item.odd a").val().regex_match("^Title [0-9]*7$")"#).unwrap();
+        let replace = CrawlerScript::new(r#"selector(".item a").attr("href").regex_replace("^/v/", "https://example.com/v/")"#).unwrap();
+        assert!(matches!(&extract.commands[0], Command::Selector(selector) if selector.compiled.is_some()));
+
+        let start = std::time::Instant::now();
+        for _ in 0..20 {
+            let ids = extract.get_values(root.clone(), &mut RuntimeVariable::new()).unwrap();
+            assert_eq!(ids.len(), 2000);
+            assert_eq!(ids[0], "0000");
+            assert_eq!(ids[1999], "1999");
+
+            let titles = filter.get_values(root.clone(), &mut RuntimeVariable::new()).unwrap();
+            let expected = (0..2000)
+                .filter(|index| index % 2 == 1 && index % 10 == 7)
+                .map(|index| format!("Title {}", index))
+                .collect::<Vec<_>>();
+            assert_eq!(titles, expected);
+
+            let urls = replace.get_values(root.clone(), &mut RuntimeVariable::new()).unwrap();
+            assert_eq!(urls[42], "https://example.com/v/42");
+        }
+        log::debug!("20 次执行大页面脚本耗时: {:?}", start.elapsed());
+    }
+
+    #[test]
+    fn test_dynamic_compiled_params() {
+        let html = scraper::Html::parse_fragment(
+            r#"<div><p class="a">A1</p><p class="b">B1</p><p class="a">A2</p></div>"#,
+        );
+        let root = vec![html.root_element()];
+
+        let crawler_script = CrawlerScript::new(r#"selector(${class}).val()"#).unwrap();
+        let Command::Selector(selector) = &crawler_script.commands[0] else {
+            panic!("Unexpected command type");
+        };
+        assert!(selector.compiled.is_none());
+
+        // 变量值不同时使用各自编译的选择器，缓存在脚本的克隆之间共享
+        let values = crawler_script
+            .get_values(root.clone(), &mut runtime_variable_with("class", &["p.a"]))
+            .unwrap();
+        assert_eq!(values, vec!["A1", "A2"]);
+        let values = crawler_script
+            .clone()
+            .get_values(root.clone(), &mut runtime_variable_with("class", &["p.b"]))
+            .unwrap();
+        assert_eq!(values, vec!["B1"]);
+        let values = crawler_script
+            .get_values(root.clone(), &mut runtime_variable_with("class", &["p.a"]))
+            .unwrap();
+        assert_eq!(values, vec!["A1", "A2"]);
+        assert_eq!(selector.cache.lock().unwrap().len(), 2);
+
+        let crawler_script = CrawlerScript::new(r#"selector("p").val().regex_match(${pattern})"#).unwrap();
+        let values = crawler_script
+            .get_values(root.clone(), &mut runtime_variable_with("pattern", &["2$"]))
+            .unwrap();
+        assert_eq!(values, vec!["A2"]);
+
+        // 变量缺失或编译失败时仍在执行时报错
+        assert!(matches!(
+            crawler_script.get_values(root.clone(), &mut RuntimeVariable::new()),
+            Err(CrawlerErr::FieldNotFound(_))
+        ));
+        assert!(crawler_script
+            .get_values(root, &mut runtime_variable_with("pattern", &["[["]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_interpolated_selector() {
+        let html = scraper::Html::parse_fragment(
+            r#"<ul><li data-id="ABC-001">first</li><li data-id="ABC-002">second</li></ul>"#,
+        );
+        let root = vec![html.root_element()];
+
+        let crawler_script =
+            CrawlerScript::new(r#"selector("li[data-id='${crawl_name}']").val()"#).unwrap();
+        let Command::Selector(selector) = &crawler_script.commands[0] else {
+            panic!("Unexpected command type");
+        };
+        assert_eq!(
+            selector.param,
+            Param::Interpolated(vec![
+                Param::StaticStr("li[data-id='".to_string()),
+                Param::DynamicStr("crawl_name".to_string()),
+                Param::StaticStr("']".to_string()),
+            ])
+        );
+        assert_eq!(selector.param.to_string(), "li[data-id='${crawl_name}']");
+
+        let values = crawler_script
+            .get_values(root.clone(), &mut runtime_variable_with("crawl_name", &["ABC-002"]))
+            .unwrap();
+        assert_eq!(values, vec!["second"]);
+
+        // 下标参数与多个变量
+        let crawler_script = CrawlerScript::new(
+            r#"selector("${tag}[data-id='${ids[-1]}']").val()"#,
+        )
+        .unwrap();
+        let mut runtime_variable = runtime_variable_with("ids", &["ABC-002", "ABC-001"]);
+        runtime_variable.insert("tag".to_string(), vec!["li".to_string()]);
+        let values = crawler_script.get_values(root.clone(), &mut runtime_variable).unwrap();
+        assert_eq!(values, vec!["first"]);
+
+        assert!(matches!(
+            crawler_script.get_values(root, &mut RuntimeVariable::new()),
+            Err(CrawlerErr::FieldNotFound(_))
+        ));
+
+        // 没有变量的字符串保持静态，在解析时编译
+        let crawler_script = CrawlerScript::new(r#"selector("li[data-id='$1']").val()"#).unwrap();
+        assert!(matches!(&crawler_script.commands[0], Command::Selector(selector) if selector.compiled.is_some()));
+    }
+
+    #[test]
+    fn test_dynamic_param_index_and_list() {
+        let crawler_script =
+            CrawlerScript::new(r#"selector("li").html().append(${ids[0]}).prepend(${ids[*]})"#)
+                .unwrap();
+
+        match &crawler_script.commands[2] {
+            Command::Append(param) => {
+                assert_eq!(*param, Param::DynamicIndex("ids".to_string(), 0));
+                assert_eq!(param.to_string(), "${ids[0]}");
+            }
+            _ => panic!("Unexpected append command type"),
+        }
+
+        match &crawler_script.commands[3] {
+            Command::Prepend(param) => {
+                assert_eq!(*param, Param::DynamicList("ids".to_string()));
+                assert_eq!(param.to_string(), "${ids[*]}");
+            }
+            _ => panic!("Unexpected prepend command type"),
+        }
+    }
+
+    #[test]
+    fn test_dynamic_param_index_value() {
+        let runtime_variable = runtime_variable_with("ids", &["a", "b", "c"]);
+
+        let first = Param::DynamicIndex("ids".to_string(), 0);
+        let last = Param::DynamicIndex("ids".to_string(), -1);
+        let out_of_range = Param::DynamicIndex("ids".to_string(), 3);
+        let list = Param::DynamicList("ids".to_string());
+
+        assert_eq!(first.get_value(&runtime_variable).unwrap(), "a");
+        assert_eq!(last.get_value(&runtime_variable).unwrap(), "c");
+        assert!(matches!(
+            out_of_range.get_value(&runtime_variable),
+            Err(CrawlerErr::DynIndexOutOfRange(_, 3, 3))
+        ));
+        assert!(matches!(
+            list.get_value(&runtime_variable),
+            Err(CrawlerErr::DynListNotAllowed(_))
+        ));
+    }
+
+    #[test]
+    fn test_dynamic_param_list_expansion() {
+        let html = scraper::Html::parse_fragment("<ul><li>x</li><li>y</li></ul>");
+        let root = vec![html.root_element()];
+
+        let crawler_script =
+            CrawlerScript::new(r#"selector("li").val().append(${ids[*]})"#).unwrap();
+        let mut runtime_variable = runtime_variable_with("ids", &["1", "2"]);
+        let values = crawler_script
+            .get_values(root.clone(), &mut runtime_variable)
+            .unwrap();
+        assert_eq!(values, vec!["x1".to_string(), "y2".to_string()]);
+
+        let mut runtime_variable = runtime_variable_with("ids", &["1", "2", "3"]);
+        assert!(matches!(
+            crawler_script.get_values(root, &mut runtime_variable),
+            Err(CrawlerErr::DynLengthMismatch(_, 3, 2))
+        ));
+    }
+
+    #[test]
+    fn test_attrs_multiple_attributes() {
+        let html = scraper::Html::parse_fragment(
+            r#"<div><a class="tile" data-preview="p1.jpg" href="f1.jpg"></a><a class="tile" data-preview="p2.jpg" href="f2.jpg"></a></div>"#,
+        );
+        let crawler_script =
+            CrawlerScript::new(r#"selector(".tile").attrs("data-preview", "href")"#).unwrap();
+
+        assert_eq!(crawler_script.rule, Rule::value_access);
+        assert_eq!(crawler_script.value_arity(), 2);
+        assert_eq!(
+            crawler_script.commands[1].to_string(),
+            "attrs(data-preview,href)"
+        );
+
+        let values = crawler_script
+            .get_values(vec![html.root_element()], &mut RuntimeVariable::new())
+            .unwrap();
+        assert_eq!(values, vec!["p1.jpg", "f1.jpg", "p2.jpg", "f2.jpg"]);
+
+        // attrs 只能作为取值脚本的访问器
+        assert!(CrawlerScript::new(r#"attrs("a", "b").equals("x")"#).is_err());
+    }
+
+    #[test]
+    fn test_take() {
+        let images = (1..=10)
+            .map(|index| format!(r#"<img src="{}.jpg">"#, index))
+            .collect::<String>();
+        let html = scraper::Html::parse_fragment(&format!("<div>{}</div>", images));
+
+        let crawler_script = CrawlerScript::new(r#"selector("img").take(3)"#).unwrap();
+        assert_eq!(crawler_script.rule, Rule::element_access);
+        assert_eq!(crawler_script.commands[1].to_string(), "take(3)");
+        let elements = crawler_script
+            .get_elements(vec![html.root_element()], &mut RuntimeVariable::new())
+            .unwrap();
+        assert_eq!(elements.len(), 3);
+
+        let crawler_script = CrawlerScript::new(r#"selector("img").take(3).attr("src")"#).unwrap();
+        let values = crawler_script
+            .get_values(vec![html.root_element()], &mut RuntimeVariable::new())
+            .unwrap();
+        assert_eq!(values, vec!["1.jpg", "2.jpg", "3.jpg"]);
+
+        // 转换之后同样可以截取，first() 等同于 take(1)
+        let crawler_script =
+            CrawlerScript::new(r#"selector("img").attr("src").uppercase().first()"#).unwrap();
+        let values = crawler_script
+            .get_values(vec![html.root_element()], &mut RuntimeVariable::new())
+            .unwrap();
+        assert_eq!(values, vec!["1.JPG"]);
+
+        assert!(CrawlerScript::new(r#"selector("img").take()"#).is_err());
+    }
+
+    #[test]
+    fn test_index() {
+        let html = scraper::Html::parse_fragment(
+            r#"<ul><li>first</li><li>second</li><li>third</li></ul>"#,
+        );
+        let root = vec![html.root_element()];
+
+        let crawler_script = CrawlerScript::new(r#"selector("li").index(1)"#).unwrap();
+        assert_eq!(crawler_script.rule, Rule::element_access);
+        assert_eq!(crawler_script.commands[1].to_string(), "index(1)");
+        let elements = crawler_script
+            .get_elements(root.clone(), &mut RuntimeVariable::new())
+            .unwrap();
+        assert_eq!(elements.len(), 1);
+        assert_eq!(elements[0].text().collect::<String>(), "second");
+
+        let values = CrawlerScript::new(r#"selector("li").val().index(2)"#)
+            .unwrap()
+            .get_values(root.clone(), &mut RuntimeVariable::new())
+            .unwrap();
+        assert_eq!(values, vec!["third"]);
+
+        // 超出范围时没有结果
+        let values = CrawlerScript::new(r#"selector("li").index(3).val()"#)
+            .unwrap()
+            .get_values(root, &mut RuntimeVariable::new())
+            .unwrap();
+        assert!(values.is_empty());
+    }
+
+    #[test]
+    fn test_html_decode() {
+        // 文本中的实体被二次转义，val() 只能解码一层
+        let html = scraper::Html::parse_fragment(
+            r#"<div><h1 title="Tom &amp;amp; Jerry&amp;#039;s">Tom &amp;amp; Jerry&amp;#039;s</h1></div>"#,
+        );
+        let root = vec![html.root_element()];
+
+        let values = CrawlerScript::new(r#"selector("h1").val().html_decode()"#)
+            .unwrap()
+            .get_values(root.clone(), &mut RuntimeVariable::new())
+            .unwrap();
+        assert_eq!(values, vec!["Tom & Jerry's"]);
+
+        let values = CrawlerScript::new(r#"selector("h1").attr("title").html_decode()"#)
+            .unwrap()
+            .get_values(root.clone(), &mut RuntimeVariable::new())
+            .unwrap();
+        assert_eq!(values, vec!["Tom & Jerry's"]);
+
+        // 元素脚本中解码后再过滤
+        let elements = CrawlerScript::new(r#"selector("h1").val().html_decode().equals("Tom & Jerry's")"#)
+            .unwrap()
+            .get_elements(root, &mut RuntimeVariable::new())
+            .unwrap();
+        assert_eq!(elements.len(), 1);
+
+        assert_eq!(decode_html_entities("Tom &amp; Jerry&#039;s"), "Tom & Jerry's");
+        assert_eq!(decode_html_entities("a &lt; b <c> &#x41;"), "a < b <c> A");
+        assert_eq!(decode_html_entities("no entities"), "no entities");
+
+        // 字面的 `<`、`>` 与无法识别的 `&` 原样保留
+        assert_eq!(decode_html_entities("a <b> c &amp; d</b> > e"), "a <b> c & d</b> > e");
+        assert_eq!(decode_html_entities("<br/>R&D &unknown; & &#; &#x;"), "<br/>R&D &unknown; & &#; &#x;");
+        assert_eq!(decode_html_entities("&copy2024 &notit; &nbsp;"), "©2024 ¬it; \u{a0}");
+        assert_eq!(decode_html_entities("&#128512;&#x1F600&#0;&#xD800;"), "😀😀\u{FFFD}\u{FFFD}");
+        assert_eq!(decode_html_entities("&NotEqualTilde;"), "\u{2242}\u{338}");
+    }
+
+    #[test]
+    fn test_decode_entities_and_normalize_space() {
+        let html = scraper::Html::parse_fragment(
+            "<div><h1>  Tom &amp;amp;&nbsp;Jerry&amp;#39;s \n\t Summer&nbsp;&nbsp;Vacation  </h1></div>",
+        );
+        let root = vec![html.root_element()];
+
+        let values = CrawlerScript::new(r#"selector("h1").val().decode_entities().normalize_space()"#)
+            .unwrap()
+            .get_values(root.clone(), &mut RuntimeVariable::new())
+            .unwrap();
+        assert_eq!(values, vec!["Tom & Jerry's Summer Vacation"]);
+
+        // 与其他转换命令组合，并可在元素脚本中用于过滤
+        let values = CrawlerScript::new(r#"selector("h1").val().normalize_space().uppercase().regex_replace(" ", "_")"#)
+            .unwrap()
+            .get_values(root.clone(), &mut RuntimeVariable::new())
+            .unwrap();
+        assert_eq!(values, vec!["TOM_&AMP;_JERRY&#39;S_SUMMER_VACATION"]);
+
+        let elements = CrawlerScript::new(
+            r#"selector("h1").val().decode_entities().normalize_space().equals("Tom & Jerry's Summer Vacation")"#,
+        )
+        .unwrap()
+        .get_elements(root, &mut RuntimeVariable::new())
+        .unwrap();
+        assert_eq!(elements.len(), 1);
+
+        let crawler_script = CrawlerScript::new(r#"val().decode_entities().normalize_space()"#).unwrap();
+        assert!(matches!(
+            crawler_script.commands[..],
+            [Command::Val, Command::HtmlDecode, Command::NormalizeSpace]
+        ));
+
+        assert_eq!(normalize_space("\u{a0} a\u{a0}\u{a0}b \r\n c \u{3000}"), "a b c");
+        assert_eq!(normalize_space(""), "");
+    }
+
+    #[test]
+    fn test_count() {
+        let html = scraper::Html::parse_fragment(
+            r#"<ul><li class="part">A</li><li class="part">B</li><li>C</li><li class="part">D</li></ul>"#,
+        );
+        let root = vec![html.root_element()];
+
+        let values = CrawlerScript::new(r#"selector("li.part").count()"#)
+            .unwrap()
+            .get_values(root.clone(), &mut RuntimeVariable::new())
+            .unwrap();
+        assert_eq!(values, vec!["3"]);
+
+        let values = CrawlerScript::new(r#"selector("li").val().regex_match("^[AB]$").count().prepend("parts: ")"#)
+            .unwrap()
+            .get_values(root.clone(), &mut RuntimeVariable::new())
+            .unwrap();
+        assert_eq!(values, vec!["parts: 2"]);
+
+        // 没有匹配元素时为 "0"
+        let values = CrawlerScript::new(r#"selector("li.missing").count()"#)
+            .unwrap()
+            .get_values(root.clone(), &mut RuntimeVariable::new())
+            .unwrap();
+        assert_eq!(values, vec!["0"]);
+
+        let values = CrawlerScript::new(r#"selector("li").val().equals("E").count()"#)
+            .unwrap()
+            .get_values(root, &mut RuntimeVariable::new())
+            .unwrap();
+        assert_eq!(values, vec!["0"]);
+
+        assert!(CrawlerScript::new(r#"selector("li").count().val()"#).is_err());
+    }
+
+    #[test]
+    fn test_dedup() {
+        let html = scraper::Html::parse_fragment(
+            r#"<div><span class="tag">A</span><span class="tag">B</span><span class="tag">A</span></div>"#,
+        );
+        let root = vec![html.root_element()];
+
+        let values = CrawlerScript::new(r#"selector(".tag").val()"#)
+            .unwrap()
+            .get_values(root.clone(), &mut RuntimeVariable::new())
+            .unwrap();
+        assert_eq!(values, vec!["A", "B", "A"]);
+
+        let crawler_script = CrawlerScript::new(r#"selector(".tag").val().dedup()"#).unwrap();
+        assert_eq!(crawler_script.commands[2].to_string(), "dedup()");
+        let values = crawler_script
+            .get_values(root, &mut RuntimeVariable::new())
+            .unwrap();
+        assert_eq!(values, vec!["A", "B"]);
+    }
+
+    #[test]
+    fn test_if_matches() {
+        let html = scraper::Html::parse_fragment(r#"<ul><li>有码</li><li>无码</li><li>素人</li><li> </li></ul>"#);
+        let root = vec![html.root_element()];
+
+        let values = CrawlerScript::new(r#"selector("li").val().if_matches("^有码$", "censored")"#)
+            .unwrap()
+            .get_values(root.clone(), &mut RuntimeVariable::new())
+            .unwrap();
+        assert_eq!(values, vec!["censored", "无码", "素人", " "]);
+
+        let crawler_script = CrawlerScript::new(
+            r#"selector("li").val().if_matches("^有码$", "censored").if_matches("无码", "uncensored").otherwise("other").uppercase()"#,
+        )
+        .unwrap();
+        assert_eq!(
+            crawler_script.commands[2].to_string(),
+            "if_matches(reg:^有码$, value:censored).if_matches(reg:无码, value:uncensored).otherwise(other)"
+        );
+        let values = crawler_script
+            .get_values(root.clone(), &mut RuntimeVariable::new())
+            .unwrap();
+        assert_eq!(values, vec!["CENSORED", "UNCENSORED", "OTHER", "OTHER"]);
+
+        // 匹配时可替换为空值，再由后续过滤去掉
+        let values = CrawlerScript::new(r#"selector("li").val().if_matches("码","").regex_match(".+")"#)
+            .unwrap()
+            .get_values(root.clone(), &mut RuntimeVariable::new())
+            .unwrap();
+        assert_eq!(values, vec!["素人", " "]);
+
+        let values = CrawlerScript::new(r#"selector("li").val().if_empty("unknown").append("!")"#)
+            .unwrap()
+            .get_values(root, &mut RuntimeVariable::new())
+            .unwrap();
+        assert_eq!(values, vec!["有码!", "无码!", "素人!", "unknown!"]);
+
+        assert!(CrawlerScript::new(r#"val().otherwise("x")"#).is_err());
+        assert!(CrawlerScript::new(r#"val().if_matches("[", "x")"#).is_err());
+    }
 }
@@ -2,7 +2,7 @@
 mod tests {
     use std::collections::HashMap;
 
-    use crate::Template;
+    use crate::{HostLimiter, Template};
 
     #[derive(Default, Debug, Clone)]
     struct Movie {
@@ -127,4 +127,1430 @@ mod tests {
             assert_eq!(result.actors, vec!["演员1".to_string(),]);
         });
     }
+
+    #[test]
+    fn test_crawler_with_html_fixtures() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async move {
+            let base_url = "https://fixtures.invalid";
+            let html_by_url = HashMap::from([
+                (
+                    format!("{}/search?q=TEST-MOVIE1&f=all", base_url),
+                    SAMPLE_SEARCH.to_string(),
+                ),
+                (format!("{}/detail/1", base_url), SAMPLE_DETAIL.to_string()),
+            ]);
+
+            let template = Template::<Movie>::from_yaml(SAMPLE_YAML).unwrap();
+
+            let mut init_params = HashMap::new();
+            init_params.insert("base_url", base_url.to_string());
+            init_params.insert("crawl_name", "TEST-MOVIE1".to_string());
+
+            // 搜索页与详情页两个工作流都从提供的页面内容中读取
+            let result = template
+                .crawler_with_html(&init_params, &html_by_url)
+                .await
+                .unwrap();
+
+            assert_eq!(result.title, "TEST-MOVIE1 的title");
+            assert_eq!(result.detail_url, Some(format!("{}/detail/1", base_url)));
+            assert_eq!(result.tags.map(|tags| tags.len()), Some(7));
+            assert_eq!(result.actors, vec!["演员1".to_string()]);
+
+            // 缺少的页面按 404 处理
+            init_params.insert("crawl_name", "MISSING-001".to_string());
+            assert!(matches!(
+                template.crawler_with_html(&init_params, &html_by_url).await,
+                Err(crate::CrawlerErr::NotFound(_))
+            ));
+        });
+    }
+
+    const LOOKBACK_YAML: &str = r#"
+entrypoint: "${base_url}/search?q=${crawl_name}&f=all"
+nodes:
+  main:
+    script: selector(".movie-list")
+    children:
+      match_div:
+        script: selector(".video-title>strong").val().uppercase().equals(${crawl_name}).parent(2)
+        children:
+          detail_url:
+            script: attr("href").insert(0,${base_url})
+            request: true
+            children:
+              title: selector(".video-detail .current-title").val()
+              thumbnail:
+                script: selector(".movie-list img").attr("src")
+                source: entrypoint
+              tags:
+                script: selector(".movie-list .meta").val()
+                source: entrypoint
+              actors: selector(".female").prev().val()
+"#;
+
+    #[test]
+    fn test_source_reads_previous_page() {
+        let base_url = "https://fixtures.invalid";
+        let html_by_url = HashMap::from([
+            (
+                format!("{}/search?q=TEST-MOVIE1&f=all", base_url),
+                SAMPLE_SEARCH.to_string(),
+            ),
+            (format!("{}/detail/1", base_url), SAMPLE_DETAIL.to_string()),
+        ]);
+        let template = Template::<Movie>::from_yaml(LOOKBACK_YAML).unwrap();
+        assert_eq!(
+            template.retained_sources,
+            std::collections::HashSet::from([crate::ENTRYPOINT_SOURCE.to_string()])
+        );
+
+        let mut init_params = HashMap::new();
+        init_params.insert("base_url", base_url.to_string());
+        init_params.insert("crawl_name", "TEST-MOVIE1".to_string());
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let result = rt
+            .block_on(template.crawler_with_html(&init_params, &html_by_url))
+            .unwrap();
+
+        // 标题与演员来自详情页，封面与日期来自搜索页
+        assert_eq!(result.title.trim(), "标题1");
+        assert_eq!(result.actors, vec!["演员1".to_string()]);
+        assert_eq!(result.thumbnail, Some("https://cdn.example.com/111.jpg".to_string()));
+        assert_eq!(result.tags.map(|tags| tags[0].clone()), Some("2017-08-11".to_string()));
+
+        // 同步爬取同样保留入口页面
+        let mut server = mockito::Server::new();
+        let _search = server
+            .mock("GET", "/search?q=TEST-MOVIE1&f=all")
+            .with_body(SAMPLE_SEARCH)
+            .create();
+        let _detail = server.mock("GET", "/detail/1").with_body(SAMPLE_DETAIL).create();
+        init_params.insert("base_url", server.url());
+        let result = template.crawler_block(&init_params).unwrap();
+        assert_eq!(result.title.trim(), "标题1");
+        assert_eq!(result.thumbnail, Some("https://cdn.example.com/111.jpg".to_string()));
+
+        // 没有节点引用先前页面时不保留任何页面
+        let sample = Template::<Movie>::from_yaml(SAMPLE_YAML).unwrap();
+        assert!(sample.retained_sources.is_empty());
+    }
+
+    #[test]
+    fn test_source_validation() {
+        // 请求节点引用另一个请求节点的页面时，被引用的工作流先执行
+        let ordered = r#"
+entrypoint: "${base_url}"
+nodes:
+  a_url:
+    script: selector("a.review").attr("href")
+    request: true
+    children:
+      tags:
+        script: selector(".score").val()
+        source: z_url
+  z_url:
+    script: selector("a.detail").attr("href")
+    request: true
+    children:
+      title: selector("h1").val()
+"#;
+        let template = Template::<Movie>::from_yaml(ordered).unwrap();
+        let keys: Vec<_> = template.workflows.iter().map(|workflow| workflow.url_key.as_str()).collect();
+        assert_eq!(keys, vec!["", "z_url", "a_url"]);
+
+        let invalid = [
+            // 引用不存在的请求节点
+            (
+                r#"
+entrypoint: "${base_url}"
+nodes:
+  title:
+    script: selector("h1").val()
+    source: missing_url
+"#,
+                "neither a request node",
+            ),
+            // 入口页面的节点没有更早的页面可引用
+            (
+                r#"
+entrypoint: "${base_url}"
+nodes:
+  title:
+    script: selector("h1").val()
+    source: entrypoint
+"#,
+                "fetched before",
+            ),
+            // 引用自身所在的工作流
+            (
+                r#"
+entrypoint: "${base_url}"
+nodes:
+  detail_url:
+    script: selector("a").attr("href")
+    request: true
+    children:
+      title:
+        script: selector("h1").val()
+        source: detail_url
+"#,
+                "fetched before",
+            ),
+            // 相互引用
+            (
+                r#"
+entrypoint: "${base_url}"
+nodes:
+  a_url:
+    script: selector("a.first").attr("href")
+    request: true
+    children:
+      title:
+        script: selector("h1").val()
+        source: b_url
+  b_url:
+    script: selector("a.second").attr("href")
+    request: true
+    children:
+      tags:
+        script: selector(".tag").val()
+        source: a_url
+"#,
+                "cycle",
+            ),
+        ];
+        for (yaml, message) in invalid {
+            let error = Template::<Movie>::from_yaml(yaml).unwrap_err().to_string();
+            assert!(error.contains(message), "{}", error);
+        }
+    }
+
+    /// 从内存返回页面并记录请求地址的 Fetcher
+    #[derive(Debug, Default)]
+    struct MockFetcher {
+        pages: HashMap<String, String>,
+        requested: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl crate::Fetcher for MockFetcher {
+        fn fetch<'a>(
+            &'a self,
+            url: &'a str,
+        ) -> futures_util::future::BoxFuture<'a, Result<String, crate::CrawlerErr>> {
+            self.requested.lock().unwrap().push(url.to_string());
+            let body = self
+                .pages
+                .get(url)
+                .cloned()
+                .ok_or_else(|| crate::CrawlerErr::NotFound(url.to_string()));
+            Box::pin(async move { body })
+        }
+    }
+
+    #[test]
+    fn test_crawler_with_mock_fetcher() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async move {
+            let base_url = "https://mock.invalid";
+            let search_url = format!("{}/search?q=TEST-MOVIE1&f=all", base_url);
+            let detail_url = format!("{}/detail/1", base_url);
+            let fetcher = MockFetcher {
+                pages: HashMap::from([
+                    (search_url.clone(), SAMPLE_SEARCH.to_string()),
+                    (detail_url.clone(), SAMPLE_DETAIL.to_string()),
+                ]),
+                ..Default::default()
+            };
+            let requested = fetcher.requested.clone();
+
+            let template = Template::<Movie>::from_yaml(SAMPLE_YAML)
+                .unwrap()
+                .with_fetcher(fetcher);
+
+            let mut init_params = HashMap::new();
+            init_params.insert("base_url", base_url.to_string());
+            init_params.insert("crawl_name", "TEST-MOVIE1".to_string());
+
+            let result = template.crawler(&init_params).await.unwrap();
+
+            assert_eq!(result.title, "TEST-MOVIE1 的title");
+            assert_eq!(result.detail_url, Some(detail_url.clone()));
+            assert_eq!(result.actors, vec!["演员1".to_string()]);
+            assert_eq!(*requested.lock().unwrap(), vec![search_url, detail_url]);
+        });
+    }
+
+    const MIRROR_YAML: &str = r#"
+extends: sample.yaml
+entrypoint: "${mirror_url}/search?q=${crawl_name}&f=all"
+nodes:
+  main:
+    children:
+      match_div:
+        children:
+          thumbnail: selector("img").attr("loading")
+"#;
+
+    fn sample_loader(name: &str) -> std::io::Result<String> {
+        match name {
+            "sample.yaml" => Ok(SAMPLE_YAML.to_string()),
+            _ => Err(std::io::ErrorKind::NotFound.into()),
+        }
+    }
+
+    #[test]
+    fn test_template_extends() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async move {
+            let mut server = mockito::Server::new_async().await;
+            let mut mirror_server = mockito::Server::new_async().await;
+
+            let url = server.url();
+
+            let _m = mirror_server
+                .mock("GET", "/search?q=TEST-MOVIE1&f=all")
+                .with_status(200)
+                .with_body(SAMPLE_SEARCH)
+                .create();
+
+            let _m2 = server
+                .mock("GET", "/detail/1")
+                .with_status(200)
+                .with_body(SAMPLE_DETAIL)
+                .create();
+
+            let template = Template::<Movie>::from_yaml_with_loader(MIRROR_YAML, sample_loader).unwrap();
+
+            let mut init_params = HashMap::new();
+            init_params.insert("base_url", url.clone());
+            init_params.insert("mirror_url", mirror_server.url());
+            init_params.insert("crawl_name", "TEST-MOVIE1".to_string());
+
+            let result = template.crawler(&init_params).await.unwrap();
+
+            // 覆盖的节点使用子模板脚本，其余节点继承自父模板
+            assert_eq!(result.thumbnail, Some("lazy".to_string()));
+            assert_eq!(result.title, "TEST-MOVIE1 的title");
+            assert_eq!(result.detail_url, Some(format!("{}/detail/1", url)));
+            assert_eq!(result.actors, vec!["演员1".to_string(),]);
+        });
+    }
+
+    #[test]
+    fn test_template_extends_cycle() {
+        let loader = |name: &str| match name {
+            "a.yaml" => Ok("extends: b.yaml".to_string()),
+            "b.yaml" => Ok("extends: a.yaml".to_string()),
+            _ => sample_loader(name),
+        };
+
+        let err = Template::<Movie>::from_yaml_with_loader("extends: a.yaml", loader).unwrap_err();
+        match err {
+            crate::CrawlerErr::TemplateExtendsCycle(chain) => {
+                assert_eq!(chain, "a.yaml -> b.yaml -> a.yaml");
+            }
+            err => panic!("Unexpected error: {}", err),
+        }
+
+        assert!(matches!(
+            Template::<Movie>::from_yaml_with_loader("extends: missing.yaml", sample_loader),
+            Err(crate::CrawlerErr::IOError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_with_parameter_overrides_env() {
+        let yaml = r#"
+entrypoint: "${base_url}/search?q=${crawl_name}"
+env:
+  base_url: ["https://javdb.com"]
+nodes:
+  title: selector("h1").val()
+"#;
+        let template = Template::<Movie>::from_yaml(yaml)
+            .unwrap()
+            .with_parameter("base_url", "https://javdb368.com");
+
+        let mut parameters = template.get_start_parameters();
+        parameters.insert("crawl_name".to_string(), vec!["ABC-001".to_string()]);
+        assert_eq!(
+            template.build_entrypoint_url(&parameters).unwrap(),
+            "https://javdb368.com/search?q=ABC-001"
+        );
+    }
+
+    #[test]
+    fn test_request_timeout() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async move {
+            let mut server = mockito::Server::new_async().await;
+
+            let _m = server
+                .mock("GET", "/slow")
+                .with_status(200)
+                .with_chunked_body(|writer| {
+                    std::thread::sleep(std::time::Duration::from_secs(3));
+                    writer.write_all(b"<html></html>")
+                })
+                .create();
+
+            let yaml = r#"
+entrypoint: "${base_url}/slow"
+timeout: 1
+nodes:
+  title: selector("title").val()
+"#;
+            let template = Template::<Movie>::from_yaml(yaml).unwrap();
+            assert_eq!(template.timeout(), std::time::Duration::from_secs(1));
+
+            let mut init_params = HashMap::new();
+            init_params.insert("base_url", server.url());
+
+            match template.crawler(&init_params).await {
+                Err(crate::CrawlerErr::Timeout(url)) => {
+                    assert_eq!(url, format!("{}/slow", server.url()));
+                }
+                Err(err) => panic!("Unexpected error: {}", err),
+                Ok(_) => panic!("Expected timeout error"),
+            }
+        });
+    }
+
+    #[test]
+    fn test_default_request_timeout() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async move {
+            let mut server = mockito::Server::new_async().await;
+
+            let _m = server
+                .mock("GET", "/slow")
+                .with_status(200)
+                .with_chunked_body(|writer| {
+                    std::thread::sleep(std::time::Duration::from_secs(3));
+                    writer.write_all(b"<html></html>")
+                })
+                .create();
+
+            let yaml = r#"
+entrypoint: "${base_url}/slow"
+nodes:
+  title: selector("title").val()
+"#;
+            let template = Template::<Movie>::from_yaml(yaml).unwrap();
+            assert_eq!(
+                template.timeout(),
+                std::time::Duration::from_secs(crate::DEFAULT_REQUEST_TIMEOUT_SECS)
+            );
+
+            let template = template
+                .with_default_timeout(std::time::Duration::from_secs(1))
+                .unwrap();
+            assert_eq!(template.timeout(), std::time::Duration::from_secs(1));
+
+            let mut init_params = HashMap::new();
+            init_params.insert("base_url", server.url());
+
+            let result = template.crawler(&init_params).await;
+            assert!(matches!(result, Err(crate::CrawlerErr::Timeout(_))));
+        });
+
+        let yaml = r#"
+entrypoint: "http://localhost/slow"
+request_timeout_seconds: 5
+nodes:
+  title: selector("title").val()
+"#;
+        let template = Template::<Movie>::from_yaml(yaml)
+            .unwrap()
+            .with_default_timeout(std::time::Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(template.timeout(), std::time::Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_http_status_errors() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async move {
+            let mut server = mockito::Server::new_async().await;
+
+            let _m = server
+                .mock("GET", "/search?q=MISSING-001&f=all")
+                .with_status(404)
+                .create();
+
+            let _m2 = server
+                .mock("GET", "/search?q=BROKEN-001&f=all")
+                .with_status(500)
+                .create();
+
+            let template = Template::<Movie>::from_yaml(SAMPLE_YAML).unwrap();
+
+            let mut init_params = HashMap::new();
+            init_params.insert("base_url", server.url());
+            init_params.insert("crawl_name", "MISSING-001".to_string());
+
+            match template.crawler(&init_params).await {
+                Err(crate::CrawlerErr::NotFound(url)) => {
+                    assert_eq!(url, format!("{}/search?q=MISSING-001&f=all", server.url()));
+                }
+                Err(err) => panic!("Unexpected error: {}", err),
+                Ok(_) => panic!("Expected not found error"),
+            }
+
+            init_params.insert("crawl_name", "BROKEN-001".to_string());
+
+            match template.crawler(&init_params).await {
+                Err(crate::CrawlerErr::HttpStatus(status, _)) => assert_eq!(status, 500),
+                Err(err) => panic!("Unexpected error: {}", err),
+                Ok(_) => panic!("Expected HTTP status error"),
+            }
+        });
+    }
+
+    #[test]
+    fn test_best_effort_partial_crawl() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async move {
+            let mut server = mockito::Server::new_async().await;
+
+            let _m = server
+                .mock("GET", "/movie")
+                .with_status(200)
+                .with_body(r#"<h1>TEST-001</h1><div class="actor">演员A</div><div class="actor">演员B</div>"#)
+                .create();
+
+            let yaml = r#"
+entrypoint: "${base_url}/movie"
+nodes:
+  title: selector("h1").val()
+  actors: selector(".actor").val()
+  tags:
+    script: selector(".tag").val()
+    required: true
+"#;
+            let mut init_params = HashMap::new();
+            init_params.insert("base_url", server.url());
+
+            // 默认模式下必需节点失败会中止整个爬取
+            let template = Template::<Movie>::from_yaml(yaml).unwrap();
+            assert!(template.crawler(&init_params).await.is_err());
+
+            // 尽力模式下保留其余字段，并记录失败节点
+            let template = template.with_best_effort(true);
+            let (result, warnings) = template.crawler_with_warnings(&init_params).await.unwrap();
+
+            assert_eq!(result.title, "TEST-001");
+            assert_eq!(result.actors, vec!["演员A".to_string(), "演员B".to_string()]);
+            assert_eq!(result.tags, None);
+            assert_eq!(warnings.len(), 1);
+            assert_eq!(warnings[0].node, "tags");
+        });
+    }
+
+    #[test]
+    fn test_node_error_names_node_and_url() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async move {
+            let mut server = mockito::Server::new_async().await;
+
+            let _m = server
+                .mock("GET", "/movie")
+                .with_status(200)
+                .with_body(r#"<h1>TEST-001</h1>"#)
+                .create();
+
+            let yaml = r#"
+entrypoint: "${base_url}/movie"
+nodes:
+  title:
+    script: selector("${title_selector}").val()
+    required: true
+"#;
+            let mut init_params = HashMap::new();
+            init_params.insert("base_url", server.url());
+            init_params.insert("title_selector", "h1[[".to_string());
+
+            let template = Template::<Movie>::from_yaml(yaml).unwrap();
+            let err = template.crawler(&init_params).await.unwrap_err();
+
+            let url = format!("{}/movie", server.url());
+            match &err {
+                crate::CrawlerErr::NodeError { node, url: failed_url, source } => {
+                    assert_eq!(node, "title");
+                    assert_eq!(failed_url, &url);
+                    assert!(matches!(**source, crate::CrawlerErr::SelectorError(_)));
+                }
+                other => panic!("Expected NodeError, got: {:?}", other),
+            }
+            let message = err.to_string();
+            assert!(message.contains("'title'"), "{}", message);
+            assert!(message.contains(&url), "{}", message);
+        });
+    }
+
+    #[test]
+    fn test_crawl_snapshot_on_failure() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async move {
+            let mut server = mockito::Server::new_async().await;
+
+            let search_body = r#"<div class="actor">演员A</div><div class="actor">演员B</div><a href="/detail">详情</a>"#;
+            let detail_body = r#"<div class="plot">剧情简介</div>"#;
+            let _search = server
+                .mock("GET", "/search")
+                .with_status(200)
+                .with_body(search_body)
+                .create();
+            let _detail = server
+                .mock("GET", "/detail")
+                .with_status(200)
+                .with_body(detail_body)
+                .create();
+
+            let yaml = r#"
+entrypoint: "${base_url}/search"
+nodes:
+  actors: selector(".actor").val()
+  detail_url:
+    script: selector("a").attr("href").insert(0,${base_url})
+    request: true
+    children:
+      title:
+        script: selector("h1").val()
+        required: true
+"#;
+            let mut init_params = HashMap::new();
+            init_params.insert("base_url", server.url());
+
+            // 详情页缺少必需的标题节点，爬取失败
+            let template = Template::<Movie>::from_yaml(yaml).unwrap();
+            let failure = template
+                .crawler_with_snapshot(&init_params)
+                .await
+                .unwrap_err();
+
+            assert!(matches!(&failure.error, crate::CrawlerErr::Custom(msg) if msg.contains("'title'")));
+            assert_eq!(failure.snapshot.pages.len(), 2);
+            assert_eq!(failure.snapshot.pages[0].url, format!("{}/search", server.url()));
+            assert_eq!(failure.snapshot.pages[0].body, search_body);
+            assert_eq!(failure.snapshot.pages[1].url, format!("{}/detail", server.url()));
+            assert_eq!(failure.snapshot.pages[1].body, detail_body);
+            assert_eq!(
+                failure.snapshot.variables.get("actors"),
+                Some(&vec!["演员A".to_string(), "演员B".to_string()])
+            );
+            assert_eq!(
+                failure.snapshot.variables.get("detail_url"),
+                Some(&vec![format!("{}/detail", server.url())])
+            );
+            assert!(!failure.snapshot.variables.contains_key("title"));
+        });
+    }
+
+    #[test]
+    fn test_node_merge_mode() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async move {
+            let mut server = mockito::Server::new_async().await;
+
+            let _page1 = server
+                .mock("GET", "/list/1")
+                .with_status(200)
+                .with_body(r#"<h1>列表</h1><div class="tag">A</div><div class="tag">B</div><a class="next" href="/list/2">下一页</a>"#)
+                .create();
+            let _page2 = server
+                .mock("GET", "/list/2")
+                .with_status(200)
+                .with_body(r#"<h1>列表</h1><div class="tag">C</div>"#)
+                .create();
+
+            // 翻页时同一工作流再次执行，节点的值按合并方式写入
+            let crawl_tags = |merge_mode: &str| {
+                let yaml = format!(
+                    r#"
+entrypoint: "${{base_url}}/list/1"
+next_page: selector("a.next").attr("href")
+nodes:
+  title:
+    script: selector("h1").val()
+    merge_mode: first_wins
+  tags:
+    script: selector(".tag").val()
+    merge_mode: {}
+"#,
+                    merge_mode
+                );
+                let mut init_params = HashMap::new();
+                init_params.insert("base_url", server.url());
+
+                async move {
+                    let template = Template::<Movie>::from_yaml(&yaml).unwrap();
+                    template.crawler(&init_params).await.unwrap().tags.unwrap()
+                }
+            };
+
+            assert_eq!(crawl_tags("append").await, vec!["A", "B", "C"]);
+            // 第二页的值覆盖第一页已写入的值
+            assert_eq!(crawl_tags("replace").await, vec!["C"]);
+            assert_eq!(crawl_tags("first_wins").await, vec!["A", "B"]);
+
+            assert!(Template::<Movie>::from_yaml(
+                r#"
+entrypoint: "https://example.com"
+nodes:
+  tags:
+    script: selector(".tag").val()
+    merge_mode: prepend
+"#
+            )
+            .is_err());
+        });
+    }
+
+    #[test]
+    fn test_next_page_pagination() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async move {
+            let mut server = mockito::Server::new_async().await;
+
+            let page1 = server
+                .mock("GET", "/actor/1")
+                .with_status(200)
+                .with_body(r#"<h1>演员作品</h1><div class="movie">ABC-001</div><div class="movie">ABC-002</div><a class="next" href="/actor/2">下一页</a>"#)
+                .expect(2)
+                .create();
+            // 最后一页的下一页链接指回第一页，翻页应在重复地址处停止
+            let page2 = server
+                .mock("GET", "/actor/2")
+                .with_status(200)
+                .with_body(r#"<h1>演员作品</h1><div class="movie">ABC-003</div><a class="prev" href="/actor/1">上一页</a>"#)
+                .expect(1)
+                .create();
+
+            let yaml = r#"
+entrypoint: "${base_url}/actor/1"
+next_page: selector("a.next, a.prev").attr("href")
+nodes:
+  title: selector("h1").val()
+  tags: selector(".movie").val()
+"#;
+            let mut init_params = HashMap::new();
+            init_params.insert("base_url", server.url());
+
+            let template = Template::<Movie>::from_yaml(yaml).unwrap();
+            let result = template.crawler(&init_params).await.unwrap();
+            assert_eq!(
+                result.tags,
+                Some(vec![
+                    "ABC-001".to_string(),
+                    "ABC-002".to_string(),
+                    "ABC-003".to_string()
+                ])
+            );
+
+            // max_pages 限制翻页数量
+            let limited_yaml = format!("{}max_pages: 1\n", yaml);
+            let template = Template::<Movie>::from_yaml(&limited_yaml).unwrap();
+            let result = template.crawler(&init_params).await.unwrap();
+            assert_eq!(
+                result.tags,
+                Some(vec!["ABC-001".to_string(), "ABC-002".to_string()])
+            );
+
+            page1.assert();
+            page2.assert();
+        });
+    }
+
+    /// 启动一个统计同时处理中的详情页请求数量的 HTTP 服务，返回服务地址与最大并发数
+    async fn spawn_counting_server(
+        detail_count: usize,
+    ) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+
+        let max = max_in_flight.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let in_flight = in_flight.clone();
+                let max = max.clone();
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 1024];
+                    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                        let n = socket.read(&mut buf).await.unwrap();
+                        if n == 0 {
+                            return;
+                        }
+                        request.extend_from_slice(&buf[..n]);
+                    }
+                    let request = String::from_utf8_lossy(&request);
+                    let path = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+
+                    let body = match path.strip_prefix("/detail/") {
+                        Some(id) => {
+                            let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                            max.fetch_max(current, Ordering::SeqCst);
+                            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                            in_flight.fetch_sub(1, Ordering::SeqCst);
+                            format!(r#"<div class="tag">TAG-{}</div>"#, id)
+                        }
+                        None => (0..detail_count)
+                            .map(|id| format!(r#"<a href="/detail/{}">详情</a>"#, id))
+                            .collect::<String>()
+                            + "<h1>搜索结果</h1>",
+                    };
+
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+
+        (base_url, max_in_flight)
+    }
+
+    #[test]
+    fn test_concurrent_detail_requests() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async move {
+            let (base_url, max_in_flight) = spawn_counting_server(6).await;
+
+            let yaml = r#"
+entrypoint: "${base_url}/search"
+nodes:
+  title: selector("h1").val()
+  detail_url:
+    script: selector("a").attr("href").insert(0,${base_url})
+    request: true
+    children:
+      tags: selector(".tag").val()
+"#;
+            let mut init_params = HashMap::new();
+            init_params.insert("base_url", base_url);
+
+            let template = Template::<Movie>::from_yaml(yaml)
+                .unwrap()
+                .with_concurrency(2);
+            let result = template.crawler(&init_params).await.unwrap();
+
+            // 并发请求的结果仍按详情页顺序写入
+            let expected = (0..6).map(|id| format!("TAG-{}", id)).collect::<Vec<_>>();
+            assert_eq!(result.tags, Some(expected));
+            assert_eq!(
+                max_in_flight.load(std::sync::atomic::Ordering::SeqCst),
+                2
+            );
+        });
+    }
+
+    #[test]
+    fn test_shared_request_limit() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async move {
+            let (base_url, max_in_flight) = spawn_counting_server(6).await;
+
+            let yaml = r#"
+entrypoint: "${base_url}/search"
+nodes:
+  title: selector("h1").val()
+  detail_url:
+    script: selector("a").attr("href").insert(0,${base_url})
+    request: true
+    children:
+      tags: selector(".tag").val()
+"#;
+            let mut init_params = HashMap::new();
+            init_params.insert("base_url", base_url);
+
+            // 两个模板共享同一个许可，各自的并发数之和超过上限
+            let limit = std::sync::Arc::new(tokio::sync::Semaphore::new(3));
+            let first = Template::<Movie>::from_yaml(yaml)
+                .unwrap()
+                .with_concurrency(4)
+                .with_request_limit(limit.clone());
+            let second = first.clone();
+
+            let (first, second) = futures_util::join!(
+                first.crawler(&init_params),
+                second.crawler(&init_params)
+            );
+            assert_eq!(first.unwrap().tags.map(|tags| tags.len()), Some(6));
+            assert_eq!(second.unwrap().tags.map(|tags| tags.len()), Some(6));
+            assert_eq!(
+                max_in_flight.load(std::sync::atomic::Ordering::SeqCst),
+                3
+            );
+        });
+    }
+
+    #[test]
+    fn test_shared_host_limit() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async move {
+            let (base_url, max_in_flight) = spawn_counting_server(6).await;
+            let (other_url, other_max_in_flight) = spawn_counting_server(6).await;
+
+            let yaml = r#"
+entrypoint: "${base_url}/search"
+nodes:
+  title: selector("h1").val()
+  detail_url:
+    script: selector("a").attr("href").insert(0,${base_url})
+    request: true
+    children:
+      tags: selector(".tag").val()
+"#;
+            let params = |base_url: &str| {
+                let mut init_params = HashMap::new();
+                init_params.insert("base_url", base_url.to_string());
+                init_params
+            };
+            let (first_params, other_params) = (params(&base_url), params(&other_url));
+
+            // 三个爬取共享同一个主机限制，其中两个访问同一主机
+            let limit = HostLimiter::new(2);
+            let first = Template::<Movie>::from_yaml(yaml)
+                .unwrap()
+                .with_concurrency(4)
+                .with_host_limit(limit.clone());
+            let second = first.clone();
+            let other = first.clone();
+
+            let (first, second, other) = futures_util::join!(
+                first.crawler(&first_params),
+                second.crawler(&first_params),
+                other.crawler(&other_params)
+            );
+            for result in [first, second, other] {
+                assert_eq!(result.unwrap().tags.map(|tags| tags.len()), Some(6));
+            }
+            assert_eq!(max_in_flight.load(std::sync::atomic::Ordering::SeqCst), 2);
+            // 不同主机的许可互不影响
+            assert_eq!(other_max_in_flight.load(std::sync::atomic::Ordering::SeqCst), 2);
+        });
+    }
+
+    #[test]
+    fn test_blocking_crawl_returns_errors() {
+        let mut server = mockito::Server::new();
+        let _broken = server.mock("GET", "/broken").with_status(500).create();
+        let _empty = server
+            .mock("GET", "/empty")
+            .with_status(200)
+            .with_body("<div></div>")
+            .create();
+
+        let yaml = r#"
+entrypoint: "${base_url}/${path}"
+nodes:
+  title:
+    script: selector("h1").val()
+    required: true
+"#;
+        let template = Template::<Movie>::from_yaml(yaml).unwrap();
+        let params = |path: &str| {
+            let mut params = HashMap::new();
+            params.insert("base_url", server.url());
+            params.insert("path", path.to_string());
+            params
+        };
+
+        assert!(matches!(
+            template.crawler_block(&params("broken")),
+            Err(crate::CrawlerErr::HttpStatus(500, _))
+        ));
+        // 必需节点缺失时返回错误而不是 panic
+        assert!(template.crawler_block(&params("empty")).is_err());
+    }
+
+    #[test]
+    fn test_response_size_and_content_type_limits() {
+        let mut server = mockito::Server::new();
+        let oversized = format!("<h1>{}</h1>", "a".repeat(2048));
+        let _declared = server
+            .mock("GET", "/declared")
+            .with_status(200)
+            .with_header("content-type", "text/html")
+            .with_body(&oversized)
+            .create();
+        // 分块传输没有 Content-Length，只能边读取边检查
+        let streamed = oversized.clone();
+        let _streamed = server
+            .mock("GET", "/streamed")
+            .with_status(200)
+            .with_header("content-type", "text/html; charset=utf-8")
+            .with_chunked_body(move |writer| writer.write_all(streamed.as_bytes()))
+            .create();
+        let _binary = server
+            .mock("GET", "/binary")
+            .with_status(200)
+            .with_header("content-type", "application/octet-stream")
+            .with_body([0u8, 1, 2, 3])
+            .create();
+        let _small = server
+            .mock("GET", "/small")
+            .with_status(200)
+            .with_header("content-type", "text/html; charset=Shift_JIS")
+            .with_body(b"<h1>\x83\x65\x83\x58\x83\x67</h1>")
+            .create();
+
+        let yaml = r#"
+entrypoint: "${base_url}/${path}"
+max_response_bytes: 1024
+nodes:
+  title: selector("h1").val()
+"#;
+        let template = Template::<Movie>::from_yaml(yaml).unwrap();
+        let params = |path: &str| {
+            let mut params = HashMap::new();
+            params.insert("base_url", server.url());
+            params.insert("path", path.to_string());
+            params
+        };
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        for path in ["declared", "streamed"] {
+            let result = rt.block_on(template.crawler(&params(path)));
+            assert!(
+                matches!(result, Err(crate::CrawlerErr::ResponseTooLarge { limit: 1024, .. })),
+                "{}: {:?}",
+                path,
+                result
+            );
+            assert!(matches!(
+                template.crawler_block(&params(path)),
+                Err(crate::CrawlerErr::ResponseTooLarge { limit: 1024, .. })
+            ));
+        }
+
+        let result = rt.block_on(template.crawler(&params("binary")));
+        assert!(matches!(
+            result,
+            Err(crate::CrawlerErr::UnexpectedContentType { ref content_type, .. })
+                if content_type == "application/octet-stream"
+        ));
+        assert!(matches!(
+            template.crawler_block(&params("binary")),
+            Err(crate::CrawlerErr::UnexpectedContentType { .. })
+        ));
+
+        // 未超过上限的页面按声明的字符集解码
+        let result = rt.block_on(template.crawler(&params("small"))).unwrap();
+        assert_eq!(result.title, "テスト");
+        assert_eq!(template.crawler_block(&params("small")).unwrap().title, "テスト");
+
+        // 提高上限后可以读取
+        let template = template.with_max_response_bytes(4096);
+        assert!(rt.block_on(template.crawler(&params("streamed"))).is_ok());
+    }
+
+    #[test]
+    fn test_blocking_crawl_deadline() {
+        // 接受连接但从不响应的服务器
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let yaml = format!(
+            r#"
+entrypoint: "http://{}/movie"
+timeout: 30
+nodes:
+  title: selector("h1").val()
+"#,
+            listener.local_addr().unwrap()
+        );
+        let template = Template::<Movie>::from_yaml(&yaml).unwrap();
+
+        let start = std::time::Instant::now();
+        let result = template.crawler_block_with_deadline(&HashMap::new(), std::time::Duration::from_millis(300));
+        assert!(matches!(result, Err(crate::CrawlerErr::Timeout(_))));
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+
+        let result = template.crawler_block_with_deadline(&HashMap::new(), std::time::Duration::ZERO);
+        assert!(matches!(result, Err(crate::CrawlerErr::Timeout(_))));
+
+        drop(listener);
+    }
+
+    #[test]
+    fn test_invalid_selector_fails_to_load() {
+        let yaml = r#"
+entrypoint: "${base_url}"
+nodes:
+  detail:
+    script: selector("a").attr("href")
+    request: true
+    children:
+      title: selector("div..bad").val()
+"#;
+        let error = Template::<Movie>::from_yaml(yaml).unwrap_err().to_string();
+        assert!(error.contains("node 'detail': node 'title'"), "{}", error);
+        assert!(error.contains("'div..bad'"), "{}", error);
+    }
+
+    #[test]
+    fn test_template_lint() {
+        let kinds = |yaml: &str| {
+            Template::<Movie>::from_yaml(yaml)
+                .unwrap()
+                .lint()
+                .into_iter()
+                .map(|warning| (warning.kind, warning.node))
+                .collect::<Vec<_>>()
+        };
+
+        let clean = r#"
+entrypoint: "${base_url}/search?q=${crawl_name}"
+env:
+  base_url: [https://example.com]
+nodes:
+  detail_url:
+    script: selector("a").attr("href")
+    request: true
+    children:
+      title: selector("h1").val()
+"#;
+        assert!(kinds(clean).is_empty());
+
+        // 请求节点选择的是元素而不是地址，详情页工作流不会执行
+        let unreachable = r#"
+entrypoint: "${base_url}"
+env:
+  base_url: [https://example.com]
+nodes:
+  detail_url:
+    script: selector("a")
+    request: true
+    children:
+      title: selector("h1").val()
+"#;
+        assert_eq!(
+            kinds(unreachable),
+            vec![(crate::LintKind::UnreachableRequest, "detail_url".to_string())]
+        );
+
+        let unknown_parameter = r#"
+entrypoint: "${base_url}/search?q=${keyword}"
+env:
+  base_url: [https://example.com]
+nodes:
+  title: selector("h1").val()
+"#;
+        assert_eq!(
+            kinds(unknown_parameter),
+            vec![(crate::LintKind::UnknownEntrypointParameter, "keyword".to_string())]
+        );
+
+        let env_collision = r#"
+entrypoint: "${base_url}"
+env:
+  base_url: [https://example.com]
+nodes:
+  base_url: selector("a.home").attr("href")
+"#;
+        assert_eq!(
+            kinds(env_collision),
+            vec![(crate::LintKind::EnvCollision, "base_url".to_string())]
+        );
+
+        let empty_request = r#"
+entrypoint: "${base_url}"
+env:
+  base_url: [https://example.com]
+nodes:
+  detail_url:
+    script: selector("a").attr("href")
+    request: true
+"#;
+        assert_eq!(
+            kinds(empty_request),
+            vec![(crate::LintKind::EmptyRequest, "detail_url".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_script_parse_error_context() {
+        let yaml = r#"
+entrypoint: "${base_url}"
+nodes:
+  title: |
+    selector("h1").val()
+    .bogus(1)
+"#;
+        let error = Template::<Movie>::from_yaml(yaml).unwrap_err().to_string();
+        assert!(error.contains("node 'title'"), "{}", error);
+        assert!(error.contains("line 2, column 1"), "{}", error);
+        assert!(error.contains("'.bogus(1)'"), "{}", error);
+    }
+
+    #[test]
+    fn test_next_page_validation() {
+        let max_pages_only = r#"
+entrypoint: "${base_url}"
+max_pages: 3
+nodes:
+  title: selector("h1").val()
+"#;
+        assert!(Template::<Movie>::from_yaml(max_pages_only).is_err());
+
+        let element_script = r#"
+entrypoint: "${base_url}"
+next_page: selector("a.next")
+nodes:
+  title: selector("h1").val()
+"#;
+        assert!(Template::<Movie>::from_yaml(element_script).is_err());
+
+        let non_request_node = r#"
+entrypoint: "${base_url}"
+nodes:
+  title:
+    script: selector("h1").val()
+    next_page: selector("a.next").attr("href")
+"#;
+        assert!(Template::<Movie>::from_yaml(non_request_node).is_err());
+    }
+
+    #[test]
+    fn test_template_group_node() {
+        let yaml = r#"
+entrypoint: "${base_url}"
+nodes:
+  actors:
+    script: selector(".actor")
+    group: true
+    children:
+      name:
+        script: selector("a").val()
+        required: true
+      thumb: selector("img").attr("src")
+  name: selector("h1").val()
+"#;
+        let template = Template::<Movie>::from_yaml(yaml).unwrap();
+
+        let html = scraper::Html::parse_document(
+            r#"<h1>TEST-001</h1>
+<div class="actor"><img src="a.jpg"><a>演员A</a></div>
+<div class="actor"><a>演员B</a><img src="b.jpg"></div>
+<div class="actor"><img src="c.jpg"></div>
+<div class="actor"><a>演员D</a></div>"#,
+        );
+        let mut runtime_variable = HashMap::new();
+        for node in &template.workflows[0].node {
+            node.process(vec![html.root_element()], &mut runtime_variable)
+                .unwrap();
+        }
+
+        // 每个元素对应一条记录，缺少必需字段的元素被跳过
+        let records = runtime_variable
+            .get("actors")
+            .unwrap()
+            .iter()
+            .map(|value| crate::parse_record(value).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0]["name"], "演员A");
+        assert_eq!(records[0]["thumb"], "a.jpg");
+        assert_eq!(records[1]["name"], "演员B");
+        assert_eq!(records[1]["thumb"], "b.jpg");
+        assert_eq!(records[2]["name"], "演员D");
+        assert_eq!(records[2]["thumb"], "");
+
+        // 分组子节点名称不与其他节点冲突
+        assert_eq!(runtime_variable.get("name"), Some(&vec!["TEST-001".to_string()]));
+
+        // 分组子节点不能继续嵌套
+        let invalid = r#"
+entrypoint: "${base_url}"
+nodes:
+  actors:
+    script: selector(".actor")
+    group: true
+    children:
+      name:
+        script: selector("a")
+        children:
+          inner: val()
+"#;
+        assert!(Template::<Movie>::from_yaml(invalid).is_err());
+    }
+
+    #[test]
+    fn test_parallel_group_node_keeps_alignment() {
+        let yaml = r#"
+entrypoint: "${base_url}"
+nodes:
+  actor_rows:
+    script: selector(".actor")
+    group: true
+    parallel: true
+    children:
+      actors:
+        script: selector("a").val()
+        required: true
+      actor_thumbs: selector("img").attr("src")
+"#;
+        let template = Template::<Movie>::from_yaml(yaml).unwrap();
+
+        let html = scraper::Html::parse_document(
+            r#"<div class="actor"><a>演员A</a><img src="a.jpg"></div>
+<div class="actor"><a>演员B</a></div>
+<div class="actor"><img src="c.jpg"></div>
+<div class="actor"><a>演员D</a><img src="d.jpg"></div>"#,
+        );
+        let mut runtime_variable = HashMap::new();
+        for node in &template.workflows[0].node {
+            node.process(vec![html.root_element()], &mut runtime_variable)
+                .unwrap();
+        }
+
+        // 缺少头像的元素以空字符串占位，缺少必需名称的元素整体跳过
+        assert_eq!(
+            runtime_variable.get("actors"),
+            Some(&vec!["演员A".to_string(), "演员B".to_string(), "演员D".to_string()])
+        );
+        assert_eq!(
+            runtime_variable.get("actor_thumbs"),
+            Some(&vec!["a.jpg".to_string(), String::new(), "d.jpg".to_string()])
+        );
+        assert!(!runtime_variable.contains_key("actor_rows"));
+
+        let without_group = r#"
+entrypoint: "${base_url}"
+nodes:
+  actors:
+    script: selector(".actor").val()
+    parallel: true
+"#;
+        assert!(Template::<Movie>::from_yaml(without_group).is_err());
+
+        // parallel 分组的子节点写入同名变量，不能与其他节点重名
+        let duplicate_key = r#"
+entrypoint: "${base_url}"
+nodes:
+  actors: selector(".cast").val()
+  actor_rows:
+    script: selector(".actor")
+    group: true
+    parallel: true
+    children:
+      actors: selector("a").val()
+"#;
+        let err = Template::<Movie>::from_yaml(duplicate_key).unwrap_err();
+        assert!(err.to_string().contains("Duplicate key 'actors'"), "{}", err);
+
+        // 普通分组的子节点只是记录字段，可以与其他节点重名
+        let record_group = duplicate_key.replace("    parallel: true\n", "");
+        assert!(Template::<Movie>::from_yaml(&record_group).is_ok());
+    }
+
+    #[test]
+    fn test_optional_section() {
+        let yaml = r#"
+entrypoint: "${base_url}"
+nodes:
+  title: selector("h1").val()
+  thumbnail: selector(".cover img").attr("src").optional()
+  series:
+    script: selector(".series").optional()
+    children:
+      series_name:
+        script: selector("a").val()
+        required: true
+  detail_url: selector(".detail a").attr("href")
+"#;
+        let template = Template::<Movie>::from_yaml(yaml).unwrap();
+
+        let html = scraper::Html::parse_document(r#"<h1>TEST-001</h1>"#);
+        let mut runtime_variable = HashMap::new();
+        for node in &template.workflows[0].node {
+            node.process(vec![html.root_element()], &mut runtime_variable)
+                .unwrap();
+        }
+
+        // 缺失的可选区块不写入变量，其必需的子节点也不会报错
+        assert_eq!(runtime_variable.get("title"), Some(&vec!["TEST-001".to_string()]));
+        assert!(!runtime_variable.contains_key("thumbnail"));
+        assert!(!runtime_variable.contains_key("series_name"));
+        // 未标记 optional 的节点仍写入空值
+        assert_eq!(runtime_variable.get("detail_url"), Some(&vec![]));
+
+        let html = scraper::Html::parse_document(
+            r#"<h1>TEST-001</h1><div class="cover"><img src="cover.jpg"></div><div class="series"><a>系列A</a></div>"#,
+        );
+        let mut runtime_variable = HashMap::new();
+        for node in &template.workflows[0].node {
+            node.process(vec![html.root_element()], &mut runtime_variable)
+                .unwrap();
+        }
+
+        assert_eq!(runtime_variable.get("thumbnail"), Some(&vec!["cover.jpg".to_string()]));
+        assert_eq!(runtime_variable.get("series_name"), Some(&vec!["系列A".to_string()]));
+    }
+
+    #[test]
+    fn test_template_multi_target_node() {
+        let yaml = r#"
+entrypoint: "${base_url}"
+nodes:
+  images:
+    script: selector(".tile-images")
+    children:
+      "preview_images, fanarts": selector("a").attrs("data-preview", "href")
+"#;
+        let template = Template::<Movie>::from_yaml(yaml).unwrap();
+
+        let html = scraper::Html::parse_document(
+            r#"<div class="tile-images"><a data-preview="p1.jpg" href="f1.jpg"></a><a data-preview="p2.jpg" href="f2.jpg"></a></div>"#,
+        );
+        let mut runtime_variable = HashMap::new();
+        for node in &template.workflows[0].node {
+            node.process(vec![html.root_element()], &mut runtime_variable)
+                .unwrap();
+        }
+
+        assert_eq!(
+            runtime_variable.get("preview_images"),
+            Some(&vec!["p1.jpg".to_string(), "p2.jpg".to_string()])
+        );
+        assert_eq!(
+            runtime_variable.get("fanarts"),
+            Some(&vec!["f1.jpg".to_string(), "f2.jpg".to_string()])
+        );
+
+        // 变量数量与属性数量不一致时拒绝模板
+        let invalid = r#"
+entrypoint: "${base_url}"
+nodes:
+  "a, b, c": selector("a").attrs("data-preview", "href")
+"#;
+        assert!(Template::<Movie>::from_yaml(invalid).is_err());
+    }
 }
@@ -93,3 +93,38 @@ fn test_conversion_error() {
         matches!(result, Err(CrawlerParseError::ConversionFailed(field)) if field == "optional_field")
     );
 }
+
+#[derive(Crawler, Debug, PartialEq, Clone)]
+struct TransformTestData {
+    #[crawler(trim)]
+    trimmed: String,
+    #[crawler(trim, lowercase)]
+    code: Option<String>,
+    #[crawler(trim)]
+    numbers: Vec<u32>,
+    #[crawler(uppercase)]
+    tags: Option<Vec<String>>,
+}
+
+#[test]
+fn test_field_transform_attributes() {
+    let mut map = HashMap::new();
+    map.insert("trimmed".to_string(), vec![" value ".to_string()]);
+    map.insert("code".to_string(), vec!["  ABC-001\n".to_string()]);
+    map.insert(
+        "numbers".to_string(),
+        vec![" 1".to_string(), "2 ".to_string()],
+    );
+    map.insert("tags".to_string(), vec!["drama".to_string()]);
+
+    let parsed = TransformTestData::parse(&map).expect("解析带预处理属性的字段失败");
+    assert_eq!(
+        parsed,
+        TransformTestData {
+            trimmed: "value".to_string(),
+            code: Some("abc-001".to_string()),
+            numbers: vec![1, 2],
+            tags: Some(vec!["DRAMA".to_string()]),
+        }
+    );
+}
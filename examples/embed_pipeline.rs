@@ -0,0 +1,52 @@
+//! 在其他程序中嵌入影片整理流程
+//!
+//! 用法: cargo run --example embed_pipeline -- <配置文件> <模板目录> <视频文件> [--apply]
+//! 默认只预览整理结果，加上 `--apply` 时实际整理文件
+
+use std::path::Path;
+
+use jav_tidy_rs::{AppConfig, Pipeline, ProcessOutcome, ProgressReporter};
+
+/// 将处理阶段输出到标准输出
+struct PrintProgress;
+
+impl ProgressReporter for PrintProgress {
+    fn set_message(&self, message: &str) {
+        println!("[进度] {}", message);
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [config_file, template_dir, video_file, rest @ ..] = args.as_slice() else {
+        anyhow::bail!("用法: embed_pipeline <配置文件> <模板目录> <视频文件> [--apply]");
+    };
+    let apply = rest.iter().any(|arg| arg == "--apply");
+
+    let config = AppConfig::new(Path::new(config_file))?;
+    let pipeline = Pipeline::new(config, Path::new(template_dir))?.with_progress(PrintProgress);
+    let video_file = Path::new(video_file);
+
+    let plan = pipeline.preview(video_file).await?;
+    println!("影片ID: {}", plan.movie_id);
+    println!("标题: {}", plan.nfo.title);
+    println!("视频文件: {}", plan.video_path.display());
+    for nfo_path in &plan.nfo_paths {
+        println!("NFO文件: {}", nfo_path.display());
+    }
+    for warning in &plan.warnings {
+        println!("警告: {:?}", warning);
+    }
+
+    if apply {
+        match pipeline.process_file(video_file).await? {
+            ProcessOutcome::Organized { video_path, .. } => {
+                println!("整理完成: {}", video_path.display());
+            }
+            ProcessOutcome::Skipped { reason } => println!("已跳过: {}", reason),
+        }
+    }
+
+    Ok(())
+}
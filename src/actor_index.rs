@@ -0,0 +1,236 @@
+// 按演员浏览媒体库的索引：<输出目录>/.actors/<演员名>/person.nfo 与可选的 M3U 播放列表
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::AppConfig,
+    file::{safe_write::safe_write, sanitize_file_name},
+    nfo::MovieNfo,
+};
+
+/// 演员索引目录，位于输出目录下
+pub const ACTOR_INDEX_DIR: &str = ".actors";
+
+const PERSON_NFO: &str = "person.nfo";
+
+/// 同一时间只允许一个更新读写演员索引，避免合并时丢失影片
+static UPDATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 演员索引NFO
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename = "person")]
+pub struct PersonNfo {
+    #[serde(rename = "name", default)]
+    pub name: String,
+    #[serde(rename = "thumb", default, skip_serializing_if = "String::is_empty")]
+    pub thumb: String,
+    #[serde(rename = "movie", default, skip_serializing_if = "Vec::is_empty")]
+    pub movies: Vec<PersonMovie>,
+}
+
+/// 演员参演的影片
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct PersonMovie {
+    #[serde(rename = "id", default)]
+    pub movie_id: String,
+    #[serde(rename = "title", default, skip_serializing_if = "String::is_empty")]
+    pub title: String,
+    #[serde(rename = "year", default, skip_serializing_if = "Option::is_none")]
+    pub year: Option<u16>,
+    /// 整理后的视频文件路径
+    #[serde(rename = "path", default)]
+    pub path: String,
+}
+
+/// 把影片合并到每位演员的索引，未启用 `generate_actor_index` 时不做任何操作
+///
+/// 同一影片ID只记录一次，重新整理时更新标题、年份与路径；返回写入的 person.nfo 文件
+pub fn update_actor_index(
+    nfo: &MovieNfo,
+    movie_id: &str,
+    video_path: &Path,
+    config: &AppConfig,
+) -> anyhow::Result<Vec<PathBuf>> {
+    if !config.generate_actor_index {
+        return Ok(Vec::new());
+    }
+
+    let _guard = UPDATE_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    let index_dir = config.get_output_dir().join(ACTOR_INDEX_DIR);
+    let movie = PersonMovie {
+        movie_id: movie_id.to_string(),
+        title: nfo.title.clone(),
+        year: nfo.release_year(),
+        path: video_path.to_string_lossy().into_owned(),
+    };
+
+    let mut written = Vec::new();
+    for actor in &nfo.actors {
+        let dir_name = sanitize_file_name(&actor.name);
+        if dir_name.is_empty() {
+            log::debug!("演员名 '{}' 无法用作目录名，跳过演员索引", actor.name);
+            continue;
+        }
+        let actor_dir = index_dir.join(&dir_name);
+        let person_path = actor_dir.join(PERSON_NFO);
+
+        let mut person = read_person_nfo(&person_path)?.unwrap_or_default();
+        person.name = actor.name.trim().to_string();
+        if !actor.thumb.trim().is_empty() {
+            person.thumb = actor.thumb.trim().to_string();
+        }
+        match person
+            .movies
+            .iter_mut()
+            .find(|existing| existing.movie_id.eq_ignore_ascii_case(movie_id))
+        {
+            Some(existing) => *existing = movie.clone(),
+            None => person.movies.push(movie.clone()),
+        }
+
+        std::fs::create_dir_all(&actor_dir)?;
+        safe_write(&person_path, format_person_nfo(&person)?)?;
+        if config.actor_playlists {
+            safe_write(&actor_dir.join(format!("{}.m3u", dir_name)), format_playlist(&person))?;
+        }
+        written.push(person_path);
+    }
+
+    log::info!("已更新 {} 位演员的索引", written.len());
+    Ok(written)
+}
+
+/// 读取已有的演员索引，文件不存在时返回 None
+pub fn read_person_nfo(path: &Path) -> anyhow::Result<Option<PersonNfo>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(path)?;
+    let xml = content
+        .find("<person")
+        .map_or(content.as_str(), |start| &content[start..]);
+    let person = quick_xml::de::from_str(xml)
+        .map_err(|e| anyhow::anyhow!("解析演员索引失败 {}: {}", path.display(), e))?;
+    Ok(Some(person))
+}
+
+fn format_person_nfo(person: &PersonNfo) -> anyhow::Result<String> {
+    Ok(format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n{}",
+        quick_xml::se::to_string(person)?
+    ))
+}
+
+/// 演员参演影片的 M3U 播放列表，按影片ID排序
+fn format_playlist(person: &PersonNfo) -> String {
+    let mut movies: Vec<&PersonMovie> = person.movies.iter().collect();
+    movies.sort_by(|a, b| a.movie_id.cmp(&b.movie_id));
+
+    let mut playlist = String::from("#EXTM3U\n");
+    for movie in movies {
+        let title = if movie.title.is_empty() { &movie.movie_id } else { &movie.title };
+        playlist.push_str(&format!("#EXTINF:-1,{}\n{}\n", title, movie.path));
+    }
+    playlist
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nfo::{Actor, MovieNfoCrawler};
+
+    fn create_config(name: &str, output_dir: &Path) -> AppConfig {
+        let config_path = std::env::temp_dir().join(format!("{}.toml", name));
+        std::fs::write(
+            &config_path,
+            format!(
+                r#"
+migrate_files = ["mp4"]
+ignored_id_pattern = []
+input_dir = "./input"
+output_dir = "{}"
+thread_limit = 1
+template_priority = []
+generate_actor_index = true
+actor_playlists = true
+"#,
+                output_dir.display().to_string().replace('\\', "/")
+            ),
+        )
+        .unwrap();
+        let config = AppConfig::new(&config_path).unwrap();
+        std::fs::remove_file(&config_path).unwrap();
+        config
+    }
+
+    fn movie(title: &str, actors: &[&str]) -> MovieNfo {
+        MovieNfo::from(MovieNfoCrawler {
+            title: title.to_string(),
+            actors: actors
+                .iter()
+                .map(|name| Actor {
+                    name: name.to_string(),
+                    thumb: format!("https://example.com/{}.jpg", name),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_actor_index_merges_movies() {
+        let output_dir = std::env::temp_dir().join("test_actor_index_merges_movies");
+        let _ = std::fs::remove_dir_all(&output_dir);
+        let config = create_config("test_actor_index_merges_movies", &output_dir);
+
+        let first = output_dir.join("AAA-001").join("AAA-001.mp4");
+        let second = output_dir.join("BBB-002").join("BBB-002.mp4");
+        update_actor_index(&movie("第一部", &["演员A", "演员B"]), "AAA-001", &first, &config).unwrap();
+        update_actor_index(&movie("第二部", &["演员A"]), "BBB-002", &second, &config).unwrap();
+        // 重新整理同一影片不重复记录
+        update_actor_index(&movie("第二部", &["演员A"]), "bbb-002", &second, &config).unwrap();
+
+        let actor_dir = output_dir.join(ACTOR_INDEX_DIR).join("演员A");
+        let person = read_person_nfo(&actor_dir.join(PERSON_NFO)).unwrap().unwrap();
+        assert_eq!(person.name, "演员A");
+        assert_eq!(person.thumb, "https://example.com/演员A.jpg");
+        let ids: Vec<_> = person.movies.iter().map(|movie| movie.movie_id.as_str()).collect();
+        assert_eq!(ids, vec!["AAA-001", "bbb-002"]);
+        assert_eq!(person.movies[0].title, "第一部");
+        assert_eq!(person.movies[0].path, first.to_string_lossy());
+
+        let other = read_person_nfo(&output_dir.join(ACTOR_INDEX_DIR).join("演员B").join(PERSON_NFO))
+            .unwrap()
+            .unwrap();
+        assert_eq!(other.movies.len(), 1);
+
+        let playlist = std::fs::read_to_string(actor_dir.join("演员A.m3u")).unwrap();
+        assert!(playlist.starts_with("#EXTM3U\n"));
+        assert_eq!(playlist.matches("#EXTINF").count(), 2);
+        assert!(playlist.contains(&*second.to_string_lossy()));
+
+        std::fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    #[test]
+    fn test_actor_index_sanitizes_names() {
+        let output_dir = std::env::temp_dir().join("test_actor_index_sanitizes_names");
+        let _ = std::fs::remove_dir_all(&output_dir);
+        let config = create_config("test_actor_index_sanitizes_names", &output_dir);
+
+        let video = output_dir.join("CCC-003.mp4");
+        let written =
+            update_actor_index(&movie("第三部", &["../演员:C", ".."]), "CCC-003", &video, &config).unwrap();
+
+        // 路径分隔符被移除，无法用作目录名的演员被跳过
+        assert_eq!(written, vec![output_dir.join(ACTOR_INDEX_DIR).join("..演员C").join(PERSON_NFO)]);
+
+        std::fs::remove_dir_all(&output_dir).unwrap();
+    }
+}
@@ -1,7 +1,18 @@
 use std::path::PathBuf;
 
+use flexi_logger::{Age, Cleanup, Criterion};
 use structopt::StructOpt;
 
+use crate::config::TemplateOverride;
+
+/// `--log-level` 可选的日志级别
+const LOG_LEVELS: [&str; 5] = ["trace", "debug", "info", "warn", "error"];
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+/// 始终屏蔽的 MQTT 库日志
+const LOG_SUPPRESSIONS: &str = "pago_mqtt=error,paho_mqtt_c=error";
+
 #[derive(Debug, StructOpt)]
 pub struct StartParam {
     #[structopt(
@@ -31,6 +42,22 @@ pub struct StartParam {
     #[structopt(short = "l", long = "log", parse(from_os_str), default_value = "./log")]
     pub log_location: PathBuf,
 
+    /// 日志级别 (trace/debug/info/warn/error)，优先于 -v
+    #[structopt(long = "log-level", possible_values = &LOG_LEVELS, case_insensitive = true)]
+    pub log_level: Option<String>,
+
+    /// 输出更详细的日志：-v 为 debug，-vv 为 trace
+    #[structopt(short = "v", long = "verbose", parse(from_occurrences))]
+    pub verbose: u8,
+
+    /// 保留的日志文件数量，超出时删除最旧的文件；日志按天轮转，启用按大小轮转时一天可能产生多个文件
+    #[structopt(long = "log-keep-files", default_value = "7", parse(try_from_str = parse_positive))]
+    pub log_keep_files: u64,
+
+    /// 单个日志文件达到该大小 (MB) 时提前轮转，不指定时只按天轮转
+    #[structopt(long = "log-max-size-mb", parse(try_from_str = parse_size_mb))]
+    pub log_max_size_mb: Option<u64>,
+
     #[cfg(debug_assertions)]
     #[structopt(
         short = "t",
@@ -48,4 +75,111 @@ pub struct StartParam {
         default_value = "/var/lib/javtidy/template"
     )]
     pub template_location: PathBuf,
+
+    /// 覆盖模板参数，格式: 模板文件名:参数=值，可重复指定，优先于配置文件
+    #[structopt(long = "set", number_of_values = 1)]
+    pub template_overrides: Vec<TemplateOverride>,
+
+    /// 加载并检查模板后退出，发现问题时退出码为 1
+    #[structopt(long = "validate-templates")]
+    pub validate_templates: bool,
+
+    /// 数据质量过低或缺少必需字段而被跳过的影片ID追加到 ignored_ids_file，之后不再尝试整理
+    #[structopt(long = "remember-rejections")]
+    pub remember_rejections: bool,
+
+    /// 忽略爬取缓存重新爬取影片数据，爬取结果仍写入缓存
+    #[structopt(long = "refresh-metadata")]
+    pub refresh_metadata: bool,
+
+    /// 检查输出目录中影片路径与NFO内容是否一致，列出不一致的影片与移动计划后退出
+    #[structopt(long = "check-consistency")]
+    pub check_consistency: bool,
+
+    /// 与 --check-consistency 一起使用，把不一致的影片移动到按NFO计算出的路径
+    #[structopt(long = "fix", requires = "check-consistency")]
+    pub fix: bool,
+}
+
+impl StartParam {
+    /// 传给日志构建器的日志规格，默认 info
+    pub fn log_spec(&self) -> String {
+        let level = match (&self.log_level, self.verbose) {
+            (Some(level), _) => level.to_lowercase(),
+            (None, 0) => "info".to_string(),
+            (None, 1) => "debug".to_string(),
+            (None, _) => "trace".to_string(),
+        };
+        format!("{},{}", level, LOG_SUPPRESSIONS)
+    }
+
+    /// 日志文件的轮转条件与清理策略
+    pub fn log_rotation(&self) -> (Criterion, Cleanup) {
+        let criterion = match self.log_max_size_mb.and_then(|size_mb| size_mb.checked_mul(BYTES_PER_MB)) {
+            Some(size) => Criterion::AgeOrSize(Age::Day, size),
+            None => Criterion::Age(Age::Day),
+        };
+        (criterion, Cleanup::KeepLogFiles(self.log_keep_files as usize))
+    }
+}
+
+/// 解析大于 0 的整数
+fn parse_positive(value: &str) -> Result<u64, String> {
+    match value.parse::<u64>() {
+        Ok(0) => Err("必须大于 0".to_string()),
+        Ok(value) => Ok(value),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// 解析以 MB 为单位的大小，换算为字节后不能溢出
+fn parse_size_mb(value: &str) -> Result<u64, String> {
+    let size_mb = parse_positive(value)?;
+    size_mb
+        .checked_mul(BYTES_PER_MB)
+        .map(|_| size_mb)
+        .ok_or_else(|| "大小超出范围".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> StartParam {
+        StartParam::from_iter_safe(std::iter::once("jav-tidy-rs").chain(args.iter().copied())).unwrap()
+    }
+
+    #[test]
+    fn test_log_spec() {
+        assert_eq!(parse(&[]).log_spec(), "info,pago_mqtt=error,paho_mqtt_c=error");
+        assert_eq!(parse(&["-v"]).log_spec(), "debug,pago_mqtt=error,paho_mqtt_c=error");
+        assert_eq!(parse(&["-vv"]).log_spec(), "trace,pago_mqtt=error,paho_mqtt_c=error");
+        assert_eq!(parse(&["-vvv"]).log_spec(), "trace,pago_mqtt=error,paho_mqtt_c=error");
+
+        // --log-level 优先于 -v
+        assert_eq!(parse(&["--log-level", "WARN", "-vv"]).log_spec(), "warn,pago_mqtt=error,paho_mqtt_c=error");
+        assert!(StartParam::from_iter_safe(["jav-tidy-rs", "--log-level", "verbose"]).is_err());
+    }
+
+    #[test]
+    fn test_log_rotation() {
+        assert!(matches!(
+            parse(&[]).log_rotation(),
+            (Criterion::Age(Age::Day), Cleanup::KeepLogFiles(7))
+        ));
+        assert!(matches!(
+            parse(&["--log-keep-files", "30", "--log-max-size-mb", "10"]).log_rotation(),
+            (Criterion::AgeOrSize(Age::Day, 10_485_760), Cleanup::KeepLogFiles(30))
+        ));
+
+        let overflow = (u64::MAX / BYTES_PER_MB + 1).to_string();
+        for args in [
+            ["--log-keep-files", "0"],
+            ["--log-max-size-mb", "0"],
+            ["--log-keep-files", "-1"],
+            ["--log-max-size-mb", overflow.as_str()],
+        ] {
+            assert!(StartParam::from_iter_safe(std::iter::once("jav-tidy-rs").chain(args)).is_err());
+        }
+    }
 }
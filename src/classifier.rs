@@ -0,0 +1,240 @@
+use crate::config::{ClassificationConfig, ClassificationRule, MovieCategory};
+use crate::nfo::MovieNfoCrawler;
+use regex::Regex;
+
+/// 正则匹配条件的前缀
+const REGEX_PREFIX: &str = "re:";
+
+/// 按分类规则判断影片的分类
+///
+/// 规则按顺序匹配，第一条命中的规则生效；都不命中时返回 None
+pub fn classify(
+    movie_id: &str,
+    data: &MovieNfoCrawler,
+    config: &ClassificationConfig,
+) -> anyhow::Result<Option<MovieCategory>> {
+    for rule in &config.rules {
+        if rule_matches(rule, movie_id, data)? {
+            return Ok(Some(rule.category));
+        }
+    }
+    Ok(None)
+}
+
+/// 分类影片并把结果合并到爬取数据：追加分类标签，没有分级时填入分类对应的 mpaa
+///
+/// 返回影片的分类，未启用分类或没有规则命中时返回 None
+pub fn apply_classification(
+    movie_id: &str,
+    data: &mut MovieNfoCrawler,
+    config: &ClassificationConfig,
+) -> anyhow::Result<Option<MovieCategory>> {
+    if !config.enabled {
+        return Ok(None);
+    }
+
+    let Some(category) = classify(movie_id, data, config)? else {
+        log::debug!("影片 {} 没有命中任何分类规则", movie_id);
+        return Ok(None);
+    };
+    log::info!("影片 {} 分类为: {}", movie_id, category.as_str());
+
+    let tag = config.tags.get(category);
+    if !tag.is_empty() && !data.tags.iter().any(|existing| existing.eq_ignore_ascii_case(tag)) {
+        data.tags.push(tag.to_string());
+    }
+
+    let mpaa = config.mpaa.get(category);
+    if data.mpaa.trim().is_empty() && !mpaa.is_empty() {
+        data.mpaa = mpaa.to_string();
+    }
+
+    Ok(Some(category))
+}
+
+/// 由NFO标签还原影片的分类，标签与某个分类配置的标签相同（不区分大小写）即属于该分类
+///
+/// 文件名后缀标签（如 "uncensored"）与自动分类写入的标签一样参与判断
+pub fn categories_from_tags(tags: &[String], config: &ClassificationConfig) -> Vec<MovieCategory> {
+    [
+        MovieCategory::Censored,
+        MovieCategory::Uncensored,
+        MovieCategory::Western,
+    ]
+    .into_iter()
+    .filter(|category| {
+        let tag = config.tags.get(*category);
+        !tag.is_empty() && tags.iter().any(|existing| existing.eq_ignore_ascii_case(tag))
+    })
+    .collect()
+}
+
+/// 检查分类规则中的正则表达式
+pub fn validate_rules(rules: &[ClassificationRule]) -> anyhow::Result<()> {
+    for (index, rule) in rules.iter().enumerate() {
+        let studio_patterns = rule
+            .studios
+            .iter()
+            .filter_map(|studio| studio.strip_prefix(REGEX_PREFIX));
+        for pattern in rule.id_patterns.iter().map(String::as_str).chain(studio_patterns) {
+            Regex::new(pattern).map_err(|e| {
+                anyhow::anyhow!("分类规则 #{} 的正则 '{}' 无效: {}", index + 1, pattern, e)
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// 影片ID前缀、ID正则、制作商或来源模板任意一项命中时规则命中
+fn rule_matches(
+    rule: &ClassificationRule,
+    movie_id: &str,
+    data: &MovieNfoCrawler,
+) -> anyhow::Result<bool> {
+    let upper_id = movie_id.to_uppercase();
+    if rule
+        .id_prefixes
+        .iter()
+        .any(|prefix| upper_id.starts_with(&prefix.to_uppercase()))
+    {
+        return Ok(true);
+    }
+
+    for pattern in &rule.id_patterns {
+        if Regex::new(pattern)?.is_match(movie_id) {
+            return Ok(true);
+        }
+    }
+
+    for studio in &rule.studios {
+        let matched = match studio.strip_prefix(REGEX_PREFIX) {
+            Some(regex) => {
+                let re = Regex::new(regex)?;
+                data.studios.iter().any(|value| re.is_match(value))
+            }
+            None => data
+                .studios
+                .iter()
+                .any(|value| value.trim().eq_ignore_ascii_case(studio)),
+        };
+        if matched {
+            return Ok(true);
+        }
+    }
+
+    Ok(!data.source_template.is_empty() && rule.templates.contains(&data.source_template))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn movie(studios: &[&str], source_template: &str) -> MovieNfoCrawler {
+        MovieNfoCrawler {
+            studios: studios.iter().map(|studio| studio.to_string()).collect(),
+            source_template: source_template.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_classify_default_rules() {
+        let config = ClassificationConfig::default();
+        let cases = [
+            ("ABP-123", &[][..], Some(MovieCategory::Censored)),
+            ("SSIS001", &[], Some(MovieCategory::Censored)),
+            ("FC2-PPV-1234567", &[], Some(MovieCategory::Uncensored)),
+            ("HEYZO-2345", &[], Some(MovieCategory::Uncensored)),
+            ("1PON-012345_678", &[], Some(MovieCategory::Uncensored)),
+            ("012345-678", &[], Some(MovieCategory::Uncensored)),
+            ("012345_01", &[], Some(MovieCategory::Uncensored)),
+            ("n1234", &[], Some(MovieCategory::Uncensored)),
+            ("ABC-123", &["一本道"], Some(MovieCategory::Uncensored)),
+            ("ABC-123", &["caribbeancom"], Some(MovieCategory::Uncensored)),
+            ("Blacked.23.01.15", &[], Some(MovieCategory::Western)),
+            ("SCENE-4321", &["Blacked Raw"], Some(MovieCategory::Western)),
+            ("some random movie", &[], None),
+        ];
+
+        for (movie_id, studios, expected) in cases {
+            let actual = classify(movie_id, &movie(studios, ""), &config).unwrap();
+            assert_eq!(actual, expected, "影片ID: {}, 制作商: {:?}", movie_id, studios);
+        }
+    }
+
+    #[test]
+    fn test_classify_by_template_and_studio_regex() {
+        let config = ClassificationConfig {
+            rules: vec![ClassificationRule {
+                category: MovieCategory::Uncensored,
+                id_prefixes: Vec::new(),
+                id_patterns: Vec::new(),
+                studios: vec!["re:^(?i)tokyo.?hot".to_string()],
+                templates: vec!["fc2.yaml".to_string()],
+            }],
+            ..Default::default()
+        };
+
+        let classify = |studios: &[&str], template: &str| {
+            classify("ABP-123", &movie(studios, template), &config).unwrap()
+        };
+        assert_eq!(classify(&[], "fc2.yaml"), Some(MovieCategory::Uncensored));
+        assert_eq!(classify(&["TOKYO HOT"], "javdb.yaml"), Some(MovieCategory::Uncensored));
+        assert_eq!(classify(&["S1"], "javdb.yaml"), None);
+    }
+
+    #[test]
+    fn test_apply_classification() {
+        let config = ClassificationConfig {
+            enabled: true,
+            ..Default::default()
+        };
+
+        // 追加分类标签，空分级填入分类对应的 mpaa
+        let mut data = movie(&[], "");
+        data.tags = vec!["剧情".to_string()];
+        let category = apply_classification("HEYZO-0001", &mut data, &config).unwrap();
+        assert_eq!(category, Some(MovieCategory::Uncensored));
+        assert_eq!(data.tags, vec!["剧情", "uncensored"]);
+        assert_eq!(data.mpaa, "JP-18+");
+
+        // 已有的标签不重复追加，已有的分级保留
+        let mut data = movie(&[], "");
+        data.tags = vec!["Uncensored".to_string()];
+        data.mpaa = "R18".to_string();
+        apply_classification("FC2-123456", &mut data, &config).unwrap();
+        assert_eq!(data.tags, vec!["Uncensored"]);
+        assert_eq!(data.mpaa, "R18");
+
+        // 未启用时不修改数据
+        let mut data = movie(&[], "");
+        let disabled = ClassificationConfig::default();
+        assert_eq!(apply_classification("FC2-123456", &mut data, &disabled).unwrap(), None);
+        assert!(data.tags.is_empty());
+        assert!(data.mpaa.is_empty());
+    }
+
+    #[test]
+    fn test_categories_from_tags() {
+        let config = ClassificationConfig::default();
+        let tags = vec!["剧情".to_string(), "Western".to_string()];
+        assert_eq!(categories_from_tags(&tags, &config), vec![MovieCategory::Western]);
+        assert!(categories_from_tags(&[], &config).is_empty());
+    }
+
+    #[test]
+    fn test_validate_rules() {
+        let rule = |id_pattern: &str, studio: &str| ClassificationRule {
+            category: MovieCategory::Censored,
+            id_prefixes: Vec::new(),
+            id_patterns: vec![id_pattern.to_string()],
+            studios: vec![studio.to_string()],
+            templates: Vec::new(),
+        };
+
+        assert!(validate_rules(&ClassificationConfig::default().rules).is_ok());
+        assert!(validate_rules(&[rule("^[0-9]+$", "re:(?i)s1")]).is_ok());
+        assert!(validate_rules(&[rule("([", "S1")]).is_err());
+        assert!(validate_rules(&[rule("^[0-9]+$", "re:([")]).is_err());
+    }
+}
@@ -1,4 +1,6 @@
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use config::Config;
 use serde::Deserialize;
@@ -18,6 +20,15 @@ pub struct ImageConfig {
     /// 图片下载超时时间（秒）
     #[serde(default = "default_image_download_timeout")]
     pub timeout: u64,
+    /// 智能下载：已有图片按 ETag/Last-Modified 重新验证，中断的下载按 Range 续传
+    #[serde(default)]
+    pub smart_download: bool,
+    /// 图片去重：相同内容的图片在 `<output>/.artwork-cache` 中只保存一份，影片目录中为硬链接
+    #[serde(default)]
+    pub dedupe: bool,
+    /// 图片尺寸偏好：候选 URL 包含越靠前的关键字越优先下载，如 `["full", "large", "cover", "thumb"]`
+    #[serde(default)]
+    pub size_preference: Vec<String>,
 }
 
 /// 翻译服务配置
@@ -32,9 +43,15 @@ pub struct TranslationConfig {
     /// 翻译服务 API Key
     #[serde(default = "default_translation_api_key")]
     pub api_key: Option<String>,
-    /// 翻译模型名称
+    /// 翻译模型名称，留空时使用提供商的默认模型（Ollama 必须指定）
     #[serde(default = "default_translation_model")]
     pub model: String,
+    /// 覆盖提供商的 API 地址（OpenAI 兼容接口地址，如 http://nas:11434/v1）
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// 每个翻译请求附加的 HTTP 头
+    #[serde(default)]
+    pub extra_headers: HashMap<String, String>,
     /// 目标语言
     #[serde(default = "default_translation_target_language")]
     pub target_language: String,
@@ -53,6 +70,56 @@ pub struct TranslationConfig {
     /// 翻译重试次数
     #[serde(default = "default_translation_retry_count")]
     pub retry_count: u32,
+    /// 重试退避的基础延迟（毫秒），每次重试翻倍
+    #[serde(default = "default_translation_retry_base_delay_ms")]
+    pub retry_base_delay_ms: u64,
+    /// 重试退避的最大延迟（毫秒）
+    #[serde(default = "default_translation_retry_max_delay_ms")]
+    pub retry_max_delay_ms: u64,
+    /// 重试延迟的随机抖动比例 (0.0-1.0)
+    #[serde(default = "default_translation_retry_jitter")]
+    pub retry_jitter: f32,
+    /// 需要翻译的字段白名单 (title/plot/tagline/series_name/series_overview/tags/genres)
+    #[serde(default = "default_translation_fields")]
+    pub fields: Vec<String>,
+    /// 是否使用流式响应（适合慢速本地模型翻译长文本）
+    #[serde(default)]
+    pub stream: bool,
+    /// 翻译标题前是否将原文保留到原始标题（仅在原始标题为空时）
+    #[serde(default = "default_preserve_original_title")]
+    pub preserve_original_title: bool,
+    /// 不翻译的术语表（演员名、厂牌等），翻译前以占位符替换，翻译后还原
+    #[serde(default)]
+    pub no_translate_terms: Vec<String>,
+    /// 演员名的处理方式
+    #[serde(default)]
+    pub actor_name_policy: ActorNamePolicy,
+}
+
+/// 演员名的处理方式
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActorNamePolicy {
+    /// 保持爬取到的演员名
+    #[default]
+    Keep,
+    /// 只由假名组成的演员名转为罗马字，其余保持不变
+    Romaji,
+    /// 使用翻译服务翻译为目标语言，术语表中的名字保持不变
+    TargetLanguage,
+}
+
+/// 分类表之外的类型的处理方式
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnmappedGenres {
+    /// 移到标签中
+    #[default]
+    Tag,
+    /// 丢弃
+    Drop,
+    /// 保留为类型
+    Keep,
 }
 
 /// 标签处理配置
@@ -94,6 +161,248 @@ pub struct NamingConfig {
     pub multi_actor_strategy: String,
     /// 是否将文件名转为小写
     pub capital: bool,
+    /// NFO 文件名风格
+    #[serde(default)]
+    pub nfo_filename_style: NfoFilenameStyle,
+}
+
+/// NFO 文件名风格
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum NfoFilenameStyle {
+    /// 与视频文件同名，如 `影片名 (2023).nfo`（Emby 推荐）
+    #[default]
+    Basename,
+    /// 固定为 `movie.nfo`，适合每部影片一个目录的结构
+    Movie,
+    /// 同时写入以上两种文件名
+    Both,
+}
+
+/// 文件完整性检查模式
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrityMode {
+    /// 只比较文件大小，修改时间变化仅记录警告
+    Size,
+    /// 比较文件大小和修改时间
+    #[default]
+    #[serde(rename = "size+mtime")]
+    SizeMtime,
+    /// 比较文件大小和首尾部分内容的校验和
+    Checksum,
+}
+
+/// 文件完整性检查配置
+#[derive(Debug, Deserialize, Clone)]
+pub struct IntegrityConfig {
+    /// 检查模式 (size/size+mtime/checksum)
+    #[serde(default)]
+    pub mode: IntegrityMode,
+    /// 读取文件元数据失败时的重试次数（应对 NFS 等网络存储的短暂失效）
+    #[serde(default = "default_integrity_stat_retries")]
+    pub stat_retries: u32,
+}
+
+/// 输出路由规则：按爬取到的字段把影片整理到不同的输出目录
+#[derive(Debug, Deserialize, Clone)]
+pub struct OutputRoute {
+    /// 匹配条件，键为字段名（genre/tag/studio/director/actor/series/title/category），值默认
+    /// 不区分大小写精确匹配，`re:` 前缀表示正则匹配；所有条件都满足时规则命中
+    #[serde(rename = "match")]
+    pub conditions: HashMap<String, String>,
+    /// 命中时使用的输出目录
+    pub output_dir: PathBuf,
+    /// 命中时使用的命名模板，未设置时使用全局模板
+    #[serde(default)]
+    pub naming_template: Option<String>,
+}
+
+/// 命令行 `--set 模板文件名:参数=值` 指定的模板参数覆盖
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateOverride {
+    pub template: String,
+    pub key: String,
+    pub value: String,
+}
+
+impl std::str::FromStr for TemplateOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (template, assignment) = s
+            .split_once(':')
+            .ok_or_else(|| format!("模板参数覆盖 '{}' 缺少模板文件名，格式: 模板文件名:参数=值", s))?;
+        let (key, value) = assignment
+            .split_once('=')
+            .ok_or_else(|| format!("模板参数覆盖 '{}' 缺少参数值，格式: 模板文件名:参数=值", s))?;
+
+        let (template, key) = (template.trim(), key.trim());
+        if template.is_empty() || key.is_empty() {
+            return Err(format!("模板参数覆盖 '{}' 的模板文件名和参数名不能为空", s));
+        }
+
+        Ok(Self {
+            template: template.to_string(),
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+/// 调试相关配置
+#[derive(Debug, Deserialize, Clone)]
+pub struct DebugConfig {
+    /// 模板爬取失败时保存获取到的页面和运行时变量
+    #[serde(default)]
+    pub dump_failed_crawls: bool,
+    /// 爬取失败现场的保存目录
+    #[serde(default = "default_debug_dump_dir")]
+    pub dump_dir: PathBuf,
+}
+
+/// 处理结果通知相关配置
+#[derive(Debug, Deserialize, Clone)]
+pub struct WebhookConfig {
+    /// 接收通知的地址，未设置时不发送通知
+    #[serde(default)]
+    pub url: Option<String>,
+    /// 请求体模板，`{id}`、`{file}`、`{outcome}`、`{message}` 替换为对应的值，未设置时发送默认 JSON
+    #[serde(default)]
+    pub body: Option<String>,
+    /// 请求超时时间（秒）
+    #[serde(default = "default_webhook_timeout")]
+    pub timeout: u64,
+}
+
+/// MQTT 状态发布相关配置
+#[derive(Debug, Deserialize, Clone)]
+pub struct MqttConfig {
+    /// MQTT 服务器地址，未设置时不发布状态
+    #[serde(default)]
+    pub host: Option<String>,
+    #[serde(default = "default_mqtt_port")]
+    pub port: u16,
+    #[serde(default = "default_mqtt_client_id")]
+    pub client_id: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// 单个文件处理状态的主题
+    #[serde(default = "default_mqtt_status_topic")]
+    pub status_topic: String,
+    /// 处理队列统计的主题
+    #[serde(default = "default_mqtt_stats_topic")]
+    pub stats_topic: String,
+    /// 心跳间隔（秒），0 表示不发送心跳
+    #[serde(default = "default_mqtt_keep_alive")]
+    pub keep_alive: u16,
+}
+
+/// NFO 输出相关配置
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct NfoConfig {
+    /// 写入 NFO 前清空的字段，对应的元素不会出现在输出中
+    #[serde(default)]
+    pub omit_fields: Vec<String>,
+    /// NFO 标题风格
+    #[serde(default)]
+    pub title_style: NfoTitleStyle,
+}
+
+/// NFO 标题风格
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum NfoTitleStyle {
+    /// 只写影片ID，如 `ABC-123`
+    Id,
+    /// 爬取到的标题
+    #[default]
+    Title,
+    /// 影片ID加标题，如 `ABC-123 - 标题`
+    #[serde(rename = "id - title")]
+    IdTitle,
+}
+
+/// 影片分类
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MovieCategory {
+    /// 有码
+    Censored,
+    /// 无码
+    Uncensored,
+    /// 欧美
+    Western,
+}
+
+impl MovieCategory {
+    /// 配置与输出路由中使用的分类名称
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MovieCategory::Censored => "censored",
+            MovieCategory::Uncensored => "uncensored",
+            MovieCategory::Western => "western",
+        }
+    }
+}
+
+/// 影片分类规则：影片ID前缀、ID正则、制作商或来源模板任意一项命中即归入该分类
+#[derive(Debug, Deserialize, Clone)]
+pub struct ClassificationRule {
+    /// 命中时的分类
+    pub category: MovieCategory,
+    /// 影片ID前缀（不区分大小写），如 "FC2"、"HEYZO"
+    #[serde(default)]
+    pub id_prefixes: Vec<String>,
+    /// 匹配影片ID的正则表达式，如一本道的日期编号 "^[0-9]{6}[-_][0-9]{3}$"
+    #[serde(default)]
+    pub id_patterns: Vec<String>,
+    /// 制作商名称，默认不区分大小写精确匹配，`re:` 前缀表示正则匹配
+    #[serde(default)]
+    pub studios: Vec<String>,
+    /// 爬取数据来源的模板文件名，如 "fc2.yaml"
+    #[serde(default)]
+    pub templates: Vec<String>,
+}
+
+/// 每个分类对应的取值，留空表示该分类不设置
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct CategoryValues {
+    #[serde(default)]
+    pub censored: String,
+    #[serde(default)]
+    pub uncensored: String,
+    #[serde(default)]
+    pub western: String,
+}
+
+impl CategoryValues {
+    pub fn get(&self, category: MovieCategory) -> &str {
+        match category {
+            MovieCategory::Censored => &self.censored,
+            MovieCategory::Uncensored => &self.uncensored,
+            MovieCategory::Western => &self.western,
+        }
+    }
+}
+
+/// 影片分类配置
+#[derive(Debug, Deserialize, Clone)]
+pub struct ClassificationConfig {
+    /// 是否在爬取后自动分类
+    #[serde(default)]
+    pub enabled: bool,
+    /// 分类规则，按顺序匹配，第一条命中的规则决定分类；配置后替换内置规则
+    #[serde(default = "default_classification_rules")]
+    pub rules: Vec<ClassificationRule>,
+    /// 各分类追加到NFO的标签
+    #[serde(default = "default_classification_tags")]
+    pub tags: CategoryValues,
+    /// 爬取结果没有分级时为各分类填入的 mpaa
+    #[serde(default = "default_classification_mpaa")]
+    pub mpaa: CategoryValues,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -101,13 +410,118 @@ pub struct AppConfig {
     // 基础配置
     pub migrate_files: Vec<String>,
     ignored_id_pattern: Vec<String>,
+    /// 不整理的影片ID，规范化后不区分大小写精确匹配
+    #[serde(default)]
+    pub ignored_ids: Vec<String>,
+    /// 不整理的影片ID列表文件，每行一个ID，`#` 开头的行为注释，修改后自动重新加载
+    #[serde(default)]
+    pub ignored_ids_file: Option<PathBuf>,
+    /// 数据质量过低或缺少必需字段而被跳过的影片ID追加到 `ignored_ids_file`
+    #[serde(default)]
+    pub remember_rejections: bool,
+    /// 文件名中影片ID之后的后缀标记及其对应的标签，如 C -> chinese-sub
+    #[serde(default)]
+    suffix_tags: HashMap<String, String>,
     pub input_dir: PathBuf,
     output_dir: PathBuf,
+    /// 扫描与监控输入目录时跳过的目录名（支持 `*`、`?` 通配符，不区分大小写）
+    #[serde(default = "default_scan_exclude_dirs")]
+    pub scan_exclude_dirs: Vec<String>,
     #[allow(dead_code)] // 预留给未来的并发控制功能
     pub thread_limit: usize,
     pub template_priority: Vec<String>,
     #[serde(default = "default_maximum_fetch_count")]
     pub maximum_fetch_count: usize,
+    /// 爬取结果的最低数据质量评分，低于该评分的结果会被丢弃
+    #[serde(default = "default_minimum_data_quality")]
+    pub minimum_data_quality: i32,
+    /// 爬取结果与影片ID、文件名的最低匹配评分，低于该评分时尝试下一个模板
+    #[serde(default)]
+    pub minimum_match_score: i32,
+    /// 缺失时中止整理该文件的NFO字段，如 title、plot、actors
+    #[serde(default)]
+    fail_on_missing: Vec<String>,
+    /// 尽力爬取：单个字段或详情页请求失败时保留已获取的数据，而不是放弃整个模板
+    #[serde(default)]
+    pub best_effort_crawl: bool,
+    /// 同一模板中并发请求的详情页数量
+    #[serde(default = "default_crawl_concurrency")]
+    pub crawl_concurrency: usize,
+    /// 所有模板同时进行的请求数量上限，未设置时不限制
+    #[serde(default)]
+    pub global_request_concurrency: Option<usize>,
+    /// 所有模板对同一站点（主机与端口）同时进行的请求数量上限，0 表示不限制
+    #[serde(default = "default_max_concurrent_per_host")]
+    pub max_concurrent_per_host: usize,
+    /// 模板请求的默认超时时间（秒），模板中配置了 `timeout` 时以模板为准
+    #[serde(default)]
+    pub request_timeout_seconds: Option<u64>,
+    /// 爬取结果缓存目录，未设置时不启用缓存
+    #[serde(default)]
+    pub crawl_cache_dir: Option<PathBuf>,
+    /// 爬取结果缓存的有效期（天），0 表示永不过期
+    #[serde(default = "default_crawl_cache_ttl_days")]
+    pub crawl_cache_ttl_days: u64,
+    /// 忽略爬取缓存重新爬取影片数据，爬取结果仍写入缓存
+    #[serde(default)]
+    pub refresh_metadata: bool,
+    /// 控制接口的端口，设置后提供 `GET /status`、`POST /pause`、`POST /resume`
+    #[serde(default)]
+    pub control_api_port: Option<u16>,
+    /// 控制接口监听的地址，接口没有认证，默认只允许本机访问
+    #[serde(default = "default_control_api_host")]
+    pub control_api_host: String,
+    /// 文件处理失败（不含按规则跳过）后的重试次数，0 表示不重试
+    #[serde(default)]
+    pub file_retry_attempts: u32,
+    /// 第一次重试前等待的时间（秒），之后每次重试加倍
+    #[serde(default = "default_file_retry_delay_seconds")]
+    pub file_retry_delay_seconds: u64,
+    /// 每秒最多开始处理的文件数，0 表示不限制
+    #[serde(default)]
+    pub max_files_per_second: f64,
+    /// `.strm` 文件原地生成NFO与图片，不移动到输出目录
+    #[serde(default = "default_strm_in_place")]
+    pub strm_in_place: bool,
+    /// 读取视频文件的分辨率、编码、时长与声道写入NFO的 fileinfo/streamdetails
+    #[serde(default)]
+    pub probe_media: bool,
+    /// ffprobe 可执行文件路径，配置后用 ffprobe 读取流信息，否则只内置解析 MP4/MOV
+    #[serde(default)]
+    pub ffprobe_path: Option<PathBuf>,
+    /// 生成NFO前规范化内容（排序、去重、去除空白），重复整理同一影片时生成相同的NFO
+    #[serde(default = "default_deterministic_nfo")]
+    pub deterministic_nfo: bool,
+    /// 类型或标签中出现任一标记时将NFO标记为成人内容，不依赖模板提供 is_adult
+    #[serde(default)]
+    pub adult_genre_markers: Vec<String>,
+    /// 爬取结果没有简介时截取剧情的前 N 个字符生成 `<outline>`，0 表示不生成
+    #[serde(default)]
+    pub plot_outline_length: usize,
+    /// 制作商/厂牌到系列名称的映射，爬取结果没有系列时按制作商补全（不区分大小写）
+    #[serde(default)]
+    pub label_series_map: HashMap<String, String>,
+    /// 类型分类表文件（TOML，规范类型名 = [别名, ...]），配置后类型统一映射为规范名称
+    #[serde(default)]
+    pub genre_taxonomy_file: Option<PathBuf>,
+    /// 分类表之外的类型的处理方式 (tag/drop/keep)
+    #[serde(default)]
+    pub unmapped_genres: UnmappedGenres,
+    /// 在输出目录的 `.actors/<演员名>/person.nfo` 中维护每位演员参演的影片
+    #[serde(default)]
+    pub generate_actor_index: bool,
+    /// 生成演员索引时同时为每位演员生成 M3U 播放列表
+    #[serde(default)]
+    pub actor_playlists: bool,
+    /// 整理完成后执行的命令及参数，参数中的 `{video}`、`{nfo}`、`{id}` 替换为视频文件、主NFO文件与影片ID
+    #[serde(default)]
+    pub post_process_command: Vec<String>,
+    /// 输出路由规则，按顺序匹配，第一条命中的规则生效，都不命中时使用 output_dir
+    #[serde(default)]
+    pub output_routes: Vec<OutputRoute>,
+    /// 按模板文件名覆盖模板的 env 参数，如镜像站的 base_url
+    #[serde(default)]
+    template_overrides: HashMap<String, HashMap<String, String>>,
 
     // 分组配置
     /// 图片下载相关配置
@@ -125,6 +539,24 @@ pub struct AppConfig {
     /// 文件命名相关配置
     #[serde(default)]
     pub naming: NamingConfig,
+    /// 文件完整性检查相关配置
+    #[serde(default)]
+    pub integrity: IntegrityConfig,
+    /// 调试相关配置
+    #[serde(default)]
+    pub debug: DebugConfig,
+    /// 影片分类相关配置
+    #[serde(default)]
+    pub classification: ClassificationConfig,
+    /// 处理结果通知相关配置
+    #[serde(default)]
+    pub webhook: WebhookConfig,
+    /// MQTT 状态发布相关配置
+    #[serde(default)]
+    pub mqtt: MqttConfig,
+    /// NFO 输出相关配置
+    #[serde(default)]
+    pub nfo: NfoConfig,
 
     // 兼容性字段（保持向后兼容）
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -177,6 +609,35 @@ fn default_maximum_fetch_count() -> usize {
     3
 }
 
+fn default_minimum_data_quality() -> i32 {
+    0
+}
+
+fn default_scan_exclude_dirs() -> Vec<String> {
+    ["@eaDir", "#recycle", ".recycle"].map(String::from).to_vec()
+}
+
+fn default_strm_in_place() -> bool {
+    true
+}
+
+fn default_deterministic_nfo() -> bool {
+    true
+}
+
+fn default_crawl_cache_ttl_days() -> u64 {
+    30
+}
+
+/// 默认详情页并发请求数：与模板引擎一致
+fn default_crawl_concurrency() -> usize {
+    crawler_template::DEFAULT_CONCURRENCY
+}
+
+fn default_max_concurrent_per_host() -> usize {
+    2
+}
+
 /// 默认文件命名模板：系列名/影片标题 (年份)
 fn default_file_naming_template() -> String {
     "$series$/$title$ ($year$)".to_string()
@@ -240,9 +701,9 @@ fn default_translation_api_key() -> Option<String> {
     None
 }
 
-/// 默认翻译模型：GPT-3.5 Turbo
+/// 默认翻译模型：空，由翻译器按提供商选择
 fn default_translation_model() -> String {
-    "gpt-3.5-turbo".to_string()
+    String::new()
 }
 
 /// 默认目标语言：中文
@@ -275,6 +736,39 @@ fn default_translation_retry_count() -> u32 {
     3
 }
 
+/// 默认重试基础延迟：1秒
+fn default_translation_retry_base_delay_ms() -> u64 {
+    1000
+}
+
+/// 默认重试最大延迟：30秒
+fn default_translation_retry_max_delay_ms() -> u64 {
+    30_000
+}
+
+/// 默认重试抖动：±20%
+fn default_translation_retry_jitter() -> f32 {
+    0.2
+}
+
+/// 默认保留原标题：启用
+fn default_preserve_original_title() -> bool {
+    true
+}
+
+/// 默认翻译字段：全部可翻译字段
+fn default_translation_fields() -> Vec<String> {
+    vec![
+        "title".to_string(),
+        "plot".to_string(),
+        "tagline".to_string(),
+        "series_name".to_string(),
+        "series_overview".to_string(),
+        "tags".to_string(),
+        "genres".to_string(),
+    ]
+}
+
 /// 默认翻译标签：启用
 fn default_translate_tags() -> bool {
     true
@@ -301,6 +795,115 @@ fn default_ai_merge_threshold() -> f32 {
     0.8
 }
 
+/// 默认元数据读取重试次数：2
+fn default_integrity_stat_retries() -> u32 {
+    2
+}
+
+/// 默认爬取失败现场保存目录：./debug
+fn default_debug_dump_dir() -> PathBuf {
+    PathBuf::from("./debug")
+}
+
+fn default_webhook_timeout() -> u64 {
+    10
+}
+
+fn default_control_api_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_file_retry_delay_seconds() -> u64 {
+    60
+}
+
+fn default_mqtt_port() -> u16 {
+    1883
+}
+
+fn default_mqtt_client_id() -> String {
+    "jav-tidy".to_string()
+}
+
+fn default_mqtt_status_topic() -> String {
+    "jav-tidy/status".to_string()
+}
+
+fn default_mqtt_stats_topic() -> String {
+    "jav-tidy/stats".to_string()
+}
+
+fn default_mqtt_keep_alive() -> u16 {
+    60
+}
+
+/// 内置分类规则：先识别无码与欧美影片，其余符合常见番号格式的影片归为有码
+fn default_classification_rules() -> Vec<ClassificationRule> {
+    let strings = |values: &[&str]| values.iter().map(|value| value.to_string()).collect();
+    vec![
+        ClassificationRule {
+            category: MovieCategory::Uncensored,
+            id_prefixes: strings(&[
+                "FC2", "HEYZO", "1PON", "CARIB", "10MU", "PACO", "KIN8", "H4610", "H0930", "C0930",
+            ]),
+            id_patterns: strings(&["^[0-9]{6}[-_][0-9]{2,3}$", "^(?i)n[0-9]{4}$"]),
+            studios: strings(&[
+                "一本道",
+                "1pondo",
+                "カリビアンコム",
+                "Caribbeancom",
+                "HEYZO",
+                "東京熱",
+                "Tokyo-Hot",
+                "天然むすめ",
+                "10musume",
+                "パコパコママ",
+                "pacopacomama",
+            ]),
+            templates: Vec::new(),
+        },
+        ClassificationRule {
+            category: MovieCategory::Western,
+            id_prefixes: Vec::new(),
+            id_patterns: strings(&[r"^[A-Za-z]+\.[0-9]{2}\.[0-9]{2}\.[0-9]{2}"]),
+            studios: strings(&[
+                "Blacked",
+                "Blacked Raw",
+                "Tushy",
+                "Vixen",
+                "Deeper",
+                "Brazzers",
+                "Reality Kings",
+                "Naughty America",
+            ]),
+            templates: Vec::new(),
+        },
+        ClassificationRule {
+            category: MovieCategory::Censored,
+            id_prefixes: Vec::new(),
+            id_patterns: strings(&["^(?i)[a-z]{2,6}-?[0-9]{2,5}$"]),
+            studios: Vec::new(),
+            templates: Vec::new(),
+        },
+    ]
+}
+
+fn default_classification_tags() -> CategoryValues {
+    CategoryValues {
+        censored: "censored".to_string(),
+        uncensored: "uncensored".to_string(),
+        western: "western".to_string(),
+    }
+}
+
+fn default_classification_mpaa() -> CategoryValues {
+    CategoryValues {
+        censored: "JP-18+".to_string(),
+        uncensored: "JP-18+".to_string(),
+        western: "XXX".to_string(),
+    }
+}
+
 // 为新的配置结构实现默认值
 impl Default for ImageConfig {
     fn default() -> Self {
@@ -309,6 +912,9 @@ impl Default for ImageConfig {
             download_preview_images: default_download_preview_images(),
             media_center_type: default_media_center_type(),
             timeout: default_image_download_timeout(),
+            smart_download: false,
+            dedupe: false,
+            size_preference: Vec::new(),
         }
     }
 }
@@ -320,12 +926,22 @@ impl Default for TranslationConfig {
             provider: default_translation_provider(),
             api_key: default_translation_api_key(),
             model: default_translation_model(),
+            base_url: None,
+            extra_headers: HashMap::new(),
             target_language: default_translation_target_language(),
             source_language: default_translation_source_language(),
             max_tokens: default_translation_max_tokens(),
             temperature: default_translation_temperature(),
             timeout: default_translation_timeout(),
             retry_count: default_translation_retry_count(),
+            retry_base_delay_ms: default_translation_retry_base_delay_ms(),
+            retry_max_delay_ms: default_translation_retry_max_delay_ms(),
+            retry_jitter: default_translation_retry_jitter(),
+            fields: default_translation_fields(),
+            stream: false,
+            preserve_original_title: default_preserve_original_title(),
+            no_translate_terms: Vec::new(),
+            actor_name_policy: ActorNamePolicy::default(),
         }
     }
 }
@@ -356,6 +972,61 @@ impl Default for NamingConfig {
             template: default_file_naming_template(),
             multi_actor_strategy: default_multi_actor_strategy(),
             capital: false, // 默认不转小写
+            nfo_filename_style: NfoFilenameStyle::default(),
+        }
+    }
+}
+
+impl Default for IntegrityConfig {
+    fn default() -> Self {
+        Self {
+            mode: IntegrityMode::default(),
+            stat_retries: default_integrity_stat_retries(),
+        }
+    }
+}
+
+impl Default for DebugConfig {
+    fn default() -> Self {
+        Self {
+            dump_failed_crawls: false,
+            dump_dir: default_debug_dump_dir(),
+        }
+    }
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            body: None,
+            timeout: default_webhook_timeout(),
+        }
+    }
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            host: None,
+            port: default_mqtt_port(),
+            client_id: default_mqtt_client_id(),
+            username: None,
+            password: None,
+            status_topic: default_mqtt_status_topic(),
+            stats_topic: default_mqtt_stats_topic(),
+            keep_alive: default_mqtt_keep_alive(),
+        }
+    }
+}
+
+impl Default for ClassificationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            rules: default_classification_rules(),
+            tags: default_classification_tags(),
+            mpaa: default_classification_mpaa(),
         }
     }
 }
@@ -373,6 +1044,10 @@ impl AppConfig {
         // 处理向后兼容性
         config.apply_legacy_fields();
 
+        crate::output_router::validate_routes(&config.output_routes)?;
+        crate::classifier::validate_rules(&config.classification.rules)?;
+        crate::nfo::validate_omit_fields(&config.nfo.omit_fields)?;
+
         Ok(config)
     }
 
@@ -458,15 +1133,14 @@ impl AppConfig {
         }
     }
 
-    pub fn get_migrate_files_ext(&self) -> &'static [&'static str] {
-        let leaked_strs: Vec<&'static str> = self
-            .migrate_files
-            .clone()
-            .into_iter()
-            .map(|s| Box::leak(s.into_boxed_str()) as &'static str)
-            .collect();
-
-        Box::leak(leaked_strs.into_boxed_slice())
+    /// 获取需要处理的文件扩展名集合（统一转为小写）
+    pub fn get_migrate_files_ext(&self) -> Arc<HashSet<String>> {
+        Arc::new(
+            self.migrate_files
+                .iter()
+                .map(|ext| ext.to_lowercase())
+                .collect(),
+        )
     }
 
     pub fn is_useing_template(&self, template: &str) -> bool {
@@ -477,11 +1151,41 @@ impl AppConfig {
         self.template_priority.iter().position(|t| t == template)
     }
 
+    /// 应用命令行指定的模板参数覆盖，优先于配置文件中的 `template_overrides`
+    pub fn apply_template_overrides(&mut self, overrides: &[TemplateOverride]) {
+        for item in overrides {
+            self.template_overrides
+                .entry(item.template.clone())
+                .or_default()
+                .insert(item.key.clone(), item.value.clone());
+        }
+    }
+
+    /// 获取指定模板的参数覆盖
+    pub fn get_template_overrides(&self, template: &str) -> Option<&HashMap<String, String>> {
+        self.template_overrides.get(template)
+    }
+
     /// 获取要忽略的ID模式列表
     pub fn get_ignored_id_pattern(&self) -> &[String] {
         &self.ignored_id_pattern
     }
 
+    /// 检查缺失指定NFO字段时是否应中止整理（字段名不区分大小写）
+    pub fn should_fail_on_missing(&self, field: &str) -> bool {
+        self.fail_on_missing
+            .iter()
+            .any(|f| f.eq_ignore_ascii_case(field))
+    }
+
+    /// 获取后缀标记对应的标签（后缀不区分大小写）
+    pub fn get_suffix_tag(&self, suffix: &str) -> Option<&str> {
+        self.suffix_tags
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(suffix))
+            .map(|(_, tag)| tag.as_str())
+    }
+
     /// 获取输出目录
     pub fn get_output_dir(&self) -> &std::path::Path {
         &self.output_dir
@@ -502,6 +1206,21 @@ impl AppConfig {
         &self.naming.multi_actor_strategy
     }
 
+    /// 获取NFO文件名风格
+    pub fn get_nfo_filename_style(&self) -> NfoFilenameStyle {
+        self.naming.nfo_filename_style
+    }
+
+    /// 获取文件完整性检查模式
+    pub fn get_integrity_mode(&self) -> IntegrityMode {
+        self.integrity.mode
+    }
+
+    /// 获取文件元数据读取重试次数
+    pub fn get_integrity_stat_retries(&self) -> u32 {
+        self.integrity.stat_retries
+    }
+
     /// 获取支持的字幕文件扩展名
     pub fn get_subtitle_extensions(&self) -> &[String] {
         &self.subtitle.extensions
@@ -522,6 +1241,11 @@ impl AppConfig {
         self.image.download_preview_images
     }
 
+    /// 获取是否启用智能下载（ETag 重新验证与断点续传）
+    pub fn is_smart_image_download(&self) -> bool {
+        self.image.smart_download
+    }
+
     /// 获取媒体中心类型
     pub fn get_media_center_type(&self) -> &str {
         &self.image.media_center_type
@@ -553,6 +1277,16 @@ impl AppConfig {
         &self.translation.model
     }
 
+    /// 获取覆盖的翻译 API 地址
+    pub fn get_translation_base_url(&self) -> &Option<String> {
+        &self.translation.base_url
+    }
+
+    /// 获取翻译请求附加的 HTTP 头
+    pub fn get_translation_extra_headers(&self) -> &HashMap<String, String> {
+        &self.translation.extra_headers
+    }
+
     /// 获取翻译目标语言
     pub fn get_translation_target_language(&self) -> &str {
         &self.translation.target_language
@@ -583,6 +1317,46 @@ impl AppConfig {
         self.translation.retry_count
     }
 
+    /// 获取翻译重试基础延迟（毫秒）
+    pub fn get_translation_retry_base_delay_ms(&self) -> u64 {
+        self.translation.retry_base_delay_ms
+    }
+
+    /// 获取翻译重试最大延迟（毫秒）
+    pub fn get_translation_retry_max_delay_ms(&self) -> u64 {
+        self.translation.retry_max_delay_ms
+    }
+
+    /// 获取翻译重试抖动比例
+    pub fn get_translation_retry_jitter(&self) -> f32 {
+        self.translation.retry_jitter
+    }
+
+    /// 获取是否使用流式翻译响应
+    pub fn is_translation_stream_enabled(&self) -> bool {
+        self.translation.stream
+    }
+
+    /// 获取翻译标题时是否保留原文到原始标题
+    pub fn should_preserve_original_title(&self) -> bool {
+        self.translation.preserve_original_title
+    }
+
+    /// 获取不翻译的术语表
+    pub fn get_translation_no_translate_terms(&self) -> &Vec<String> {
+        &self.translation.no_translate_terms
+    }
+
+    /// 获取演员名的处理方式
+    pub fn get_actor_name_policy(&self) -> ActorNamePolicy {
+        self.translation.actor_name_policy
+    }
+
+    /// 获取指定字段是否在翻译白名单中
+    pub fn should_translate_field(&self, field: &str) -> bool {
+        self.translation.fields.iter().any(|f| f == field)
+    }
+
     /// 获取是否翻译标签
     pub fn should_translate_tags(&self) -> bool {
         self.tag.translate
@@ -0,0 +1,367 @@
+// 已整理影片的一致性检查：按 NFO 内容重新计算的路径与实际路径不符时列出，可选择移动到计算出的路径
+
+use std::path::{Path, PathBuf};
+
+use crate::{
+    config::{AppConfig, NfoTitleStyle},
+    crawler::FileProcessingTransaction, file_organizer::FileOrganizer,
+    nfo::MovieNfo, parser::FileNameParser, template_parser::TemplateParser,
+};
+
+/// NFO 内容与所在路径不一致的影片
+#[derive(Debug)]
+pub struct Mismatch {
+    pub video_path: PathBuf,
+    pub nfo_path: PathBuf,
+    /// 按 NFO 内容与命名模板计算出的视频文件路径
+    pub expected_video_path: PathBuf,
+    /// 移动到计算出的路径所需的文件移动，无法移动时为原因
+    pub plan: Result<Vec<(PathBuf, PathBuf)>, String>,
+}
+
+/// 命名模板与输出路由会用到的 NFO 字段，省略后无法由NFO计算整理路径
+const NAMING_FIELDS: &[&str] = &[
+    "original_title",
+    "year",
+    "premiered",
+    "release_date",
+    "imdb_id",
+    "genres",
+    "tags",
+    "studios",
+    "directors",
+    "actors",
+    "set",
+];
+
+/// 检查输出目录（含输出路由的目录）中所有已整理的影片
+///
+/// 只检查有 NFO 的视频文件，跳过 `.` 开头的目录（如演员索引）与符号链接（多演员链接）
+pub fn check_consistency(config: &AppConfig) -> anyhow::Result<Vec<Mismatch>> {
+    let templates = std::iter::once(config.get_file_naming_template())
+        .chain(config.output_routes.iter().filter_map(|route| route.naming_template.as_deref()));
+    for template in templates {
+        if TemplateParser::uses_source_variables(template) {
+            return Err(anyhow::anyhow!(
+                "命名模板 '{}' 使用了 $source_dir$ 或 $source_name$，无法由NFO计算整理路径",
+                template
+            ));
+        }
+    }
+    let redacted: Vec<&str> = config
+        .nfo
+        .omit_fields
+        .iter()
+        .map(|field| field.trim())
+        .filter(|field| NAMING_FIELDS.contains(field))
+        .collect();
+    if !redacted.is_empty() || config.nfo.title_style != NfoTitleStyle::Title {
+        return Err(anyhow::anyhow!(
+            "NFO 省略了命名所需的字段或改写了标题（nfo.omit_fields / nfo.title_style），无法由NFO计算整理路径"
+        ));
+    }
+
+    let organizer = FileOrganizer::new();
+    let parser = FileNameParser::default();
+    let mut output_dirs: Vec<&Path> = std::iter::once(config.get_output_dir())
+        .chain(config.output_routes.iter().map(|route| route.output_dir.as_path()))
+        .collect();
+    output_dirs.sort();
+    output_dirs.dedup();
+
+    let mut videos = Vec::new();
+    for output_dir in output_dirs {
+        if output_dir.is_dir() {
+            collect_videos(output_dir, config, &mut videos)?;
+        }
+    }
+    videos.sort();
+
+    let mut mismatches = Vec::new();
+    for video_path in videos {
+        let Some(nfo_path) = find_nfo(&video_path) else {
+            log::debug!("视频文件没有NFO，跳过一致性检查: {}", video_path.display());
+            continue;
+        };
+        let nfo = match MovieNfo::read_from(&nfo_path) {
+            Ok(nfo) => nfo,
+            Err(e) => {
+                log::warn!("跳过一致性检查: {}", e);
+                continue;
+            }
+        };
+
+        let expected_video_path = match expected_path(&organizer, &parser, &video_path, &nfo, config) {
+            Ok(path) => path,
+            Err(e) => {
+                log::warn!("无法计算 {} 的整理路径: {}", video_path.display(), e);
+                continue;
+            }
+        };
+        if expected_video_path == video_path || is_same_file(&video_path, &expected_video_path) {
+            continue;
+        }
+
+        let plan = organizer
+            .plan_movie_move(&video_path, &expected_video_path, config)
+            .map_err(|e| e.to_string());
+        mismatches.push(Mismatch {
+            video_path,
+            nfo_path,
+            expected_video_path,
+            plan,
+        });
+    }
+
+    log::info!("一致性检查完成，发现 {} 个不一致的影片", mismatches.len());
+    Ok(mismatches)
+}
+
+/// 按计划移动影片的所有文件，全部移动完成后删除空的原目录
+pub fn fix_mismatch(mismatch: &Mismatch) -> anyhow::Result<()> {
+    let moves = mismatch
+        .plan
+        .as_ref()
+        .map_err(|reason| anyhow::anyhow!("无法移动 {}: {}", mismatch.video_path.display(), reason))?;
+
+    let mut transaction = FileProcessingTransaction::new(&mismatch.video_path);
+    for (from, to) in moves {
+        transaction.add_file_move(from.clone(), to.clone());
+    }
+    transaction.commit()?;
+
+    if let Some(old_dir) = mismatch.video_path.parent() {
+        // 目录中还有其他文件时删除失败，保留原目录
+        if std::fs::remove_dir(old_dir).is_ok() {
+            log::debug!("删除空目录: {}", old_dir.display());
+        }
+    }
+    log::info!(
+        "影片已移动: {} -> {}",
+        mismatch.video_path.display(),
+        mismatch.expected_video_path.display()
+    );
+    Ok(())
+}
+
+/// 输出检查结果，`fix` 为 true 时移动不一致的影片；返回仍未解决的影片数量
+pub fn run(config: &AppConfig, fix: bool) -> anyhow::Result<usize> {
+    let mismatches = check_consistency(config)?;
+    let mut unresolved = 0;
+
+    for mismatch in &mismatches {
+        println!("{}", mismatch.video_path.display());
+        println!("  NFO: {}", mismatch.nfo_path.display());
+        println!("  应为: {}", mismatch.expected_video_path.display());
+        match &mismatch.plan {
+            Ok(moves) => {
+                for (from, to) in moves {
+                    println!("  移动: {} -> {}", from.display(), to.display());
+                }
+            }
+            Err(reason) => println!("  无法移动: {}", reason),
+        }
+
+        if !fix {
+            unresolved += 1;
+            continue;
+        }
+        if let Err(e) = fix_mismatch(mismatch) {
+            println!("  修复失败: {:#}", e);
+            unresolved += 1;
+        }
+    }
+
+    match (mismatches.len(), fix) {
+        (0, _) => println!("所有影片的路径与NFO一致"),
+        (count, false) => println!("发现 {} 个不一致的影片，使用 --fix 按上述计划移动", count),
+        (count, true) => println!("发现 {} 个不一致的影片，已修复 {} 个", count, count - unresolved),
+    }
+    Ok(unresolved)
+}
+
+fn collect_videos(dir: &Path, config: &AppConfig, videos: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    let migrate_files = config.get_migrate_files_ext();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        if file_type.is_symlink() {
+            continue;
+        }
+        if file_type.is_dir() {
+            if !entry.file_name().to_string_lossy().starts_with('.') {
+                collect_videos(&path, config, videos)?;
+            }
+            continue;
+        }
+        if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| migrate_files.contains(&ext.to_lowercase()))
+        {
+            videos.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// 视频文件对应的NFO，`文件名.nfo` 优先于 `movie.nfo`
+fn find_nfo(video_path: &Path) -> Option<PathBuf> {
+    [video_path.with_extension("nfo"), video_path.with_file_name("movie.nfo")]
+        .into_iter()
+        .find(|path| path.is_file())
+}
+
+/// 按整理时的规则计算视频文件路径，包括与其他影片同名时追加影片ID的目录
+fn expected_path(
+    organizer: &FileOrganizer,
+    parser: &FileNameParser,
+    video_path: &Path,
+    nfo: &MovieNfo,
+    config: &AppConfig,
+) -> anyhow::Result<PathBuf> {
+    let suffix_tags = parser
+        .extract_movie_info(video_path, config)
+        .map(|parsed| parsed.suffix_tags)
+        .unwrap_or_default();
+    let expected = organizer.generate_new_file_path(video_path, nfo, config, &suffix_tags)?;
+    let (Some(movie_dir), Some(file_name)) = (expected.parent(), expected.file_name()) else {
+        return Ok(expected);
+    };
+    let movie_id = nfo
+        .unique_ids
+        .iter()
+        .find(|id| id.default == Some(true))
+        .or_else(|| nfo.unique_ids.first())
+        .map(|id| id.value.as_str())
+        .unwrap_or_default();
+    Ok(organizer
+        .ensure_unique_movie_dir(movie_dir, nfo, movie_id)?
+        .join(file_name))
+}
+
+/// 是否为同一文件（多演员硬链接）
+#[cfg(unix)]
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (std::fs::metadata(a), std::fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_same_file(_a: &Path, _b: &Path) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nfo::{NfoFormatter, UniqueId};
+
+    fn create_config(root: &Path) -> AppConfig {
+        std::fs::create_dir_all(root).unwrap();
+        let config_path = root.join("config.toml");
+        std::fs::write(
+            &config_path,
+            format!(
+                r#"
+migrate_files = ["mp4"]
+ignored_id_pattern = []
+input_dir = "{}"
+output_dir = "{}"
+thread_limit = 1
+template_priority = []
+
+[naming]
+template = "$title$ ($year$)"
+capital = false
+"#,
+                root.join("input").display().to_string().replace('\\', "/"),
+                root.join("output").display().to_string().replace('\\', "/")
+            ),
+        )
+        .unwrap();
+        AppConfig::new(&config_path).unwrap()
+    }
+
+    fn write_movie(dir: &Path, stem: &str, id: &str, title: &str) {
+        std::fs::create_dir_all(dir).unwrap();
+        let nfo = MovieNfo {
+            title: title.to_string(),
+            year: Some(2023),
+            unique_ids: vec![UniqueId {
+                id_type: "javdb".to_string(),
+                default: Some(true),
+                value: id.to_string(),
+            }],
+            ..Default::default()
+        };
+        std::fs::write(dir.join(format!("{}.mp4", stem)), "video").unwrap();
+        std::fs::write(dir.join(format!("{}.nfo", stem)), nfo.format_to_xml()).unwrap();
+        std::fs::write(dir.join(format!("{}.zh.srt", stem)), "subtitle").unwrap();
+        std::fs::write(dir.join(format!("{}-poster.jpg", id)), "poster").unwrap();
+    }
+
+    #[test]
+    fn test_detect_and_fix_mismatch() {
+        let root = std::env::temp_dir().join("test_consistency_detect_and_fix");
+        let _ = std::fs::remove_dir_all(&root);
+        let config = create_config(&root);
+        let output_dir = root.join("output");
+
+        // 手动修改了NFO标题的影片与路径一致的影片
+        write_movie(&output_dir.join("旧标题 (2023)"), "旧标题 (2023)", "AAA-001", "新标题");
+        write_movie(&output_dir.join("标题二 (2023)"), "标题二 (2023)", "BBB-002", "标题二");
+
+        let mismatches = check_consistency(&config).unwrap();
+        assert_eq!(mismatches.len(), 1);
+        let expected_dir = output_dir.join("新标题 (2023)");
+        assert_eq!(mismatches[0].video_path, output_dir.join("旧标题 (2023)").join("旧标题 (2023).mp4"));
+        assert_eq!(mismatches[0].expected_video_path, expected_dir.join("新标题 (2023).mp4"));
+        assert_eq!(mismatches[0].plan.as_ref().unwrap().len(), 4);
+
+        // 未指定修复时不移动任何文件
+        assert_eq!(run(&config, false).unwrap(), 1);
+        assert!(!expected_dir.exists());
+
+        assert_eq!(run(&config, true).unwrap(), 0);
+        assert!(!output_dir.join("旧标题 (2023)").exists());
+        for name in ["新标题 (2023).mp4", "新标题 (2023).nfo", "新标题 (2023).zh.srt", "AAA-001-poster.jpg"] {
+            assert!(expected_dir.join(name).is_file(), "{}", name);
+        }
+        assert!(check_consistency(&config).unwrap().is_empty());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_mismatch_in_shared_dir() {
+        let root = std::env::temp_dir().join("test_consistency_shared_dir");
+        let _ = std::fs::remove_dir_all(&root);
+        let config = create_config(&root);
+        let output_dir = root.join("output");
+
+        // 目标目录已被其他影片使用时改用追加影片ID的目录，只移动以视频文件名开头的文件
+        let shared_dir = output_dir.join("合集");
+        write_movie(&shared_dir, "第一部", "CCC-001", "标题三");
+        write_movie(&shared_dir, "第二部", "CCC-002", "标题四");
+        write_movie(&output_dir.join("标题三 (2023)"), "标题三 (2023)", "DDD-001", "标题三");
+
+        let mismatches = check_consistency(&config).unwrap();
+        assert_eq!(mismatches.len(), 2);
+        let expected = output_dir.join("标题三 (2023) [CCC-001]").join("标题三 (2023).mp4");
+        assert_eq!(mismatches[0].expected_video_path, expected);
+
+        fix_mismatch(&mismatches[0]).unwrap();
+        assert!(expected.is_file());
+        assert!(expected.with_extension("nfo").is_file());
+        assert!(shared_dir.join("CCC-001-poster.jpg").is_file());
+        assert!(shared_dir.join("第二部.mp4").is_file());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,290 @@
+// 控制接口：查询文件处理队列的状态，暂停、恢复与排空处理
+
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use http_body_util::Full;
+use hyper::{
+    body::{Bytes, Incoming},
+    server::conn::http1,
+    service::service_fn,
+    Method, Request, Response, StatusCode,
+};
+use hyper_util::rt::TokioIo;
+use serde::Serialize;
+use tokio::sync::watch;
+
+use crate::{config::AppConfig, mqtt::QueueStats, pipeline::ProcessOutcome};
+
+/// 文件处理队列的共享状态
+#[derive(Debug)]
+pub struct QueueState {
+    stats: Mutex<QueueStats>,
+    current: Mutex<Option<PathBuf>>,
+    paused: watch::Sender<bool>,
+    draining: watch::Sender<bool>,
+}
+
+/// `GET /status` 的响应
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueueSnapshot {
+    pub paused: bool,
+    /// 已停止接收新文件，处理完队列中的文件后退出
+    pub draining: bool,
+    /// 正在处理的文件
+    pub current: Option<PathBuf>,
+    pub stats: QueueStats,
+}
+
+impl Default for QueueState {
+    fn default() -> Self {
+        Self {
+            stats: Mutex::default(),
+            current: Mutex::default(),
+            paused: watch::Sender::new(false),
+            draining: watch::Sender::new(false),
+        }
+    }
+}
+
+impl QueueState {
+    /// 暂停时等待恢复，未暂停时立即返回
+    pub async fn wait_until_resumed(&self) {
+        let mut paused = self.paused.subscribe();
+        if *paused.borrow() {
+            log::info!("文件处理已暂停，等待恢复");
+        }
+        // 发送端由自身持有，等待不会因通道关闭而失败
+        let _ = paused.wait_for(|paused| !paused).await;
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        if self.paused.send_replace(paused) != paused {
+            log::info!("文件处理已{}", if paused { "暂停" } else { "恢复" });
+        }
+    }
+
+    /// 停止接收新文件，队列中已有的文件处理完后队列退出；暂停中的队列同时恢复
+    pub fn drain(&self) {
+        if !self.draining.send_replace(true) {
+            log::info!("文件处理队列停止接收新文件，处理完队列中的文件后退出");
+        }
+        self.set_paused(false);
+    }
+
+    /// 等待开始排空，已在排空时立即返回
+    pub async fn wait_for_drain(&self) {
+        // 发送端由自身持有，等待不会因通道关闭而失败
+        let _ = self.draining.subscribe().wait_for(|draining| *draining).await;
+    }
+
+    /// 文件开始处理
+    pub fn start(&self, file_path: &Path) {
+        *self.current.lock().unwrap_or_else(PoisonError::into_inner) = Some(file_path.to_path_buf());
+    }
+
+    /// 文件处理完成，`pending` 为等待处理的文件数
+    pub fn finish(&self, result: &anyhow::Result<ProcessOutcome>, pending: usize) {
+        *self.current.lock().unwrap_or_else(PoisonError::into_inner) = None;
+        self.stats
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .record(result, pending);
+    }
+
+    pub fn snapshot(&self) -> QueueSnapshot {
+        QueueSnapshot {
+            paused: *self.paused.borrow(),
+            draining: *self.draining.borrow(),
+            current: self.current.lock().unwrap_or_else(PoisonError::into_inner).clone(),
+            stats: self.stats.lock().unwrap_or_else(PoisonError::into_inner).clone(),
+        }
+    }
+}
+
+/// 在配置的端口启动控制接口，返回实际监听的地址；未配置端口时不启动
+///
+/// 需要在 tokio 运行时中调用
+pub fn start(config: &AppConfig, state: Arc<QueueState>) -> anyhow::Result<Option<SocketAddr>> {
+    let Some(port) = config.control_api_port else {
+        return Ok(None);
+    };
+
+    let listener = std::net::TcpListener::bind((config.control_api_host.as_str(), port))
+        .map_err(|e| anyhow::anyhow!("控制接口无法监听 {}:{}: {}", config.control_api_host, port, e))?;
+    listener.set_nonblocking(true)?;
+    let listener = tokio::net::TcpListener::from_std(listener)?;
+    let address = listener.local_addr()?;
+    log::info!("控制接口已启动: http://{}", address);
+
+    tokio::spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    log::warn!("控制接口接受连接失败: {}", e);
+                    continue;
+                }
+            };
+            let state = state.clone();
+            tokio::spawn(async move {
+                let service = service_fn(move |request| handle(request, state.clone()));
+                if let Err(e) = http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await
+                {
+                    log::debug!("控制接口连接出错: {}", e);
+                }
+            });
+        }
+    });
+
+    Ok(Some(address))
+}
+
+async fn handle(
+    request: Request<Incoming>,
+    state: Arc<QueueState>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    let response = match (request.method(), request.uri().path()) {
+        (&Method::GET, "/status") => json_response(StatusCode::OK, &state.snapshot()),
+        (&Method::POST, "/pause") => {
+            state.set_paused(true);
+            json_response(StatusCode::OK, &state.snapshot())
+        }
+        (&Method::POST, "/resume") => {
+            state.set_paused(false);
+            json_response(StatusCode::OK, &state.snapshot())
+        }
+        (&Method::POST, "/drain") => {
+            state.drain();
+            json_response(StatusCode::OK, &state.snapshot())
+        }
+        (_, "/status" | "/pause" | "/resume" | "/drain") => error_response(StatusCode::METHOD_NOT_ALLOWED),
+        _ => error_response(StatusCode::NOT_FOUND),
+    };
+    Ok(response)
+}
+
+fn json_response(status: StatusCode, body: &impl Serialize) -> Response<Full<Bytes>> {
+    match serde_json::to_vec(body) {
+        Ok(body) => Response::builder()
+            .status(status)
+            .header(hyper::header::CONTENT_TYPE, "application/json")
+            .body(Full::new(Bytes::from(body)))
+            .unwrap_or_else(|_| error_response(StatusCode::INTERNAL_SERVER_ERROR)),
+        Err(e) => {
+            log::warn!("序列化控制接口响应失败: {}", e);
+            error_response(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+fn error_response(status: StatusCode) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::new()));
+    *response.status_mut() = status;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn create_config(port: u16) -> AppConfig {
+        let config_path = std::env::temp_dir().join("test_control_api.toml");
+        std::fs::write(
+            &config_path,
+            format!(
+                r#"
+migrate_files = ["mp4"]
+ignored_id_pattern = []
+input_dir = "./input"
+output_dir = "./output"
+thread_limit = 1
+template_priority = []
+control_api_port = {}
+"#,
+                port
+            ),
+        )
+        .unwrap();
+        let config = AppConfig::new(&config_path).unwrap();
+        std::fs::remove_file(&config_path).unwrap();
+        config
+    }
+
+    #[tokio::test]
+    async fn test_control_api_status_and_pause() {
+        let state = Arc::new(QueueState::default());
+        state.start(Path::new("/input/API-001.mp4"));
+        state.finish(&Err(anyhow::anyhow!("爬取失败")), 2);
+        state.start(Path::new("/input/API-002.mp4"));
+
+        let address = start(&create_config(0), state.clone()).unwrap().unwrap();
+        let client = reqwest::Client::new();
+        let url = |path: &str| format!("http://{}{}", address, path);
+
+        let status: serde_json::Value = client
+            .get(url("/status"))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(
+            status,
+            serde_json::json!({
+                "paused": false,
+                "draining": false,
+                "current": "/input/API-002.mp4",
+                "stats": {"processed": 1, "organized": 0, "skipped": 0, "failed": 1, "pending": 2},
+            })
+        );
+
+        let paused: serde_json::Value =
+            client.post(url("/pause")).send().await.unwrap().json().await.unwrap();
+        assert_eq!(paused["paused"], true);
+
+        // 暂停期间等待恢复
+        let waiting = tokio::spawn({
+            let state = state.clone();
+            async move { state.wait_until_resumed().await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiting.is_finished());
+
+        let resumed: serde_json::Value =
+            client.post(url("/resume")).send().await.unwrap().json().await.unwrap();
+        assert_eq!(resumed["paused"], false);
+        tokio::time::timeout(Duration::from_secs(5), waiting)
+            .await
+            .unwrap()
+            .unwrap();
+
+        // 排空时恢复暂停的队列
+        state.set_paused(true);
+        let draining: serde_json::Value =
+            client.post(url("/drain")).send().await.unwrap().json().await.unwrap();
+        assert_eq!(draining["draining"], true);
+        assert_eq!(draining["paused"], false);
+        tokio::time::timeout(Duration::from_secs(5), state.wait_for_drain())
+            .await
+            .unwrap();
+
+        let response = client.get(url("/pause")).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::METHOD_NOT_ALLOWED);
+        let response = client.get(url("/missing")).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+
+        // 未配置端口时不启动
+        let mut config = create_config(0);
+        config.control_api_port = None;
+        assert!(start(&config, state).unwrap().is_none());
+    }
+}
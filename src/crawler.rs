@@ -2,36 +2,51 @@ use std::{
     collections::HashMap,
     fs::File,
     path::{Path, PathBuf},
-    sync::{Arc, LazyLock},
+    future::Future,
+    sync::{Arc, LazyLock, Mutex, PoisonError},
     time::SystemTime,
 };
 
 use crate::{
-    config::AppConfig,
+    actor_index::update_actor_index,
+    classifier::apply_classification,
+    config::{AppConfig, IntegrityMode},
+    control_api::{self, QueueState},
     error::AppError,
+    file::{safe_write::safe_write, MediaItemKind},
     file_organizer::FileOrganizer,
+    ignored_ids::IgnoredIds,
     image_manager::ImageManager,
-    nfo::{MediaCenterType, MovieNfo, MovieNfoCrawler, NfoFormatter},
-    nfo_generator::NfoGenerator,
-    parser::FileNameParser,
+    matcher::{rank_candidates, score_candidate},
+    media_probe::probe_media,
+    mqtt::QueueStatus,
+    nfo::{MovieNfo, MovieNfoCrawler, NfoFormatter},
+    nfo_generator::{NfoGenerator, NfoValidationStats, NfoWarning},
+    output_router::{route_output, OutputTarget},
+    parser::{FileNameParser, ParsedFileName},
+    pipeline::{Pipeline, PreviewPlan, ProcessOutcome, ProgressReporter},
+    taxonomy::{apply_genre_taxonomy, Taxonomy},
     translator::Translator,
+    webhook::{ProcessEvent, Webhook},
 };
 use anyhow::Context;
-use crawler_template::Template;
+use crawler_template::{CrawlFailure, CrawlSnapshot, CrawlerErr, Template};
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use tokio::sync::mpsc;
 
-type Templates = Arc<Vec<(String, Template<MovieNfoCrawler>)>>;
+pub(crate) type Templates = Arc<Vec<(String, Template<MovieNfoCrawler>)>>;
 
 /// 文件处理的依赖项集合
-struct ProcessingDependencies<'a> {
-    parser: &'a FileNameParser,
-    nfo_generator: &'a NfoGenerator,
-    file_organizer: &'a FileOrganizer,
-    image_manager: &'a ImageManager,
-    translator: Option<&'a Translator>,
-    templates: &'a Templates,
-    config: &'a AppConfig,
+pub(crate) struct ProcessingDependencies<'a> {
+    pub(crate) parser: &'a FileNameParser,
+    pub(crate) nfo_generator: &'a NfoGenerator,
+    pub(crate) file_organizer: &'a FileOrganizer,
+    pub(crate) image_manager: &'a ImageManager,
+    pub(crate) translator: Option<&'a Translator>,
+    pub(crate) ignored_ids: &'a IgnoredIds,
+    pub(crate) genre_taxonomy: Option<&'a Taxonomy>,
+    pub(crate) templates: &'a Templates,
+    pub(crate) config: &'a AppConfig,
 }
 
 /// 文件处理锁，防止文件在处理过程中被其他进程操作
@@ -102,34 +117,64 @@ impl Drop for FileProcessingLock {
 }
 
 /// 文件完整性检查器
+#[derive(Clone)]
 pub struct FileIntegrityChecker {
     path: PathBuf,
+    mode: IntegrityMode,
+    stat_retries: u32,
     initial_size: u64,
     initial_modified: SystemTime,
+    initial_checksum: Option<u64>,
 }
 
+/// 校验和读取的首尾数据块大小
+const CHECKSUM_CHUNK_SIZE: u64 = 4 * 1024 * 1024;
+
+/// 元数据读取重试间隔
+const STAT_RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(200);
+
 impl FileIntegrityChecker {
-    pub fn new(path: &Path) -> anyhow::Result<Self> {
-        let metadata = std::fs::metadata(path)?;
-        let initial_size = metadata.len();
-        let initial_modified = metadata.modified()?;
+    pub fn new(path: &Path, mode: IntegrityMode, stat_retries: u32) -> anyhow::Result<Self> {
+        let (initial_size, initial_modified, is_dir) = Self::stat_item(path, stat_retries)?;
+        let initial_checksum = match mode {
+            // 目录没有可直接读取的内容，只比较大小和修改时间
+            IntegrityMode::Checksum if !is_dir => Some(Self::checksum(path, initial_size)?),
+            _ => None,
+        };
 
         Ok(FileIntegrityChecker {
             path: path.to_path_buf(),
+            mode,
+            stat_retries,
             initial_size,
             initial_modified,
+            initial_checksum,
         })
     }
 
-    pub fn verify_integrity(&self) -> anyhow::Result<bool> {
-        if !self.path.exists() {
-            log::warn!("文件已不存在: {}", self.path.display());
-            return Ok(false);
-        }
+    /// 按配置创建检查器，在阻塞线程中读取元数据与校验和，重试等待不占用异步运行时
+    pub async fn from_config(path: &Path, config: &AppConfig) -> anyhow::Result<Self> {
+        let path = path.to_path_buf();
+        let mode = config.get_integrity_mode();
+        let stat_retries = config.get_integrity_stat_retries();
+        tokio::task::spawn_blocking(move || Self::new(&path, mode, stat_retries)).await?
+    }
 
-        let metadata = std::fs::metadata(&self.path)?;
-        let current_size = metadata.len();
-        let current_modified = metadata.modified()?;
+    /// 在阻塞线程中执行 `verify_integrity`
+    pub async fn verify(&self) -> anyhow::Result<bool> {
+        let checker = self.clone();
+        tokio::task::spawn_blocking(move || checker.verify_integrity()).await?
+    }
+
+    pub fn verify_integrity(&self) -> anyhow::Result<bool> {
+        let (current_size, current_modified, _) = match Self::stat_item(&self.path, self.stat_retries) {
+            Ok(stat) => stat,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                log::warn!("文件已不存在: {}", self.path.display());
+                return Ok(false);
+            }
+            Err(e) => return Err(e.into()),
+        };
 
         if current_size != self.initial_size {
             log::warn!(
@@ -141,13 +186,88 @@ impl FileIntegrityChecker {
             return Ok(false);
         }
 
+        if let Some(initial_checksum) = self.initial_checksum {
+            if Self::checksum(&self.path, current_size)? != initial_checksum {
+                log::warn!("文件内容校验和发生变化: {}", self.path.display());
+                return Ok(false);
+            }
+        }
+
         if current_modified != self.initial_modified {
-            log::warn!("文件修改时间发生变化: {}", self.path.display());
-            return Ok(false);
+            if self.mode == IntegrityMode::SizeMtime {
+                log::warn!("文件修改时间发生变化: {}", self.path.display());
+                return Ok(false);
+            }
+            log::warn!("文件修改时间发生变化，但内容检查通过，继续处理: {}", self.path.display());
         }
 
         Ok(true)
     }
+
+    /// 读取大小、修改时间以及是否为目录
+    ///
+    /// 蓝光原盘目录统计 `BDMV` 中所有文件的总大小与最新修改时间，
+    /// 写入目录根部的NFO与图片不影响检查
+    fn stat_item(path: &Path, retries: u32) -> std::io::Result<(u64, SystemTime, bool)> {
+        let metadata = Self::stat_with_retry(path, retries)?;
+        if !metadata.is_dir() {
+            return Ok((metadata.len(), metadata.modified()?, false));
+        }
+
+        let mut size = 0;
+        let mut modified = SystemTime::UNIX_EPOCH;
+        for entry in walkdir::WalkDir::new(path.join("BDMV")) {
+            let entry = entry?;
+            if entry.file_type().is_file() {
+                let metadata = entry.metadata()?;
+                size += metadata.len();
+                modified = modified.max(metadata.modified()?);
+            }
+        }
+
+        Ok((size, modified, true))
+    }
+
+    /// 读取文件元数据，失败时重试以应对网络存储的短暂失效
+    fn stat_with_retry(path: &Path, retries: u32) -> std::io::Result<std::fs::Metadata> {
+        let mut attempt = 0;
+        loop {
+            match std::fs::metadata(path) {
+                Ok(metadata) => return Ok(metadata),
+                Err(e) if attempt < retries => {
+                    log::debug!("读取文件元数据失败 (第{}次): {}: {}", attempt + 1, path.display(), e);
+                    attempt += 1;
+                    std::thread::sleep(STAT_RETRY_DELAY);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// 计算文件长度及首尾各 4 MiB 数据的校验和，避免读取整个大文件
+    fn checksum(path: &Path, len: u64) -> anyhow::Result<u64> {
+        use std::hash::{Hash, Hasher};
+        use std::io::{Read, Seek, SeekFrom};
+
+        let mut file = File::open(path)?;
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        len.hash(&mut hasher);
+
+        let head_len = len.min(CHECKSUM_CHUNK_SIZE);
+        let mut buffer = vec![0u8; head_len as usize];
+        file.read_exact(&mut buffer)?;
+        hasher.write(&buffer);
+
+        let tail_start = len.saturating_sub(CHECKSUM_CHUNK_SIZE).max(head_len);
+        if tail_start < len {
+            file.seek(SeekFrom::Start(tail_start))?;
+            let mut buffer = vec![0u8; (len - tail_start) as usize];
+            file.read_exact(&mut buffer)?;
+            hasher.write(&buffer);
+        }
+
+        Ok(hasher.finish())
+    }
 }
 
 /// 文件处理事务，确保操作的原子性
@@ -200,10 +320,14 @@ impl FileProcessingTransaction {
                     if let Some(parent) = path.parent() {
                         std::fs::create_dir_all(parent)?;
                     }
-                    std::fs::write(path, content)?;
+                    safe_write(path, content)?;
                 }
                 TransactionOperation::MoveFile { from, to } => {
                     log::debug!("移动文件: {} -> {}", from.display(), to.display());
+                    // 目录（如蓝光原盘）整体重命名，不与已存在的目录合并
+                    if from.is_dir() && to.exists() {
+                        return Err(anyhow::anyhow!("目标目录已存在: {}", to.display()));
+                    }
                     if let Some(parent) = to.parent() {
                         std::fs::create_dir_all(parent)?;
                     }
@@ -234,91 +358,94 @@ impl Drop for FileProcessingTransaction {
     }
 }
 
+/// 运行中的文件处理队列
+pub struct FileQueue {
+    pub state: Arc<QueueState>,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl FileQueue {
+    /// 停止接收新文件，等待队列中已有的文件全部处理完成
+    pub async fn drain(self) {
+        self.state.drain();
+        if let Err(e) = self.handle.await {
+            log::error!("文件处理队列异常退出: {}", e);
+        }
+    }
+}
+
 pub fn initial(
     template_path: &Path,
     config: &AppConfig,
     file_rx: mpsc::Receiver<PathBuf>,
     multi_progress: MultiProgress,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<FileQueue> {
     log::info!("初始化爬虫系统...");
     log::info!("模板目录: {}", template_path.display());
-    
-    let templates = Arc::new(
-        get_templates(template_path, config)
-            .with_context(|| format!("get template from {}", template_path.display()))?,
-    );
-    
-    log::info!("成功加载 {} 个模板", templates.len());
 
-    let config = Arc::new(config.clone());
+    let pipeline = Pipeline::new(config.clone(), template_path)?;
+
+    let queue_state = Arc::new(QueueState::default());
+    control_api::start(config, queue_state.clone())?;
 
     // 启动文件处理任务
     log::info!("启动文件处理队列任务...");
-    tokio::spawn(process_file_queue(
+    let handle = tokio::spawn(process_file_queue(
         file_rx,
-        templates,
-        config,
+        pipeline,
         multi_progress,
+        queue_state.clone(),
     ));
 
     log::info!("爬虫系统初始化完成");
-    Ok(())
+    Ok(FileQueue {
+        state: queue_state,
+        handle,
+    })
 }
 
 /// 文件处理队列的主循环
+///
+/// 开始排空后关闭通道不再接收新文件，已进入通道的文件处理完后退出
 async fn process_file_queue(
     mut file_rx: mpsc::Receiver<PathBuf>,
-    templates: Templates,
-    config: Arc<AppConfig>,
+    pipeline: Pipeline,
     multi_progress: MultiProgress,
+    queue_state: Arc<QueueState>,
 ) {
     log::info!("文件处理队列已启动");
 
-    // 创建工具实例
-    let parser = match FileNameParser::new() {
-        Ok(p) => p,
-        Err(e) => {
-            log::error!("创建文件名解析器失败: {}", e);
-            return;
-        }
-    };
+    pipeline.test_translation_connection().await;
+    let webhook = Webhook::from_config(&pipeline.config().webhook);
+    let mut status = QueueStatus::from_config(&pipeline.config().mqtt);
 
-    // 创建通用 NFO 生成器
-    let nfo_generator = NfoGenerator::for_media_center(MediaCenterType::Universal);
-    let file_organizer = FileOrganizer::new();
-    let image_manager = ImageManager::new();
-    
-    // 创建翻译器（如果启用）
-    let translator = if config.is_translation_enabled() {
-        match Translator::from_app_config(&config) {
-            Ok(translator) => {
-                log::info!("翻译器初始化成功，提供商: {}", config.get_translation_provider());
-                
-                // 测试连接
-                match translator.test_connection().await {
-                    Ok(_) => {
-                        log::info!("翻译服务连接测试成功");
-                    }
-                    Err(e) => {
-                        log::warn!("翻译服务连接测试失败: {}，翻译功能可能无法正常工作", e);
-                    }
-                }
-                
-                Some(translator)
-            }
-            Err(e) => {
-                log::warn!("翻译器初始化失败: {}，将跳过翻译功能", e);
-                None
-            }
-        }
-    } else {
-        log::info!("翻译功能已禁用");
-        None
-    };
+    let mut rate_limiter = StartRateLimiter::new(pipeline.config().max_files_per_second);
 
     // 处理文件队列
-    while let Some(file_path) = file_rx.recv().await {
+    let mut accepting = true;
+    loop {
+        let file_path = tokio::select! {
+            file_path = file_rx.recv() => file_path,
+            _ = queue_state.wait_for_drain(), if accepting => {
+                accepting = false;
+                file_rx.close();
+                log::info!("文件处理队列已停止接收新文件，剩余 {} 个文件", file_rx.len());
+                continue;
+            }
+        };
+        let Some(file_path) = file_path else {
+            break;
+        };
+        queue_state.wait_until_resumed().await;
+        if let Some(rate_limiter) = &mut rate_limiter {
+            rate_limiter.acquire().await;
+        }
         log::info!("接收到新文件: {}", file_path.display());
+        queue_state.start(&file_path);
+        let movie_id = pipeline.movie_id(&file_path).unwrap_or_default();
+        if let Some(status) = &status {
+            status.processing(&file_path, &movie_id);
+        }
 
         // 创建进度条
         let progress_bar = get_progress_bar(
@@ -328,146 +455,208 @@ async fn process_file_queue(
                 file_path
                     .file_name()
                     .unwrap_or_default()
-                    .to_str()
-                    .unwrap_or("未知")
+                    .to_string_lossy()
             ),
         );
 
-        // 处理单个文件
-        let deps = ProcessingDependencies {
-            parser: &parser,
-            nfo_generator: &nfo_generator,
-            file_organizer: &file_organizer,
-            image_manager: &image_manager,
-            translator: translator.as_ref(),
-            templates: &templates,
-            config: &config,
-        };
-        
-        match process_single_file(
-            &file_path,
-            &deps,
-            &progress_bar,
+        // 处理单个文件，失败时按配置重试
+        let config = pipeline.config();
+        let result = process_with_retry(
+            config.file_retry_attempts,
+            std::time::Duration::from_secs(config.file_retry_delay_seconds),
+            || pipeline.process_file_with_progress(&file_path, &progress_bar),
+            |attempt, e| {
+                log::warn!(
+                    "处理文件 {} 失败: {}，将进行第 {}/{} 次重试",
+                    file_path.display(),
+                    e,
+                    attempt,
+                    config.file_retry_attempts
+                );
+                progress_bar.set_message(format!("处理失败，等待第 {} 次重试", attempt));
+                if let Some(status) = &status {
+                    status.retrying(&file_path, &movie_id, attempt, e);
+                }
+            },
         )
-        .await
-        {
-            Ok(_) => {
+        .await;
+        match &result {
+            Ok(ProcessOutcome::Organized { .. }) => {
                 progress_bar.finish_with_message("处理完成");
             }
+            Ok(ProcessOutcome::Skipped { reason }) => {
+                log::info!("跳过文件 {}: {}", file_path.display(), reason);
+                progress_bar.finish_with_message("已跳过");
+            }
             Err(e) => {
-                if let Some(app_error) = e.downcast_ref::<AppError>() {
-                    if app_error.should_skip_processing() {
-                        let reason = app_error.skip_reason().unwrap_or("未知原因");
-                        log::info!("跳过文件 {}: {}", file_path.display(), reason);
-                        progress_bar.finish_with_message("已跳过");
-                    } else {
-                        log::error!("处理文件 {} 失败: {}", file_path.display(), e);
-                        progress_bar.finish_with_message("处理失败");
-                    }
-                } else {
-                    log::error!("处理文件 {} 失败: {}", file_path.display(), e);
-                    progress_bar.finish_with_message("处理失败");
-                }
+                log::error!("处理文件 {} 失败: {}", file_path.display(), e);
+                progress_bar.finish_with_message("处理失败");
             }
         }
 
         multi_progress.remove(&progress_bar);
+        queue_state.finish(&result, file_rx.len());
+
+        if let Some(status) = &mut status {
+            status.finished(&file_path, &movie_id, &result, file_rx.len());
+        }
+        if let Some(webhook) = &webhook {
+            webhook
+                .notify(&ProcessEvent::new(&file_path, &movie_id, &result))
+                .await;
+        }
+
+        // 队列处理完毕时输出本轮NFO验证汇总
+        if file_rx.is_empty() {
+            pipeline.log_validation_summary();
+        }
     }
 
     log::info!("文件处理队列已停止");
 }
 
+/// 限制开始处理的速率：容量为 1 的令牌桶，相邻两次开始至少间隔 `1 / per_second` 秒
+struct StartRateLimiter {
+    interval: std::time::Duration,
+    next_start: Option<tokio::time::Instant>,
+}
+
+impl StartRateLimiter {
+    /// `per_second` 不为正数时不限制，返回 None
+    fn new(per_second: f64) -> Option<Self> {
+        if !(per_second > 0.0 && per_second.is_finite()) {
+            return None;
+        }
+        Some(Self {
+            interval: std::time::Duration::from_secs_f64(1.0 / per_second),
+            next_start: None,
+        })
+    }
+
+    /// 等待到允许开始的时间
+    async fn acquire(&mut self) {
+        let now = tokio::time::Instant::now();
+        let start = match self.next_start {
+            Some(next_start) if next_start > now => {
+                tokio::time::sleep_until(next_start).await;
+                next_start
+            }
+            _ => now,
+        };
+        self.next_start = Some(start + self.interval);
+    }
+}
+
+/// 处理失败（按规则跳过不算失败）时等待后重新处理，最多重试 `attempts` 次，每次重试的等待时间加倍；
+/// 每次等待前以重试序号（从 1 开始）与本次的错误调用 `on_retry`。
+/// 文件名无法解析、文件不存在等重试也不会成功的错误直接返回
+async fn process_with_retry<F, Fut>(
+    attempts: u32,
+    delay: std::time::Duration,
+    mut process: F,
+    mut on_retry: impl FnMut(u32, &anyhow::Error),
+) -> anyhow::Result<ProcessOutcome>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<ProcessOutcome>>,
+{
+    let mut attempt = 0;
+    loop {
+        match process().await {
+            Err(e) if attempt < attempts && is_retryable(&e) => {
+                attempt += 1;
+                on_retry(attempt, &e);
+                tokio::time::sleep(delay.saturating_mul(1 << (attempt - 1).min(16))).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn is_retryable(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<AppError>().is_none_or(AppError::is_retryable)
+}
+
 /// 处理单个文件（带文件保护机制）
-async fn process_single_file(
+pub(crate) async fn process_single_file(
     file_path: &Path,
     deps: &ProcessingDependencies<'_>,
-    progress_bar: &ProgressBar,
-) -> anyhow::Result<()> {
-    progress_bar.set_message("获取文件锁...");
+    progress: &dyn ProgressReporter,
+    validation_stats: &Mutex<NfoValidationStats>,
+) -> anyhow::Result<ProcessOutcome> {
+    progress.set_message("获取文件锁...");
 
     let _lock = FileProcessingLock::acquire(file_path)
         .with_context(|| format!("无法获取文件锁: {}", file_path.display()))?;
 
-    let integrity_checker = FileIntegrityChecker::new(file_path)
-        .with_context(|| format!("无法创建文件完整性检查器: {}", file_path.display()))?;
-
     if !file_path.exists() {
-        return Err(anyhow::anyhow!("文件不存在: {}", file_path.display()));
+        return Err(AppError::FileProcessing(format!("文件不存在: {}", file_path.display())).into());
     }
 
+    let integrity_checker = FileIntegrityChecker::from_config(file_path, deps.config)
+        .await
+        .with_context(|| format!("无法创建文件完整性检查器: {}", file_path.display()))?;
+
     log::info!("开始安全处理文件: {}", file_path.display());
 
-    progress_bar.set_message("解析文件名...");
+    let kind = MediaItemKind::of(file_path);
 
-    let movie_id = deps.parser
-        .extract_movie_id(file_path, deps.config)
-        .ok_or_else(|| anyhow::anyhow!("无法从文件名提取影片ID"))?;
+    progress.set_message("解析文件名...");
 
-    log::info!("提取到影片ID: {}", movie_id);
+    let parsed_name = parse_file_name(file_path, deps)?;
+    let movie_id = parsed_name.movie_id.clone();
+
+    if deps.ignored_ids.contains(&movie_id) {
+        log::info!("影片 {} 在忽略列表中，跳过处理", movie_id);
+        return Ok(ProcessOutcome::Skipped {
+            reason: "影片ID在忽略列表中".to_string(),
+        });
+    }
 
     // 验证文件完整性（第一次检查）
-    if !integrity_checker.verify_integrity()? {
+    if !integrity_checker.verify().await? {
         return Err(anyhow::anyhow!("文件在处理过程中被修改"));
     }
 
-    progress_bar.set_message(format!("搜索影片信息: {}", movie_id));
-
-    let crawler_data = match crawler(
-        &movie_id,
-        progress_bar,
-        deps.templates.clone(),
-        &Arc::new(deps.config.clone()),
-    )
-    .await
-    {
-        Ok(data) => {
-            log::info!("影片 {} 数据爬取成功", movie_id);
-            data
-        }
-        Err(e) => {
-            log::warn!("影片 {} 数据爬取失败: {}，跳过处理此文件", movie_id, e);
-            progress_bar.set_message("爬取失败，跳过处理");
-            
-            return Err(anyhow::Error::from(e));
-        }
-    };
+    let crawler_data = crawl_movie(file_path, &movie_id, deps, progress)
+        .await
+        .inspect_err(|e| remember_rejection(e, &movie_id, deps))?;
 
-    if !integrity_checker.verify_integrity()? {
+    if !integrity_checker.verify().await? {
         return Err(anyhow::anyhow!("文件在爬取过程中被修改"));
     }
 
-    // 翻译影片数据（如果启用）
-    let mut final_crawler_data = crawler_data.clone();
-    if let Some(translator) = deps.translator {
-        progress_bar.set_message("翻译影片内容...");
-        
-        if let Err(e) = translator.translate_movie_data(&mut final_crawler_data, deps.config).await {
-            log::warn!("影片数据翻译失败: {}，继续使用原始数据", e);
-            final_crawler_data = crawler_data.clone();
-        } else {
-            log::info!("影片数据翻译完成");
-        }
-    }
-
-    let movie_nfo = MovieNfo::for_universal(final_crawler_data.clone());
+    let (final_crawler_data, mut movie_nfo) =
+        build_movie_nfo(&crawler_data, &parsed_name, deps, progress).await;
+    apply_media_probe(file_path, &mut movie_nfo, deps.config, progress).await;
 
-    progress_bar.set_message("验证NFO数据...");
+    progress.set_message("验证NFO数据...");
 
     let warnings = deps.nfo_generator.validate_nfo(&movie_nfo);
-    if !warnings.is_empty() {
-        log::warn!("NFO数据验证警告: {:?}", warnings);
-    }
+    validation_stats
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .record(&warnings);
+    check_required_nfo_fields(&warnings, deps.config, &movie_id)
+        .map_err(anyhow::Error::from)
+        .inspect_err(|e| remember_rejection(e, &movie_id, deps))?;
+
+    // 按输出路由规则选择输出目录和命名模板
+    let output_target = route_output(&movie_nfo, deps.config)?;
+    let organized_video_path =
+        plan_video_path(file_path, &movie_nfo, &parsed_name, &output_target, deps)?;
 
     // 阶段4.5: 下载图片（如果启用）
     if deps.config.should_download_images() {
-        progress_bar.set_message("下载影片图片...");
+        progress.set_message("下载影片图片...");
         
-        let output_dir = if deps.file_organizer.needs_organization(file_path, deps.config) {
-            // 预览组织后的目录结构
-            let (video_path, _) = deps.file_organizer.preview_media_center_structure(file_path, &movie_nfo, deps.config)?;
-            video_path.parent().unwrap_or(deps.config.get_output_dir()).to_path_buf()
+        let output_dir = if kind == MediaItemKind::BdmvFolder {
+            // 图片放在蓝光原盘目录内，随目录一起移动
+            file_path.to_path_buf()
+        } else if let Some(video_path) = &organized_video_path {
+            video_path.parent().unwrap_or(&output_target.output_dir).to_path_buf()
         } else {
-            file_path.parent().unwrap_or(deps.config.get_output_dir()).to_path_buf()
+            file_path.parent().unwrap_or(&output_target.output_dir).to_path_buf()
         };
 
         match deps.image_manager.download_movie_images(
@@ -493,42 +682,42 @@ async fn process_single_file(
     }
 
     // 阶段5: 创建处理事务
-    progress_bar.set_message("准备文件操作...");
+    progress.set_message("准备文件操作...");
 
     let mut transaction = FileProcessingTransaction::new(file_path);
 
-    let (final_video_path, final_nfo_path) = if deps.file_organizer.needs_organization(file_path, deps.config)
-    {
-        let (video_path, nfo_path) =
-            deps.file_organizer.preview_media_center_structure(file_path, &movie_nfo, deps.config)?;
-
+    let final_video_path = if let Some(video_path) = organized_video_path {
         transaction.add_file_move(file_path.to_path_buf(), video_path.clone());
-
-        (video_path, nfo_path)
+        video_path
     } else {
-        let nfo_path = file_path.with_extension("nfo");
-        (file_path.to_path_buf(), nfo_path)
+        file_path.to_path_buf()
     };
+    // 按NFO文件名风格写入一个或两个NFO文件
+    let final_nfo_paths = deps.file_organizer.item_nfo_paths(&final_video_path, kind, deps.config);
 
     let nfo_xml_content = format!(
         "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<!-- Generated by jav-tidy-rs with media center compatibility -->\n{}",
-        movie_nfo.format_to_xml()
+        nfo_for_output(&movie_nfo, &movie_id, deps.config).format_to_xml()
     );
-    transaction.add_nfo_creation(final_nfo_path.clone(), nfo_xml_content);
+    for nfo_path in &final_nfo_paths {
+        transaction.add_nfo_creation(nfo_path.clone(), nfo_xml_content.clone());
+    }
 
-    if !integrity_checker.verify_integrity()? {
+    if !integrity_checker.verify().await? {
         return Err(anyhow::anyhow!("文件在准备操作时被修改"));
     }
 
     // 阶段6: 执行事务
-    progress_bar.set_message("执行文件操作...");
+    progress.set_message("执行文件操作...");
 
     transaction
         .commit()
         .with_context(|| format!("文件处理事务失败: {}", file_path.display()))?;
 
-    if deps.config.migrate_subtitles() {
-        progress_bar.set_message("处理字幕文件...");
+    if kind == MediaItemKind::BdmvFolder {
+        log::debug!("蓝光原盘目录整体移动，跳过字幕迁移与多演员链接");
+    } else if deps.config.migrate_subtitles() {
+        progress.set_message("处理字幕文件...");
         
         if let Some(input_dir) = file_path.parent() {
             match deps.file_organizer.migrate_subtitle_files(
@@ -556,15 +745,16 @@ async fn process_single_file(
     }
 
     // 阶段8: 处理多演员链接策略
-    if movie_nfo.actors.len() > 1 {
-        progress_bar.set_message("处理多演员链接...");
+    if movie_nfo.actors.len() > 1 && kind != MediaItemKind::BdmvFolder {
+        progress.set_message("处理多演员链接...");
         
         match deps.file_organizer.handle_multi_actor_links(
             file_path,
             &movie_nfo,
             deps.config,
+            &output_target,
+            &parsed_name.suffix_tags,
             &final_video_path,
-            &final_nfo_path,
         ) {
             Ok(additional_paths) => {
                 if !additional_paths.is_empty() {
@@ -581,88 +771,723 @@ async fn process_single_file(
         }
     }
 
+    if deps.config.generate_actor_index {
+        progress.set_message("更新演员索引...");
+
+        if let Err(e) = update_actor_index(&movie_nfo, &movie_id, &final_video_path, deps.config) {
+            log::warn!("演员索引更新失败: {}", e);
+        }
+    }
+
+    if !deps.config.post_process_command.is_empty() {
+        progress.set_message("执行整理后命令...");
+
+        let command = deps.config.post_process_command.clone();
+        let video_path = final_video_path.clone();
+        let nfo_path = final_nfo_paths.first().cloned().unwrap_or_default();
+        let id = movie_id.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            run_post_process_command(&command, &video_path, &nfo_path, &id)
+        })
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result);
+        match result {
+            Ok(()) => log::info!("影片 {} 的整理后命令执行完成", movie_id),
+            Err(e) => log::warn!("影片 {} 的整理后命令执行失败: {}", movie_id, e),
+        }
+    }
+
     // 阶段9: 完成处理
-    progress_bar.set_message("处理完成");
+    progress.set_message("处理完成");
 
     log::info!(
-        "影片 {} 处理完成 - 媒体中心结构已创建\n  原始文件: {}\n  视频文件: {}\n  NFO文件: {}",
+        "影片 {} 处理完成 - 媒体中心结构已创建\n  原始文件: {}\n  视频文件: {}\n  NFO文件: {:?}",
         movie_id,
         file_path.display(),
         final_video_path.display(),
-        final_nfo_path.display()
+        final_nfo_paths
     );
 
+    Ok(ProcessOutcome::Organized {
+        movie_id,
+        video_path: final_video_path,
+        nfo_paths: final_nfo_paths,
+    })
+}
+
+/// 执行整理后命令，参数中的 `{video}`、`{nfo}`、`{id}` 替换为对应的值，命令退出码非 0 时返回错误
+fn run_post_process_command(
+    command: &[String],
+    video_path: &Path,
+    nfo_path: &Path,
+    movie_id: &str,
+) -> anyhow::Result<()> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| anyhow::anyhow!("整理后命令为空"))?;
+    let substitute = |arg: &String| {
+        arg.replace("{video}", &video_path.to_string_lossy())
+            .replace("{nfo}", &nfo_path.to_string_lossy())
+            .replace("{id}", movie_id)
+    };
+
+    log::debug!("执行整理后命令: {} {:?}", program, args);
+    let output = std::process::Command::new(program)
+        .args(args.iter().map(substitute))
+        .output()
+        .with_context(|| format!("无法启动命令: {}", program))?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "命令 {} 退出状态 {}: {}",
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
     Ok(())
 }
 
-async fn crawler(
+/// 预览文件的处理结果：爬取数据并计算整理后的路径，不修改任何文件
+pub(crate) async fn preview_file(
+    file_path: &Path,
+    deps: &ProcessingDependencies<'_>,
+    progress: &dyn ProgressReporter,
+) -> anyhow::Result<PreviewPlan> {
+    progress.set_message("解析文件名...");
+
+    let parsed_name = parse_file_name(file_path, deps)?;
+    let movie_id = parsed_name.movie_id.clone();
+    let crawler_data = crawl_movie(file_path, &movie_id, deps, progress).await?;
+    let (_, mut movie_nfo) = build_movie_nfo(&crawler_data, &parsed_name, deps, progress).await;
+    apply_media_probe(file_path, &mut movie_nfo, deps.config, progress).await;
+    let warnings = deps.nfo_generator.validate_nfo(&movie_nfo);
+
+    let output_target = route_output(&movie_nfo, deps.config)?;
+    let video_path = plan_video_path(file_path, &movie_nfo, &parsed_name, &output_target, deps)?
+        .unwrap_or_else(|| file_path.to_path_buf());
+    let nfo_paths = deps
+        .file_organizer
+        .item_nfo_paths(&video_path, MediaItemKind::of(file_path), deps.config);
+
+    let nfo = nfo_for_output(&movie_nfo, &movie_id, deps.config);
+    Ok(PreviewPlan {
+        movie_id,
+        nfo,
+        video_path,
+        nfo_paths,
+        warnings,
+    })
+}
+
+/// 写入文件的NFO：按配置省略字段并改写标题，命名与路由仍使用完整数据
+fn nfo_for_output(movie_nfo: &MovieNfo, movie_id: &str, config: &AppConfig) -> MovieNfo {
+    let mut nfo = movie_nfo.clone();
+    nfo.redact(&config.nfo.omit_fields);
+    nfo.apply_title_style(config.nfo.title_style, movie_id);
+    nfo
+}
+
+/// 从文件名提取影片ID与后缀标签
+fn parse_file_name(
+    file_path: &Path,
+    deps: &ProcessingDependencies<'_>,
+) -> anyhow::Result<ParsedFileName> {
+    let parsed_name = deps.parser
+        .extract_movie_info(file_path, deps.config)
+        .ok_or_else(|| AppError::FileProcessing(format!("无法从文件名提取影片ID: {}", file_path.display())))?;
+
+    log::info!("提取到影片ID: {}", parsed_name.movie_id);
+
+    Ok(parsed_name)
+}
+
+/// 启用 `remember_rejections` 时，把因数据质量过低或缺少必需字段而跳过的影片ID加入忽略列表
+///
+/// 影片数据不存在可能只是站点暂时没有收录，不加入忽略列表
+fn remember_rejection(err: &anyhow::Error, movie_id: &str, deps: &ProcessingDependencies<'_>) {
+    if !deps.config.remember_rejections {
+        return;
+    }
+    let rejected = matches!(
+        err.downcast_ref::<AppError>(),
+        Some(AppError::MovieDataQualityTooLow(_) | AppError::MovieDataIncomplete(_))
+    );
+    if rejected {
+        if let Err(e) = deps.ignored_ids.remember(movie_id) {
+            log::warn!("记录被拒绝的影片 {} 失败: {}", movie_id, e);
+        }
+    }
+}
+
+/// 按模板优先级爬取文件对应影片的数据
+async fn crawl_movie(
+    file_path: &Path,
+    movie_id: &str,
+    deps: &ProcessingDependencies<'_>,
+    progress: &dyn ProgressReporter,
+) -> anyhow::Result<MovieNfoCrawler> {
+    progress.set_message(&format!("搜索影片信息: {}", movie_id));
+
+    let file_name = file_path.file_stem().unwrap_or_default().to_string_lossy();
+    match crawler(
+        movie_id,
+        &file_name,
+        &SourceParams::from_path(file_path),
+        progress,
+        deps.templates,
+        deps.config,
+    )
+    .await
+    {
+        Ok(data) => {
+            log::info!("影片 {} 数据爬取成功", movie_id);
+            Ok(data)
+        }
+        Err(e) => {
+            log::warn!("影片 {} 数据爬取失败: {}，跳过处理此文件", movie_id, e);
+            progress.set_message("爬取失败，跳过处理");
+
+            Err(anyhow::Error::from(e))
+        }
+    }
+}
+
+/// 由爬取结果生成最终的NFO：翻译（如果启用）、合并文件名后缀标签并规范化
+///
+/// 返回用于下载图片的影片数据与生成的NFO
+async fn build_movie_nfo(
+    crawler_data: &MovieNfoCrawler,
+    parsed_name: &ParsedFileName,
+    deps: &ProcessingDependencies<'_>,
+    progress: &dyn ProgressReporter,
+) -> (MovieNfoCrawler, MovieNfo) {
+    // 翻译影片数据（如果启用）
+    let mut final_crawler_data = crawler_data.clone();
+    if let Some(translator) = deps.translator {
+        progress.set_message("翻译影片内容...");
+        
+        if let Err(e) = translator.translate_movie_data(&mut final_crawler_data, deps.config).await {
+            log::warn!("影片数据翻译失败: {}，继续使用原始数据", e);
+            final_crawler_data = crawler_data.clone();
+        } else {
+            log::info!("影片数据翻译完成");
+        }
+    }
+
+    // 合并文件名后缀得到的标签（如中文字幕、无码）
+    for tag in &parsed_name.suffix_tags {
+        if !final_crawler_data.tags.contains(tag) {
+            final_crawler_data.tags.push(tag.clone());
+        }
+    }
+
+    // 按分类表映射类型
+    if let Some(taxonomy) = deps.genre_taxonomy {
+        apply_genre_taxonomy(&mut final_crawler_data, taxonomy, deps.config.unmapped_genres);
+    }
+
+    // 按分类规则追加有码/无码/欧美标签并补全分级
+    if let Err(e) = apply_classification(
+        &parsed_name.movie_id,
+        &mut final_crawler_data,
+        &deps.config.classification,
+    ) {
+        log::warn!("影片 {} 分类失败: {}", parsed_name.movie_id, e);
+    }
+
+    let mut movie_nfo = MovieNfo::for_universal(final_crawler_data.clone());
+    movie_nfo.apply_adult_markers(&deps.config.adult_genre_markers);
+    movie_nfo.fill_plot_outline(deps.config.plot_outline_length);
+    movie_nfo.apply_label_series(&deps.config.label_series_map);
+    if deps.config.deterministic_nfo {
+        movie_nfo.normalize();
+    }
+
+    (final_crawler_data, movie_nfo)
+}
+
+/// 启用 `probe_media` 时读取视频文件的流信息写入NFO，读取失败时记录警告并继续处理
+///
+/// `.strm` 与蓝光原盘目录没有可读取的视频文件，跳过
+async fn apply_media_probe(file_path: &Path, movie_nfo: &mut MovieNfo, config: &AppConfig, progress: &dyn ProgressReporter) {
+    if !config.probe_media || MediaItemKind::of(file_path) != MediaItemKind::Video {
+        return;
+    }
+    progress.set_message("读取视频流信息...");
+
+    let path = file_path.to_path_buf();
+    let ffprobe_path = config.ffprobe_path.clone();
+    let result = tokio::task::spawn_blocking(move || probe_media(&path, ffprobe_path.as_deref()))
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result);
+    match result {
+        Ok(details) => {
+            log::info!("读取视频流信息: {:?}", details);
+            movie_nfo.apply_stream_details(details);
+        }
+        Err(e) => log::warn!("读取视频流信息失败 {}: {:#}", file_path.display(), e),
+    }
+}
+
+/// 预览组织后的视频路径，文件已在输出目录中或原地处理时返回 None
+///
+/// 目录已被清理后同名的其他影片占用时改用带影片ID的目录，蓝光原盘目录返回整理后的目录。
+/// 目录属于同一影片（如上次处理在移动视频前中断）时继续使用该目录，视频使用规范的文件名，
+/// 只有该文件名已被其他视频占用时才追加序号，NFO 文件名跟随视频文件名
+fn plan_video_path(
+    file_path: &Path,
+    movie_nfo: &MovieNfo,
+    parsed_name: &ParsedFileName,
+    output_target: &OutputTarget,
+    deps: &ProcessingDependencies<'_>,
+) -> anyhow::Result<Option<PathBuf>> {
+    let movie_id = parsed_name.movie_id.as_str();
+    let kind = MediaItemKind::of(file_path);
+    if kind == MediaItemKind::Strm && deps.config.strm_in_place {
+        log::info!(".strm 文件原地生成NFO: {}", file_path.display());
+        return Ok(None);
+    }
+
+    if !deps.file_organizer.needs_organization(file_path, deps.config) {
+        return Ok(None);
+    }
+
+    let (video_path, _) = deps.file_organizer.preview_media_center_structure(
+        file_path,
+        movie_nfo,
+        deps.config,
+        output_target,
+        &parsed_name.suffix_tags,
+    )?;
+    if kind == MediaItemKind::BdmvFolder {
+        let movie_dir = deps.file_organizer.ensure_unique_movie_dir(&video_path, movie_nfo, movie_id)?;
+        return Ok(Some(movie_dir));
+    }
+    let movie_dir = video_path.parent().unwrap_or(&output_target.output_dir);
+    let movie_dir = deps.file_organizer.ensure_unique_movie_dir(movie_dir, movie_nfo, movie_id)?;
+    let video_path = movie_dir.join(video_path.file_name().unwrap_or_default());
+    if video_path == file_path {
+        return Ok(Some(video_path));
+    }
+
+    Ok(Some(deps.file_organizer.resolve_filename_conflict(&video_path)?))
+}
+
+/// 爬取结果的磁盘缓存，每个模板中的每个影片ID对应一个 JSON 文件
+///
+/// 缓存的是模板解析后的结果，匹配评分、数据质量与合并在读取缓存后重新计算
+struct CrawlCache {
+    dir: PathBuf,
+    ttl: Option<std::time::Duration>,
+    /// 忽略已有缓存重新爬取，爬取结果仍写入缓存
+    refresh: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CrawlCacheEntry {
+    /// 缓存写入时间（Unix 时间戳，秒）
+    cached_at: u64,
+    data: MovieNfoCrawler,
+}
+
+impl CrawlCache {
+    /// 根据配置创建缓存，未配置缓存目录时返回 None
+    fn from_config(config: &AppConfig) -> Option<Self> {
+        let dir = config.crawl_cache_dir.clone()?;
+        let ttl = (config.crawl_cache_ttl_days > 0)
+            .then(|| std::time::Duration::from_secs(config.crawl_cache_ttl_days * 24 * 3600));
+        Some(Self {
+            dir,
+            ttl,
+            refresh: config.refresh_metadata,
+        })
+    }
+
+    /// 缓存文件路径 `<缓存目录>/<模板名>/<影片ID>.json`，影片ID统一转为大写，
+    /// 非字母数字字符替换为下划线
+    fn entry_path(&self, template_name: &str, movie_id: &str) -> PathBuf {
+        let sanitize = |value: &str| -> String {
+            value
+                .trim()
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+                .collect()
+        };
+        let template = Path::new(template_name)
+            .file_stem()
+            .map_or_else(|| template_name.into(), |stem| stem.to_string_lossy());
+        self.dir
+            .join(sanitize(&template))
+            .join(format!("{}.json", sanitize(&movie_id.to_uppercase())))
+    }
+
+    /// 读取未过期的缓存，缓存不存在、损坏、过期或要求重新爬取时返回 None
+    fn get(&self, template_name: &str, movie_id: &str) -> Option<MovieNfoCrawler> {
+        if self.refresh {
+            return None;
+        }
+        let path = self.entry_path(template_name, movie_id);
+        let content = std::fs::read_to_string(&path).ok()?;
+        let entry: CrawlCacheEntry = match serde_json::from_str(&content) {
+            Ok(entry) => entry,
+            Err(e) => {
+                log::warn!("爬取缓存文件损坏，忽略: {}: {}", path.display(), e);
+                return None;
+            }
+        };
+
+        let cached_at = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(entry.cached_at);
+        if let Some(ttl) = self.ttl {
+            if cached_at.elapsed().unwrap_or_default() > ttl {
+                log::debug!("影片 {} 的爬取缓存已过期", movie_id);
+                return None;
+            }
+        }
+
+        Some(entry.data)
+    }
+
+    fn put(&self, template_name: &str, movie_id: &str, data: &MovieNfoCrawler) -> anyhow::Result<()> {
+        let path = self.entry_path(template_name, movie_id);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let entry = CrawlCacheEntry {
+            cached_at: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)?
+                .as_secs(),
+            data: data.clone(),
+        };
+        safe_write(&path, serde_json::to_string_pretty(&entry)?)?;
+        Ok(())
+    }
+}
+
+/// 由文件路径得到的模板参数，模板在 entrypoint 或脚本中通过 `${parent_dir}` 等引用
+///
+/// 与 `crawl_name` 一样覆盖模板 `env`（含 `template_overrides`）中的同名参数
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct SourceParams {
+    /// 原始文件名（含扩展名）
+    pub original_filename: String,
+    /// 文件所在目录的名称
+    pub parent_dir: String,
+    /// 文件所在目录的上一级目录的名称
+    pub grandparent_dir: String,
+}
+
+impl SourceParams {
+    /// 由文件路径得到参数，去除控制字符并合并连续的空白，不存在的部分为空字符串
+    pub fn from_path(file_path: &Path) -> Self {
+        let parent = file_path.parent();
+        let name = |path: Option<&Path>| {
+            path.and_then(Path::file_name)
+                .map(|name| sanitize_source_param(&name.to_string_lossy()))
+                .unwrap_or_default()
+        };
+        Self {
+            original_filename: name(Some(file_path)),
+            parent_dir: name(parent),
+            grandparent_dir: name(parent.and_then(Path::parent)),
+        }
+    }
+
+    fn insert_into<'a>(&'a self, params: &mut HashMap<&'a str, String>) {
+        params.insert("original_filename", self.original_filename.clone());
+        params.insert("parent_dir", self.parent_dir.clone());
+        params.insert("grandparent_dir", self.grandparent_dir.clone());
+    }
+}
+
+fn sanitize_source_param(value: &str) -> String {
+    value
+        .split(|c: char| c.is_whitespace() || c.is_control())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 按模板优先级爬取影片数据
+///
+/// 每个模板的结果先按影片ID与原始文件名 `file_name` 计算匹配评分，低于最低匹配评分的
+/// 结果视为搜到了其他影片并继续尝试下一个模板；通过的结果按匹配评分排序后合并
+pub(crate) async fn crawler(
     crawler_name: &str,
-    process: &ProgressBar,
-    templates: Templates,
-    app_config: &Arc<AppConfig>,
+    file_name: &str,
+    source: &SourceParams,
+    process: &dyn ProgressReporter,
+    templates: &Templates,
+    app_config: &AppConfig,
 ) -> Result<MovieNfoCrawler, AppError> {
-    let mut succecc_nfo = vec![];
+    let cache = CrawlCache::from_config(app_config);
+
+    let mut scored_nfos = vec![];
+    let mut accepted_count = 0;
+    // 因影片不存在以外的原因（网络、超时、模板失效等）失败的模板数量
+    let mut failed_count = 0;
     log::info!("开始爬取影片数据: {}", crawler_name);
 
+    // 所有模板使用相同的初始参数
+    let mut init_params = HashMap::new();
+    source.insert_into(&mut init_params);
+    init_params.insert("crawl_name", crawler_name.to_string());
+
     for (template_name, template) in templates.iter() {
         log::info!("尝试使用模板 '{}' 爬取数据", template_name);
-        process.set_message(format!("正在使用: {} 模版爬取数据", template_name));
-        let mut init_params = HashMap::new();
-        init_params.insert("crawl_name", crawler_name.to_string());
+        process.set_message(&format!("正在使用: {} 模版爬取数据", template_name));
+
+        let cached = cache
+            .as_ref()
+            .and_then(|cache| cache.get(template_name, crawler_name));
+        let crawled = match cached {
+            Some(cached) => {
+                log::info!("使用模板 '{}' 中影片 {} 的爬取缓存", template_name, crawler_name);
+                process.set_message(&format!("使用 {} 模版的缓存数据", template_name));
+                Ok(cached)
+            }
+            None => {
+                // 只有需要保存失败现场时才保留爬取期间的页面
+                let crawled = if app_config.debug.dump_failed_crawls {
+                    template
+                        .crawler_with_snapshot(&init_params)
+                        .await
+                        .map(|(movie_nfo, _)| movie_nfo)
+                } else {
+                    template.crawler(&init_params).await.map_err(|error| CrawlFailure {
+                        error,
+                        snapshot: CrawlSnapshot::default(),
+                    })
+                };
+                crawled.inspect(|movie_nfo| {
+                    if let Some(cache) = &cache {
+                        if let Err(e) = cache.put(template_name, crawler_name, movie_nfo) {
+                            log::warn!("写入影片 {} 的爬取缓存失败: {}", crawler_name, e);
+                        }
+                    }
+                })
+            }
+        };
 
-        match template.crawler(&init_params).await {
-            Ok(movie_nfo) => {
+        match crawled {
+            Ok(mut movie_nfo) => {
                 log::info!("模板 '{}' 爬取成功", template_name);
+                movie_nfo.source_template = template_name.clone();
+                if movie_nfo.site_id.trim().is_empty() {
+                    movie_nfo.site_id = crawler_name.to_string();
+                }
                 log::debug!("爬取到的数据摘要: 标题='{}', 演员数={}, 导演数={}, 厂商数={}", 
                     movie_nfo.title, 
                     movie_nfo.actors.len(),
                     movie_nfo.directors.len(),
                     movie_nfo.studios.len()
                 );
+
+                let match_score = score_candidate(&movie_nfo, crawler_name, file_name);
+                if match_score < app_config.minimum_match_score {
+                    log::warn!("模板 '{}' 返回的影片 '{}' 匹配评分过低 (评分: {}, 最低: {}), 尝试下一个模板",
+                        template_name, movie_nfo.title, match_score, app_config.minimum_match_score);
+                    process.set_message(&format!("{} 模版结果与影片不匹配", template_name));
+                    continue;
+                }
+                log::info!("模板 '{}' 匹配评分: {}", template_name, match_score);
                 
                 // 检查数据质量
                 let data_quality_score = calculate_data_quality(&movie_nfo);
                 log::info!("数据质量评分: {}/100", data_quality_score);
                 
-                if data_quality_score < 20 {
+                if data_quality_score < app_config.minimum_data_quality {
+                    log::warn!("模板 '{}' 返回的数据质量低于最低要求 (评分: {}, 最低: {}), 丢弃该结果", 
+                        template_name, data_quality_score, app_config.minimum_data_quality);
+                } else if data_quality_score < 20 {
                     log::warn!("模板 '{}' 返回的数据质量较差 (评分: {}), 数据可能不完整", 
                         template_name, data_quality_score);
+                    accepted_count += 1;
                 } else {
                     log::info!("模板 '{}' 返回的数据质量良好 (评分: {})", 
                         template_name, data_quality_score);
+                    accepted_count += 1;
                 }
                 
-                succecc_nfo.push(movie_nfo);
-                if succecc_nfo.len() >= app_config.maximum_fetch_count {
+                scored_nfos.push(((template_name.clone(), movie_nfo, data_quality_score), match_score));
+                if accepted_count >= app_config.maximum_fetch_count {
                     log::info!("已达到最大爬取数量限制: {}", app_config.maximum_fetch_count);
                     break;
                 }
             }
-            Err(e) => {
-                log::error!("模板 '{}' 爬取失败: {}", template_name, e);
-                process.set_message(format!("{} 模版爬取数据失败", template_name));
+            Err(CrawlFailure {
+                error: CrawlerErr::NotFound(url),
+                ..
+            }) => {
+                log::info!("模板 '{}' 中不存在该影片 ({})，尝试下一个模板", template_name, url);
+                process.set_message(&format!("{} 模版中不存在该影片", template_name));
+                continue;
+            }
+            Err(failure) => {
+                failed_count += 1;
+                if app_config.debug.dump_failed_crawls {
+                    match dump_failed_crawl(&app_config.debug.dump_dir, crawler_name, template_name, &failure) {
+                        Ok(dump_dir) => log::error!(
+                            "模板 '{}' 爬取失败: {}，现场已保存到 {}",
+                            template_name,
+                            failure.error,
+                            dump_dir.display()
+                        ),
+                        Err(e) => log::error!(
+                            "模板 '{}' 爬取失败: {}，保存现场失败: {}",
+                            template_name,
+                            failure.error,
+                            e
+                        ),
+                    }
+                } else {
+                    log::error!("模板 '{}' 爬取失败: {}", template_name, failure.error);
+                }
+                process.set_message(&format!("{} 模版爬取数据失败", template_name));
                 continue;
             }
         };
     }
 
-    if succecc_nfo.is_empty() {
+    let ranked = rank_candidates(scored_nfos, app_config.minimum_match_score);
+    log::info!(
+        "影片 {} 各模板匹配评分: {}",
+        crawler_name,
+        ranked
+            .iter()
+            .map(|((template_name, _, _), score)| format!("{}={}", template_name, score))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let scored_nfos: Vec<_> = ranked.into_iter().map(|(candidate, _)| candidate).collect();
+
+    if scored_nfos.is_empty() {
         log::error!("所有模板爬取失败，影片ID: {}", crawler_name);
+        if failed_count > 0 {
+            return Err(AppError::MovieDataUnavailable(format!(
+                "{} 个模版爬取出错，影片ID: {}",
+                failed_count, crawler_name
+            )));
+        }
         return Err(AppError::MovieDataNotFound(format!("所有模版爬取失败，影片ID: {}", crawler_name)));
     }
 
+    let succecc_nfo = filter_by_data_quality(scored_nfos, app_config.minimum_data_quality, crawler_name)?;
+
     log::info!("总共成功爬取 {} 个数据源", succecc_nfo.len());
     let crawler_nfo = clean_crawler_nfos(succecc_nfo).await?;
 
     Ok(crawler_nfo)
 }
 
-/// 计算数据质量评分 (0-100)
-fn calculate_data_quality(nfo: &MovieNfoCrawler) -> u32 {
-    let mut score = 0u32;
+/// 保存爬取失败现场：获取到的页面、已收集的运行时变量和错误信息
+///
+/// 每次失败写入 `<dump_dir>/<毫秒时间戳>_<影片ID>_<模板名>/`，返回该目录
+fn dump_failed_crawl(
+    dump_dir: &Path,
+    movie_id: &str,
+    template_name: &str,
+    failure: &CrawlFailure,
+) -> anyhow::Result<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)?
+        .as_millis();
+    let folder_name: String = format!(
+        "{}_{}_{}",
+        timestamp,
+        movie_id,
+        template_name.trim_end_matches(".yaml")
+    )
+    .chars()
+    .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+    .collect();
+    let dir = dump_dir.join(folder_name);
+    std::fs::create_dir_all(&dir)
+        .with_context(|| format!("创建调试目录失败: {}", dir.display()))?;
+
+    for (index, page) in failure.snapshot.pages.iter().enumerate() {
+        let content = format!("<!-- {} -->\n{}", page.url, page.body);
+        safe_write(&dir.join(format!("page_{}.html", index + 1)), content)?;
+    }
+
+    let variables = serde_json::to_string_pretty(&failure.snapshot.variables)?;
+    safe_write(&dir.join("variables.json"), variables)?;
+    safe_write(&dir.join("error.txt"), failure.error.to_string())?;
+
+    Ok(dir)
+}
+
+/// 检查NFO验证警告，缺失 `fail_on_missing` 中配置的字段时返回 `MovieDataIncomplete`
+fn check_required_nfo_fields(
+    warnings: &[NfoWarning],
+    config: &AppConfig,
+    movie_id: &str,
+) -> Result<(), AppError> {
+    if !warnings.is_empty() {
+        log::debug!(
+            "影片 {} NFO数据验证警告: {}",
+            movie_id,
+            warnings.iter().map(|w| w.to_string()).collect::<Vec<_>>().join("，")
+        );
+    }
+
+    let missing = warnings
+        .iter()
+        .filter(|warning| config.should_fail_on_missing(warning.field()))
+        .map(|warning| warning.field())
+        .collect::<Vec<_>>();
+
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    log::warn!("影片 {} 缺少必需的NFO字段: {}，中止整理", movie_id, missing.join(", "));
+    Err(AppError::MovieDataIncomplete(format!(
+        "影片ID: {}，缺失字段: {}",
+        movie_id,
+        missing.join(", ")
+    )))
+}
+
+/// 按最低数据质量评分筛选爬取结果
+///
+/// `scored_nfos` 为 (模板名称, 爬取结果, 评分)，全部结果低于最低评分时返回 `MovieDataQualityTooLow`
+fn filter_by_data_quality(
+    scored_nfos: Vec<(String, MovieNfoCrawler, i32)>,
+    minimum_data_quality: i32,
+    crawler_name: &str,
+) -> Result<Vec<MovieNfoCrawler>, AppError> {
+    let scores = scored_nfos
+        .iter()
+        .map(|(template_name, _, score)| format!("{}={}", template_name, score))
+        .collect::<Vec<_>>()
+        .join(", ");
+    log::info!("影片 {} 各模板数据质量评分: {}", crawler_name, scores);
+
+    let accepted: Vec<MovieNfoCrawler> = scored_nfos
+        .into_iter()
+        .filter(|(_, _, score)| *score >= minimum_data_quality)
+        .map(|(_, nfo, _)| nfo)
+        .collect();
+
+    if accepted.is_empty() {
+        log::error!("所有爬取结果的数据质量均低于最低评分 {}，影片ID: {}", minimum_data_quality, crawler_name);
+        return Err(AppError::MovieDataQualityTooLow(format!(
+            "影片ID: {}，最低评分: {}，各模板评分: {}",
+            crawler_name, minimum_data_quality, scores
+        )));
+    }
+
+    Ok(accepted)
+}
+
+/// 计算数据质量评分 (0-100)，标题为空时评分为负数
+fn calculate_data_quality(nfo: &MovieNfoCrawler) -> i32 {
+    let mut score = 0i32;
     
     // 基本信息权重
-    if !nfo.title.is_empty() { score += 15; }
+    if !nfo.title.is_empty() { score += 15; } else { score -= 100; }
     if !nfo.plot.is_empty() { score += 10; }
     if !nfo.tagline.is_empty() { score += 5; }
     if nfo.year.is_some() { score += 10; }
@@ -686,16 +1511,35 @@ async fn clean_crawler_nfos(nfos: Vec<MovieNfoCrawler>) -> Result<MovieNfoCrawle
     Ok(nfos[0].clone())
 }
 
-fn get_templates(
+/// 加载配置中的所有模板并进行静态检查，返回发现的问题数量，问题已写入日志
+pub fn validate_templates(path: &Path, config: &AppConfig) -> anyhow::Result<usize> {
+    let templates = get_templates(path, config)?;
+    let warning_count = templates
+        .iter()
+        .map(|(_, template)| template.lint().len())
+        .sum();
+    log::info!("已检查 {} 个模板，发现 {} 个问题", templates.len(), warning_count);
+    Ok(warning_count)
+}
+
+pub(crate) fn get_templates(
     path: &Path,
     config: &AppConfig,
 ) -> anyhow::Result<Vec<(String, Template<MovieNfoCrawler>)>> {
     let mut templates = vec![None; config.template_priority.len()];
+    let request_limit = config
+        .global_request_concurrency
+        .map(|limit| std::sync::Arc::new(tokio::sync::Semaphore::new(limit.max(1))));
+    let host_limit = (config.max_concurrent_per_host > 0)
+        .then(|| crawler_template::HostLimiter::new(config.max_concurrent_per_host));
 
     for entry in path.read_dir()? {
         let entry = entry?;
         let file_name = entry.file_name();
-        let file_name = file_name.to_str().unwrap();
+        let Some(file_name) = file_name.to_str() else {
+            log::warn!("跳过文件名不是有效 UTF-8 的模板: {}", entry.path().display());
+            continue;
+        };
 
         if entry.path().is_file()
             && entry.path().extension() == Some("yaml".as_ref())
@@ -703,7 +1547,31 @@ fn get_templates(
         {
             let yaml = std::fs::read_to_string(entry.path())?;
             let index = config.get_template_index(file_name).unwrap();
-            let template = Template::from_yaml(&yaml)?;
+            let mut template =
+                Template::from_yaml_with_loader(&yaml, |name| std::fs::read_to_string(path.join(name)))?
+                    .with_best_effort(config.best_effort_crawl)
+                    .with_concurrency(config.crawl_concurrency);
+
+            if let Some(limit) = &request_limit {
+                template = template.with_request_limit(limit.clone());
+            }
+            if let Some(limit) = &host_limit {
+                template = template.with_host_limit(limit.clone());
+            }
+
+            if let Some(seconds) = config.request_timeout_seconds {
+                template =
+                    template.with_default_timeout(std::time::Duration::from_secs(seconds))?;
+            }
+
+            for (key, value) in config.get_template_overrides(file_name).into_iter().flatten() {
+                log::info!("模板 {} 的参数 {} 覆盖为: {}", file_name, key, value);
+                template = template.with_parameter(key, value);
+            }
+
+            for warning in template.lint() {
+                log::warn!("模板 {} 检查发现问题: {}", file_name, warning);
+            }
 
             templates[index] = Some((file_name.to_string(), template));
         }
@@ -738,9 +1606,698 @@ static PROGRESS_STYLE: LazyLock<ProgressStyle> = LazyLock::new(|| {
         ])
 });
 
-fn get_progress_bar(multi_progress: &MultiProgress, msg: &str) -> ProgressBar {
+pub(crate) fn get_progress_bar(multi_progress: &MultiProgress, msg: &str) -> ProgressBar {
     let progress_bar = multi_progress.add(ProgressBar::new(0));
     progress_bar.set_style(PROGRESS_STYLE.clone());
     progress_bar.set_message(msg.to_string());
     progress_bar
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{IntegrityConfig, TemplateOverride};
+    use crate::tests::harness::{create_config, create_site_pipeline};
+    use std::time::Duration;
+
+    fn create_test_file(name: &str, content: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn touch(path: &Path, modified: SystemTime) {
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_drain_file_queue() {
+        let mut server = mockito::Server::new_async().await;
+        let mut mocks = Vec::new();
+        for index in 1..=3 {
+            mocks.push(
+                server
+                    .mock("GET", format!("/movie/DRAIN-00{}", index).as_str())
+                    .with_status(200)
+                    .with_body(format!("<h1>排空影片{}</h1>", index))
+                    .expect(1)
+                    .create_async()
+                    .await,
+            );
+        }
+
+        let (pipeline, test_dir) =
+            create_site_pipeline("test_drain_file_queue", &server.url(), "file_naming_template = \"$title$\"");
+
+        let (file_tx, file_rx) = mpsc::channel(8);
+        for index in 1..=3 {
+            let video_file = test_dir.join("input").join(format!("DRAIN-00{}.mp4", index));
+            std::fs::write(&video_file, b"video content").unwrap();
+            file_tx.send(video_file).await.unwrap();
+        }
+
+        let state = Arc::new(QueueState::default());
+        state.set_paused(true);
+        let queue = FileQueue {
+            state: state.clone(),
+            handle: tokio::spawn(process_file_queue(
+                file_rx,
+                pipeline,
+                MultiProgress::with_draw_target(indicatif::ProgressDrawTarget::hidden()),
+                state.clone(),
+            )),
+        };
+
+        // 排空时恢复暂停的队列，返回前已进入队列的文件全部处理完成
+        tokio::time::timeout(Duration::from_secs(30), queue.drain())
+            .await
+            .unwrap();
+        for (index, mock) in mocks.iter().enumerate() {
+            mock.assert_async().await;
+            let video_path = test_dir
+                .join("output")
+                .join(format!("排空影片{}", index + 1))
+                .join(format!("排空影片{}.mp4", index + 1));
+            assert!(video_path.is_file(), "{} 未整理", video_path.display());
+        }
+        let snapshot = state.snapshot();
+        assert!(snapshot.draining);
+        assert_eq!(snapshot.stats.organized, 3);
+        assert!(snapshot.current.is_none());
+
+        // 排空后不再接收新文件
+        assert!(file_tx.send(test_dir.join("input").join("DRAIN-004.mp4")).await.is_err());
+
+        std::fs::remove_dir_all(&test_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_start_rate_limiter() {
+        assert!(StartRateLimiter::new(0.0).is_none());
+        assert!(StartRateLimiter::new(-1.0).is_none());
+
+        // 每秒 5 个时 10 次开始至少需要 1.8 秒
+        let mut limiter = StartRateLimiter::new(5.0).unwrap();
+        let started = std::time::Instant::now();
+        for _ in 0..10 {
+            limiter.acquire().await;
+        }
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(1800), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_secs(5), "{:?}", elapsed);
+    }
+
+    #[tokio::test]
+    async fn test_process_with_retry() {
+        let organized = ProcessOutcome::Organized {
+            movie_id: "RETRY-001".to_string(),
+            video_path: PathBuf::from("/output/RETRY-001.mp4"),
+            nfo_paths: vec![PathBuf::from("/output/RETRY-001.nfo")],
+        };
+
+        // 失败两次后成功
+        let calls = Mutex::new(0);
+        let mut retries = Vec::new();
+        let result = process_with_retry(
+            3,
+            Duration::ZERO,
+            || async {
+                let mut calls = calls.lock().unwrap();
+                *calls += 1;
+                match *calls {
+                    1 | 2 => Err(anyhow::anyhow!("第 {} 次失败", calls)),
+                    _ => Ok(organized.clone()),
+                }
+            },
+            |attempt, e| retries.push((attempt, e.to_string())),
+        )
+        .await;
+        assert_eq!(result.unwrap(), organized);
+        assert_eq!(*calls.lock().unwrap(), 3);
+        assert_eq!(
+            retries,
+            [(1, "第 1 次失败".to_string()), (2, "第 2 次失败".to_string())]
+        );
+
+        // 重试次数用完后返回最后一次的错误，跳过的文件不重试
+        let mut retries = 0;
+        let result = process_with_retry(
+            1,
+            Duration::ZERO,
+            || async { Err(anyhow::anyhow!("一直失败")) },
+            |_, _| retries += 1,
+        )
+        .await;
+        assert_eq!(result.unwrap_err().to_string(), "一直失败");
+        assert_eq!(retries, 1);
+
+        let result = process_with_retry(
+            3,
+            Duration::ZERO,
+            || async { Ok(ProcessOutcome::Skipped { reason: "忽略".to_string() }) },
+            |_, _| panic!("跳过的文件不应重试"),
+        )
+        .await;
+        assert!(matches!(result, Ok(ProcessOutcome::Skipped { .. })));
+
+        // 文件名无法解析等重试也不会成功的错误不重试，暂时无法获取数据时重试
+        let result = process_with_retry(
+            3,
+            Duration::ZERO,
+            || async { Err(AppError::FileProcessing("无法从文件名提取影片ID".to_string()).into()) },
+            |_, _| panic!("无法解析的文件名不应重试"),
+        )
+        .await;
+        assert!(result.is_err());
+
+        let mut retries = 0;
+        let result = process_with_retry(
+            2,
+            Duration::ZERO,
+            || async { Err(AppError::MovieDataUnavailable("请求超时".to_string()).into()) },
+            |_, _| retries += 1,
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(retries, 2);
+    }
+
+    #[test]
+    fn test_integrity_mode_config() {
+        let mode = |toml: &str| toml::from_str::<IntegrityConfig>(toml).map(|config| config.mode);
+        assert_eq!(mode(r#"mode = "size""#).unwrap(), IntegrityMode::Size);
+        assert_eq!(mode(r#"mode = "size+mtime""#).unwrap(), IntegrityMode::SizeMtime);
+        assert_eq!(mode(r#"mode = "checksum""#).unwrap(), IntegrityMode::Checksum);
+        assert_eq!(mode("").unwrap(), IntegrityMode::SizeMtime);
+
+        // 拼写错误不能悄悄退回默认模式
+        assert!(mode(r#"mode = "checksums""#).is_err());
+        assert!(mode(r#"mode = "crc""#).is_err());
+    }
+
+    #[test]
+    fn test_integrity_size_mode_tolerates_mtime_change() {
+        let path = create_test_file("test_integrity_size.mp4", b"video content");
+        let checker = FileIntegrityChecker::new(&path, IntegrityMode::Size, 0).unwrap();
+
+        touch(&path, SystemTime::now() + Duration::from_secs(60));
+        assert!(checker.verify_integrity().unwrap());
+
+        std::fs::write(&path, b"video content changed").unwrap();
+        assert!(!checker.verify_integrity().unwrap());
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(!checker.verify_integrity().unwrap());
+    }
+
+    #[test]
+    fn test_integrity_size_mtime_mode_detects_mtime_change() {
+        let path = create_test_file("test_integrity_size_mtime.mp4", b"video content");
+        let checker = FileIntegrityChecker::new(&path, IntegrityMode::SizeMtime, 0).unwrap();
+        assert!(checker.verify_integrity().unwrap());
+
+        touch(&path, SystemTime::now() + Duration::from_secs(60));
+        assert!(!checker.verify_integrity().unwrap());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_integrity_checksum_mode_detects_same_size_overwrite() {
+        let path = create_test_file("test_integrity_checksum.mp4", b"video content A");
+        let original_modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+
+        let size_mtime_checker = FileIntegrityChecker::new(&path, IntegrityMode::SizeMtime, 0).unwrap();
+        let checksum_checker = FileIntegrityChecker::new(&path, IntegrityMode::Checksum, 0).unwrap();
+
+        // 大小不变的覆盖写入，并恢复修改时间
+        std::fs::write(&path, b"video content B").unwrap();
+        touch(&path, original_modified);
+
+        assert!(size_mtime_checker.verify_integrity().unwrap());
+        assert!(!checksum_checker.verify_integrity().unwrap());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_integrity_checksum_covers_head_and_tail() {
+        let len = (CHECKSUM_CHUNK_SIZE * 3) as usize;
+        let path = create_test_file("test_integrity_checksum_large.mp4", &vec![0u8; len]);
+        let checker = FileIntegrityChecker::new(&path, IntegrityMode::Checksum, 0).unwrap();
+
+        // 首尾数据块之外的修改不在检查范围内
+        let mut content = vec![0u8; len];
+        content[len / 2] = 1;
+        std::fs::write(&path, &content).unwrap();
+        assert!(checker.verify_integrity().unwrap());
+
+        content[len - 1] = 1;
+        std::fs::write(&path, &content).unwrap();
+        assert!(!checker.verify_integrity().unwrap());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    fn create_rich_nfo() -> MovieNfoCrawler {
+        MovieNfoCrawler {
+            title: "测试影片".to_string(),
+            plot: "剧情简介".to_string(),
+            year: Some(2023),
+            actors: vec![crate::nfo::Actor {
+                name: "演员A".to_string(),
+                role: String::new(),
+                thumb: String::new(),
+                order: Some(1),
+            }],
+            studios: vec!["制作商".to_string()],
+            genres: vec!["剧情".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_calculate_data_quality() {
+        assert_eq!(calculate_data_quality(&create_rich_nfo()), 75);
+
+        let poor = MovieNfoCrawler {
+            title: "测试影片".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(calculate_data_quality(&poor), 15);
+
+        // 标题为空时无论其他字段如何都不能通过
+        let untitled = MovieNfoCrawler {
+            title: String::new(),
+            ..create_rich_nfo()
+        };
+        assert!(calculate_data_quality(&untitled) < 0);
+    }
+
+    fn create_fail_on_missing_config(fail_on_missing: &str) -> AppConfig {
+        create_config(
+            &std::env::temp_dir().join(format!("test_fail_on_missing_{}", fail_on_missing.len())),
+            "javdb.yaml",
+            &format!("fail_on_missing = {}", fail_on_missing),
+        )
+    }
+
+    #[test]
+    fn test_check_required_nfo_fields() {
+        let generator = NfoGenerator::new();
+        let untitled = MovieNfo::for_universal(MovieNfoCrawler {
+            title: String::new(),
+            ..create_rich_nfo()
+        });
+        let warnings = generator.validate_nfo(&untitled);
+
+        // 未配置 fail_on_missing 时只记录警告
+        let config = create_fail_on_missing_config("[]");
+        assert!(check_required_nfo_fields(&warnings, &config, "TEST-001").is_ok());
+
+        // 缺少配置的字段时中止整理，且该错误会被视为跳过
+        let config = create_fail_on_missing_config(r#"["Title", "runtime"]"#);
+        match check_required_nfo_fields(&warnings, &config, "TEST-001") {
+            Err(err @ AppError::MovieDataIncomplete(_)) => {
+                assert!(err.to_string().contains("title, runtime"));
+                assert!(err.should_skip_processing());
+            }
+            _ => panic!("Expected MovieDataIncomplete"),
+        }
+
+        // 配置的字段均存在时继续处理
+        let rich = MovieNfo::for_universal(create_rich_nfo());
+        let warnings = generator.validate_nfo(&rich);
+        let config = create_fail_on_missing_config(r#"["title", "actors"]"#);
+        assert!(check_required_nfo_fields(&warnings, &config, "TEST-001").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_crawl_cache_skips_http_requests() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/movie/CACHE-001")
+            .with_status(200)
+            .with_body(r#"<h1>缓存影片</h1><div class="actor">演员A</div><div class="plot">剧情简介</div>"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let yaml = format!(
+            r#"
+entrypoint: "{}/movie/${{crawl_name}}"
+nodes:
+  title: selector("h1").val()
+  actors: selector(".actor").val()
+  plot: selector(".plot").val()
+"#,
+            server.url()
+        );
+        let templates: Templates = Arc::new(vec![(
+            "cache.yaml".to_string(),
+            Template::from_yaml(&yaml).unwrap(),
+        )]);
+
+        let cache_dir = std::env::temp_dir().join("test_crawl_cache");
+        let _ = std::fs::remove_dir_all(&cache_dir);
+        let config = Arc::new(create_config(
+            &std::env::temp_dir().join("test_crawl_cache_config"),
+            "cache.yaml",
+            &format!("crawl_cache_dir = \"{}\"", cache_dir.display().to_string().replace('\\', "/")),
+        ));
+        let progress = ProgressBar::hidden();
+
+        let first = crawler("CACHE-001", "CACHE-001", &SourceParams::default(), &progress, &templates, &config).await.unwrap();
+        assert!(cache_dir.join("cache").join("CACHE-001.json").exists());
+
+        // 第二次爬取直接读取缓存，不再发起请求
+        let second = crawler("cache-001", "cache-001", &SourceParams::default(), &progress, &templates, &config).await.unwrap();
+        mock.assert_async().await;
+
+        assert_eq!(first.title, "缓存影片");
+        assert_eq!(second.title, first.title);
+        assert_eq!(second.actors[0].name, "演员A");
+
+        // 要求刷新时忽略缓存重新爬取，并用新结果更新缓存
+        mock.remove_async().await;
+        let refreshed_mock = server
+            .mock("GET", "/movie/CACHE-001")
+            .with_status(200)
+            .with_body(r#"<h1>更新后的影片</h1><div class="actor">演员A</div><div class="plot">剧情简介</div>"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let mut refresh_config = (*config).clone();
+        refresh_config.refresh_metadata = true;
+        let refreshed = crawler("CACHE-001", "CACHE-001", &SourceParams::default(), &progress, &templates, &refresh_config).await.unwrap();
+        assert_eq!(refreshed.title, "更新后的影片");
+        let cached = crawler("CACHE-001", "CACHE-001", &SourceParams::default(), &progress, &templates, &config).await.unwrap();
+        assert_eq!(cached.title, "更新后的影片");
+        refreshed_mock.assert_async().await;
+
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_post_process_command() {
+        let test_dir = std::env::temp_dir().join("test_run_post_process_command");
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).unwrap();
+        let marker = test_dir.join("marker.txt");
+        let video = test_dir.join("HOOK-001.mp4");
+        let nfo = test_dir.join("HOOK-001.nfo");
+
+        let command: Vec<String> = [
+            "sh",
+            "-c",
+            r#"printf '%s|%s|%s' "$1" "$2" "$3" > "$4""#,
+            "sh",
+            "{id}",
+            "{video}",
+            "{nfo}",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .chain([marker.to_string_lossy().into_owned()])
+        .collect();
+        run_post_process_command(&command, &video, &nfo, "HOOK-001").unwrap();
+
+        let content = std::fs::read_to_string(&marker).unwrap();
+        assert_eq!(content, format!("HOOK-001|{}|{}", video.display(), nfo.display()));
+
+        // 退出码非 0 与无法启动的命令返回错误
+        let failing = vec!["sh".to_string(), "-c".to_string(), "exit 3".to_string()];
+        assert!(run_post_process_command(&failing, &video, &nfo, "HOOK-001").is_err());
+        let missing = vec!["/nonexistent/jav-tidy-hook".to_string()];
+        assert!(run_post_process_command(&missing, &video, &nfo, "HOOK-001").is_err());
+
+        std::fs::remove_dir_all(&test_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dump_failed_crawl() {
+        let mut server = mockito::Server::new_async().await;
+        let search_body = r#"<div class="actor">演员A</div><a href="/detail/DUMP-001">DUMP-001</a>"#;
+        let detail_body = r#"<div class="plot">剧情简介</div>"#;
+        let _search = server
+            .mock("GET", "/search/DUMP-001")
+            .with_status(200)
+            .with_body(search_body)
+            .create_async()
+            .await;
+        let _detail = server
+            .mock("GET", "/detail/DUMP-001")
+            .with_status(200)
+            .with_body(detail_body)
+            .create_async()
+            .await;
+
+        let yaml = format!(
+            r#"
+entrypoint: "{0}/search/${{crawl_name}}"
+nodes:
+  actors: selector(".actor").val()
+  detail_url:
+    script: selector("a").attr("href").insert(0,"{0}")
+    request: true
+    children:
+      plot: selector(".plot").val()
+      title:
+        script: selector("h1").val()
+        required: true
+"#,
+            server.url()
+        );
+        let templates: Templates = Arc::new(vec![(
+            "dump.yaml".to_string(),
+            Template::from_yaml(&yaml).unwrap(),
+        )]);
+
+        let dump_dir = std::env::temp_dir().join("test_dump_failed_crawl");
+        let _ = std::fs::remove_dir_all(&dump_dir);
+        let config = Arc::new(create_config(
+            &std::env::temp_dir().join("test_dump_failed_crawl_config"),
+            "dump.yaml",
+            &format!(
+                "[debug]\ndump_failed_crawls = true\ndump_dir = \"{}\"",
+                dump_dir.display().to_string().replace('\\', "/")
+            ),
+        ));
+
+        // 详情页缺少必需的标题字段，模板爬取失败
+        let result = crawler("DUMP-001", "DUMP-001", &SourceParams::default(), &ProgressBar::hidden(), &templates, &config).await;
+        assert!(result.is_err());
+
+        let dumps: Vec<PathBuf> = std::fs::read_dir(&dump_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(dumps.len(), 1);
+        let dump = &dumps[0];
+        assert!(dump.file_name().unwrap().to_str().unwrap().ends_with("_DUMP-001_dump"));
+
+        let search_page = std::fs::read_to_string(dump.join("page_1.html")).unwrap();
+        assert!(search_page.contains(search_body));
+        let detail_page = std::fs::read_to_string(dump.join("page_2.html")).unwrap();
+        assert!(detail_page.contains(detail_body));
+
+        let variables: HashMap<String, Vec<String>> =
+            serde_json::from_str(&std::fs::read_to_string(dump.join("variables.json")).unwrap()).unwrap();
+        assert_eq!(variables["actors"], vec!["演员A".to_string()]);
+        assert_eq!(variables["detail_url"], vec![format!("{}/detail/DUMP-001", server.url())]);
+        assert_eq!(variables["crawl_name"], vec!["DUMP-001".to_string()]);
+        assert!(!variables.contains_key("title"));
+
+        let error = std::fs::read_to_string(dump.join("error.txt")).unwrap();
+        assert!(error.contains("title"));
+
+        std::fs::remove_dir_all(&dump_dir).unwrap();
+    }
+
+    #[test]
+    fn test_filter_by_data_quality() {
+        let scored = || {
+            let rich = create_rich_nfo();
+            let poor = MovieNfoCrawler {
+                title: "低质量".to_string(),
+                ..Default::default()
+            };
+            vec![
+                ("poor.yaml".to_string(), poor.clone(), calculate_data_quality(&poor)),
+                ("rich.yaml".to_string(), rich.clone(), calculate_data_quality(&rich)),
+            ]
+        };
+
+        // 默认阈值保留全部结果
+        let accepted = filter_by_data_quality(scored(), 0, "TEST-001").unwrap();
+        assert_eq!(accepted.len(), 2);
+
+        // 低质量结果被丢弃
+        let accepted = filter_by_data_quality(scored(), 50, "TEST-001").unwrap();
+        assert_eq!(accepted.len(), 1);
+        assert_eq!(accepted[0].title, "测试影片");
+
+        // 全部低于阈值视为爬取失败
+        let result = filter_by_data_quality(scored(), 90, "TEST-001");
+        match result {
+            Err(AppError::MovieDataQualityTooLow(msg)) => {
+                assert!(msg.contains("poor.yaml=15"));
+                assert!(msg.contains("rich.yaml=75"));
+            }
+            _ => panic!("Expected MovieDataQualityTooLow"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_template_overrides_base_url() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/movie/MIRROR-001")
+            .with_status(200)
+            .with_body(r#"<h1>镜像影片</h1><div class="actor">演员A</div>"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let template_dir = std::env::temp_dir().join("test_template_overrides");
+        let _ = std::fs::remove_dir_all(&template_dir);
+        std::fs::create_dir_all(&template_dir).unwrap();
+        std::fs::write(
+            template_dir.join("mirror.yaml"),
+            r#"
+entrypoint: "${base_url}/movie/${crawl_name}"
+env:
+  base_url: ["http://dead-mirror.invalid"]
+nodes:
+  title: selector("h1").val()
+  actors: selector(".actor").val()
+"#,
+        )
+        .unwrap();
+
+        let mut config = create_config(
+            &template_dir,
+            "mirror.yaml",
+            "[template_overrides.\"mirror.yaml\"]\nbase_url = \"http://config-mirror.invalid\"",
+        );
+        assert_eq!(
+            config.get_template_overrides("mirror.yaml").unwrap()["base_url"],
+            "http://config-mirror.invalid"
+        );
+
+        // 命令行覆盖优先于配置文件
+        let cli_override: TemplateOverride = format!("mirror.yaml:base_url={}", server.url()).parse().unwrap();
+        config.apply_template_overrides(&[cli_override]);
+
+        let templates: Templates = Arc::new(get_templates(&template_dir, &config).unwrap());
+        let config = Arc::new(config);
+        let nfo = crawler("MIRROR-001", "MIRROR-001", &SourceParams::default(), &ProgressBar::hidden(), &templates, &config).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(nfo.title, "镜像影片");
+
+        std::fs::remove_dir_all(&template_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_source_params_in_entrypoint() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/search/SRC-001")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("actor".into(), "Yua Mikami".into()),
+                mockito::Matcher::UrlEncoded("up".into(), "downloads".into()),
+                mockito::Matcher::UrlEncoded("file".into(), "SRC-001 C.mp4".into()),
+            ]))
+            .with_status(200)
+            .with_body(r#"<h1>来源影片</h1><div class="actor">演员A</div>"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let template_dir = std::env::temp_dir().join("test_source_params_in_entrypoint");
+        let _ = std::fs::remove_dir_all(&template_dir);
+        std::fs::create_dir_all(&template_dir).unwrap();
+        std::fs::write(
+            template_dir.join("source.yaml"),
+            format!(
+                r#"
+entrypoint: "{}/search/${{crawl_name}}?actor=${{parent_dir}}&up=${{grandparent_dir}}&file=${{original_filename}}"
+env:
+  parent_dir: ["被覆盖的参数"]
+nodes:
+  title: selector("h1").val()
+  actors: selector(".actor").val()
+"#,
+                server.url()
+            ),
+        )
+        .unwrap();
+
+        let config = create_config(&template_dir, "source.yaml", "");
+        let templates: Templates = Arc::new(get_templates(&template_dir, &config).unwrap());
+
+        // 目录名中的控制字符与连续空白被清理，文件路径的参数覆盖模板 env 中的同名参数
+        let source = SourceParams::from_path(Path::new("/downloads/Yua \t Mikami/SRC-001 C.mp4"));
+        assert_eq!(
+            source,
+            SourceParams {
+                original_filename: "SRC-001 C.mp4".to_string(),
+                parent_dir: "Yua Mikami".to_string(),
+                grandparent_dir: "downloads".to_string(),
+            }
+        );
+        let nfo = crawler("SRC-001", "SRC-001 C", &source, &ProgressBar::hidden(), &templates, &config)
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(nfo.title, "来源影片");
+
+        std::fs::remove_dir_all(&template_dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_template_override() {
+        let item: TemplateOverride = "javdb.yaml:base_url=https://javdb368.com?a=b".parse().unwrap();
+        assert_eq!(item.template, "javdb.yaml");
+        assert_eq!(item.key, "base_url");
+        assert_eq!(item.value, "https://javdb368.com?a=b");
+
+        assert!("javdb.yaml".parse::<TemplateOverride>().is_err());
+        assert!("base_url=https://javdb368.com".parse::<TemplateOverride>().is_err());
+        assert!(":base_url=x".parse::<TemplateOverride>().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_get_templates_skips_non_utf8_names() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let template_dir = std::env::temp_dir().join("test_get_templates_non_utf8");
+        let _ = std::fs::remove_dir_all(&template_dir);
+        std::fs::create_dir_all(&template_dir).unwrap();
+        let yaml = r#"
+entrypoint: "${base_url}/movie/${crawl_name}"
+nodes:
+  title: selector("h1").val()
+"#;
+        std::fs::write(template_dir.join("site.yaml"), yaml).unwrap();
+        std::fs::write(template_dir.join(OsStr::from_bytes(b"\xff\xfe.yaml")), yaml).unwrap();
+
+        let config = create_config(&template_dir, "site.yaml", "");
+
+        let templates = get_templates(&template_dir, &config).unwrap();
+        assert_eq!(templates.len(), 1);
+        assert_eq!(templates[0].0, "site.yaml");
+
+        std::fs::remove_dir_all(&template_dir).unwrap();
+    }
+}
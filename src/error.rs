@@ -5,7 +5,6 @@ use crawler_template::CrawlerErr;
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("File processing error: {0}")]
-    #[allow(dead_code)]
     FileProcessing(String),
     
     #[error("Configuration error: {0}")]
@@ -16,8 +15,14 @@ pub enum AppError {
     MovieDataNotFound(String),
     
     #[error("Movie data quality too low: {0}")]
-    #[allow(dead_code)]
     MovieDataQualityTooLow(String),
+
+    #[error("Required NFO fields missing: {0}")]
+    MovieDataIncomplete(String),
+
+    /// 没有模板返回数据，且至少一个模板因网络、超时等原因失败，稍后重试可能成功
+    #[error("Movie data unavailable: {0}")]
+    MovieDataUnavailable(String),
     
     #[error("Template error: {0}")]
     Template(Box<CrawlerErr>),
@@ -32,7 +37,9 @@ pub enum AppError {
 impl AppError {
     pub fn should_skip_processing(&self) -> bool {
         match self {
-            AppError::MovieDataNotFound(_) | AppError::MovieDataQualityTooLow(_) => true,
+            AppError::MovieDataNotFound(_)
+            | AppError::MovieDataQualityTooLow(_)
+            | AppError::MovieDataIncomplete(_) => true,
             AppError::Template(crawler_err) => {
                 if let CrawlerErr::Custom(msg) = crawler_err.as_ref() {
                     msg.starts_with("DATA_NOT_FOUND:")
@@ -44,11 +51,18 @@ impl AppError {
         }
     }
     
+    /// 重新处理可能成功的错误；文件本身的问题、配置错误和按规则跳过的情况重试也不会改变结果
+    pub fn is_retryable(&self) -> bool {
+        !matches!(self, AppError::FileProcessing(_) | AppError::Configuration(_))
+            && !self.should_skip_processing()
+    }
+
     pub fn skip_reason(&self) -> Option<&str> {
         if self.should_skip_processing() {
             match self {
                 AppError::MovieDataNotFound(_) => Some("影片数据不存在"),
                 AppError::MovieDataQualityTooLow(_) => Some("数据质量过低"),
+                AppError::MovieDataIncomplete(_) => Some("缺少必需的NFO字段"),
                 AppError::Template(crawler_err) if matches!(crawler_err.as_ref(), CrawlerErr::Custom(msg) if msg.starts_with("DATA_NOT_FOUND:")) => Some("数据不存在"),
                 _ => Some("未知原因"),
             }
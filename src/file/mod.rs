@@ -1,11 +1,15 @@
-#[cfg(target_os = "windows")]
-use std::path::Path;
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 mod notify;
+pub mod safe_write;
+mod scan;
 
 use anyhow::Ok;
+use indicatif::MultiProgress;
 use notify::SourceNotify;
+use scan::{full_scan, ScanExclusions};
 use tokio::sync::mpsc;
 
 use crate::config::AppConfig;
@@ -13,66 +17,99 @@ use crate::config::AppConfig;
 pub async fn initial(
     config: &AppConfig,
     return_tx: mpsc::Sender<PathBuf>,
+    multi_progress: &MultiProgress,
 ) -> anyhow::Result<SourceNotify> {
     log::info!("初始化文件监控系统...");
     let migrate_files_ext = config.get_migrate_files_ext();
     log::debug!("支持的文件扩展名: {:?}", migrate_files_ext);
 
     let return_tx_notify = return_tx.clone();
+    let exclusions = Arc::new(ScanExclusions::from_config(config));
 
     log::info!("创建文件监控器，监控输入目录: {}", config.input_dir.display());
     let source_notify = SourceNotify::new(
-        &[config.input_dir.clone()],
+        std::slice::from_ref(&config.input_dir),
         return_tx_notify,
-        migrate_files_ext,
+        migrate_files_ext.clone(),
+        exclusions.clone(),
     )?;
 
     let input_dir = config.input_dir.clone();
     log::info!("启动初始全目录扫描任务: {}", input_dir.display());
-    tokio::spawn(full_scan(input_dir, return_tx, migrate_files_ext));
+    let progress = crate::crawler::get_progress_bar(multi_progress, "扫描输入目录...");
+    tokio::spawn(async move {
+        if let Err(e) = full_scan(input_dir, return_tx, migrate_files_ext, exclusions, progress).await {
+            log::error!("全目录扫描失败: {}", e);
+        }
+    });
 
     log::info!("文件监控系统初始化完成");
     Ok(source_notify)
 }
 
-async fn full_scan(
-    source: PathBuf,
-    return_tx: mpsc::Sender<PathBuf>,
-    migrate_files_ext: &'static [&'static str],
-) -> anyhow::Result<()> {
-    log::info!("开始全目录扫描: {}", source.display());
-    let mut file_count = 0;
-    let mut matched_count = 0;
-    
-    for entry in walkdir::WalkDir::new(&source) {
-        let entry = entry?;
-        if entry.file_type().is_file() {
-            file_count += 1;
-            let path = entry.path();
-            log::debug!("扫描文件: {}", path.display());
-            
-            #[cfg(target_os = "windows")]
-            if is_recycle_bin(path) {
-                log::debug!("跳过回收站文件: {}", path.display());
-                continue;
-            }
-            
-            if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
-                if is_migrate_files(migrate_files_ext, extension) {
-                    matched_count += 1;
-                    log::info!("发现匹配文件: {}", path.display());
-                    return_tx.send(path.to_owned()).await?;
-                } else {
-                    log::debug!("跳过不匹配扩展名 '{}' 的文件: {}", extension, path.display());
-                }
-            } else {
-                log::debug!("跳过无扩展名文件: {}", path.display());
-            }
+/// 待整理影片条目的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaItemKind {
+    /// 普通视频文件
+    Video,
+    /// `.strm` 指向网络地址的文件，默认原地生成NFO与图片而不移动
+    Strm,
+    /// 包含 `BDMV/index.bdmv` 的蓝光原盘目录，整个目录作为一部影片移动
+    BdmvFolder,
+}
+
+impl MediaItemKind {
+    pub fn of(path: &Path) -> Self {
+        if is_bdmv_folder(path) {
+            Self::BdmvFolder
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("strm"))
+        {
+            Self::Strm
+        } else {
+            Self::Video
         }
     }
-    
-    log::info!("全目录扫描完成: 总文件数={}, 匹配文件数={}", file_count, matched_count);
-    Ok(())
+}
+
+/// 文件名最多保留的字节数，避免路径过长
+const MAX_FILE_NAME_BYTES: usize = 100;
+
+/// 清理用作单个文件名或目录名的文本：移除路径分隔符等非法字符、合并多余空格并限制长度
+///
+/// 清理后只剩 `.` 或 `..` 时返回空字符串，调用方需要处理空名称
+pub fn sanitize_file_name(name: &str) -> String {
+    let illegal_chars = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+    let mut sanitized = name
+        .replace(illegal_chars, "")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if sanitized.len() > MAX_FILE_NAME_BYTES {
+        let mut end = MAX_FILE_NAME_BYTES;
+        while !sanitized.is_char_boundary(end) {
+            end -= 1;
+        }
+        sanitized.truncate(end);
+        sanitized.truncate(sanitized.trim_end().len());
+    }
+
+    if sanitized == "." || sanitized == ".." {
+        sanitized.clear();
+    }
+    sanitized
+}
+
+/// 检查目录是否为蓝光原盘目录（包含 `BDMV/index.bdmv`）
+pub fn is_bdmv_folder(path: &Path) -> bool {
+    path.join("BDMV").join("index.bdmv").is_file()
+}
+
+/// 查找路径所属的蓝光原盘目录，路径本身是蓝光原盘目录时返回其自身
+pub(in crate::file) fn find_bdmv_folder(path: &Path) -> Option<&Path> {
+    path.ancestors().find(|ancestor| is_bdmv_folder(ancestor))
 }
 
 #[cfg(target_os = "windows")]
@@ -82,11 +119,9 @@ pub(in crate::file) fn is_recycle_bin(path: &Path) -> bool {
         .is_some_and(|com| com.as_os_str().to_str().unwrap_or("").eq("$RECYCLE.BIN"))
 }
 
-pub(in crate::file) fn is_migrate_files(
-    migrate_files_ext: &'static [&'static str],
-    ext: &str,
-) -> bool {
-    let matches = migrate_files_ext.contains(&ext);
+/// 检查扩展名是否需要处理，`migrate_files_ext` 中的扩展名已统一为小写
+pub(in crate::file) fn is_migrate_files(migrate_files_ext: &HashSet<String>, ext: &str) -> bool {
+    let matches = migrate_files_ext.contains(&ext.to_lowercase());
     log::debug!("扩展名匹配检查: '{}' 在 {:?} 中 = {}", ext, migrate_files_ext, matches);
     matches
 }
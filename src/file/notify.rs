@@ -8,6 +8,7 @@ use std::{
 use notify::{Config, Error, Event, EventKind, RecommendedWatcher, Watcher};
 use tokio::sync::{mpsc, RwLock};
 
+use super::{find_bdmv_folder, is_migrate_files, scan::ScanExclusions};
 #[cfg(target_os = "windows")]
 use super::is_recycle_bin;
 
@@ -25,7 +26,9 @@ pub struct SourceNotify {
 
 struct SourceNotifyInner {
     watcher: RwLock<RecommendedWatcher>,
-    allowed_extensions: HashSet<String>,
+    sources: Vec<PathBuf>,
+    allowed_extensions: Arc<HashSet<String>>,
+    exclusions: Arc<ScanExclusions>,
 }
 
 /// 事件处理器配置
@@ -54,14 +57,16 @@ impl SourceNotify {
     /// # 参数
     /// - `sources`: 要监控的目录列表
     /// - `return_tx`: 文件路径发送通道
-    /// - `migrate_files_ext`: 允许的文件扩展名列表
+    /// - `allowed_extensions`: 允许的文件扩展名集合（小写）
+    /// - `exclusions`: 跳过的目录，其中的文件事件被忽略
     ///
     /// # 返回
     /// 返回监控器实例或错误
     pub fn new(
         sources: &[PathBuf],
         return_tx: mpsc::Sender<PathBuf>,
-        migrate_files_ext: &'static [&'static str],
+        allowed_extensions: Arc<HashSet<String>>,
+        exclusions: Arc<ScanExclusions>,
     ) -> anyhow::Result<Self> {
         // 创建事件通道
         let (event_tx, event_rx) = mpsc::unbounded_channel();
@@ -85,18 +90,14 @@ impl SourceNotify {
             Config::default(),
         )?;
 
-        // 预处理扩展名为HashSet，提高查找效率
-        let allowed_extensions: HashSet<String> = migrate_files_ext
-            .iter()
-            .map(|ext| ext.to_lowercase())
-            .collect();
-        
         log::info!("配置监控文件扩展名: {:?}", allowed_extensions);
 
         let source_notify = SourceNotify {
             inner: Arc::new(SourceNotifyInner {
                 watcher: RwLock::new(watcher),
+                sources: sources.to_vec(),
                 allowed_extensions,
+                exclusions,
             }),
         };
 
@@ -231,12 +232,6 @@ impl SourceNotify {
 
         for path in event.paths {
             log::debug!("检查文件: {}", path.display());
-            
-            // 基本过滤
-            if !path.is_file() {
-                log::debug!("跳过非文件: {}", path.display());
-                continue;
-            }
 
             #[cfg(target_os = "windows")]
             if is_recycle_bin(&path) {
@@ -244,16 +239,39 @@ impl SourceNotify {
                 continue;
             }
 
-            // 检查扩展名
-            if !Self::is_allowed_file(&path, &inner.allowed_extensions) {
-                if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                    log::debug!("跳过不支持的文件扩展名 '{}': {}", ext, path.display());
-                } else {
-                    log::debug!("跳过无扩展名文件: {}", path.display());
-                }
+            if inner
+                .sources
+                .iter()
+                .any(|source| inner.exclusions.contains(source, &path))
+            {
+                log::debug!("跳过排除目录中的文件: {}", path.display());
                 continue;
             }
 
+            // 蓝光原盘目录及其中的文件都按整个目录处理
+            let path = if let Some(folder) = find_bdmv_folder(&path) {
+                log::debug!("{} 属于蓝光原盘目录: {}", path.display(), folder.display());
+                folder.to_path_buf()
+            } else {
+                // 基本过滤
+                if !path.is_file() {
+                    log::debug!("跳过非文件: {}", path.display());
+                    continue;
+                }
+
+                // 检查扩展名
+                if !Self::is_allowed_file(&path, &inner.allowed_extensions) {
+                    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+                        log::debug!("跳过不支持的文件扩展名 '{}': {}", ext, path.display());
+                    } else {
+                        log::debug!("跳过无扩展名文件: {}", path.display());
+                    }
+                    continue;
+                }
+
+                path
+            };
+
             // 去重检查
             if recent_files.contains(&path) {
                 log::debug!("跳过重复文件: {}", path.display());
@@ -276,7 +294,7 @@ impl SourceNotify {
     fn is_allowed_file(path: &Path, allowed_extensions: &HashSet<String>) -> bool {
         path.extension()
             .and_then(|ext| ext.to_str())
-            .map(|ext| allowed_extensions.contains(&ext.to_lowercase()))
+            .map(|ext| is_migrate_files(allowed_extensions, ext))
             .unwrap_or(false)
     }
 
@@ -0,0 +1,114 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// 获取目标文件在同一目录下的临时文件路径：`<name>.tmp`
+pub fn temp_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".tmp");
+    path.with_file_name(file_name)
+}
+
+/// 安全写入文件
+///
+/// 先写入同目录下的临时文件并 fsync，再重命名覆盖目标文件。写入中断时目标文件
+/// 保持原样，不会留下被截断的内容
+pub fn safe_write(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let tmp_path = temp_path(path);
+
+    let result = write_temp(&tmp_path, contents.as_ref()).and_then(|_| persist(&tmp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+fn write_temp(tmp_path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut file = File::create(tmp_path)?;
+    file.write_all(contents)?;
+    file.flush()?;
+    file.sync_all()
+}
+
+/// 将已 fsync 的临时文件重命名为目标文件，并在 unix 上 fsync 所在目录
+pub fn persist(tmp_path: &Path, path: &Path) -> io::Result<()> {
+    rename_over(tmp_path, path)?;
+    sync_parent_dir(path)
+}
+
+#[cfg(not(windows))]
+fn rename_over(from: &Path, to: &Path) -> io::Result<()> {
+    fs::rename(from, to)
+}
+
+/// Windows 上目标文件被占用或只读时无法直接覆盖，删除后重试一次
+#[cfg(windows)]
+fn rename_over(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if to.exists() => {
+            log::debug!("覆盖 {} 失败，删除后重试: {}", to.display(), e);
+            fs::remove_file(to)?;
+            fs::rename(from, to)
+        }
+        result => result,
+    }
+}
+
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    match path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        Some(parent) => File::open(parent)?.sync_all(),
+        None => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_safe_write_replaces_target() {
+        let dir = test_dir("test_safe_write_replace");
+        let path = dir.join("movie.nfo");
+
+        safe_write(&path, "<movie>old</movie>").unwrap();
+        safe_write(&path, "<movie>new</movie>").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "<movie>new</movie>");
+        assert!(!temp_path(&path).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_safe_write_interrupted_leaves_no_partial_target() {
+        let dir = test_dir("test_safe_write_interrupted");
+        let path = dir.join("movie.nfo");
+        let existing = dir.join("existing.nfo");
+        safe_write(&existing, "<movie>old</movie>").unwrap();
+
+        // 临时文件路径被目录占用，模拟写入临时文件阶段失败
+        fs::create_dir(temp_path(&path)).unwrap();
+        fs::create_dir(temp_path(&existing)).unwrap();
+
+        assert!(safe_write(&path, "<movie>new</movie>").is_err());
+        assert!(!path.exists());
+
+        assert!(safe_write(&existing, "<movie>new</movie>").is_err());
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "<movie>old</movie>");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,431 @@
+// 输入目录的全目录扫描：多个目录并发读取，跳过排除的目录，通过进度条报告扫描进度
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use indicatif::ProgressBar;
+use regex::Regex;
+use tokio::{sync::mpsc, task::JoinSet};
+
+use super::{is_bdmv_folder, is_migrate_files};
+#[cfg(target_os = "windows")]
+use super::is_recycle_bin;
+use crate::config::AppConfig;
+
+/// 同时读取的目录数量
+const SCAN_WORKERS: usize = 8;
+
+/// 每扫描这么多文件输出一次进度日志
+const SCAN_LOG_INTERVAL: usize = 1000;
+
+/// 扫描与监控时跳过的目录
+#[derive(Debug, Default)]
+pub struct ScanExclusions {
+    /// 目录名通配符
+    patterns: Vec<Regex>,
+    /// 位于输入目录中的输出目录，同时保存以输入目录拼接的路径与规范化路径
+    dirs: Vec<PathBuf>,
+}
+
+impl ScanExclusions {
+    /// `excluded_dirs` 中位于 `source` 内的目录会被跳过，其余忽略
+    pub fn new<'a>(
+        source: &Path,
+        patterns: &[String],
+        excluded_dirs: impl IntoIterator<Item = &'a Path>,
+    ) -> Self {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| pattern.trim())
+            .filter(|pattern| !pattern.is_empty())
+            .map(glob_regex)
+            .collect();
+
+        let mut dirs = Vec::new();
+        if let Ok(canonical_source) = source.canonicalize() {
+            for dir in excluded_dirs {
+                let Ok(canonical_dir) = dir.canonicalize() else {
+                    continue;
+                };
+                if let Ok(relative) = canonical_dir.strip_prefix(&canonical_source) {
+                    if relative.as_os_str().is_empty() {
+                        continue;
+                    }
+                    log::info!("输出目录位于输入目录中，扫描时跳过: {}", dir.display());
+                    dirs.push(source.join(relative));
+                    dirs.push(canonical_dir.clone());
+                }
+            }
+        }
+
+        Self { patterns, dirs }
+    }
+
+    /// 按配置的通配符跳过目录，并跳过位于输入目录中的输出目录（含输出路由的目录）
+    pub fn from_config(config: &AppConfig) -> Self {
+        let output_dirs = std::iter::once(config.get_output_dir())
+            .chain(config.output_routes.iter().map(|route| route.output_dir.as_path()));
+        Self::new(&config.input_dir, &config.scan_exclude_dirs, output_dirs)
+    }
+
+    /// 扫描时是否跳过该目录
+    fn is_excluded_dir(&self, path: &Path) -> bool {
+        #[cfg(target_os = "windows")]
+        if is_recycle_bin(path) {
+            return true;
+        }
+        self.dirs.iter().any(|dir| dir == path)
+            || path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| self.matches_name(name))
+    }
+
+    /// 路径是否位于跳过的目录中，用于过滤文件监控事件
+    pub fn contains(&self, source: &Path, path: &Path) -> bool {
+        if self.dirs.iter().any(|dir| path.starts_with(dir)) {
+            return true;
+        }
+        let relative = path.strip_prefix(source).unwrap_or(path);
+        let Some(parent) = relative.parent() else {
+            return false;
+        };
+        parent
+            .components()
+            .filter_map(|component| component.as_os_str().to_str())
+            .any(|name| self.matches_name(name))
+    }
+
+    fn matches_name(&self, name: &str) -> bool {
+        self.patterns.iter().any(|pattern| pattern.is_match(name))
+    }
+}
+
+/// 目录名通配符转换为正则：`*` 匹配任意字符，`?` 匹配单个字符，不区分大小写
+fn glob_regex(pattern: &str) -> Regex {
+    let mut regex = String::from("(?i)^");
+    for c in pattern.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            c => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).expect("转义后的通配符总是有效的正则")
+}
+
+/// 读取一个目录的结果
+struct DirListing {
+    /// 需要继续扫描的子目录
+    subdirs: Vec<PathBuf>,
+    /// 匹配的文件与蓝光原盘目录
+    items: Vec<PathBuf>,
+    file_count: usize,
+}
+
+/// 全目录扫描，匹配的文件与蓝光原盘目录发送到 `return_tx`
+///
+/// 无法读取的子目录记录警告后跳过，只有输入目录本身无法读取时返回错误；发送顺序不固定
+pub(super) async fn full_scan(
+    source: PathBuf,
+    return_tx: mpsc::Sender<PathBuf>,
+    migrate_files_ext: Arc<HashSet<String>>,
+    exclusions: Arc<ScanExclusions>,
+    progress: ProgressBar,
+) -> anyhow::Result<()> {
+    log::info!("开始全目录扫描: {}", source.display());
+    let mut dir_count = 0;
+    let mut file_count = 0;
+    let mut matched_count = 0;
+
+    let mut pending = vec![source.clone()];
+    let mut reads = JoinSet::new();
+    loop {
+        while reads.len() < SCAN_WORKERS {
+            let Some(dir) = pending.pop() else {
+                break;
+            };
+            let migrate_files_ext = migrate_files_ext.clone();
+            let exclusions = exclusions.clone();
+            reads.spawn_blocking(move || {
+                let listing = read_directory(&dir, &migrate_files_ext, &exclusions);
+                (dir, listing)
+            });
+        }
+
+        let Some(joined) = reads.join_next().await else {
+            break;
+        };
+        let (dir, listing) = joined?;
+        let listing = match listing {
+            Ok(listing) => listing,
+            Err(e) if dir == source => {
+                return Err(anyhow::anyhow!("无法读取输入目录 {}: {}", source.display(), e));
+            }
+            Err(e) => {
+                log::warn!("无法读取目录 {}，跳过: {}", dir.display(), e);
+                continue;
+            }
+        };
+
+        dir_count += 1;
+        let previous_file_count = file_count;
+        file_count += listing.file_count;
+        if file_count / SCAN_LOG_INTERVAL > previous_file_count / SCAN_LOG_INTERVAL {
+            log::info!(
+                "全目录扫描中: 已扫描目录数={}, 文件数={}, 匹配文件数={}",
+                dir_count,
+                file_count,
+                matched_count
+            );
+        }
+
+        pending.extend(listing.subdirs);
+        for item in listing.items {
+            matched_count += 1;
+            log::info!("发现匹配文件: {}", item.display());
+            return_tx.send(item).await?;
+        }
+        progress.set_message(format!(
+            "扫描输入目录: 已扫描 {} 个目录，匹配 {} 个文件",
+            dir_count, matched_count
+        ));
+    }
+
+    progress.finish_and_clear();
+    log::info!(
+        "全目录扫描完成: 目录数={}, 总文件数={}, 匹配文件数={}",
+        dir_count,
+        file_count,
+        matched_count
+    );
+    Ok(())
+}
+
+/// 读取单个目录：匹配的文件与蓝光原盘目录作为结果，其余未排除的子目录继续扫描
+fn read_directory(
+    dir: &Path,
+    migrate_files_ext: &HashSet<String>,
+    exclusions: &ScanExclusions,
+) -> std::io::Result<DirListing> {
+    let mut listing = DirListing {
+        subdirs: Vec::new(),
+        items: Vec::new(),
+        file_count: 0,
+    };
+
+    for entry in std::fs::read_dir(dir)? {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                log::warn!("读取目录 {} 的条目失败，跳过: {}", dir.display(), e);
+                continue;
+            }
+        };
+        let path = entry.path();
+        let file_type = match entry.file_type() {
+            Ok(file_type) => file_type,
+            Err(e) => {
+                log::warn!("无法获取 {} 的文件类型，跳过: {}", path.display(), e);
+                continue;
+            }
+        };
+
+        if file_type.is_dir() {
+            if exclusions.is_excluded_dir(&path) {
+                log::debug!("跳过排除的目录: {}", path.display());
+            } else if is_bdmv_folder(&path) {
+                // 蓝光原盘目录作为一个整体处理，不再扫描其中的文件
+                log::info!("发现蓝光原盘目录: {}", path.display());
+                listing.items.push(path);
+            } else {
+                listing.subdirs.push(path);
+            }
+            continue;
+        }
+
+        if !file_type.is_file() {
+            continue;
+        }
+        listing.file_count += 1;
+        log::debug!("扫描文件: {}", path.display());
+
+        if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
+            if is_migrate_files(migrate_files_ext, extension) {
+                listing.items.push(path);
+            } else {
+                log::debug!("跳过不匹配扩展名 '{}' 的文件: {}", extension, path.display());
+            }
+        } else if path.extension().is_some() {
+            log::debug!("跳过扩展名不是有效 UTF-8 的文件: {}", path.display());
+        } else {
+            log::debug!("跳过无扩展名文件: {}", path.display());
+        }
+    }
+
+    Ok(listing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::{find_bdmv_folder, MediaItemKind};
+
+    async fn scan(dir: &Path, extensions: &[&str], exclusions: ScanExclusions) -> Vec<PathBuf> {
+        let extensions: Arc<HashSet<String>> =
+            Arc::new(extensions.iter().map(|ext| ext.to_string()).collect());
+        let (tx, mut rx) = mpsc::channel(16);
+        let scan = tokio::spawn(full_scan(
+            dir.to_path_buf(),
+            tx,
+            extensions,
+            Arc::new(exclusions),
+            ProgressBar::hidden(),
+        ));
+
+        let mut found = Vec::new();
+        while let Some(path) = rx.recv().await {
+            found.push(path.strip_prefix(dir).unwrap().to_path_buf());
+        }
+        scan.await.unwrap().unwrap();
+        found.sort();
+        found
+    }
+
+    #[tokio::test]
+    async fn test_full_scan_filters_extensions() {
+        let dir = std::env::temp_dir().join("test_full_scan_filters_extensions");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        for name in ["a.mp4", "b.MKV", "c.txt", "sub/d.mp4", "e"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+
+        let found = scan(&dir, &["mp4", "mkv"], ScanExclusions::default()).await;
+
+        assert_eq!(
+            found,
+            vec![
+                PathBuf::from("a.mp4"),
+                PathBuf::from("b.MKV"),
+                PathBuf::from("sub").join("d.mp4"),
+            ]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_full_scan_finds_bdmv_folders() {
+        let dir = std::env::temp_dir().join("test_full_scan_finds_bdmv_folders");
+        let _ = std::fs::remove_dir_all(&dir);
+        let stream_dir = dir.join("ABC-123").join("BDMV").join("STREAM");
+        std::fs::create_dir_all(&stream_dir).unwrap();
+        std::fs::write(dir.join("ABC-123").join("BDMV").join("index.bdmv"), b"").unwrap();
+        std::fs::write(stream_dir.join("00000.m2ts"), b"").unwrap();
+        // 没有 index.bdmv 的目录按普通目录扫描
+        std::fs::create_dir_all(dir.join("DEF-456").join("BDMV")).unwrap();
+        std::fs::write(dir.join("DEF-456").join("BDMV").join("00001.m2ts"), b"").unwrap();
+        std::fs::write(dir.join("GHI-789.strm"), b"http://example.com/GHI-789.mp4").unwrap();
+
+        let found = scan(&dir, &["m2ts", "strm"], ScanExclusions::default()).await;
+
+        assert_eq!(
+            found,
+            vec![
+                PathBuf::from("ABC-123"),
+                PathBuf::from("DEF-456").join("BDMV").join("00001.m2ts"),
+                PathBuf::from("GHI-789.strm"),
+            ]
+        );
+        assert_eq!(MediaItemKind::of(&dir.join("ABC-123")), MediaItemKind::BdmvFolder);
+        assert_eq!(MediaItemKind::of(&dir.join("GHI-789.strm")), MediaItemKind::Strm);
+        assert_eq!(
+            find_bdmv_folder(&stream_dir.join("00000.m2ts")),
+            Some(dir.join("ABC-123").as_path())
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_full_scan_prunes_excluded_dirs() {
+        let dir = std::env::temp_dir().join("test_full_scan_prunes_excluded_dirs");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        // 每层都有一个视频、一个缩略图目录与一个回收站目录
+        let mut expected = Vec::new();
+        let mut level = dir.clone();
+        for depth in 0..12 {
+            std::fs::create_dir_all(level.join("@eaDir").join(format!("SKIP-{:03}.mp4", depth)))
+                .unwrap();
+            std::fs::write(level.join("@eaDir").join("thumb.mp4"), b"").unwrap();
+            std::fs::create_dir_all(level.join("#Recycle")).unwrap();
+            std::fs::write(level.join("#Recycle").join("deleted.mp4"), b"").unwrap();
+            let video = level.join(format!("DEEP-{:03}.mp4", depth));
+            std::fs::write(&video, b"").unwrap();
+            expected.push(video.strip_prefix(&dir).unwrap().to_path_buf());
+            level = level.join(format!("level{}", depth));
+        }
+        std::fs::create_dir_all(&level).unwrap();
+        // 位于输入目录中的输出目录
+        let output_dir = dir.join("level0").join("output");
+        std::fs::create_dir_all(output_dir.join("DONE-001")).unwrap();
+        std::fs::write(output_dir.join("DONE-001").join("DONE-001.mp4"), b"").unwrap();
+        expected.sort();
+
+        let patterns = ["@eaDir".to_string(), "#recycle".to_string()];
+        let exclusions = ScanExclusions::new(&dir, &patterns, [output_dir.as_path()]);
+        assert!(exclusions.contains(&dir, &output_dir.join("DONE-001").join("DONE-001.mp4")));
+        assert!(exclusions.contains(&dir, &dir.join("level0").join("@eaDir").join("thumb.mp4")));
+        assert!(!exclusions.contains(&dir, &dir.join("level0").join("DEEP-001.mp4")));
+
+        let found = scan(&dir, &["mp4"], exclusions).await;
+        assert_eq!(found, expected);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_full_scan_skips_unreadable_dirs() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join("test_full_scan_skips_unreadable_dirs");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("locked")).unwrap();
+        std::fs::create_dir_all(dir.join("open")).unwrap();
+        std::fs::write(dir.join("locked").join("LOCK-001.mp4"), b"").unwrap();
+        std::fs::write(dir.join("open").join("OPEN-001.mp4"), b"").unwrap();
+        std::fs::set_permissions(dir.join("locked"), std::fs::Permissions::from_mode(0o000))
+            .unwrap();
+        // root 用户不受权限限制，仍能读取该目录
+        let locked_readable = std::fs::read_dir(dir.join("locked")).is_ok();
+
+        let found = scan(&dir, &["mp4"], ScanExclusions::default()).await;
+
+        std::fs::set_permissions(dir.join("locked"), std::fs::Permissions::from_mode(0o755))
+            .unwrap();
+        let mut expected = vec![PathBuf::from("open").join("OPEN-001.mp4")];
+        if locked_readable {
+            expected.insert(0, PathBuf::from("locked").join("LOCK-001.mp4"));
+        }
+        assert_eq!(found, expected);
+
+        // 输入目录本身无法读取时返回错误
+        let (tx, _rx) = mpsc::channel(1);
+        let result = full_scan(
+            dir.join("missing"),
+            tx,
+            Arc::new(HashSet::new()),
+            Arc::new(ScanExclusions::default()),
+            ProgressBar::hidden(),
+        )
+        .await;
+        assert!(result.is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
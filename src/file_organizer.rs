@@ -1,9 +1,15 @@
-use crate::config::AppConfig;
+use crate::config::{AppConfig, NfoFilenameStyle};
+use crate::file::{sanitize_file_name, MediaItemKind};
 use crate::nfo::MovieNfo;
+use crate::output_router::{route_output, OutputTarget};
 use crate::template_parser::{TemplateParser, MultiActorStrategy};
+use std::ffi::{OsStr, OsString};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// `movie` 风格的NFO文件名
+const MOVIE_NFO_FILENAME: &str = "movie.nfo";
+
 /// 文件整理器
 ///
 /// 负责将处理完成的视频文件移动到输出目录并重命名
@@ -21,6 +27,7 @@ impl FileOrganizer {
     /// - `original_file_path`: 原始视频文件路径
     /// - `nfo`: NFO数据，用于生成新文件名和目录结构
     /// - `config`: 应用配置
+    /// - `suffix_tags`: 从原始文件名识别的后缀标签，用于 `$suffix_tags$` 变量
     ///
     /// # 返回
     /// 成功时返回新的文件路径和NFO文件路径，失败时返回错误
@@ -30,10 +37,19 @@ impl FileOrganizer {
         original_file_path: &Path,
         nfo: &MovieNfo,
         config: &AppConfig,
+        suffix_tags: &[String],
     ) -> anyhow::Result<(PathBuf, PathBuf)> {
         // 为媒体中心生成标准目录结构
-        let (movie_dir, video_filename, nfo_filename) =
-            self.generate_media_center_structure(original_file_path, nfo, config)?;
+        let target = route_output(nfo, config)?;
+        let (movie_dir, video_filename, _) =
+            self.generate_media_center_structure(original_file_path, nfo, config, &target, suffix_tags)?;
+
+        // 避免与清理后同名的其他影片共用目录
+        let movie_id = original_file_path
+            .file_name()
+            .map(|name| self.extract_identifier_from_filename(&name.to_string_lossy()))
+            .unwrap_or_default();
+        let movie_dir = self.ensure_unique_movie_dir(&movie_dir, nfo, &movie_id)?;
 
         // 确保影片目录存在
         fs::create_dir_all(&movie_dir)?;
@@ -41,20 +57,10 @@ impl FileOrganizer {
 
         // 生成最终文件路径
         let final_video_path = movie_dir.join(&video_filename);
-        let final_nfo_path = movie_dir.join(&nfo_filename);
 
-        // 处理文件名冲突
+        // 处理文件名冲突，NFO文件名跟随视频文件名
         let resolved_video_path = self.resolve_filename_conflict(&final_video_path)?;
-        let resolved_nfo_path = if resolved_video_path != final_video_path {
-            // 如果视频文件名被修改，同步修改NFO文件名
-            let video_stem = resolved_video_path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("movie");
-            movie_dir.join(format!("{}.nfo", video_stem))
-        } else {
-            final_nfo_path
-        };
+        let resolved_nfo_path = self.nfo_paths(&resolved_video_path, config).remove(0);
 
         // 移动视频文件
         self.move_file(original_file_path, &resolved_video_path)?;
@@ -78,27 +84,34 @@ impl FileOrganizer {
 
     /// 为媒体中心生成标准目录结构
     ///
-    /// 结构：输出目录/[系列名或影片ID (Year)]/影片名 (Year).扩展名
+    /// 结构：输出目录/[系列名或影片ID (Year)]/影片名 (Year).扩展名，输出目录和命名模板
+    /// 取自 `target`；蓝光原盘目录整体作为 输出目录/[系列名或影片ID (Year)] 目录
     fn generate_media_center_structure(
         &self,
         original_file_path: &Path,
         nfo: &MovieNfo,
         config: &AppConfig,
-    ) -> anyhow::Result<(PathBuf, String, String)> {
-        let output_dir = config.get_output_dir();
-
-        // 获取原文件的扩展名
-        let extension = original_file_path
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .ok_or_else(|| anyhow::anyhow!("无法获取文件扩展名"))?;
+        target: &OutputTarget,
+        suffix_tags: &[String],
+    ) -> anyhow::Result<(PathBuf, OsString, String)> {
+        let output_dir = &target.output_dir;
+        let is_bdmv = MediaItemKind::of(original_file_path) == MediaItemKind::BdmvFolder;
+
+        // 获取原文件的扩展名，保留原始字节以支持非 UTF-8 扩展名
+        let extension = match original_file_path.extension() {
+            Some(extension) => extension,
+            None if is_bdmv => OsStr::new(""),
+            None => return Err(anyhow::anyhow!("无法获取文件扩展名")),
+        };
 
         // 创建模板解析器并填充NFO数据
         let mut parser = TemplateParser::new();
         parser.populate_from_nfo(nfo)?;
+        parser.populate_suffix_tags(suffix_tags);
+        parser.populate_source(original_file_path);
 
-        // 从配置获取模板和策略
-        let template = config.get_file_naming_template();
+        // 从整理目标获取模板，从配置获取策略
+        let template = &target.naming_template;
         let strategy = MultiActorStrategy::from_string(config.get_multi_actor_strategy());
 
         // 解析模板获取路径结构
@@ -111,12 +124,51 @@ impl FileOrganizer {
         let path_parts: Vec<&str> = parse_result.primary_path.split('/').collect();
         let base_filename = path_parts.last().map_or("Unknown", |v| v);
         
-        let video_filename = format!("{}.{}", base_filename, extension);
-        let nfo_filename = format!("{}.nfo", base_filename);
+        let nfo_filename = match config.get_nfo_filename_style() {
+            NfoFilenameStyle::Movie => MOVIE_NFO_FILENAME.to_string(),
+            NfoFilenameStyle::Basename | NfoFilenameStyle::Both => format!("{}.nfo", base_filename),
+        };
+
+        // 蓝光原盘目录本身即为影片目录，目录名取文件名模板的最后一部分
+        if is_bdmv {
+            let parent_dir = movie_dir.parent().unwrap_or(output_dir).to_path_buf();
+            return Ok((parent_dir, OsString::from(base_filename), nfo_filename));
+        }
+
+        let video_filename = file_name_with_extension(base_filename, extension);
 
         Ok((movie_dir, video_filename, nfo_filename))
     }
 
+    /// 按配置的NFO文件名风格生成视频文件对应的NFO路径，第一个为主NFO
+    pub fn nfo_paths(&self, video_path: &Path, config: &AppConfig) -> Vec<PathBuf> {
+        let basename = video_path.with_extension("nfo");
+        let movie = video_path.with_file_name(MOVIE_NFO_FILENAME);
+
+        Self::select_nfo_paths(basename, movie, config)
+    }
+
+    /// 影片条目对应的NFO路径，蓝光原盘目录的NFO位于目录内并以目录名命名，第一个为主NFO
+    pub fn item_nfo_paths(&self, item_path: &Path, kind: MediaItemKind, config: &AppConfig) -> Vec<PathBuf> {
+        if kind != MediaItemKind::BdmvFolder {
+            return self.nfo_paths(item_path, config);
+        }
+
+        let dir_name = item_path.file_name().unwrap_or_default();
+        let basename = item_path.join(file_name_with_extension(dir_name, OsStr::new("nfo")));
+        let movie = item_path.join(MOVIE_NFO_FILENAME);
+
+        Self::select_nfo_paths(basename, movie, config)
+    }
+
+    fn select_nfo_paths(basename: PathBuf, movie: PathBuf, config: &AppConfig) -> Vec<PathBuf> {
+        match config.get_nfo_filename_style() {
+            NfoFilenameStyle::Basename => vec![basename],
+            NfoFilenameStyle::Movie => vec![movie],
+            NfoFilenameStyle::Both => vec![basename, movie],
+        }
+    }
+
     /// 处理多演员文件链接
     /// 
     /// 根据配置的多演员策略，为每个额外的演员创建链接
@@ -125,23 +177,25 @@ impl FileOrganizer {
         original_file_path: &Path,
         nfo: &MovieNfo,
         config: &AppConfig,
+        target: &OutputTarget,
+        suffix_tags: &[String],
         primary_video_path: &Path,
-        primary_nfo_path: &Path,
-    ) -> anyhow::Result<Vec<(PathBuf, PathBuf)>> {
+    ) -> anyhow::Result<Vec<(PathBuf, Vec<PathBuf>)>> {
         let mut additional_links = Vec::new();
         
-        // 获取原文件的扩展名
+        // 获取原文件的扩展名，保留原始字节以支持非 UTF-8 扩展名
         let extension = original_file_path
             .extension()
-            .and_then(|ext| ext.to_str())
             .ok_or_else(|| anyhow::anyhow!("无法获取文件扩展名"))?;
 
         // 创建模板解析器并填充NFO数据
         let mut parser = TemplateParser::new();
         parser.populate_from_nfo(nfo)?;
+        parser.populate_suffix_tags(suffix_tags);
+        parser.populate_source(original_file_path);
 
-        // 从配置获取模板和策略
-        let template = config.get_file_naming_template();
+        // 从整理目标获取模板，从配置获取策略
+        let template = &target.naming_template;
         let strategy = MultiActorStrategy::from_string(config.get_multi_actor_strategy());
 
         // 只有当策略为HardLink或SymLink时才处理额外链接
@@ -152,7 +206,7 @@ impl FileOrganizer {
         // 解析模板获取额外路径
         let parse_result = parser.parse_template(template, strategy.clone())?;
         
-        let output_dir = config.get_output_dir();
+        let output_dir = &target.output_dir;
         
         for additional_path in parse_result.additional_paths {
             // 构建额外演员的目录
@@ -162,8 +216,14 @@ impl FileOrganizer {
             let path_parts: Vec<&str> = additional_path.split('/').collect();
             let base_filename = path_parts.last().map_or("Unknown", |v| v);
             
-            let additional_video_path = additional_movie_dir.join(format!("{}.{}", base_filename, extension));
-            let additional_nfo_path = additional_movie_dir.join(format!("{}.nfo", base_filename));
+            let additional_video_path =
+                additional_movie_dir.join(file_name_with_extension(base_filename, extension));
+            // 按NFO文件名风格链接主目录中的每个NFO
+            let nfo_links: Vec<(PathBuf, PathBuf)> = self
+                .nfo_paths(primary_video_path, config)
+                .into_iter()
+                .zip(self.nfo_paths(&additional_video_path, config))
+                .collect();
             
             // 创建目录
             fs::create_dir_all(&additional_movie_dir)?;
@@ -177,26 +237,32 @@ impl FileOrganizer {
                         log::warn!("创建硬链接失败，回退到符号链接: {}", e);
                         self.create_symlink(primary_video_path, &additional_video_path)?;
                     }
-                    if let Err(e) = fs::hard_link(primary_nfo_path, &additional_nfo_path) {
-                        log::warn!("创建NFO硬链接失败，回退到符号链接: {}", e);
-                        self.create_symlink(primary_nfo_path, &additional_nfo_path)?;
+                    for (primary_nfo_path, additional_nfo_path) in &nfo_links {
+                        if let Err(e) = fs::hard_link(primary_nfo_path, additional_nfo_path) {
+                            log::warn!("创建NFO硬链接失败，回退到符号链接: {}", e);
+                            self.create_symlink(primary_nfo_path, additional_nfo_path)?;
+                        }
                     }
                 },
                 MultiActorStrategy::SymLink => {
                     // 创建符号链接
                     self.create_symlink(primary_video_path, &additional_video_path)?;
-                    self.create_symlink(primary_nfo_path, &additional_nfo_path)?;
+                    for (primary_nfo_path, additional_nfo_path) in &nfo_links {
+                        self.create_symlink(primary_nfo_path, additional_nfo_path)?;
+                    }
                 },
                 _ => unreachable!(), // 前面已经过滤了其他策略
             }
             
+            let additional_nfo_paths: Vec<PathBuf> =
+                nfo_links.into_iter().map(|(_, additional)| additional).collect();
             log::info!(
-                "创建多演员链接 - 视频: {}, NFO: {}",
+                "创建多演员链接 - 视频: {}, NFO: {:?}",
                 additional_video_path.display(),
-                additional_nfo_path.display()
+                additional_nfo_paths
             );
             
-            additional_links.push((additional_video_path, additional_nfo_path));
+            additional_links.push((additional_video_path, additional_nfo_paths));
         }
         
         Ok(additional_links)
@@ -223,28 +289,25 @@ impl FileOrganizer {
         original_file_path: &Path,
         nfo: &MovieNfo,
         config: &AppConfig,
+        suffix_tags: &[String],
     ) -> anyhow::Result<PathBuf> {
+        let target = route_output(nfo, config)?;
         let (movie_dir, video_filename, _) =
-            self.generate_media_center_structure(original_file_path, nfo, config)?;
+            self.generate_media_center_structure(original_file_path, nfo, config, &target, suffix_tags)?;
         Ok(movie_dir.join(video_filename))
     }
 
-    /// 解决文件名冲突
-    #[allow(dead_code)] // 预留给未来的文件冲突处理功能
-    fn resolve_filename_conflict(&self, file_path: &Path) -> anyhow::Result<PathBuf> {
+    /// 解决文件名冲突，文件已存在时追加 ` (1)` 等序号
+    pub fn resolve_filename_conflict(&self, file_path: &Path) -> anyhow::Result<PathBuf> {
         if !file_path.exists() {
             return Ok(file_path.to_path_buf());
         }
 
         let file_stem = file_path
             .file_stem()
-            .and_then(|s| s.to_str())
             .ok_or_else(|| anyhow::anyhow!("无法获取文件名"))?;
 
-        let extension = file_path
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .unwrap_or("");
+        let extension = file_path.extension();
 
         let parent = file_path
             .parent()
@@ -252,11 +315,12 @@ impl FileOrganizer {
 
         // 尝试添加序号后缀
         for i in 1..=999 {
-            let new_filename = if extension.is_empty() {
-                format!("{} ({})", file_stem, i)
-            } else {
-                format!("{} ({}).{}", file_stem, i, extension)
-            };
+            let mut new_filename = file_stem.to_os_string();
+            new_filename.push(format!(" ({})", i));
+            if let Some(extension) = extension {
+                new_filename.push(".");
+                new_filename.push(extension);
+            }
 
             let new_path = parent.join(new_filename);
             if !new_path.exists() {
@@ -316,7 +380,6 @@ impl FileOrganizer {
         
         let target_stem = target_video_path
             .file_stem()
-            .and_then(|s| s.to_str())
             .ok_or_else(|| anyhow::anyhow!("无法获取目标文件名"))?;
 
         // 遍历输入目录查找匹配的字幕文件
@@ -330,14 +393,15 @@ impl FileOrganizer {
                 continue;
             }
             
-            let file_name = match path.file_name().and_then(|s| s.to_str()) {
-                Some(name) => name,
+            // 非 UTF-8 文件名替换无效字符后用于匹配
+            let file_name = match path.file_name() {
+                Some(name) => name.to_string_lossy(),
                 None => continue,
             };
             
             // 检查是否为字幕文件
-            let extension = match path.extension().and_then(|s| s.to_str()) {
-                Some(ext) => ext.to_lowercase(),
+            let extension = match path.extension() {
+                Some(ext) => ext.to_string_lossy().to_lowercase(),
                 None => continue,
             };
             
@@ -346,7 +410,7 @@ impl FileOrganizer {
             }
             
             // 从字幕文件名中提取标识符并标准化
-            let subtitle_identifier = self.extract_identifier_from_filename(file_name);
+            let subtitle_identifier = self.extract_identifier_from_filename(&file_name);
             let normalized_subtitle_id = self.normalize_identifier(&subtitle_identifier);
             
             log::debug!(
@@ -358,7 +422,10 @@ impl FileOrganizer {
             if normalized_subtitle_id == normalized_movie_id {
                 // 生成目标字幕文件路径，包含语言标识
                 let subtitle_language = config.get_subtitle_language();
-                let target_subtitle_path = target_dir.join(format!("{}.{}.{}", target_stem, subtitle_language, extension));
+                let target_subtitle_path = target_dir.join(file_name_with_extension(
+                    target_stem,
+                    OsStr::new(&format!("{}.{}", subtitle_language, extension)),
+                ));
                 
                 // 移动字幕文件
                 if let Err(e) = fs::rename(&path, &target_subtitle_path) {
@@ -423,12 +490,10 @@ impl FileOrganizer {
 
         let original_stem = original_video_path
             .file_stem()
-            .and_then(|s| s.to_str())
             .ok_or_else(|| anyhow::anyhow!("无法获取原始文件名"))?;
 
         let new_stem = new_video_path
             .file_stem()
-            .and_then(|s| s.to_str())
             .ok_or_else(|| anyhow::anyhow!("无法获取新文件名"))?;
 
         let original_dir = original_video_path
@@ -441,9 +506,9 @@ impl FileOrganizer {
 
         // 查找并移动字幕文件
         for ext in &subtitle_extensions {
-            let subtitle_path = original_dir.join(format!("{}.{}", original_stem, ext));
+            let subtitle_path = original_dir.join(file_name_with_extension(original_stem, OsStr::new(ext)));
             if subtitle_path.exists() {
-                let new_subtitle_path = new_dir.join(format!("{}.{}", new_stem, ext));
+                let new_subtitle_path = new_dir.join(file_name_with_extension(new_stem, OsStr::new(ext)));
 
                 // 解决字幕文件的文件名冲突
                 let final_subtitle_path = self.resolve_filename_conflict(&new_subtitle_path)?;
@@ -457,35 +522,18 @@ impl FileOrganizer {
     }
 
     /// 清理文件名中的非法字符
-    #[allow(dead_code)]
     fn sanitize_filename(&self, filename: &str) -> String {
-        // 移除或替换文件名中的非法字符
-        let illegal_chars = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
-        let mut sanitized = filename.to_string();
-
-        for char in illegal_chars {
-            sanitized = sanitized.replace(char, "");
-        }
-
-        // 移除多余的空格
-        sanitized = sanitized.split_whitespace().collect::<Vec<_>>().join(" ");
-
-        // 限制长度以避免路径过长
-        if sanitized.len() > 100 {
-            sanitized.truncate(100);
-            sanitized = sanitized.trim_end().to_string();
-        }
-
-        sanitized
+        sanitize_file_name(filename)
     }
 
-    /// 检查文件是否需要整理（已经在输出目录中）
+    /// 检查文件是否需要整理（已经在全局或任一路由规则的输出目录中）
     pub fn needs_organization(&self, file_path: &Path, config: &AppConfig) -> bool {
-        let output_dir = config.get_output_dir();
+        let mut output_dirs = std::iter::once(config.get_output_dir())
+            .chain(config.output_routes.iter().map(|route| route.output_dir.as_path()));
 
         // 检查文件是否已经在输出目录中
         match file_path.parent() {
-            Some(parent) => parent != output_dir,
+            Some(parent) => !output_dirs.any(|output_dir| parent == output_dir),
             None => true, // 如果无法获取父目录，假设需要整理
         }
     }
@@ -497,9 +545,11 @@ impl FileOrganizer {
         original_file_path: &Path,
         nfo: &MovieNfo,
         config: &AppConfig,
+        suffix_tags: &[String],
     ) -> anyhow::Result<PathBuf> {
+        let target = route_output(nfo, config)?;
         let (movie_dir, _, nfo_filename) =
-            self.generate_media_center_structure(original_file_path, nfo, config)?;
+            self.generate_media_center_structure(original_file_path, nfo, config, &target, suffix_tags)?;
         Ok(movie_dir.join(nfo_filename))
     }
 
@@ -510,8 +560,9 @@ impl FileOrganizer {
         original_file_path: &Path,
         nfo: &MovieNfo,
         config: &AppConfig,
+        suffix_tags: &[String],
     ) -> anyhow::Result<PathBuf> {
-        self.generate_new_file_path(original_file_path, nfo, config)
+        self.generate_new_file_path(original_file_path, nfo, config, suffix_tags)
     }
 
     /// 预览媒体中心结构
@@ -520,13 +571,201 @@ impl FileOrganizer {
         original_file_path: &Path,
         nfo: &MovieNfo,
         config: &AppConfig,
+        target: &OutputTarget,
+        suffix_tags: &[String],
     ) -> anyhow::Result<(PathBuf, PathBuf)> {
         let (movie_dir, video_filename, nfo_filename) =
-            self.generate_media_center_structure(original_file_path, nfo, config)?;
+            self.generate_media_center_structure(original_file_path, nfo, config, target, suffix_tags)?;
         let video_path = movie_dir.join(video_filename);
-        let nfo_path = movie_dir.join(nfo_filename);
+        let nfo_path = match MediaItemKind::of(original_file_path) {
+            MediaItemKind::BdmvFolder => video_path.join(nfo_filename),
+            _ => movie_dir.join(nfo_filename),
+        };
         Ok((video_path, nfo_path))
     }
+
+    /// 确保影片目录不与其他影片混用
+    ///
+    /// 不同影片的标题清理非法字符后可能得到相同的目录名。目标目录已存在且其中的
+    /// NFO 都不属于当前影片时，改用追加影片ID的目录名，而不是合并到已有目录中
+    pub fn ensure_unique_movie_dir(
+        &self,
+        movie_dir: &Path,
+        nfo: &MovieNfo,
+        movie_id: &str,
+    ) -> anyhow::Result<PathBuf> {
+        if !self.is_occupied_by_other_movie(movie_dir, nfo, movie_id)? {
+            return Ok(movie_dir.to_path_buf());
+        }
+
+        let dir_name = movie_dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| anyhow::anyhow!("无法获取影片目录名: {}", movie_dir.display()))?;
+        let unique_dir = movie_dir.with_file_name(format!(
+            "{} [{}]",
+            dir_name,
+            self.sanitize_filename(movie_id)
+        ));
+
+        if self.is_occupied_by_other_movie(&unique_dir, nfo, movie_id)? {
+            return Err(anyhow::anyhow!(
+                "影片目录已被其他影片占用: {}",
+                unique_dir.display()
+            ));
+        }
+
+        log::warn!(
+            "影片目录 {} 已属于其他影片，改用 {}",
+            movie_dir.display(),
+            unique_dir.display()
+        );
+
+        Ok(unique_dir)
+    }
+
+    /// 规划已整理影片移动到新路径所需的文件移动，按顺序执行
+    ///
+    /// 影片目录中只有这一个视频且新目录不存在时整体重命名目录（NFO、图片、字幕随目录移动），
+    /// 否则只移动文件名以视频文件名开头的文件；文件名中的视频文件名部分替换为新的文件名
+    pub fn plan_movie_move(
+        &self,
+        video_path: &Path,
+        new_video_path: &Path,
+        config: &AppConfig,
+    ) -> anyhow::Result<Vec<(PathBuf, PathBuf)>> {
+        let (Some(old_dir), Some(new_dir)) = (video_path.parent(), new_video_path.parent()) else {
+            return Err(anyhow::anyhow!("无法获取影片目录: {}", video_path.display()));
+        };
+        let (Some(old_stem), Some(new_stem)) = (
+            video_path.file_stem().and_then(|stem| stem.to_str()),
+            new_video_path.file_stem().and_then(|stem| stem.to_str()),
+        ) else {
+            return Err(anyhow::anyhow!("视频文件名不是有效的 UTF-8: {}", video_path.display()));
+        };
+
+        let migrate_files = config.get_migrate_files_ext();
+        let mut entries: Vec<PathBuf> = fs::read_dir(old_dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<_, _>>()?;
+        entries.sort();
+        let video_count = entries
+            .iter()
+            .filter(|path| path.is_file())
+            .filter(|path| {
+                path.extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| migrate_files.contains(&ext.to_lowercase()))
+            })
+            .count();
+
+        let mut moves = Vec::new();
+        let rename_dir = old_dir != new_dir
+            && video_count == 1
+            && !new_dir.exists()
+            && !new_dir.starts_with(old_dir);
+        let current_dir = if rename_dir {
+            moves.push((old_dir.to_path_buf(), new_dir.to_path_buf()));
+            new_dir
+        } else {
+            old_dir
+        };
+
+        for entry in &entries {
+            let Some(name) = entry.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            // 只匹配 `文件名.扩展名` 与 `文件名-poster.jpg` 形式，避免误移动 `文件名 2.mp4`
+            let Some(rest) = name
+                .strip_prefix(old_stem)
+                .filter(|rest| rest.starts_with('.') || rest.starts_with('-'))
+            else {
+                continue;
+            };
+            let from = current_dir.join(name);
+            let to = new_dir.join(format!("{}{}", new_stem, rest));
+            if from == to {
+                continue;
+            }
+            if to.exists() || (rename_dir && old_dir.join(format!("{}{}", new_stem, rest)).exists()) {
+                return Err(anyhow::anyhow!("目标文件已存在: {}", to.display()));
+            }
+            moves.push((from, to));
+        }
+
+        Ok(moves)
+    }
+
+    /// 检查目录中是否已有其他影片的NFO
+    fn is_occupied_by_other_movie(
+        &self,
+        movie_dir: &Path,
+        nfo: &MovieNfo,
+        movie_id: &str,
+    ) -> anyhow::Result<bool> {
+        if !movie_dir.is_dir() {
+            return Ok(false);
+        }
+
+        let mut occupied = false;
+
+        for entry in fs::read_dir(movie_dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("nfo") {
+                continue;
+            }
+
+            let existing = match MovieNfo::read_from(&path) {
+                Ok(existing) => existing,
+                Err(e) => {
+                    log::warn!("无法解析已有NFO文件: {}", e);
+                    continue;
+                }
+            };
+
+            if self.is_same_movie(&existing, nfo, movie_id) {
+                return Ok(false);
+            }
+            occupied = true;
+        }
+
+        Ok(occupied)
+    }
+
+    /// 根据唯一标识符判断是否为同一影片，已有NFO的标识符与新NFO的标识符或番号相同即为同一影片，
+    /// 已有NFO没有标识符时比较标题
+    fn is_same_movie(&self, existing: &MovieNfo, nfo: &MovieNfo, movie_id: &str) -> bool {
+        let existing_ids = self.normalized_ids(existing);
+        if existing_ids.is_empty() {
+            return existing.title == nfo.title;
+        }
+
+        let movie_id = self.normalize_identifier(movie_id);
+        existing_ids.contains(&movie_id)
+            || self
+                .normalized_ids(nfo)
+                .iter()
+                .any(|id| existing_ids.contains(id))
+    }
+
+    /// NFO中所有非空标识符的规范化形式
+    fn normalized_ids(&self, nfo: &MovieNfo) -> Vec<String> {
+        nfo.unique_ids
+            .iter()
+            .map(|id| id.value.as_str())
+            .chain(std::iter::once(nfo.imdb_id.as_str()))
+            .filter(|id| !id.is_empty())
+            .map(|id| self.normalize_identifier(id))
+            .collect()
+    }
+}
+
+/// 拼接文件名与扩展名，保留两者的原始字节
+fn file_name_with_extension(stem: impl AsRef<OsStr>, extension: &OsStr) -> OsString {
+    let mut file_name = stem.as_ref().to_os_string();
+    file_name.push(".");
+    file_name.push(extension);
+    file_name
 }
 
 impl Default for FileOrganizer {
@@ -610,7 +849,8 @@ subtitle_language = "zh-CN"
         let nfo = create_test_nfo();
 
         let original_path = Path::new("./test_input/IPX-001.mp4");
-        let result = organizer.preview_media_center_structure(original_path, &nfo, &config);
+        let target = OutputTarget::from_config(&config);
+        let result = organizer.preview_media_center_structure(original_path, &nfo, &config, &target, &[]);
 
         assert!(result.is_ok());
         let (video_path, nfo_path) = result.unwrap();
@@ -770,4 +1010,254 @@ maximum_fetch_count = 3
         // 清理
         let _ = fs::remove_file(&config_path);
     }
+
+    #[test]
+    fn test_ensure_unique_movie_dir_for_colliding_titles() {
+        use crate::nfo::{NfoFormatter, UniqueId};
+
+        let organizer = FileOrganizer::new();
+        let temp_dir = env::temp_dir().join("test_unique_movie_dir");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let input_dir = temp_dir.join("input");
+        let output_dir = temp_dir.join("output");
+        fs::create_dir_all(&input_dir).unwrap();
+
+        let config_path = temp_dir.join("config.toml");
+        fs::write(
+            &config_path,
+            format!(
+                r#"
+migrate_files = ["mp4"]
+migrate_subtitles = false
+ignored_id_pattern = []
+capital = false
+input_dir = "{}"
+output_dir = "{}"
+thread_limit = 1
+template_priority = ["javdb.yaml"]
+"#,
+                input_dir.display(),
+                output_dir.display()
+            ),
+        )
+        .unwrap();
+        let config = AppConfig::new(&config_path).unwrap();
+
+        let create_nfo = |id: &str, title: &str| MovieNfo {
+            title: title.to_string(),
+            year: Some(2023),
+            unique_ids: vec![UniqueId {
+                id_type: "javdb".to_string(),
+                default: Some(true),
+                value: id.to_string(),
+            }],
+            ..Default::default()
+        };
+
+        // 两部影片的标题清理非法字符后得到相同的目录名
+        let first_nfo = create_nfo("ABC-001", "测试电影?");
+        let second_nfo = create_nfo("ABC-002", "测试电影*");
+
+        let first_file = input_dir.join("ABC-001.mp4");
+        fs::write(&first_file, "video").unwrap();
+        let (first_video, first_nfo_path) = organizer.organize_file(&first_file, &first_nfo, &config, &[]).unwrap();
+        fs::write(&first_nfo_path, first_nfo.format_to_xml()).unwrap();
+
+        let second_file = input_dir.join("ABC-002.mp4");
+        fs::write(&second_file, "video").unwrap();
+        let (second_video, _) = organizer.organize_file(&second_file, &second_nfo, &config, &[]).unwrap();
+
+        let first_dir = first_video.parent().unwrap();
+        let second_dir = second_video.parent().unwrap();
+        assert_ne!(first_dir, second_dir);
+        assert_eq!(
+            second_dir.file_name().unwrap().to_str().unwrap(),
+            format!("{} [ABC-002]", first_dir.file_name().unwrap().to_str().unwrap())
+        );
+
+        // 同一影片再次整理时继续使用原目录
+        let unique_dir = organizer
+            .ensure_unique_movie_dir(first_dir, &first_nfo, "abc001")
+            .unwrap();
+        assert_eq!(unique_dir, first_dir);
+
+        // 模板填写站点ID时标识符与番号不同，与新NFO的标识符相同仍视为同一影片
+        let site_nfo = create_nfo("site-9876", "站点电影");
+        let site_file = input_dir.join("ABC-003.mp4");
+        fs::write(&site_file, "video").unwrap();
+        let (site_video, site_nfo_path) = organizer.organize_file(&site_file, &site_nfo, &config, &[]).unwrap();
+        fs::write(&site_nfo_path, site_nfo.format_to_xml()).unwrap();
+        let site_dir = site_video.parent().unwrap();
+        let unique_dir = organizer
+            .ensure_unique_movie_dir(site_dir, &site_nfo, "abc003")
+            .unwrap();
+        assert_eq!(unique_dir, site_dir);
+        let other_nfo = create_nfo("site-1234", "站点电影");
+        let unique_dir = organizer
+            .ensure_unique_movie_dir(site_dir, &other_nfo, "abc004")
+            .unwrap();
+        assert_ne!(unique_dir, site_dir);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_nfo_filename_styles() {
+        use crate::crawler::FileProcessingTransaction;
+        use crate::nfo::{Actor, NfoFormatter};
+        use std::collections::BTreeSet;
+
+        let organizer = FileOrganizer::new();
+        let temp_dir = env::temp_dir().join("test_nfo_filename_styles");
+        let _ = fs::remove_dir_all(&temp_dir);
+
+        let nfo = MovieNfo {
+            title: "测试电影".to_string(),
+            year: Some(2023),
+            actors: ["演员A", "演员B"]
+                .iter()
+                .map(|name| Actor {
+                    name: name.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        let list_dir = |dir: &Path| -> BTreeSet<String> {
+            fs::read_dir(dir)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+                .collect()
+        };
+
+        for (style, expected_nfos) in [
+            ("basename", vec!["测试电影 (2023).nfo"]),
+            ("movie", vec!["movie.nfo"]),
+            ("both", vec!["测试电影 (2023).nfo", "movie.nfo"]),
+        ] {
+            let style_dir = temp_dir.join(style);
+            let input_dir = style_dir.join("input");
+            let output_dir = style_dir.join("output");
+            fs::create_dir_all(&input_dir).unwrap();
+
+            let config_path = style_dir.join("config.toml");
+            fs::write(
+                &config_path,
+                format!(
+                    r#"
+migrate_files = ["mp4"]
+migrate_subtitles = false
+ignored_id_pattern = []
+input_dir = "{}"
+output_dir = "{}"
+thread_limit = 1
+template_priority = ["javdb.yaml"]
+
+[naming]
+template = "$actor$/$title$ ($year$)"
+multi_actor_strategy = "symlink"
+capital = false
+nfo_filename_style = "{}"
+"#,
+                    input_dir.display(),
+                    output_dir.display(),
+                    style
+                ),
+            )
+            .unwrap();
+            let config = AppConfig::new(&config_path).unwrap();
+
+            let original_file = input_dir.join("ABC-001.mp4");
+            fs::write(&original_file, "video").unwrap();
+            let target = OutputTarget::from_config(&config);
+            let (video_path, nfo_path) = organizer
+                .preview_media_center_structure(&original_file, &nfo, &config, &target, &[])
+                .unwrap();
+
+            let nfo_paths = organizer.nfo_paths(&video_path, &config);
+            assert_eq!(nfo_paths[0], nfo_path);
+
+            let mut transaction = FileProcessingTransaction::new(&original_file);
+            transaction.add_file_move(original_file.clone(), video_path.clone());
+            for path in &nfo_paths {
+                transaction.add_nfo_creation(path.clone(), nfo.format_to_xml());
+            }
+            transaction.commit().unwrap();
+
+            let mut expected: BTreeSet<String> =
+                expected_nfos.iter().map(|name| name.to_string()).collect();
+            expected.insert("测试电影 (2023).mp4".to_string());
+            assert_eq!(list_dir(video_path.parent().unwrap()), expected, "style: {}", style);
+
+            // 额外演员目录中链接与主目录相同的NFO文件
+            let links = organizer
+                .handle_multi_actor_links(&original_file, &nfo, &config, &target, &[], &video_path)
+                .unwrap();
+            assert_eq!(links.len(), 1);
+            assert_eq!(links[0].1.len(), expected_nfos.len());
+            assert_eq!(list_dir(links[0].0.parent().unwrap()), expected, "style: {}", style);
+        }
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_organize_non_utf8_file() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let organizer = FileOrganizer::new();
+        let temp_dir = env::temp_dir().join("test_organize_non_utf8_file");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let input_dir = temp_dir.join("input");
+        let output_dir = temp_dir.join("output");
+        fs::create_dir_all(&input_dir).unwrap();
+
+        let config_path = temp_dir.join("config.toml");
+        fs::write(
+            &config_path,
+            format!(
+                r#"
+migrate_files = ["mp4"]
+migrate_subtitles = false
+ignored_id_pattern = []
+capital = false
+input_dir = "{}"
+output_dir = "{}"
+thread_limit = 1
+template_priority = ["javdb.yaml"]
+"#,
+                input_dir.display(),
+                output_dir.display()
+            ),
+        )
+        .unwrap();
+        let config = AppConfig::new(&config_path).unwrap();
+        let nfo = create_test_nfo();
+
+        // 文件名中的无效字节不影响整理，扩展名的原始字节被保留
+        let original_file = input_dir.join(OsStr::from_bytes(b"ABC-001 \xff.mp\xfe"));
+        fs::write(&original_file, "video").unwrap();
+        let (video_path, nfo_path) = organizer.organize_file(&original_file, &nfo, &config, &[]).unwrap();
+
+        assert!(!original_file.exists());
+        assert!(video_path.exists());
+        assert_eq!(
+            video_path.file_name().unwrap().as_bytes(),
+            "测试电影 (2023).mp".bytes().chain([0xfe]).collect::<Vec<u8>>()
+        );
+        assert_eq!(nfo_path, video_path.with_extension("nfo"));
+
+        // 再次整理到同名文件时追加序号
+        let conflict_file = input_dir.join(OsStr::from_bytes(b"ABC-001.mp\xfe"));
+        fs::write(&conflict_file, "video").unwrap();
+        let (conflict_video, _) = organizer.organize_file(&conflict_file, &nfo, &config, &[]).unwrap();
+        assert_eq!(
+            conflict_video.file_name().unwrap().as_bytes(),
+            "测试电影 (2023) (1).mp".bytes().chain([0xfe]).collect::<Vec<u8>>()
+        );
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
 }
@@ -0,0 +1,158 @@
+use std::{
+    collections::HashSet,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+    time::SystemTime,
+};
+
+use anyhow::Context;
+
+use crate::{config::AppConfig, matcher::normalize};
+
+/// 不整理的影片ID列表：配置中的 `ignored_ids` 与 `ignored_ids_file` 文件中的ID
+///
+/// 影片ID规范化后比较（只保留字母数字并转为小写），`ABP-123` 与 `abp123` 视为同一影片。
+/// 列表文件修改后在下一次匹配时自动重新加载
+#[derive(Debug, Default)]
+pub struct IgnoredIds {
+    ids: HashSet<String>,
+    file: Option<PathBuf>,
+    /// 列表文件的修改时间与其中的ID
+    loaded: Mutex<(Option<SystemTime>, HashSet<String>)>,
+}
+
+impl IgnoredIds {
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            ids: config
+                .ignored_ids
+                .iter()
+                .map(|id| normalize(id))
+                .filter(|id| !id.is_empty())
+                .collect(),
+            file: config.ignored_ids_file.clone(),
+            loaded: Mutex::default(),
+        }
+    }
+
+    /// 影片ID是否在忽略列表中
+    pub fn contains(&self, movie_id: &str) -> bool {
+        let normalized = normalize(movie_id);
+        if normalized.is_empty() {
+            return false;
+        }
+        if self.ids.contains(&normalized) {
+            return true;
+        }
+
+        let Some(file) = &self.file else {
+            return false;
+        };
+        let mut loaded = self.loaded.lock().unwrap_or_else(PoisonError::into_inner);
+        let modified = std::fs::metadata(file).and_then(|meta| meta.modified()).ok();
+        if modified != loaded.0 {
+            let ids = match read_ids_file(file) {
+                Ok(ids) => {
+                    log::info!("加载忽略影片ID列表 {}: {} 个ID", file.display(), ids.len());
+                    ids
+                }
+                Err(e) => {
+                    if modified.is_some() {
+                        log::warn!("读取忽略影片ID列表失败: {}", e);
+                    }
+                    HashSet::new()
+                }
+            };
+            *loaded = (modified, ids);
+        }
+        loaded.1.contains(&normalized)
+    }
+
+    /// 把影片ID追加到列表文件，未配置列表文件或ID已在列表中时不做任何操作
+    pub fn remember(&self, movie_id: &str) -> anyhow::Result<()> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        if self.contains(movie_id) {
+            return Ok(());
+        }
+
+        if let Some(parent) = file.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut list = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(file)
+            .with_context(|| format!("打开忽略影片ID列表失败: {}", file.display()))?;
+        writeln!(list, "{}", movie_id)?;
+        log::info!("影片 {} 已加入忽略列表 {}", movie_id, file.display());
+        Ok(())
+    }
+}
+
+/// 读取列表文件，每行一个影片ID，忽略空行与 `#` 开头的注释行
+fn read_ids_file(path: &Path) -> anyhow::Result<HashSet<String>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("无法读取文件: {}", path.display()))?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(normalize)
+        .filter(|id| !id.is_empty())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ignored_ids(ids: &[&str], file: Option<PathBuf>) -> IgnoredIds {
+        IgnoredIds {
+            ids: ids.iter().map(|id| normalize(id)).collect(),
+            file,
+            loaded: Mutex::default(),
+        }
+    }
+
+    #[test]
+    fn test_ignored_ids_matching() {
+        let ignored = ignored_ids(&["ABP-123", "home_video_01"], None);
+
+        assert!(ignored.contains("ABP-123"));
+        assert!(ignored.contains("abp123"));
+        assert!(ignored.contains("HOME-VIDEO-01"));
+        assert!(!ignored.contains("ABP-1234"));
+        assert!(!ignored.contains("ABP-12"));
+        assert!(!ignored.contains("---"));
+    }
+
+    #[test]
+    fn test_ignored_ids_file() {
+        let test_dir = std::env::temp_dir().join("test_ignored_ids_file");
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).unwrap();
+        let file = test_dir.join("ignored.txt");
+        std::fs::write(&file, "# 家庭录像\nHOME-001\n\n  ssis-002  \n#ABP-003\n").unwrap();
+
+        let ignored = ignored_ids(&[], Some(file.clone()));
+        assert!(ignored.contains("HOME-001"));
+        assert!(ignored.contains("SSIS-002"));
+        assert!(!ignored.contains("ABP-003"));
+
+        // 追加的ID在下一次匹配时生效
+        ignored.remember("ABP-004").unwrap();
+        assert!(ignored.contains("abp-004"));
+        ignored.remember("ABP-004").unwrap();
+        let content = std::fs::read_to_string(&file).unwrap();
+        assert_eq!(content.matches("ABP-004").count(), 1);
+
+        // 列表文件不存在时不忽略任何影片
+        let missing = ignored_ids(&[], Some(test_dir.join("missing.txt")));
+        assert!(!missing.contains("HOME-001"));
+
+        std::fs::remove_dir_all(&test_dir).unwrap();
+    }
+}
@@ -1,11 +1,15 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
 use anyhow::{Context, Result};
-use reqwest::Client;
+use reqwest::{header, Client, StatusCode};
+use sha2::{Digest, Sha256};
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 
 use crate::nfo::MovieNfoCrawler;
 use crate::config::AppConfig;
+use crate::file::safe_write;
 
 /// 媒体中心图片类型
 #[derive(Debug, Clone)]
@@ -32,9 +36,16 @@ pub struct ImageNamingRule {
     pub description: String,
 }
 
+/// 图片去重存储在输出目录下的目录名
+pub const ARTWORK_CACHE_DIR: &str = ".artwork-cache";
+
 /// 图片管理器
 pub struct ImageManager {
     client: Client,
+    /// 按 ETag 重新验证已有图片，并续传中断的下载
+    smart_download: bool,
+    /// 图片去重存储，未启用去重时为 None
+    artwork_store: Option<ArtworkStore>,
 }
 
 impl ImageManager {
@@ -45,7 +56,23 @@ impl ImageManager {
             .build()
             .unwrap_or_default();
 
-        Self { client }
+        Self {
+            client,
+            smart_download: false,
+            artwork_store: None,
+        }
+    }
+
+    /// 根据配置创建图片管理器
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            smart_download: config.is_smart_image_download(),
+            artwork_store: config
+                .image
+                .dedupe
+                .then(|| ArtworkStore::open(config.get_output_dir().join(ARTWORK_CACHE_DIR))),
+            ..Self::new()
+        }
     }
 
     /// 获取 Emby/Jellyfin 图片命名规则
@@ -175,12 +202,70 @@ impl ImageManager {
         rules
     }
 
+    /// 目标路径已有有效图片时保留（如上次整理中断后重新处理），否则下载；返回是否下载了图片
+    ///
+    /// 启用智能下载时总是交给 `download_image` 按 ETag 重新验证
+    async fn download_missing_image(&self, url: &str, output_path: &Path) -> Result<bool> {
+        if !self.smart_download && self.is_image_valid(output_path).await {
+            log::info!("图片已存在，跳过下载: {}", output_path.display());
+            return Ok(false);
+        }
+        self.download_image(url, output_path).await?;
+        Ok(true)
+    }
+
+    /// 按顺序尝试候选 URL，返回第一个成功的 URL 与是否下载了图片；全部失败时返回最后一个错误
+    async fn download_first_available<'a>(&self, urls: &[&'a str], output_path: &Path) -> Result<(&'a str, bool)> {
+        let mut last_error = anyhow::anyhow!("没有可用的图片 URL");
+        for url in urls {
+            match self.download_missing_image(url, output_path).await {
+                Ok(downloaded) => return Ok((url, downloaded)),
+                Err(e) => {
+                    log::warn!("下载图片失败 {}: {}，尝试下一个候选 URL", url, e);
+                    last_error = e;
+                }
+            }
+        }
+        Err(last_error)
+    }
+
     /// 下载图片到指定路径
     pub async fn download_image(&self, url: &str, output_path: &Path) -> Result<()> {
         if url.is_empty() {
             return Err(anyhow::anyhow!("图片 URL 为空"));
         }
 
+        match &self.artwork_store {
+            Some(store) => self.download_deduplicated(store, url, output_path).await,
+            None => self.download_to(url, output_path).await,
+        }
+    }
+
+    /// 启用去重时下载图片：已下载过的 URL 直接链接存储中的文件，新图片按内容哈希
+    /// 存入存储后再链接到目标路径
+    async fn download_deduplicated(&self, store: &ArtworkStore, url: &str, output_path: &Path) -> Result<()> {
+        // 同时处理的影片可能共用同一封面，串行下载以免重复下载或写入同一个文件
+        let _guard = store.downloading.lock().await;
+        if let Some(stored) = store.lookup(url) {
+            log::info!("图片已在去重存储中，跳过下载: {}", url);
+            return link_or_copy(&stored, output_path);
+        }
+
+        // 按 URL 命名的下载文件，智能下载中断后可以续传
+        let staging_path = store.dir.join(format!("{}.download", sha256_hex(url.as_bytes())));
+        self.download_to(url, &staging_path).await?;
+        let _ = fs::remove_file(download_sidecar_path(&staging_path, url, "etag")).await;
+
+        let extension = output_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("jpg");
+        let stored = store.store(url, &staging_path, extension)?;
+        link_or_copy(&stored, output_path)
+    }
+
+    /// 直接下载图片到指定路径
+    async fn download_to(&self, url: &str, output_path: &Path) -> Result<()> {
         log::debug!("开始下载图片: {} -> {}", url, output_path.display());
 
         // 创建输出目录
@@ -189,31 +274,148 @@ impl ImageManager {
                 .with_context(|| format!("创建目录失败: {}", parent.display()))?;
         }
 
-        // 下载图片
-        let response = self.client.get(url)
-            .send()
-            .await
-            .with_context(|| format!("请求图片失败: {}", url))?;
+        let etag_path = download_sidecar_path(output_path, url, "etag");
+        let partial_path = download_sidecar_path(output_path, url, "partial");
+
+        // 智能下载：有未完成的下载时续传，否则按上次保存的 ETag/Last-Modified 重新验证
+        let mut resume_from = if self.smart_download {
+            fs::metadata(&partial_path).await.map(|m| m.len()).unwrap_or(0)
+        } else {
+            0
+        };
+        let response = loop {
+            let mut request = self.client.get(url);
+            if resume_from > 0 {
+                log::debug!("从 {} 字节处续传图片: {}", resume_from, url);
+                request = request.header(header::RANGE, format!("bytes={}-", resume_from));
+            } else if self.smart_download && output_path.exists() {
+                if let Ok(validators) = fs::read_to_string(&etag_path).await {
+                    let mut lines = validators.lines();
+                    if let Some(etag) = lines.next().filter(|etag| !etag.is_empty()) {
+                        request = request.header(header::IF_NONE_MATCH, etag);
+                    }
+                    if let Some(last_modified) = lines.next().filter(|value| !value.is_empty()) {
+                        request = request.header(header::IF_MODIFIED_SINCE, last_modified);
+                    }
+                }
+            }
+
+            // 下载图片
+            let response = request
+                .send()
+                .await
+                .with_context(|| format!("请求图片失败: {}", url))?;
+
+            // 返回的范围与请求的续传位置不一致时丢弃已下载部分，重新完整下载
+            if resume_from > 0
+                && response.status() == StatusCode::PARTIAL_CONTENT
+                && content_range_start(&response) != Some(resume_from)
+            {
+                log::warn!("图片续传返回的范围与请求不一致，重新下载: {}", url);
+                let _ = fs::remove_file(&partial_path).await;
+                resume_from = 0;
+                continue;
+            }
+            break response;
+        };
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            log::info!("图片未修改，跳过下载: {}", output_path.display());
+            return Ok(());
+        }
 
         if !response.status().is_success() {
+            // 续传失败（如 416）时丢弃已下载部分，下次重新下载
+            if resume_from > 0 {
+                let _ = fs::remove_file(&partial_path).await;
+            }
             return Err(anyhow::anyhow!("HTTP 错误: {}", response.status()));
         }
 
-        let bytes = response.bytes()
+        let append = resume_from > 0 && response.status() == StatusCode::PARTIAL_CONTENT;
+        let accept_ranges = response
+            .headers()
+            .get(header::ACCEPT_RANGES)
+            .is_some_and(|value| value.as_bytes().eq_ignore_ascii_case(b"bytes"));
+        let validators = [header::ETAG, header::LAST_MODIFIED].map(|name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string()
+        });
+
+        // 先流式写入临时文件，完成后再替换目标文件，避免中断时留下不完整的图片
+        // 智能下载时写入按 URL 区分的 `.partial`，服务器支持 Range 时中断后保留以便续传
+        let tmp_path = if self.smart_download {
+            partial_path
+        } else {
+            safe_write::temp_path(output_path)
+        };
+        let result = Self::stream_to_file(response, &tmp_path, url, append).await;
+        let size = match result {
+            Ok(size) => size,
+            Err(e) => {
+                if self.smart_download && (accept_ranges || append) {
+                    log::warn!("图片下载中断，保留已下载部分以便续传: {}", tmp_path.display());
+                } else {
+                    let _ = fs::remove_file(&tmp_path).await;
+                }
+                return Err(e);
+            }
+        };
+
+        let persist_result = {
+            let tmp_path = tmp_path.clone();
+            let output_path = output_path.to_path_buf();
+            tokio::task::spawn_blocking(move || safe_write::persist(&tmp_path, &output_path)).await?
+        };
+        if let Err(e) = persist_result {
+            let _ = fs::remove_file(&tmp_path).await;
+            return Err(e).with_context(|| format!("保存图片失败: {}", output_path.display()));
+        }
+
+        if self.smart_download {
+            if validators.iter().all(String::is_empty) {
+                let _ = fs::remove_file(&etag_path).await;
+            } else if let Err(e) = fs::write(&etag_path, validators.join("\n")).await {
+                log::warn!("保存图片 ETag 失败 {}: {}", etag_path.display(), e);
+            }
+        }
+
+        log::info!("图片下载成功: {} ({} bytes)", output_path.display(), size);
+        Ok(())
+    }
+
+    /// 将响应内容流式写入文件并 fsync，返回本次写入的字节数；`append` 时追加到已有内容之后
+    async fn stream_to_file(mut response: reqwest::Response, path: &Path, url: &str, append: bool) -> Result<usize> {
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .append(append)
+            .truncate(!append)
+            .open(path)
             .await
-            .with_context(|| format!("读取图片数据失败: {}", url))?;
+            .with_context(|| format!("创建文件失败: {}", path.display()))?;
 
-        // 写入文件
-        let mut file = fs::File::create(output_path)
+        let mut size = 0;
+        while let Some(chunk) = response.chunk()
             .await
-            .with_context(|| format!("创建文件失败: {}", output_path.display()))?;
+            .with_context(|| format!("读取图片数据失败: {}", url))?
+        {
+            file.write_all(&chunk)
+                .await
+                .with_context(|| format!("写入文件失败: {}", path.display()))?;
+            size += chunk.len();
+        }
 
-        file.write_all(&bytes)
+        file.flush().await?;
+        file.sync_all()
             .await
-            .with_context(|| format!("写入文件失败: {}", output_path.display()))?;
+            .with_context(|| format!("同步文件失败: {}", path.display()))?;
 
-        log::info!("图片下载成功: {} ({} bytes)", output_path.display(), bytes.len());
-        Ok(())
+        Ok(size)
     }
 
     /// 为影片下载所有图片
@@ -229,50 +431,31 @@ impl ImageManager {
 
         log::info!("开始下载影片 {} 的图片，输出目录: {}", movie_id, output_dir.display());
 
-        // 下载海报
-        if !movie_data.posters.is_empty() {
-            let poster_url = &movie_data.posters[0]; // 使用第一个海报
-            for (image_type, rule) in &naming_rules {
-                if matches!(image_type, ImageType::Poster) {
-                    let output_path = output_dir.join(&rule.filename);
-                    if let Err(e) = self.download_image(poster_url, &output_path).await {
-                        log::warn!("下载海报失败 {}: {}", rule.filename, e);
-                    } else {
-                        downloaded_files.push(output_path);
-                        break; // 只下载第一个成功的海报
-                    }
-                }
+        // 下载海报、背景图与缩略图，按尺寸偏好依次尝试候选 URL
+        let images = [
+            (ImageType::Poster, &movie_data.posters, "海报"),
+            (ImageType::Fanart, &movie_data.fanarts, "背景图"),
+            (ImageType::Thumb, &movie_data.thumbs, "缩略图"),
+        ];
+        for (kind, urls, label) in images {
+            if urls.is_empty() {
+                continue;
             }
-        }
-
-        // 下载背景图/剧照
-        if !movie_data.fanarts.is_empty() {
-            let fanart_url = &movie_data.fanarts[0]; // 使用第一个背景图
+            let candidates = order_by_size_preference(urls, &config.image.size_preference);
             for (image_type, rule) in &naming_rules {
-                if matches!(image_type, ImageType::Fanart) {
-                    let output_path = output_dir.join(&rule.filename);
-                    if let Err(e) = self.download_image(fanart_url, &output_path).await {
-                        log::warn!("下载背景图失败 {}: {}", rule.filename, e);
-                    } else {
-                        downloaded_files.push(output_path);
-                        break; // 只下载第一个成功的背景图
-                    }
+                if std::mem::discriminant(image_type) != std::mem::discriminant(&kind) {
+                    continue;
                 }
-            }
-        }
-
-        // 下载缩略图
-        if !movie_data.thumbs.is_empty() {
-            let thumb_url = &movie_data.thumbs[0]; // 使用第一个缩略图
-            for (image_type, rule) in &naming_rules {
-                if matches!(image_type, ImageType::Thumb) {
-                    let output_path = output_dir.join(&rule.filename);
-                    if let Err(e) = self.download_image(thumb_url, &output_path).await {
-                        log::warn!("下载缩略图失败 {}: {}", rule.filename, e);
-                    } else {
-                        downloaded_files.push(output_path);
-                        break; // 只下载第一个成功的缩略图
+                let output_path = output_dir.join(&rule.filename);
+                match self.download_first_available(&candidates, &output_path).await {
+                    Ok((url, downloaded)) => {
+                        log::info!("{} {} 使用图片: {}", label, rule.filename, url);
+                        if downloaded {
+                            downloaded_files.push(output_path);
+                        }
+                        break; // 只下载第一个成功的命名规则
                     }
+                    Err(e) => log::warn!("下载{}失败 {}: {}", label, rule.filename, e),
                 }
             }
         }
@@ -282,10 +465,10 @@ impl ImageManager {
             for (i, preview_url) in movie_data.preview_images.iter().enumerate().take(10) {
                 let filename = format!("preview_{:02}.jpg", i + 1);
                 let output_path = output_dir.join(&filename);
-                if let Err(e) = self.download_image(preview_url, &output_path).await {
-                    log::warn!("下载预览图失败 {}: {}", filename, e);
-                } else {
-                    downloaded_files.push(output_path);
+                match self.download_missing_image(preview_url, &output_path).await {
+                    Ok(true) => downloaded_files.push(output_path),
+                    Ok(false) => {}
+                    Err(e) => log::warn!("下载预览图失败 {}: {}", filename, e),
                 }
             }
         }
@@ -295,7 +478,6 @@ impl ImageManager {
     }
 
     /// 检查图片是否已存在且有效
-    #[allow(dead_code)]
     pub async fn is_image_valid(&self, path: &Path) -> bool {
         if !path.exists() {
             return false;
@@ -344,6 +526,12 @@ impl ImageManager {
                 if let Some(ext) = path.extension() {
                     if matches!(ext.to_str(), Some("jpg") | Some("jpeg") | Some("png") | Some("gif") | Some("bmp")) && !self.is_image_valid(&path).await {
                         log::warn!("发现无效图片文件，准备删除: {}", path.display());
+                        // 影片目录中的图片可能是去重存储的硬链接，同时移除存储中的内容，避免再次被链接
+                        if let Some(store) = &self.artwork_store {
+                            if let Err(e) = store.evict(&path) {
+                                log::warn!("从去重存储中移除无效图片失败 {}: {}", path.display(), e);
+                            }
+                        }
                         if let Err(e) = fs::remove_file(&path).await {
                             log::error!("删除无效图片失败: {}", e);
                         } else {
@@ -362,8 +550,554 @@ impl ImageManager {
     }
 }
 
+/// 内容寻址的图片存储：相同内容的图片只保存一份 `<sha256>.<扩展名>`，
+/// 并记录图片 URL 到存储文件的索引，已下载过的 URL 不再重复下载
+struct ArtworkStore {
+    dir: PathBuf,
+    /// 图片 URL 到存储文件名的索引，保存在 `index.json`
+    index: Mutex<HashMap<String, String>>,
+    downloading: tokio::sync::Mutex<()>,
+}
+
+impl ArtworkStore {
+    const INDEX_FILE: &'static str = "index.json";
+
+    /// 打开存储目录并读取索引，索引损坏时从空索引开始
+    fn open(dir: PathBuf) -> Self {
+        let index = match std::fs::read_to_string(dir.join(Self::INDEX_FILE)) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                log::warn!("图片去重索引损坏，重新建立: {}", e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+
+        Self {
+            dir,
+            index: Mutex::new(index),
+            downloading: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// 查找 URL 已下载的存储文件，文件已被删除时返回 None
+    fn lookup(&self, url: &str) -> Option<PathBuf> {
+        let index = self.index.lock().unwrap_or_else(PoisonError::into_inner);
+        index
+            .get(url)
+            .map(|file_name| self.dir.join(file_name))
+            .filter(|path| path.is_file())
+    }
+
+    /// 将下载完成的文件按内容哈希存入存储并记录索引，返回存储文件路径
+    fn store(&self, url: &str, downloaded: &Path, extension: &str) -> Result<PathBuf> {
+        let content = std::fs::read(downloaded)
+            .with_context(|| format!("读取下载的图片失败: {}", downloaded.display()))?;
+        let file_name = format!("{}.{}", sha256_hex(&content), extension);
+        let stored = self.dir.join(&file_name);
+
+        // 其他 URL 已下载过相同内容时复用已有文件
+        if stored.is_file() {
+            std::fs::remove_file(downloaded)?;
+        } else {
+            safe_write::persist(downloaded, &stored)
+                .with_context(|| format!("保存图片到去重存储失败: {}", stored.display()))?;
+        }
+
+        let mut index = self.index.lock().unwrap_or_else(PoisonError::into_inner);
+        index.insert(url.to_string(), file_name);
+        self.save_index(&index)?;
+
+        Ok(stored)
+    }
+
+    /// 移除与指定图片内容相同的存储文件及其索引
+    fn evict(&self, image: &Path) -> Result<()> {
+        let content = std::fs::read(image)?;
+        let hash = sha256_hex(&content);
+
+        let mut index = self.index.lock().unwrap_or_else(PoisonError::into_inner);
+        let before = index.len();
+        index.retain(|_, file_name| {
+            let evicted = Path::new(file_name).file_stem().and_then(|stem| stem.to_str()) == Some(&hash);
+            if evicted {
+                let _ = std::fs::remove_file(self.dir.join(file_name.as_str()));
+            }
+            !evicted
+        });
+
+        if index.len() != before {
+            log::info!("已从去重存储中移除无效图片: {}", hash);
+            self.save_index(&index)?;
+        }
+        Ok(())
+    }
+
+    fn save_index(&self, index: &HashMap<String, String>) -> Result<()> {
+        let content = serde_json::to_string_pretty(index)?;
+        safe_write::safe_write(&self.dir.join(Self::INDEX_FILE), content)
+            .with_context(|| format!("保存图片去重索引失败: {}", self.dir.display()))
+    }
+}
+
+/// 按尺寸偏好排序候选图片 URL：URL（不区分大小写）包含偏好列表中越靠前的关键字越优先，
+/// 不包含任何关键字的排在最后，同等优先级保持原顺序
+fn order_by_size_preference<'a>(urls: &'a [String], preference: &[String]) -> Vec<&'a str> {
+    let rank = |url: &str| {
+        let url = url.to_lowercase();
+        preference
+            .iter()
+            .position(|keyword| url.contains(&keyword.to_lowercase()))
+            .unwrap_or(preference.len())
+    };
+    let mut urls = urls.iter().map(String::as_str).collect::<Vec<_>>();
+    urls.sort_by_cached_key(|url| rank(url));
+    urls
+}
+
+/// 在 `dst` 创建指向 `src` 的硬链接，无法硬链接（如跨设备）时改为复制
+fn link_or_copy(src: &Path, dst: &Path) -> Result<()> {
+    if let Some(parent) = dst.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("创建目录失败: {}", parent.display()))?;
+    }
+    if is_same_file(src, dst) {
+        return Ok(());
+    }
+
+    let tmp_path = safe_write::temp_path(dst);
+    let _ = std::fs::remove_file(&tmp_path);
+    if let Err(e) = std::fs::hard_link(src, &tmp_path) {
+        log::debug!("创建硬链接失败，改为复制 {} -> {}: {}", src.display(), dst.display(), e);
+        std::fs::copy(src, &tmp_path)
+            .with_context(|| format!("复制图片失败: {} -> {}", src.display(), dst.display()))?;
+    }
+
+    if let Err(e) = safe_write::persist(&tmp_path, dst) {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(e).with_context(|| format!("保存图片失败: {}", dst.display()));
+    }
+    log::debug!("图片已链接: {} -> {}", src.display(), dst.display());
+    Ok(())
+}
+
+/// 两个路径是否为同一文件（同一个硬链接的 inode）
+#[cfg(unix)]
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (std::fs::metadata(a), std::fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_same_file(_a: &Path, _b: &Path) -> bool {
+    false
+}
+
+fn sha256_hex(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+/// 解析 206 响应 `Content-Range: bytes <start>-<end>/<size>` 中的起始位置
+fn content_range_start(response: &reqwest::Response) -> Option<u64> {
+    response
+        .headers()
+        .get(header::CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .trim()
+        .strip_prefix("bytes ")?
+        .split_once('-')?
+        .0
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// 图片旁按下载 URL 区分的辅助文件路径，如 `poster.jpg.<URL哈希>.etag`，
+/// 同一图片的多个候选 URL 不会共用续传数据与验证头
+fn download_sidecar_path(path: &Path, url: &str, extension: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".{}.{}", &sha256_hex(url.as_bytes())[..16], extension));
+    path.with_file_name(file_name)
+}
+
 impl Default for ImageManager {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn smart_manager() -> ImageManager {
+        ImageManager {
+            smart_download: true,
+            ..ImageManager::new()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_smart_download_etag_revalidation() {
+        let mut server = mockito::Server::new_async().await;
+        let dir = std::env::temp_dir().join("test_image_etag_revalidation");
+        let _ = fs::remove_dir_all(&dir).await;
+        let output_path = dir.join("poster.jpg");
+        let url = format!("{}/poster.jpg", server.url());
+
+        // 首次下载保存 ETag 与 Last-Modified
+        let first = server
+            .mock("GET", "/poster.jpg")
+            .with_status(200)
+            .with_header("ETag", "\"v1\"")
+            .with_header("Last-Modified", "Wed, 21 Oct 2015 07:28:00 GMT")
+            .with_body("image-v1")
+            .create_async()
+            .await;
+        smart_manager().download_image(&url, &output_path).await.unwrap();
+        first.assert_async().await;
+        assert_eq!(fs::read_to_string(&output_path).await.unwrap(), "image-v1");
+        assert_eq!(
+            fs::read_to_string(download_sidecar_path(&output_path, &url, "etag")).await.unwrap(),
+            "\"v1\"\nWed, 21 Oct 2015 07:28:00 GMT"
+        );
+        first.remove_async().await;
+
+        // 再次下载时携带验证头，304 时保留原文件
+        let revalidate = server
+            .mock("GET", "/poster.jpg")
+            .match_header("if-none-match", "\"v1\"")
+            .match_header("if-modified-since", "Wed, 21 Oct 2015 07:28:00 GMT")
+            .with_status(304)
+            .create_async()
+            .await;
+        smart_manager().download_image(&url, &output_path).await.unwrap();
+        revalidate.assert_async().await;
+        assert_eq!(fs::read_to_string(&output_path).await.unwrap(), "image-v1");
+
+        let _ = fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_smart_download_resumes_partial_file() {
+        let mut server = mockito::Server::new_async().await;
+        let dir = std::env::temp_dir().join("test_image_range_resume");
+        let _ = fs::remove_dir_all(&dir).await;
+        fs::create_dir_all(&dir).await.unwrap();
+        let output_path = dir.join("fanart.jpg");
+        let url = format!("{}/fanart.jpg", server.url());
+        let partial_path = download_sidecar_path(&output_path, &url, "partial");
+        fs::write(&partial_path, "hello ").await.unwrap();
+
+        let resume = server
+            .mock("GET", "/fanart.jpg")
+            .match_header("range", "bytes=6-")
+            .with_status(206)
+            .with_header("Accept-Ranges", "bytes")
+            .with_header("Content-Range", "bytes 6-10/11")
+            .with_body("world")
+            .create_async()
+            .await;
+
+        smart_manager().download_image(&url, &output_path).await.unwrap();
+
+        resume.assert_async().await;
+        assert_eq!(fs::read_to_string(&output_path).await.unwrap(), "hello world");
+        assert!(!partial_path.exists());
+        resume.remove_async().await;
+
+        // 其他 URL 留下的部分文件不用于续传
+        fs::write(download_sidecar_path(&output_path, "https://example.com/other.jpg", "partial"), "other ")
+            .await
+            .unwrap();
+        let other_url = server
+            .mock("GET", "/fanart.jpg")
+            .match_header("range", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_header("Accept-Ranges", "bytes")
+            .with_body("new image")
+            .create_async()
+            .await;
+        smart_manager().download_image(&url, &output_path).await.unwrap();
+        other_url.assert_async().await;
+        assert_eq!(fs::read_to_string(&output_path).await.unwrap(), "new image");
+        other_url.remove_async().await;
+
+        // 服务器返回的范围不是续传位置时丢弃部分文件，重新完整下载
+        fs::write(&partial_path, "hello ").await.unwrap();
+        let wrong_range = server
+            .mock("GET", "/fanart.jpg")
+            .match_header("range", "bytes=6-")
+            .with_status(206)
+            .with_header("Accept-Ranges", "bytes")
+            .with_header("Content-Range", "bytes 0-10/11")
+            .with_body("hello world")
+            .expect(1)
+            .create_async()
+            .await;
+        let restart = server
+            .mock("GET", "/fanart.jpg")
+            .match_header("range", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_body("restarted image")
+            .expect(1)
+            .create_async()
+            .await;
+        smart_manager().download_image(&url, &output_path).await.unwrap();
+        wrong_range.assert_async().await;
+        restart.assert_async().await;
+        assert_eq!(fs::read_to_string(&output_path).await.unwrap(), "restarted image");
+        wrong_range.remove_async().await;
+        restart.remove_async().await;
+
+        // 未启用智能下载时忽略已有的部分文件，完整下载
+        fs::write(&partial_path, "stale").await.unwrap();
+        let full = server
+            .mock("GET", "/fanart.jpg")
+            .match_header("range", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_body("full image")
+            .create_async()
+            .await;
+        ImageManager::new().download_image(&url, &output_path).await.unwrap();
+        full.assert_async().await;
+        assert_eq!(fs::read_to_string(&output_path).await.unwrap(), "full image");
+
+        let _ = fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_existing_valid_image_is_kept() {
+        let mut server = mockito::Server::new_async().await;
+        let dir = std::env::temp_dir().join("test_image_existing_kept");
+        let _ = fs::remove_dir_all(&dir).await;
+        fs::create_dir_all(&dir).await.unwrap();
+
+        // 已有有效的 JPEG 保留，过小或不是图片的文件重新下载
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0];
+        jpeg.resize(2048, 0);
+        let poster = dir.join("poster.jpg");
+        fs::write(&poster, &jpeg).await.unwrap();
+        fs::write(dir.join("fanart.jpg"), "broken").await.unwrap();
+
+        let kept = server.mock("GET", "/poster.jpg").expect(0).create_async().await;
+        let replaced = server
+            .mock("GET", "/fanart.jpg")
+            .with_status(200)
+            .with_body("new-fanart")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let manager = ImageManager::new();
+        assert!(!manager.download_missing_image(&format!("{}/poster.jpg", server.url()), &poster).await.unwrap());
+        assert!(manager
+            .download_missing_image(&format!("{}/fanart.jpg", server.url()), &dir.join("fanart.jpg"))
+            .await
+            .unwrap());
+
+        kept.assert_async().await;
+        replaced.assert_async().await;
+        assert_eq!(fs::read(&poster).await.unwrap(), jpeg);
+        assert_eq!(fs::read_to_string(dir.join("fanart.jpg")).await.unwrap(), "new-fanart");
+
+        fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dedupe_shares_artwork_between_movies() {
+        let mut server = mockito::Server::new_async().await;
+        let dir = std::env::temp_dir().join("test_image_dedupe");
+        let _ = fs::remove_dir_all(&dir).await;
+        let cache_dir = dir.join(ARTWORK_CACHE_DIR);
+        let url = format!("{}/cover.jpg", server.url());
+
+        // 两部影片使用同一封面，只请求一次
+        let cover = server
+            .mock("GET", "/cover.jpg")
+            .with_status(200)
+            .with_body("shared-cover")
+            .expect(1)
+            .create_async()
+            .await;
+        let manager = ImageManager {
+            artwork_store: Some(ArtworkStore::open(cache_dir.clone())),
+            ..ImageManager::new()
+        };
+        let first = dir.join("movie1").join("poster.jpg");
+        let second = dir.join("movie2").join("poster.jpg");
+        manager.download_image(&url, &first).await.unwrap();
+        manager.download_image(&url, &second).await.unwrap();
+
+        // 重新打开存储时从索引得知已下载过，不再请求
+        let reopened = ImageManager {
+            artwork_store: Some(ArtworkStore::open(cache_dir.clone())),
+            ..ImageManager::new()
+        };
+        let third = dir.join("movie3").join("poster.jpg");
+        reopened.download_image(&url, &third).await.unwrap();
+        cover.assert_async().await;
+
+        for path in [&first, &second, &third] {
+            assert_eq!(fs::read_to_string(path).await.unwrap(), "shared-cover");
+        }
+        let cached = |dir: PathBuf| {
+            std::fs::read_dir(dir)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+                .filter(|name| name != ArtworkStore::INDEX_FILE)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(cached(cache_dir.clone()), vec![format!("{}.jpg", sha256_hex(b"shared-cover"))]);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let inode = |path: &Path| std::fs::metadata(path).unwrap().ino();
+            assert_eq!(inode(&first), inode(&second));
+            assert_eq!(inode(&first), inode(&third));
+        }
+
+        // 清理无效图片时同时移除存储中的内容，下次重新下载
+        let removed = manager.cleanup_invalid_images(&dir.join("movie1")).await.unwrap();
+        assert_eq!(removed, vec![first.clone()]);
+        assert!(cached(cache_dir.clone()).is_empty());
+        assert!(manager.artwork_store.as_ref().unwrap().lookup(&url).is_none());
+
+        let _ = fs::remove_dir_all(&dir).await;
+    }
+
+    fn image_config(dir: &Path, size_preference: &str) -> AppConfig {
+        let config_path = dir.join("config.toml");
+        std::fs::write(
+            &config_path,
+            format!(
+                r#"
+migrate_files = ["mp4"]
+migrate_subtitles = false
+ignored_id_pattern = []
+capital = false
+input_dir = "./input"
+output_dir = "./output"
+thread_limit = 4
+template_priority = []
+
+[image]
+size_preference = {}
+"#,
+                size_preference
+            ),
+        )
+        .unwrap();
+        AppConfig::new(&config_path).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_image_falls_back_to_next_candidate() {
+        let mut server = mockito::Server::new_async().await;
+        let dir = std::env::temp_dir().join("test_image_candidate_fallback");
+        let _ = fs::remove_dir_all(&dir).await;
+        fs::create_dir_all(&dir).await.unwrap();
+        let config = image_config(&dir, "[]");
+
+        // 第一个候选 URL 已失效，改用第二个
+        let expired = server
+            .mock("GET", "/expired.jpg")
+            .with_status(404)
+            .expect(1)
+            .create_async()
+            .await;
+        let fallback = server
+            .mock("GET", "/fallback.jpg")
+            .with_status(200)
+            .with_body("fallback-poster")
+            .expect(1)
+            .create_async()
+            .await;
+        let movie = MovieNfoCrawler {
+            posters: vec![
+                format!("{}/expired.jpg", server.url()),
+                format!("{}/fallback.jpg", server.url()),
+            ],
+            ..Default::default()
+        };
+
+        let output_dir = dir.join("movie");
+        let downloaded = ImageManager::new()
+            .download_movie_images(&movie, &output_dir, "FALL-001", &config)
+            .await
+            .unwrap();
+        expired.assert_async().await;
+        fallback.assert_async().await;
+        assert_eq!(downloaded, vec![output_dir.join("FALL-001.jpg")]);
+        assert_eq!(fs::read_to_string(&downloaded[0]).await.unwrap(), "fallback-poster");
+
+        let _ = fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_image_size_preference() {
+        let urls = [
+            "https://example.com/covers/thumb/a.jpg",
+            "https://example.com/a.jpg",
+            "https://example.com/covers/FULL/a.jpg",
+            "https://example.com/covers/large/a.jpg",
+        ]
+        .map(String::from);
+        let preference = ["full", "large", "cover", "thumb"].map(String::from);
+        assert_eq!(
+            order_by_size_preference(&urls, &preference),
+            vec![
+                "https://example.com/covers/FULL/a.jpg",
+                "https://example.com/covers/large/a.jpg",
+                "https://example.com/covers/thumb/a.jpg",
+                "https://example.com/a.jpg",
+            ]
+        );
+        // 未配置偏好时保持爬取顺序
+        assert_eq!(order_by_size_preference(&urls, &[]), urls.iter().map(String::as_str).collect::<Vec<_>>());
+
+        let mut server = mockito::Server::new_async().await;
+        let dir = std::env::temp_dir().join("test_image_size_preference");
+        let _ = fs::remove_dir_all(&dir).await;
+        fs::create_dir_all(&dir).await.unwrap();
+        let config = image_config(&dir, r#"["full", "large", "cover", "thumb"]"#);
+
+        // 大图排在前面，小图不再请求
+        let small = server
+            .mock("GET", "/covers/small/b.jpg")
+            .with_status(200)
+            .with_body("small")
+            .expect(0)
+            .create_async()
+            .await;
+        let full = server
+            .mock("GET", "/covers/full/b.jpg")
+            .with_status(200)
+            .with_body("full")
+            .expect(1)
+            .create_async()
+            .await;
+        let movie = MovieNfoCrawler {
+            fanarts: vec![
+                format!("{}/covers/small/b.jpg", server.url()),
+                format!("{}/covers/full/b.jpg", server.url()),
+            ],
+            ..Default::default()
+        };
+
+        let output_dir = dir.join("movie");
+        let downloaded = ImageManager::new()
+            .download_movie_images(&movie, &output_dir, "PREF-001", &config)
+            .await
+            .unwrap();
+        small.assert_async().await;
+        full.assert_async().await;
+        assert_eq!(downloaded, vec![output_dir.join("backdrop.jpg")]);
+        assert_eq!(fs::read_to_string(&downloaded[0]).await.unwrap(), "full");
+
+        let _ = fs::remove_dir_all(&dir).await;
+    }
+}
@@ -1,16 +1,32 @@
-// 库入口文件，用于导出公共 API 给测试使用
+// 库入口文件，导出公共 API 供测试和其他程序嵌入使用
 
+pub mod actor_index;
+pub mod classifier;
 pub mod config;
+pub mod consistency;
+pub mod control_api;
 pub mod crawler;
 pub mod error;
 pub mod file;
 pub mod file_organizer;
+pub mod ignored_ids;
 pub mod image_manager;
+pub mod matcher;
+pub mod media_probe;
+pub mod mqtt;
 pub mod nfo;
 pub mod nfo_generator;
+pub mod output_router;
 pub mod parser;
+pub mod pipeline;
+pub mod romaji;
+pub mod taxonomy;
 pub mod template_parser;
 pub mod translator;
+pub mod webhook;
+
+pub use config::AppConfig;
+pub use pipeline::{Pipeline, PreviewPlan, ProcessOutcome, ProgressReporter};
 
 // 测试模块
 #[cfg(test)]
@@ -1,22 +1,38 @@
+mod actor_index;
 mod args;
+mod classifier;
 mod config;
+mod consistency;
+mod control_api;
 mod crawler;
 mod error;
 mod file;
 mod file_organizer;
+mod ignored_ids;
 mod image_manager;
+mod matcher;
+mod media_probe;
+mod mqtt;
 mod nfo;
 mod nfo_generator;
+mod output_router;
 mod parser;
+#[allow(dead_code)] // 对外接口，命令行程序只使用文件队列需要的部分
+mod pipeline;
+mod romaji;
+mod taxonomy;
 mod template_parser;
 mod translator;
+mod webhook;
+
+#[cfg(test)]
+mod tests;
 
 use std::path::Path;
 
 use std::result::Result::{Ok, Err};
 use flexi_logger::{
-    colored_detailed_format, Age, Cleanup, Criterion, Duplicate, FileSpec, Logger, Naming,
-    WriteMode,
+    colored_detailed_format, Cleanup, Criterion, Duplicate, FileSpec, Logger, Naming, WriteMode,
 };
 use indicatif::MultiProgress;
 use indicatif_log_bridge::LogWrapper;
@@ -46,36 +62,63 @@ async fn main() -> anyhow::Result<()> {
     };
     
     println!("初始化日志系统...");
-    let multi_progress = log_init(&arg.log_location).unwrap();
+    let multi_progress = log_init(&arg.log_location, &arg.log_spec(), arg.log_rotation()).unwrap();
 
     println!("加载应用配置...");
-    let config = config::AppConfig::new(&arg.config_file)?;
+    let mut config = config::AppConfig::new(&arg.config_file)?;
+    config.apply_template_overrides(&arg.template_overrides);
+    config.remember_rejections |= arg.remember_rejections;
+    config.refresh_metadata |= arg.refresh_metadata;
     log::info!("应用配置加载完成");
     log::info!("输入目录: {}", config.input_dir.display());
     log::info!("输出目录: {}", config.get_output_dir().display());
     log::info!("支持的文件类型: {:?}", config.get_migrate_files_ext());
 
+    if arg.validate_templates {
+        println!("检查模板...");
+        let warning_count = crawler::validate_templates(&arg.template_location, &config)?;
+        std::process::exit(if warning_count == 0 { 0 } else { 1 });
+    }
+
+    if arg.check_consistency {
+        println!("检查影片路径与NFO的一致性...");
+        let unresolved = consistency::run(&config, arg.fix)?;
+        std::process::exit(if unresolved == 0 { 0 } else { 1 });
+    }
+
     println!("创建文件处理通道...");
     let (file_tx, file_rx) = tokio::sync::mpsc::channel(8);
     log::info!("文件处理通道创建完成，通道容量: 8");
     
     println!("初始化文件监控系统...");
-    let _source_notify = file::initial(&config, file_tx).await?;
+    let _source_notify = file::initial(&config, file_tx, &multi_progress).await?;
 
     println!("初始化爬虫系统...");
-    crawler::initial(&arg.template_location, &config, file_rx, multi_progress)?;
+    let queue = crawler::initial(&arg.template_location, &config, file_rx, multi_progress)?;
 
     println!("JAV-Tidy-RS 初始化完成，开始监控文件...");
     log::info!("JAV-Tidy-RS 已完全启动，等待文件处理");
 
-    // 保持主线程运行
-    loop {
-        tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
-        log::debug!("主线程保活检查");
+    // 收到中断信号或控制接口请求排空后，处理完队列中的文件再退出；再次中断立即退出
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => {
+            result?;
+            println!("收到中断信号，处理完队列中的文件后退出，再次中断立即退出...");
+        }
+        _ = queue.state.wait_for_drain() => {}
+    }
+    tokio::select! {
+        _ = queue.drain() => log::info!("队列中的文件已处理完毕，JAV-Tidy-RS 退出"),
+        _ = tokio::signal::ctrl_c() => log::warn!("再次收到中断信号，立即退出"),
     }
+    Ok(())
 }
 
-fn log_init(log_location: &Path) -> anyhow::Result<MultiProgress> {
+fn log_init(
+    log_location: &Path,
+    log_spec: &str,
+    (criterion, cleanup): (Criterion, Cleanup),
+) -> anyhow::Result<MultiProgress> {
     if log_location.is_file() {
         return Err(anyhow::anyhow!("log file is a file, not a directory"));
     } else if log_location.is_dir() {
@@ -88,18 +131,14 @@ fn log_init(log_location: &Path) -> anyhow::Result<MultiProgress> {
 
     let file_spec = FileSpec::default().directory(log_location);
 
-    let (logger, _) = Logger::try_with_str("info,pago_mqtt=error,paho_mqtt_c=error")?
+    let (logger, _) = Logger::try_with_str(log_spec)?
         .write_mode(WriteMode::SupportCapture)
         .log_to_file(file_spec)
         .duplicate_to_stderr(Duplicate::All)
         .format_for_stderr(colored_detailed_format)
         .format_for_stdout(colored_detailed_format)
         .set_palette(String::from("b196;208;28;7;8"))
-        .rotate(
-            Criterion::Age(Age::Day),
-            Naming::Timestamps,
-            Cleanup::KeepLogFiles(7),
-        )
+        .rotate(criterion, Naming::Timestamps, cleanup)
         .build()?;
 
     let multi = MultiProgress::new();
@@ -0,0 +1,217 @@
+use crate::nfo::MovieNfoCrawler;
+
+/// 标题或唯一标识符中包含影片ID时的加分
+const ID_MATCH_SCORE: i32 = 100;
+/// 发行年份、演员、厂商各自存在时的加分
+const PRESENCE_SCORE: i32 = 5;
+/// 文件名与标题词语相似度的最高加分
+const TITLE_SIMILARITY_SCORE: f64 = 20.0;
+
+/// 计算候选结果与影片ID、原始文件名的匹配评分
+///
+/// - 标题或唯一标识符中包含标准化后的影片ID: +100
+/// - 发行年份、演员、厂商存在: 各 +5
+/// - 文件名中除影片ID外的词语与标题词语的编辑距离相似度: 最高 +20
+pub fn score_candidate(candidate: &MovieNfoCrawler, movie_id: &str, file_name: &str) -> i32 {
+    let normalized_id = normalize(movie_id);
+    let id_matched = !normalized_id.is_empty() && candidate_ids(candidate).any(|id| normalize(id) == normalized_id);
+
+    let mut score = 0;
+    if id_matched {
+        score += ID_MATCH_SCORE;
+    }
+    if candidate.year.is_some() {
+        score += PRESENCE_SCORE;
+    }
+    if !candidate.actors.is_empty() {
+        score += PRESENCE_SCORE;
+    }
+    if !candidate.studios.is_empty() {
+        score += PRESENCE_SCORE;
+    }
+
+    let similarity = title_similarity(&candidate.title, file_name, &normalized_id);
+    score += (similarity * TITLE_SIMILARITY_SCORE).round() as i32;
+
+    log::debug!(
+        "候选 '{}' 匹配评分: {} (ID匹配={}, 标题相似度={:.2})",
+        candidate.title,
+        score,
+        id_matched,
+        similarity
+    );
+
+    score
+}
+
+/// 过滤低于最低匹配评分的候选结果，并按评分从高到低排序，评分相同时保持原有顺序
+pub fn rank_candidates<T>(candidates: Vec<(T, i32)>, minimum_score: i32) -> Vec<(T, i32)> {
+    let mut ranked: Vec<(T, i32)> = candidates
+        .into_iter()
+        .filter(|(_, score)| *score >= minimum_score)
+        .collect();
+    ranked.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+    ranked
+}
+
+/// 可能包含影片ID的字段：标题中的词语和各唯一标识符
+fn candidate_ids(candidate: &MovieNfoCrawler) -> impl Iterator<Item = &str> {
+    candidate
+        .title
+        .split_whitespace()
+        .chain(
+            candidate
+                .original_title
+                .iter()
+                .flat_map(|title| title.split_whitespace()),
+        )
+        .chain([
+            candidate.imdb_id.as_str(),
+            candidate.tmdb_id.as_str(),
+            candidate.tvdb_id.as_str(),
+        ])
+}
+
+/// 文件名中除影片ID外的词语与标题词语的平均最佳相似度 (0.0-1.0)
+fn title_similarity(title: &str, file_name: &str, normalized_id: &str) -> f64 {
+    let title_tokens = tokenize(title);
+    let file_tokens: Vec<String> = tokenize(file_name)
+        .into_iter()
+        .filter(|token| token.chars().count() >= 2 && !normalized_id.contains(token.as_str()))
+        .collect();
+
+    if title_tokens.is_empty() || file_tokens.is_empty() {
+        return 0.0;
+    }
+
+    let total: f64 = file_tokens
+        .iter()
+        .map(|file_token| {
+            title_tokens
+                .iter()
+                .map(|title_token| similarity(file_token, title_token))
+                .fold(0.0, f64::max)
+        })
+        .sum();
+
+    total / file_tokens.len() as f64
+}
+
+/// 按非字母数字字符切分并转为小写
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// 只保留字母数字并转为小写，如 `IPX-001` -> `ipx001`
+pub(crate) fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// 基于编辑距离的相似度 (0.0-1.0)
+fn similarity(a: &str, b: &str) -> f64 {
+    let max_len = a.chars().count().max(b.chars().count());
+    if max_len == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(a, b) as f64 / max_len as f64
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nfo::Actor;
+
+    fn candidates() -> Vec<(&'static str, MovieNfoCrawler)> {
+        let exact = MovieNfoCrawler {
+            title: "IPX-001 Summer Vacation".to_string(),
+            imdb_id: "IPX-001".to_string(),
+            year: Some(2023),
+            actors: vec![Actor {
+                name: "演员A".to_string(),
+                ..Default::default()
+            }],
+            studios: vec!["IdeaPocket".to_string()],
+            ..Default::default()
+        };
+        let near_miss = MovieNfoCrawler {
+            title: "IPX-011 Summer Vacations".to_string(),
+            imdb_id: "IPX-011".to_string(),
+            year: Some(2023),
+            ..Default::default()
+        };
+        let unrelated = MovieNfoCrawler {
+            title: "Completely Different".to_string(),
+            ..Default::default()
+        };
+
+        vec![("unrelated", unrelated), ("near_miss", near_miss), ("exact", exact)]
+    }
+
+    #[test]
+    fn test_score_candidate() {
+        let file_name = "IPX-001 summer vacation";
+        let scores: Vec<(&str, i32)> = candidates()
+            .iter()
+            .map(|(name, nfo)| (*name, score_candidate(nfo, "IPX-001", file_name)))
+            .collect();
+
+        // ID匹配 + 年份/演员/厂商 + 标题完全相似
+        assert_eq!(scores[2], ("exact", 100 + 15 + 20));
+        // 标题相近但ID不同：只有年份和标题相似度得分
+        assert_eq!(scores[1], ("near_miss", 5 + 19));
+        // 无关影片只有少量词语相似度得分
+        assert!(scores[0].1 < 10);
+    }
+
+    #[test]
+    fn test_rank_candidates() {
+        let scored: Vec<(&str, i32)> = candidates()
+            .iter()
+            .map(|(name, nfo)| (*name, score_candidate(nfo, "IPX-001", "IPX-001 summer vacation")))
+            .collect();
+
+        let ranked = rank_candidates(scored.clone(), 0);
+        let order: Vec<&str> = ranked.iter().map(|(name, _)| *name).collect();
+        assert_eq!(order, vec!["exact", "near_miss", "unrelated"]);
+
+        // 低于最低评分的候选被丢弃
+        let ranked = rank_candidates(scored.clone(), 10);
+        let order: Vec<&str> = ranked.iter().map(|(name, _)| *name).collect();
+        assert_eq!(order, vec!["exact", "near_miss"]);
+
+        let ranked = rank_candidates(scored, ID_MATCH_SCORE);
+        let order: Vec<&str> = ranked.iter().map(|(name, _)| *name).collect();
+        assert_eq!(order, vec!["exact"]);
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(levenshtein("vacation", "vacations"), 1);
+        assert_eq!(similarity("summer", "summer"), 1.0);
+        assert_eq!(normalize("IPX-001"), "ipx001");
+    }
+}
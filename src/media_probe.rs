@@ -0,0 +1,372 @@
+// 读取视频文件的流信息（分辨率、编码、时长、声道、HDR），写入NFO的 fileinfo/streamdetails
+//
+// 配置 `ffprobe_path` 时调用 ffprobe，支持所有格式；否则内置解析 MP4/MOV（ISO BMFF）容器的 moov 盒
+
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use anyhow::Context;
+
+use crate::nfo::{AudioStream, StreamDetails, VideoStream};
+
+/// 内置解析器支持的扩展名
+const ISO_BMFF_EXTENSIONS: &[&str] = &["mp4", "m4v", "mov"];
+
+/// moov 盒的最大字节数，超过时视为无法解析，避免读取异常文件占用大量内存
+const MAX_MOOV_BYTES: u64 = 64 * 1024 * 1024;
+
+/// 读取视频文件的流信息，无法识别格式或解析失败时返回错误
+pub fn probe_media(path: &Path, ffprobe_path: Option<&Path>) -> anyhow::Result<StreamDetails> {
+    let details = match ffprobe_path {
+        Some(ffprobe) => probe_with_ffprobe(ffprobe, path)?,
+        None => {
+            let supported = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| ISO_BMFF_EXTENSIONS.iter().any(|supported| ext.eq_ignore_ascii_case(supported)));
+            if !supported {
+                anyhow::bail!("内置解析只支持 {}，其他格式需要配置 ffprobe_path", ISO_BMFF_EXTENSIONS.join("/"));
+            }
+            probe_iso_bmff(path)?
+        }
+    };
+    if details.video.is_empty() && details.audio.is_empty() {
+        anyhow::bail!("没有找到音视频流");
+    }
+    Ok(details)
+}
+
+/// 调用 `ffprobe -print_format json` 读取流信息
+fn probe_with_ffprobe(ffprobe: &Path, path: &Path) -> anyhow::Result<StreamDetails> {
+    let output = std::process::Command::new(ffprobe)
+        .args(["-v", "error", "-print_format", "json", "-show_format", "-show_streams"])
+        .arg(path)
+        .output()
+        .with_context(|| format!("无法启动 ffprobe: {}", ffprobe.display()))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "ffprobe 退出状态 {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    parse_ffprobe_output(&output.stdout)
+}
+
+/// 解析 ffprobe 的 JSON 输出
+fn parse_ffprobe_output(output: &[u8]) -> anyhow::Result<StreamDetails> {
+    let probe: serde_json::Value = serde_json::from_slice(output).context("ffprobe 输出不是有效的 JSON")?;
+    let duration = probe["format"]["duration"]
+        .as_str()
+        .and_then(|duration| duration.parse::<f64>().ok())
+        .filter(|duration| duration.is_finite() && *duration > 0.0)
+        .map(|duration| duration.round() as u64);
+
+    let mut details = StreamDetails::default();
+    for stream in probe["streams"].as_array().into_iter().flatten() {
+        let codec = stream["codec_name"].as_str().unwrap_or_default().to_string();
+        let number = |key: &str| stream[key].as_u64().and_then(|value| u32::try_from(value).ok());
+        match stream["codec_type"].as_str() {
+            // 封面图也是视频流，带 attached_pic 标记
+            Some("video") if stream["disposition"]["attached_pic"].as_u64() != Some(1) => {
+                let dolby_vision = stream["side_data_list"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .any(|side_data| {
+                        side_data["side_data_type"]
+                            .as_str()
+                            .is_some_and(|kind| kind.contains("DOVI"))
+                    });
+                let hdr_type = if dolby_vision {
+                    "dolbyvision".to_string()
+                } else {
+                    match stream["color_transfer"].as_str() {
+                        Some("smpte2084") => "hdr10".to_string(),
+                        Some("arib-std-b67") => "hlg".to_string(),
+                        _ => String::new(),
+                    }
+                };
+                details.video.push(video_stream(codec, number("width"), number("height"), duration, hdr_type));
+            }
+            Some("audio") => details.audio.push(AudioStream {
+                codec,
+                channels: number("channels"),
+            }),
+            _ => {}
+        }
+    }
+    Ok(details)
+}
+
+fn video_stream(
+    codec: String,
+    width: Option<u32>,
+    height: Option<u32>,
+    duration_in_seconds: Option<u64>,
+    hdr_type: String,
+) -> VideoStream {
+    let aspect = match (width, height) {
+        (Some(width), Some(height)) if height > 0 => format!("{:.2}", width as f64 / height as f64),
+        _ => String::new(),
+    };
+    VideoStream {
+        codec,
+        aspect,
+        width,
+        height,
+        duration_in_seconds,
+        hdr_type,
+    }
+}
+
+/// 解析 MP4/MOV 文件：定位顶层 moov 盒后读入内存，再按 trak 读取各轨道的编码信息
+fn probe_iso_bmff(path: &Path) -> anyhow::Result<StreamDetails> {
+    let mut file = File::open(path).with_context(|| format!("打开文件失败: {}", path.display()))?;
+    let file_len = file.metadata()?.len();
+
+    let mut offset = 0;
+    let moov = loop {
+        if offset + 8 > file_len {
+            anyhow::bail!("没有找到 moov 盒");
+        }
+        file.seek(SeekFrom::Start(offset))?;
+        let mut header = [0u8; 16];
+        file.read_exact(&mut header[..8])?;
+        let (size, header_len) = match u32::from_be_bytes(header[..4].try_into()?) {
+            0 => (file_len - offset, 8),
+            1 => {
+                file.read_exact(&mut header[8..])?;
+                (u64::from_be_bytes(header[8..].try_into()?), 16)
+            }
+            size => (u64::from(size), 8),
+        };
+        let Some(end) = offset
+            .checked_add(size)
+            .filter(|&end| size >= header_len && end <= file_len)
+        else {
+            anyhow::bail!("盒大小无效: {} 字节（偏移 {}）", size, offset);
+        };
+        if &header[4..8] == b"moov" {
+            if size > MAX_MOOV_BYTES {
+                anyhow::bail!("moov 盒过大: {} 字节", size);
+            }
+            let mut moov = vec![0; (size - header_len) as usize];
+            file.read_exact(&mut moov)?;
+            break moov;
+        }
+        offset = end;
+    };
+
+    let mut duration = None;
+    let mut details = StreamDetails::default();
+    for (kind, body) in boxes(&moov) {
+        match kind {
+            b"mvhd" => duration = movie_duration(body),
+            b"trak" => {
+                let Some(mdia) = child(body, b"mdia") else {
+                    continue;
+                };
+                let handler = child(mdia, b"hdlr").and_then(|hdlr| hdlr.get(8..12));
+                let Some(entry) = child(mdia, b"minf")
+                    .and_then(|minf| child(minf, b"stbl"))
+                    .and_then(|stbl| child(stbl, b"stsd"))
+                    .and_then(|stsd| boxes(stsd.get(8..)?).next())
+                else {
+                    continue;
+                };
+                match handler {
+                    Some(b"vide") => details.video.push(video_sample_entry(entry, duration)),
+                    Some(b"soun") => details.audio.push(audio_sample_entry(entry)),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    // mvhd 通常在 trak 之前，少数文件在之后
+    for video in &mut details.video {
+        video.duration_in_seconds = video.duration_in_seconds.or(duration);
+    }
+    Ok(details)
+}
+
+/// 遍历数据中连续的盒，返回盒类型与内容；遇到大小无效的盒时停止
+fn boxes(data: &[u8]) -> impl Iterator<Item = (&[u8; 4], &[u8])> {
+    let mut rest = data;
+    std::iter::from_fn(move || {
+        let size = u32::from_be_bytes(rest.get(..4)?.try_into().ok()?);
+        let kind: &[u8; 4] = rest.get(4..8)?.try_into().ok()?;
+        let (size, header_len) = match size {
+            0 => (rest.len(), 8),
+            1 => (usize::try_from(u64::from_be_bytes(rest.get(8..16)?.try_into().ok()?)).ok()?, 16),
+            size => (size as usize, 8),
+        };
+        let body = rest.get(header_len..size)?;
+        rest = &rest[size..];
+        Some((kind, body))
+    })
+}
+
+fn child<'a>(data: &'a [u8], kind: &[u8; 4]) -> Option<&'a [u8]> {
+    boxes(data).find(|(child_kind, _)| *child_kind == kind).map(|(_, body)| body)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(data.get(offset..offset + 8)?.try_into().ok()?))
+}
+
+/// 由 mvhd 的时间刻度与时长计算影片时长（秒）
+fn movie_duration(mvhd: &[u8]) -> Option<u64> {
+    let (timescale, duration) = match mvhd.first()? {
+        1 => (read_u32(mvhd, 20)?, read_u64(mvhd, 24)?),
+        _ => (read_u32(mvhd, 12)?, u64::from(read_u32(mvhd, 16)?)),
+    };
+    (timescale > 0 && duration > 0 && duration != u64::MAX && duration != u64::from(u32::MAX))
+        .then(|| (duration + u64::from(timescale) / 2) / u64::from(timescale))
+}
+
+/// 视频样本描述：78 字节的固定字段（含宽高）之后是 colr、dvcC 等子盒
+fn video_sample_entry(entry: (&[u8; 4], &[u8]), duration: Option<u64>) -> VideoStream {
+    let (kind, body) = entry;
+    let codec = match kind {
+        b"avc1" | b"avc3" => "h264",
+        b"hvc1" | b"hev1" | b"dvh1" | b"dvhe" => "hevc",
+        b"av01" => "av1",
+        b"vp09" => "vp9",
+        b"mp4v" => "mpeg4",
+        _ => "",
+    };
+    let children = || boxes(body.get(78..).unwrap_or_default());
+    let dolby_vision = matches!(kind, b"dvh1" | b"dvhe")
+        || children().any(|(child_kind, _)| matches!(child_kind, b"dvcC" | b"dvvC"));
+    let hdr_type = if dolby_vision {
+        "dolbyvision"
+    } else {
+        // colr nclx: 色彩原色、传输特性、矩阵系数，传输特性 16 为 PQ (HDR10)，18 为 HLG
+        let transfer = children()
+            .find(|(child_kind, colr)| *child_kind == b"colr" && colr.get(..4) == Some(b"nclx"))
+            .and_then(|(_, colr)| read_u16(colr, 6));
+        match transfer {
+            Some(16) => "hdr10",
+            Some(18) => "hlg",
+            _ => "",
+        }
+    };
+
+    let dimension = |offset| read_u16(body, offset).map(u32::from).filter(|value| *value > 0);
+    video_stream(
+        codec.to_string(),
+        dimension(24),
+        dimension(26),
+        duration,
+        hdr_type.to_string(),
+    )
+}
+
+/// 音频样本描述：声道数位于第 16 字节
+fn audio_sample_entry(entry: (&[u8; 4], &[u8])) -> AudioStream {
+    let (kind, body) = entry;
+    let codec = match kind {
+        b"mp4a" => "aac",
+        b"ac-3" => "ac3",
+        b"ec-3" => "eac3",
+        b"Opus" => "opus",
+        b"fLaC" => "flac",
+        b"alac" => "alac",
+        _ => "",
+    };
+    AudioStream {
+        codec: codec.to_string(),
+        channels: read_u16(body, 16).map(u32::from).filter(|channels| *channels > 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 最小的 MP4 样本：1920x1080 HEVC (HDR10) 视频轨与双声道 AAC 音频轨，时长 5400 秒，不含媒体数据
+    const SAMPLE_MP4: &[u8] = include_bytes!("tests/fixtures/sample.mp4");
+
+    #[test]
+    fn test_probe_iso_bmff() {
+        let dir = std::env::temp_dir().join("test_media_probe");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let sample = dir.join("sample.mp4");
+        std::fs::write(&sample, SAMPLE_MP4).unwrap();
+
+        let details = probe_media(&sample, None).unwrap();
+        assert_eq!(
+            details,
+            StreamDetails {
+                video: vec![VideoStream {
+                    codec: "hevc".to_string(),
+                    aspect: "1.78".to_string(),
+                    width: Some(1920),
+                    height: Some(1080),
+                    duration_in_seconds: Some(5400),
+                    hdr_type: "hdr10".to_string(),
+                }],
+                audio: vec![AudioStream {
+                    codec: "aac".to_string(),
+                    channels: Some(2),
+                }],
+            }
+        );
+
+        // 无法解析的文件返回错误而不是 panic
+        let broken = dir.join("broken.mp4");
+        std::fs::write(&broken, &SAMPLE_MP4[..40]).unwrap();
+        assert!(probe_media(&broken, None).is_err());
+        let garbage = dir.join("garbage.mp4");
+        std::fs::write(&garbage, b"not a video file at all").unwrap();
+        assert!(probe_media(&garbage, None).is_err());
+        // 64 位盒大小与偏移相加溢出
+        let overflow = dir.join("overflow.mp4");
+        let mut data = b"\x00\x00\x00\x10ftypisom\x00\x00\x00\x00\x00\x00\x00\x01free".to_vec();
+        data.extend_from_slice(&(u64::MAX - 8).to_be_bytes());
+        std::fs::write(&overflow, data).unwrap();
+        assert!(probe_iso_bmff(&overflow).unwrap_err().to_string().contains("盒大小无效"));
+        assert!(probe_media(&dir.join("sample.mkv"), None).is_err());
+        assert!(probe_media(&dir.join("missing.mp4"), None).is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_parse_ffprobe_output() {
+        let output = br#"{
+            "streams": [
+                {"codec_type": "video", "codec_name": "h264", "width": 3840, "height": 2160,
+                 "color_transfer": "arib-std-b67", "disposition": {"attached_pic": 0}},
+                {"codec_type": "audio", "codec_name": "eac3", "channels": 6},
+                {"codec_type": "video", "codec_name": "mjpeg", "width": 600, "height": 400,
+                 "disposition": {"attached_pic": 1}}
+            ],
+            "format": {"duration": "7199.6"}
+        }"#;
+        let details = parse_ffprobe_output(output).unwrap();
+        assert_eq!(details.video.len(), 1);
+        assert_eq!(details.video[0].codec, "h264");
+        assert_eq!(details.video[0].aspect, "1.78");
+        assert_eq!(details.video[0].width, Some(3840));
+        assert_eq!(details.video[0].duration_in_seconds, Some(7200));
+        assert_eq!(details.video[0].hdr_type, "hlg");
+        assert_eq!(details.audio, vec![AudioStream { codec: "eac3".to_string(), channels: Some(6) }]);
+
+        assert!(parse_ffprobe_output(b"not json").is_err());
+    }
+}
@@ -0,0 +1,518 @@
+// 处理状态发布：通过 MQTT 发布每个文件的处理状态与处理队列统计
+//
+// 只实现发布所需的 MQTT 3.1.1 子集（CONNECT、QoS 0 PUBLISH、PINGREQ），
+// 消息经通道交给后台任务发送，服务器不可用时丢弃消息并在之后重连
+
+use std::{path::Path, time::Duration};
+
+use anyhow::Context;
+use serde::Serialize;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+        TcpStream,
+    },
+    sync::mpsc,
+    time::Instant,
+};
+
+use crate::{config::MqttConfig, pipeline::ProcessOutcome};
+
+/// 待发送消息的最大数量，超出时丢弃新消息
+const QUEUE_CAPACITY: usize = 256;
+/// 连接失败后的重连间隔，期间的消息直接丢弃
+const RECONNECT_DELAY: Duration = Duration::from_secs(30);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 状态消息的发布方式
+pub trait StatusPublisher: Send + Sync {
+    /// 发布消息，不等待发送完成，发送失败不影响文件处理
+    fn publish(&self, topic: &str, payload: String);
+}
+
+/// 处理队列统计
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct QueueStats {
+    pub processed: usize,
+    pub organized: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    /// 等待处理的文件数
+    pub pending: usize,
+}
+
+impl QueueStats {
+    /// 记录一个文件的处理结果，`pending` 为等待处理的文件数
+    pub fn record(&mut self, result: &anyhow::Result<ProcessOutcome>, pending: usize) {
+        self.processed += 1;
+        self.pending = pending;
+        match result {
+            Ok(ProcessOutcome::Organized { .. }) => self.organized += 1,
+            Ok(ProcessOutcome::Skipped { .. }) => self.skipped += 1,
+            Err(_) => self.failed += 1,
+        }
+    }
+}
+
+/// 向状态主题发布文件处理状态，每个文件处理完成后向统计主题发布队列统计
+pub struct QueueStatus {
+    publisher: Box<dyn StatusPublisher>,
+    status_topic: String,
+    stats_topic: String,
+    stats: QueueStats,
+}
+
+impl QueueStatus {
+    pub fn new(publisher: Box<dyn StatusPublisher>, config: &MqttConfig) -> Self {
+        Self {
+            publisher,
+            status_topic: config.status_topic.clone(),
+            stats_topic: config.stats_topic.clone(),
+            stats: QueueStats::default(),
+        }
+    }
+
+    /// 根据配置连接 MQTT 服务器，未配置服务器地址时返回 None
+    pub fn from_config(config: &MqttConfig) -> Option<Self> {
+        let publisher = MqttPublisher::from_config(config)?;
+        Some(Self::new(Box::new(publisher), config))
+    }
+
+    /// 文件开始处理
+    pub fn processing(&self, file_path: &Path, movie_id: &str) {
+        self.publish_status(file_path, movie_id, "processing", "");
+    }
+
+    /// 文件处理失败，等待后进行第 `attempt` 次重试
+    pub fn retrying(&self, file_path: &Path, movie_id: &str, attempt: u32, error: &anyhow::Error) {
+        self.publish_status(file_path, movie_id, "retrying", &format!("第 {} 次重试: {:#}", attempt, error));
+    }
+
+    /// 文件处理完成，`pending` 为等待处理的文件数
+    pub fn finished(
+        &mut self,
+        file_path: &Path,
+        movie_id: &str,
+        result: &anyhow::Result<ProcessOutcome>,
+        pending: usize,
+    ) {
+        self.stats.record(result, pending);
+        match result {
+            Ok(ProcessOutcome::Organized { movie_id, video_path, .. }) => {
+                self.publish_status(file_path, movie_id, "done", &video_path.display().to_string());
+            }
+            Ok(ProcessOutcome::Skipped { reason }) => {
+                self.publish_status(file_path, movie_id, "skipped", reason);
+            }
+            Err(e) => {
+                self.publish_status(file_path, movie_id, "failed", &format!("{:#}", e));
+            }
+        }
+
+        match serde_json::to_string(&self.stats) {
+            Ok(payload) => self.publisher.publish(&self.stats_topic, payload),
+            Err(e) => log::warn!("序列化处理队列统计失败: {}", e),
+        }
+    }
+
+    fn publish_status(&self, file_path: &Path, movie_id: &str, status: &str, message: &str) {
+        let payload = serde_json::json!({
+            "id": movie_id,
+            "file": file_path.display().to_string(),
+            "status": status,
+            "message": message,
+        });
+        self.publisher.publish(&self.status_topic, payload.to_string());
+    }
+}
+
+/// 通过 MQTT 发布消息，连接与发送在后台任务中进行
+#[derive(Debug)]
+pub struct MqttPublisher {
+    sender: mpsc::Sender<(String, String)>,
+}
+
+impl MqttPublisher {
+    /// 启动后台发送任务，未配置服务器地址时返回 None；需要在 tokio 运行时中调用
+    pub fn from_config(config: &MqttConfig) -> Option<Self> {
+        config.host.as_ref().filter(|host| !host.trim().is_empty())?;
+        let (sender, receiver) = mpsc::channel(QUEUE_CAPACITY);
+        tokio::spawn(run_connection(config.clone(), receiver));
+        Some(Self { sender })
+    }
+}
+
+impl StatusPublisher for MqttPublisher {
+    fn publish(&self, topic: &str, payload: String) {
+        if let Err(e) = self.sender.try_send((topic.to_string(), payload)) {
+            log::debug!("MQTT 消息队列不可用，丢弃发往 {} 的消息: {}", topic, e);
+        }
+    }
+}
+
+/// 后台发送任务：按需连接服务器，写入失败时重连并重发一次
+///
+/// 连接期间持续读取并丢弃服务器发来的数据（如 PINGRESP），读到连接关闭或出错时断开，
+/// 下一条消息重新连接
+async fn run_connection(config: MqttConfig, mut receiver: mpsc::Receiver<(String, String)>) {
+    let host = config.host.clone().unwrap_or_default();
+    let keep_alive = Duration::from_secs(config.keep_alive.into());
+    let mut stream: Option<OwnedWriteHalf> = None;
+    let mut reader: Option<OwnedReadHalf> = None;
+    let mut last_sent = Instant::now();
+    let mut retry_at: Option<Instant> = None;
+    let mut buffer = [0u8; 64];
+
+    loop {
+        let send_ping = config.keep_alive > 0 && stream.is_some();
+        let message = tokio::select! {
+            message = receiver.recv() => match message {
+                Some(message) => Some(message),
+                None => break,
+            },
+            read = read_server(&mut reader, &mut buffer) => {
+                match read {
+                    Ok(0) => log::warn!("MQTT 服务器关闭了连接"),
+                    Ok(_) => continue,
+                    Err(e) => log::warn!("MQTT 连接已断开: {}", e),
+                }
+                stream = None;
+                reader = None;
+                continue;
+            }
+            // 空闲时发送心跳
+            _ = tokio::time::sleep_until(last_sent + keep_alive), if send_ping => None,
+        };
+        let packet = match &message {
+            Some((topic, payload)) => publish_packet(topic, payload.as_bytes()),
+            None => vec![0xC0, 0x00],
+        };
+
+        for attempt in 0..2 {
+            if stream.is_none() {
+                if retry_at.is_some_and(|at| Instant::now() < at) {
+                    log::debug!("MQTT 服务器 {}:{} 不可用，丢弃消息", host, config.port);
+                    break;
+                }
+                match connect(&host, &config).await {
+                    Ok(connected) => {
+                        log::info!("已连接 MQTT 服务器 {}:{}", host, config.port);
+                        let (read_half, write_half) = connected.into_split();
+                        stream = Some(write_half);
+                        reader = Some(read_half);
+                        retry_at = None;
+                    }
+                    Err(e) => {
+                        log::warn!(
+                            "连接 MQTT 服务器 {}:{} 失败: {:#}，{} 秒内不再重试",
+                            host,
+                            config.port,
+                            e,
+                            RECONNECT_DELAY.as_secs()
+                        );
+                        retry_at = Some(Instant::now() + RECONNECT_DELAY);
+                        break;
+                    }
+                }
+            }
+
+            let Some(connected) = stream.as_mut() else {
+                break;
+            };
+            match connected.write_all(&packet).await {
+                Ok(()) => {
+                    last_sent = Instant::now();
+                    break;
+                }
+                Err(e) => {
+                    log::warn!("MQTT 连接已断开: {}", e);
+                    stream = None;
+                    reader = None;
+                    // 心跳失败后不需要重发，等下一条消息再重连
+                    if attempt == 0 && message.is_none() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(mut stream) = stream {
+        let _ = stream.write_all(&[0xE0, 0x00]).await;
+    }
+}
+
+/// 从连接的读取端读取数据，没有连接时一直等待
+async fn read_server(reader: &mut Option<OwnedReadHalf>, buffer: &mut [u8]) -> std::io::Result<usize> {
+    match reader {
+        Some(reader) => reader.read(buffer).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// 建立连接并完成 CONNECT/CONNACK 握手
+async fn connect(host: &str, config: &MqttConfig) -> anyhow::Result<TcpStream> {
+    let handshake = async {
+        let mut stream = TcpStream::connect((host, config.port)).await?;
+        stream.write_all(&connect_packet(config)).await?;
+
+        let mut connack = [0u8; 4];
+        stream.read_exact(&mut connack).await?;
+        if connack[0] != 0x20 || connack[1] != 0x02 {
+            anyhow::bail!("无效的 CONNACK 响应: {:02x?}", connack);
+        }
+        if connack[3] != 0 {
+            anyhow::bail!("服务器拒绝连接，返回码 {}", connack[3]);
+        }
+        Ok(stream)
+    };
+    tokio::time::timeout(CONNECT_TIMEOUT, handshake)
+        .await
+        .context("连接超时")?
+}
+
+fn connect_packet(config: &MqttConfig) -> Vec<u8> {
+    let username = config.username.as_deref().filter(|name| !name.is_empty());
+    let password = config.password.as_deref().filter(|_| username.is_some());
+
+    // 清除会话
+    let mut flags = 0x02;
+    if username.is_some() {
+        flags |= 0x80;
+    }
+    if password.is_some() {
+        flags |= 0x40;
+    }
+
+    let mut body = Vec::new();
+    push_str(&mut body, "MQTT");
+    body.push(0x04);
+    body.push(flags);
+    body.extend_from_slice(&config.keep_alive.to_be_bytes());
+    push_str(&mut body, &config.client_id);
+    for field in [username, password].into_iter().flatten() {
+        push_str(&mut body, field);
+    }
+    packet(0x10, &body)
+}
+
+fn publish_packet(topic: &str, payload: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(topic.len() + payload.len() + 2);
+    push_str(&mut body, topic);
+    body.extend_from_slice(payload);
+    packet(0x30, &body)
+}
+
+/// 固定报头：类型与剩余长度（变长编码）
+fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = vec![header];
+    let mut remaining = body.len();
+    loop {
+        let mut byte = (remaining % 128) as u8;
+        remaining /= 128;
+        if remaining > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if remaining == 0 {
+            break;
+        }
+    }
+    packet.extend_from_slice(body);
+    packet
+}
+
+/// 写入带两字节长度前缀的 UTF-8 字符串，超长时在字符边界处截断，避免产生无效的 UTF-8
+fn push_str(buffer: &mut Vec<u8>, value: &str) {
+    let mut len = value.len().min(u16::MAX as usize);
+    while !value.is_char_boundary(len) {
+        len -= 1;
+    }
+    let bytes = &value.as_bytes()[..len];
+    buffer.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+    buffer.extend_from_slice(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        path::PathBuf,
+        sync::{Arc, Mutex},
+    };
+    use tokio::net::TcpListener;
+
+    #[derive(Clone, Default)]
+    struct RecordingPublisher(Arc<Mutex<Vec<(String, serde_json::Value)>>>);
+
+    impl StatusPublisher for RecordingPublisher {
+        fn publish(&self, topic: &str, payload: String) {
+            let payload = serde_json::from_str(&payload).unwrap();
+            self.0.lock().unwrap().push((topic.to_string(), payload));
+        }
+    }
+
+    #[test]
+    fn test_queue_status_messages() {
+        let recorder = RecordingPublisher::default();
+        let mut status = QueueStatus::new(Box::new(recorder.clone()), &MqttConfig::default());
+
+        let file = Path::new("/input/MQTT-001.mp4");
+        status.processing(file, "MQTT-001");
+        let organized = Ok(ProcessOutcome::Organized {
+            movie_id: "MQTT-001".to_string(),
+            video_path: PathBuf::from("/output/MQTT-001/MQTT-001.mp4"),
+            nfo_paths: Vec::new(),
+        });
+        status.finished(file, "MQTT-001", &organized, 1);
+        status.finished(
+            Path::new("/input/MQTT-002.mp4"),
+            "MQTT-002",
+            &Err(anyhow::anyhow!("爬取失败")),
+            0,
+        );
+
+        let messages = recorder.0.lock().unwrap();
+        let statuses: Vec<_> = messages
+            .iter()
+            .filter(|(topic, _)| topic == "jav-tidy/status")
+            .map(|(_, payload)| payload["status"].as_str().unwrap())
+            .collect();
+        assert_eq!(statuses, vec!["processing", "done", "failed"]);
+        assert_eq!(messages[1].1["message"], "/output/MQTT-001/MQTT-001.mp4");
+
+        let (topic, stats) = messages.last().unwrap();
+        assert_eq!(topic, "jav-tidy/stats");
+        assert_eq!(
+            *stats,
+            serde_json::json!({"processed": 2, "organized": 1, "skipped": 0, "failed": 1, "pending": 0})
+        );
+    }
+
+    /// 读取一个 MQTT 报文，返回类型与报文内容
+    async fn read_packet(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+        let header = stream.read_u8().await.unwrap();
+        let (mut length, mut shift) = (0usize, 0);
+        loop {
+            let byte = stream.read_u8().await.unwrap();
+            length |= ((byte & 0x7F) as usize) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        let mut body = vec![0; length];
+        stream.read_exact(&mut body).await.unwrap();
+        (header, body)
+    }
+
+    #[test]
+    fn test_push_str_truncates_at_char_boundary() {
+        let mut buffer = Vec::new();
+        // 第 65535 个字节落在 `あ` 的中间
+        push_str(&mut buffer, &format!("é{}", "あ".repeat(30_000)));
+        let len = u16::from_be_bytes([buffer[0], buffer[1]]) as usize;
+        assert_eq!(len, 65_534);
+        assert_eq!(buffer.len(), len + 2);
+        assert!(std::str::from_utf8(&buffer[2..]).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_mqtt_publisher_sends_to_broker() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let broker = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let (header, connect) = read_packet(&mut stream).await;
+            assert_eq!(header, 0x10);
+            assert_eq!(&connect[..7], b"\x00\x04MQTT\x04");
+            stream.write_all(&[0x20, 0x02, 0x00, 0x00]).await.unwrap();
+
+            let mut published = Vec::new();
+            while published.len() < 2 {
+                let (header, body) = read_packet(&mut stream).await;
+                assert_eq!(header, 0x30);
+                let topic_len = u16::from_be_bytes([body[0], body[1]]) as usize;
+                let topic = String::from_utf8(body[2..2 + topic_len].to_vec()).unwrap();
+                let payload = String::from_utf8(body[2 + topic_len..].to_vec()).unwrap();
+                published.push((topic, payload));
+            }
+            published
+        });
+
+        let publisher = MqttPublisher::from_config(&MqttConfig {
+            host: Some("127.0.0.1".to_string()),
+            port,
+            ..Default::default()
+        })
+        .unwrap();
+        publisher.publish("jav-tidy/status", r#"{"status":"processing"}"#.to_string());
+        publisher.publish("jav-tidy/stats", "x".repeat(300));
+
+        let published = tokio::time::timeout(Duration::from_secs(5), broker)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(published[0], ("jav-tidy/status".to_string(), r#"{"status":"processing"}"#.to_string()));
+        assert_eq!(published[1].1.len(), 300);
+    }
+
+    #[tokio::test]
+    async fn test_mqtt_publisher_without_broker() {
+        // 端口上没有服务器时丢弃消息，不阻塞也不出错
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let publisher = MqttPublisher::from_config(&MqttConfig {
+            host: Some("127.0.0.1".to_string()),
+            port,
+            ..Default::default()
+        })
+        .unwrap();
+        for _ in 0..QUEUE_CAPACITY * 2 {
+            publisher.publish("jav-tidy/status", "{}".to_string());
+        }
+
+        assert!(MqttPublisher::from_config(&MqttConfig::default()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_mqtt_publisher_reconnects_after_broker_disconnect() {
+        /// 完成握手并读取一条 PUBLISH 报文，返回其中的主题
+        async fn accept_publish(listener: &TcpListener) -> (TcpStream, String) {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            assert_eq!(read_packet(&mut stream).await.0, 0x10);
+            stream.write_all(&[0x20, 0x02, 0x00, 0x00]).await.unwrap();
+            let (header, body) = read_packet(&mut stream).await;
+            assert_eq!(header, 0x30);
+            let topic_len = u16::from_be_bytes([body[0], body[1]]) as usize;
+            (stream, String::from_utf8(body[2..2 + topic_len].to_vec()).unwrap())
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let publisher = MqttPublisher::from_config(&MqttConfig {
+            host: Some("127.0.0.1".to_string()),
+            port,
+            ..Default::default()
+        })
+        .unwrap();
+
+        publisher.publish("jav-tidy/first", "{}".to_string());
+        let (stream, topic) = tokio::time::timeout(Duration::from_secs(5), accept_publish(&listener))
+            .await
+            .unwrap();
+        assert_eq!(topic, "jav-tidy/first");
+
+        // 服务器关闭连接后，后台任务读到连接关闭，下一条消息通过新连接发送
+        drop(stream);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        publisher.publish("jav-tidy/second", "{}".to_string());
+        let (_stream, topic) = tokio::time::timeout(Duration::from_secs(5), accept_publish(&listener))
+            .await
+            .unwrap();
+        assert_eq!(topic, "jav-tidy/second");
+    }
+}
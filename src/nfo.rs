@@ -1,6 +1,14 @@
+use crate::config::NfoTitleStyle;
+use chrono::{Datelike, Local, NaiveDate};
 use crawler_template::Crawler;
 use serde::{Deserialize, Serialize};
-use std::str::FromStr;
+use std::{collections::HashMap, path::Path, str::FromStr};
+
+/// 爬取到的日期可接受的格式
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%Y年%m月%d日"];
+
+/// 合理的最早发行年份，更早或晚于明年的日期视为无效
+const MIN_RELEASE_YEAR: i32 = 1950;
 
 /// 媒体中心类型枚举 - 基于 NFO 研究，所有平台都使用 Kodi 标准
 #[derive(Debug, Clone, PartialEq)]
@@ -24,7 +32,17 @@ pub struct Actor {
 impl FromStr for Actor {
     type Err = anyhow::Error;
 
+    /// 支持纯演员名，以及模板 `group` 节点生成的 name/role/thumb/order 记录
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(mut record) = crawler_template::parse_record(s) {
+            return Ok(Actor {
+                name: record.remove("name").unwrap_or_default(),
+                role: record.remove("role").unwrap_or_default(),
+                thumb: record.remove("thumb").unwrap_or_default(),
+                order: record.get("order").and_then(|order| order.trim().parse().ok()),
+            });
+        }
+
         Ok(Actor {
             name: s.to_string(),
             ..Default::default()
@@ -106,10 +124,50 @@ pub struct ArtWork {
     pub landscape: String, // 横版图 URL
 }
 
-// 移除复杂的文件信息结构，根据 NFO 研究，大多数用户不需要技术细节
+/// 文件信息 - Kodi 标准的 `<fileinfo><streamdetails>` 嵌套，媒体中心据此显示分辨率等标识
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct FileInfo {
+    #[serde(rename = "streamdetails", default)]
+    pub stream_details: StreamDetails,
+}
+
+/// 视频文件的流信息
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct StreamDetails {
+    #[serde(rename = "video", default, skip_serializing_if = "Vec::is_empty")]
+    pub video: Vec<VideoStream>,
+    #[serde(rename = "audio", default, skip_serializing_if = "Vec::is_empty")]
+    pub audio: Vec<AudioStream>,
+}
+
+/// 视频流信息
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct VideoStream {
+    #[serde(rename = "codec", default, skip_serializing_if = "String::is_empty")]
+    pub codec: String, // 编码 (h264, hevc, av1 等)
+    #[serde(rename = "aspect", default, skip_serializing_if = "String::is_empty")]
+    pub aspect: String, // 宽高比，保留两位小数
+    #[serde(rename = "width", default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(rename = "height", default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    #[serde(rename = "durationinseconds", default, skip_serializing_if = "Option::is_none")]
+    pub duration_in_seconds: Option<u64>,
+    #[serde(rename = "hdrtype", default, skip_serializing_if = "String::is_empty")]
+    pub hdr_type: String, // HDR 类型 (hdr10, hlg, dolbyvision)，SDR 时为空
+}
+
+/// 音频流信息
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct AudioStream {
+    #[serde(rename = "codec", default, skip_serializing_if = "String::is_empty")]
+    pub codec: String, // 编码 (aac, ac3, eac3 等)
+    #[serde(rename = "channels", default, skip_serializing_if = "Option::is_none")]
+    pub channels: Option<u32>,
+}
 
 /// 简化的电影 NFO 数据结构 - 基于 NFO 研究，专注核心字段和通用兼容性
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(rename = "movie")]
 pub struct MovieNfo {
     // === 必需的基本信息标签 ===
@@ -126,6 +184,15 @@ pub struct MovieNfo {
     #[serde(rename = "plot", default, skip_serializing_if = "String::is_empty")]
     pub plot: String, // 通用支持
 
+    // Kodi/Jellyfin 读取 <outline>，旧版 Kodi 使用的 <plotoutline> 读取时同样接受
+    #[serde(
+        rename = "outline",
+        alias = "plotoutline",
+        default,
+        skip_serializing_if = "String::is_empty"
+    )]
+    pub plot_outline: String, // 简介，媒体中心与完整剧情分开显示
+
     #[serde(rename = "tagline", default, skip_serializing_if = "String::is_empty")]
     pub tagline: String, // 通用支持
 
@@ -140,6 +207,9 @@ pub struct MovieNfo {
     )]
     pub premiered: String, // 通用支持
 
+    #[serde(rename = "aired", default, skip_serializing_if = "String::is_empty")]
+    pub aired: String, // 部分媒体中心读取 aired 作为首映日期
+
     #[serde(
         rename = "releasedate",
         default,
@@ -193,18 +263,70 @@ pub struct MovieNfo {
     #[serde(rename = "art", default, skip_serializing_if = "Option::is_none")]
     pub art: Option<ArtWork>, // 通用支持
 
+    // === 文件信息 ===
+    #[serde(rename = "fileinfo", default, skip_serializing_if = "Option::is_none")]
+    pub file_info: Option<FileInfo>, // 启用 probe_media 时由视频文件读取
+
     // === 成人内容标记 ===
     #[serde(rename = "isadult", default)]
     pub is_adult: bool, // Emby/Jellyfin 支持
 }
 
+/// 可以通过 `nfo.omit_fields` 省略的字段
+///
+/// 标题与唯一标识用于识别已整理的影片，不允许省略；`is_adult` 始终输出
+pub const OMITTABLE_FIELDS: &[&str] = &[
+    "original_title",
+    "plot",
+    "plot_outline",
+    "tagline",
+    "year",
+    "premiered",
+    "aired",
+    "release_date",
+    "runtime",
+    "rating",
+    "ratings",
+    "imdb_id",
+    "genres",
+    "tags",
+    "studios",
+    "directors",
+    "actors",
+    "mpaa",
+    "set",
+    "art",
+    "file_info",
+];
+
+/// 检查省略字段列表，未知字段名视为配置错误
+pub fn validate_omit_fields(fields: &[String]) -> anyhow::Result<()> {
+    let unknown: Vec<&str> = fields
+        .iter()
+        .map(|field| field.trim())
+        .filter(|field| !OMITTABLE_FIELDS.contains(field))
+        .collect();
+    if !unknown.is_empty() {
+        anyhow::bail!(
+            "nfo.omit_fields 包含未知字段: {}，可用字段: {}",
+            unknown.join(", "),
+            OMITTABLE_FIELDS.join(", ")
+        );
+    }
+    Ok(())
+}
+
 /// 简化的爬虫数据结构 - 匹配简化的 NFO 结构
-#[derive(Debug, Default, Clone, Crawler)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Crawler)]
+#[serde(default)]
 pub struct MovieNfoCrawler {
     // 基本信息
+    #[crawler(trim)]
     pub title: String,
+    #[crawler(trim)]
     pub original_title: Option<String>,
     pub plot: String,
+    pub plot_outline: String,
     pub tagline: String,
 
     // 时间信息
@@ -220,6 +342,8 @@ pub struct MovieNfoCrawler {
     pub imdb_id: String,
     pub tmdb_id: String,
     pub tvdb_id: String,
+    /// 数据来源站点中的影片编号，模板没有提供时由爬取流程填入影片ID
+    pub site_id: String,
 
     // 分类信息
     pub genres: Vec<String>,
@@ -247,8 +371,12 @@ pub struct MovieNfoCrawler {
     pub ranking_categories: Vec<String>,
 
     // 电影系列/集合信息
+    #[crawler(trim)]
     pub series_name: String,
     pub series_overview: String,
+
+    // 数据来源的模板文件名，由爬取流程填写，用于影片分类
+    pub source_template: String,
 }
 
 impl MovieNfo {
@@ -262,6 +390,197 @@ impl MovieNfo {
         nfo
     }
 
+    /// 解析已有的 NFO 文件内容，忽略 XML 声明、注释与未知标签
+    pub fn from_xml(xml: &str) -> anyhow::Result<Self> {
+        quick_xml::de::from_str(xml).map_err(|e| anyhow::anyhow!("解析NFO失败: {}", e))
+    }
+
+    /// 读取 NFO 文件
+    pub fn read_from(path: &Path) -> anyhow::Result<Self> {
+        let xml = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("无法读取NFO文件 {}: {}", path.display(), e))?;
+        Self::from_xml(&xml).map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))
+    }
+
+    /// 影片年份，没有年份时由首映或发行日期得出
+    pub fn release_year(&self) -> Option<u16> {
+        self.year.or_else(|| {
+            [&self.premiered, &self.release_date]
+                .into_iter()
+                .find_map(|date| parse_release_date(date))
+                .and_then(|date| u16::try_from(date.year()).ok())
+        })
+    }
+
+    /// 规范化 NFO 内容，使相同的数据无论抓取和合并顺序如何都生成相同的 XML
+    ///
+    /// 去除所有文本字段首尾空白；类型、标签、制作商和导演按不区分大小写的顺序排序并去重；
+    /// 演员按 `order` 排序，未设置 `order` 的演员按原有顺序排在最后并补全 `order`
+    pub fn normalize(&mut self) {
+        for field in [
+            &mut self.title,
+            &mut self.original_title,
+            &mut self.plot,
+            &mut self.plot_outline,
+            &mut self.tagline,
+            &mut self.premiered,
+            &mut self.aired,
+            &mut self.release_date,
+            &mut self.imdb_id,
+            &mut self.mpaa,
+        ] {
+            trim_in_place(field);
+        }
+
+        for list in [
+            &mut self.genres,
+            &mut self.tags,
+            &mut self.studios,
+            &mut self.directors,
+        ] {
+            normalize_list(list);
+        }
+
+        if let Some(set) = &mut self.set {
+            trim_in_place(&mut set.name);
+            trim_in_place(&mut set.overview);
+        }
+
+        for actor in &mut self.actors {
+            trim_in_place(&mut actor.name);
+            trim_in_place(&mut actor.role);
+            trim_in_place(&mut actor.thumb);
+        }
+        self.actors.retain(|actor| !actor.name.is_empty());
+
+        let mut next_order = self
+            .actors
+            .iter()
+            .filter_map(|actor| actor.order)
+            .max()
+            .unwrap_or(0);
+        for actor in self.actors.iter_mut().filter(|actor| actor.order.is_none()) {
+            next_order += 1;
+            actor.order = Some(next_order);
+        }
+        self.actors
+            .sort_by(|a, b| a.order.cmp(&b.order).then_with(|| a.name.cmp(&b.name)));
+        let mut seen = std::collections::HashSet::new();
+        self.actors
+            .retain(|actor| seen.insert(actor.name.to_lowercase()));
+    }
+
+    /// 没有简介时截取剧情的前 `max_chars` 个字符作为简介，超出部分以省略号结尾
+    ///
+    /// `max_chars` 为 0 时不生成
+    pub fn fill_plot_outline(&mut self, max_chars: usize) {
+        if max_chars == 0 || !self.plot_outline.trim().is_empty() {
+            return;
+        }
+
+        let plot = self.plot.trim();
+        self.plot_outline = if plot.chars().count() <= max_chars {
+            plot.to_string()
+        } else {
+            let truncated: String = plot.chars().take(max_chars).collect();
+            format!("{}…", truncated.trim_end())
+        };
+    }
+
+    /// 类型或标签中包含任一成人内容标记（不区分大小写）时标记为成人内容
+    ///
+    /// 没有命中时保留爬取到的 `is_adult`
+    pub fn apply_adult_markers(&mut self, markers: &[String]) {
+        let is_marker = |value: &String| {
+            markers
+                .iter()
+                .any(|marker| marker.trim().eq_ignore_ascii_case(value.trim()))
+        };
+        if self.genres.iter().chain(&self.tags).any(is_marker) {
+            self.is_adult = true;
+        }
+    }
+
+    /// 没有系列时按制作商查找映射的系列名称，第一个命中的制作商生效
+    pub fn apply_label_series(&mut self, label_series_map: &HashMap<String, String>) {
+        if self.set.as_ref().is_some_and(|set| !set.name.trim().is_empty()) {
+            return;
+        }
+
+        let series = self.studios.iter().find_map(|studio| {
+            label_series_map
+                .iter()
+                .find(|(label, _)| label.trim().eq_ignore_ascii_case(studio.trim()))
+                .map(|(_, series)| series.trim())
+                .filter(|series| !series.is_empty())
+        });
+        if let Some(series) = series {
+            self.set = Some(MovieSet {
+                name: series.to_string(),
+                overview: String::new(),
+            });
+        }
+    }
+
+    /// 清空指定字段，序列化时对应元素整体省略
+    ///
+    /// 字段名需先经过 [`validate_omit_fields`] 检查，未知字段名会被忽略
+    pub fn redact(&mut self, fields: &[String]) {
+        for field in fields {
+            match field.trim() {
+                "original_title" => self.original_title.clear(),
+                "plot" => self.plot.clear(),
+                "plot_outline" => self.plot_outline.clear(),
+                "tagline" => self.tagline.clear(),
+                "year" => self.year = None,
+                "premiered" => self.premiered.clear(),
+                "aired" => self.aired.clear(),
+                "release_date" => self.release_date.clear(),
+                "runtime" => self.runtime = None,
+                "rating" => self.rating = None,
+                "ratings" => self.ratings = None,
+                "imdb_id" => self.imdb_id.clear(),
+                "genres" => self.genres.clear(),
+                "tags" => self.tags.clear(),
+                "studios" => self.studios.clear(),
+                "directors" => self.directors.clear(),
+                "actors" => self.actors.clear(),
+                "mpaa" => self.mpaa.clear(),
+                "set" => self.set = None,
+                "art" => self.art = None,
+                "file_info" => self.file_info = None,
+                _ => {}
+            }
+        }
+    }
+
+    /// 写入视频文件的流信息，爬取结果没有时长时用视频时长（四舍五入到分钟）补全
+    pub fn apply_stream_details(&mut self, details: StreamDetails) {
+        if self.runtime.is_none() {
+            self.runtime = details
+                .video
+                .iter()
+                .find_map(|video| video.duration_in_seconds)
+                .map(|seconds| (seconds + 30) / 60)
+                .filter(|minutes| *minutes > 0)
+                .and_then(|minutes| u32::try_from(minutes).ok());
+        }
+        self.file_info = Some(FileInfo {
+            stream_details: details,
+        });
+    }
+
+    /// 按标题风格改写标题，没有标题时 `id - title` 只写影片ID
+    pub fn apply_title_style(&mut self, style: NfoTitleStyle, movie_id: &str) {
+        let title = self.title.trim();
+        self.title = match style {
+            NfoTitleStyle::Title => return,
+            NfoTitleStyle::Id => movie_id.to_string(),
+            NfoTitleStyle::IdTitle if title.is_empty() => movie_id.to_string(),
+            NfoTitleStyle::IdTitle => format!("{} - {}", movie_id, title),
+        };
+    }
+
     /// 构建通用的评分系统，包含 TOP250 排名
     fn build_universal_ratings(crawler: &MovieNfoCrawler) -> Option<Ratings> {
         let mut ratings = Vec::new();
@@ -333,6 +652,19 @@ impl MovieNfo {
             });
         }
 
+        // 添加来源站点的影片编号，类型为模板名称（如 javdb），没有 IMDB ID 时设为默认
+        let site = crawler
+            .source_template
+            .rsplit_once('.')
+            .map_or(crawler.source_template.as_str(), |(stem, _)| stem);
+        if !site.is_empty() && !crawler.site_id.trim().is_empty() {
+            unique_ids.push(UniqueId {
+                id_type: site.to_string(),
+                default: crawler.imdb_id.is_empty().then_some(true),
+                value: crawler.site_id.trim().to_string(),
+            });
+        }
+
         unique_ids
     }
 
@@ -395,17 +727,37 @@ impl From<MovieNfoCrawler> for MovieNfo {
         // 先构建艺术作品，避免借用冲突
         let art = Self::build_artwork(&crawler);
 
+        // 媒体中心分别读取 premiered、aired 与 releasedate，只爬取到其中一个日期时互相补全
+        let (mut premiered, mut release_date) = match (
+            crawler.premiered.trim().is_empty(),
+            crawler.release_date.trim().is_empty(),
+        ) {
+            (true, false) => (crawler.release_date.clone(), crawler.release_date),
+            (false, true) => (crawler.premiered.clone(), crawler.premiered),
+            _ => (crawler.premiered, crawler.release_date),
+        };
+        // 日期统一为 YYYY-MM-DD，没有年份时由日期补全
+        let premiered_date = normalize_release_date(&mut premiered);
+        let release = normalize_release_date(&mut release_date);
+        let year = crawler.year.or_else(|| {
+            premiered_date
+                .or(release)
+                .and_then(|date| u16::try_from(date.year()).ok())
+        });
+
         MovieNfo {
             // 基本信息
             title: crawler.title,
             original_title: crawler.original_title.unwrap_or_default(),
             plot: crawler.plot,
+            plot_outline: crawler.plot_outline,
             tagline: crawler.tagline,
 
             // 时间信息
-            year: crawler.year,
-            premiered: crawler.premiered,
-            release_date: crawler.release_date,
+            year,
+            aired: premiered.clone(),
+            premiered,
+            release_date,
             runtime: crawler.runtime,
 
             // 评分信息 (简单)
@@ -447,6 +799,61 @@ impl From<MovieNfoCrawler> for MovieNfo {
     }
 }
 
+/// 按 [`DATE_FORMATS`] 解析日期，年份不在合理范围内时返回 None
+pub fn parse_release_date(value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+        .filter(|date| (MIN_RELEASE_YEAR..=Local::now().year() + 1).contains(&date.year()))
+}
+
+/// 将日期规范化为 ISO-8601（YYYY-MM-DD）
+///
+/// 无法识别格式的日期原样保留；能解析但年份不合理的日期清空并记录警告
+fn normalize_release_date(value: &mut String) -> Option<NaiveDate> {
+    if value.trim().is_empty() {
+        return None;
+    }
+    if let Some(date) = parse_release_date(value) {
+        *value = date.format("%Y-%m-%d").to_string();
+        return Some(date);
+    }
+
+    let recognized = DATE_FORMATS
+        .iter()
+        .any(|format| NaiveDate::parse_from_str(value.trim(), format).is_ok());
+    if recognized {
+        log::warn!("丢弃不合理的日期: {}", value.trim());
+        value.clear();
+    } else {
+        log::debug!("无法识别的日期格式，保留原值: {}", value);
+    }
+    None
+}
+
+/// 去除字符串首尾空白，没有空白时不重新分配
+fn trim_in_place(value: &mut String) {
+    let trimmed = value.trim();
+    if trimmed.len() != value.len() {
+        *value = trimmed.to_string();
+    }
+}
+
+/// 去除空白与空值后按不区分大小写的顺序排序，仅大小写不同的值只保留一个
+fn normalize_list(values: &mut Vec<String>) {
+    for value in values.iter_mut() {
+        trim_in_place(value);
+    }
+    values.retain(|value| !value.is_empty());
+    values.sort_by(|a, b| {
+        a.to_lowercase()
+            .cmp(&b.to_lowercase())
+            .then_with(|| a.cmp(b))
+    });
+    values.dedup_by(|a, b| a.to_lowercase() == b.to_lowercase());
+}
+
 /// NFO 格式化器 - 简化为通用格式
 pub trait NfoFormatter {
     fn format_to_xml(&self) -> String;
@@ -523,4 +930,410 @@ mod tests {
         assert!(xml.contains("<art>"));
         assert!(xml.contains("<fanart>"));
     }
+
+    #[test]
+    fn test_release_date_fallback() {
+        let nfo = MovieNfo::from(MovieNfoCrawler {
+            release_date: "2024-07-10".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(nfo.premiered, "2024-07-10");
+        assert_eq!(nfo.aired, "2024-07-10");
+        assert_eq!(nfo.release_date, "2024-07-10");
+
+        let xml = nfo.format_to_xml();
+        assert!(xml.contains("<premiered>2024-07-10</premiered>"));
+        assert!(xml.contains("<aired>2024-07-10</aired>"));
+        assert!(xml.contains("<releasedate>2024-07-10</releasedate>"));
+
+        // 两个日期都爬取到时分别保留
+        let nfo = MovieNfo::from(MovieNfoCrawler {
+            premiered: "2024-07-01".to_string(),
+            release_date: "2024-07-10".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(nfo.premiered, "2024-07-01");
+        assert_eq!(nfo.aired, "2024-07-01");
+        assert_eq!(nfo.release_date, "2024-07-10");
+    }
+
+    #[test]
+    fn test_release_date_formats() {
+        for value in ["2024-07-10", "2024/07/10", "2024年7月10日", " 2024年07月10日 "] {
+            let nfo = MovieNfo::from(MovieNfoCrawler {
+                premiered: value.to_string(),
+                ..Default::default()
+            });
+            assert_eq!(nfo.premiered, "2024-07-10", "日期: {}", value);
+            assert_eq!(nfo.release_date, "2024-07-10", "日期: {}", value);
+            assert_eq!(nfo.year, Some(2024), "日期: {}", value);
+        }
+
+        // 已有年份时不覆盖
+        let nfo = MovieNfo::from(MovieNfoCrawler {
+            year: Some(2023),
+            release_date: "2024/07/10".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(nfo.year, Some(2023));
+        assert_eq!(nfo.premiered, "2024-07-10");
+    }
+
+    #[test]
+    fn test_invalid_release_date() {
+        // 年份不合理的日期被清空，不补全年份
+        let nfo = MovieNfo::from(MovieNfoCrawler {
+            premiered: "1900-01-01".to_string(),
+            release_date: "2999/01/01".to_string(),
+            ..Default::default()
+        });
+        assert!(nfo.premiered.is_empty());
+        assert!(nfo.aired.is_empty());
+        assert!(nfo.release_date.is_empty());
+        assert_eq!(nfo.year, None);
+
+        // 无法识别格式的日期原样保留
+        let nfo = MovieNfo::from(MovieNfoCrawler {
+            premiered: "July 2024".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(nfo.premiered, "July 2024");
+        assert_eq!(nfo.year, None);
+
+        assert_eq!(parse_release_date("2024-02-30"), None);
+    }
+
+    #[test]
+    fn test_site_unique_id() {
+        let crawler = MovieNfoCrawler {
+            title: "测试影片".to_string(),
+            site_id: "ABP-123".to_string(),
+            source_template: "javdb.yaml".to_string(),
+            ..Default::default()
+        };
+
+        // 没有 IMDB ID 时来源站点的编号为默认标识符
+        let xml = MovieNfo::for_universal(crawler.clone()).format_to_xml();
+        assert!(xml.contains(r#"<uniqueid type="javdb" default="true">ABP-123</uniqueid>"#));
+
+        let nfo = MovieNfo::for_universal(MovieNfoCrawler {
+            imdb_id: "tt1234567".to_string(),
+            ..crawler
+        });
+        let site = nfo.unique_ids.iter().find(|id| id.id_type == "javdb").unwrap();
+        assert_eq!(site.value, "ABP-123");
+        assert_eq!(site.default, None);
+        assert_eq!(nfo.unique_ids[0].default, Some(true));
+    }
+
+    fn actor(name: &str, order: Option<u32>) -> Actor {
+        Actor {
+            name: name.to_string(),
+            order,
+            ..Default::default()
+        }
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_normalize_produces_identical_xml() {
+        let first = MovieNfoCrawler {
+            title: " 测试电影 ".to_string(),
+            genres: strings(&["剧情", "Drama", "action", "drama"]),
+            tags: strings(&["B", " a ", "c", ""]),
+            studios: strings(&["Studio B", "Studio A"]),
+            actors: vec![
+                actor("演员2", Some(2)),
+                actor("演员1", Some(1)),
+                actor("演员3", None),
+            ],
+            ..Default::default()
+        };
+        let second = MovieNfoCrawler {
+            title: "测试电影".to_string(),
+            genres: strings(&["drama", "action", "剧情", "Drama"]),
+            tags: strings(&["c", "a", "B"]),
+            studios: strings(&["Studio A", "Studio B"]),
+            actors: vec![
+                actor("演员1", Some(1)),
+                actor(" 演员3", None),
+                actor("演员2", Some(2)),
+            ],
+            ..Default::default()
+        };
+
+        let mut first = MovieNfo::for_universal(first);
+        let mut second = MovieNfo::for_universal(second);
+        assert_ne!(first.format_to_xml(), second.format_to_xml());
+
+        first.normalize();
+        second.normalize();
+
+        assert_eq!(first.format_to_xml(), second.format_to_xml());
+        assert_eq!(first.title, "测试电影");
+        assert_eq!(first.genres, strings(&["action", "Drama", "剧情"]));
+        assert_eq!(first.tags, strings(&["a", "B", "c"]));
+        assert_eq!(first.studios, strings(&["Studio A", "Studio B"]));
+        let actors: Vec<_> = first
+            .actors
+            .iter()
+            .map(|actor| (actor.name.as_str(), actor.order))
+            .collect();
+        assert_eq!(
+            actors,
+            vec![("演员1", Some(1)), ("演员2", Some(2)), ("演员3", Some(3))]
+        );
+    }
+
+    #[test]
+    fn test_actor_from_record() {
+        let mut map = std::collections::HashMap::new();
+        map.insert(
+            "actors".to_string(),
+            vec![
+                r#"{"name":"演员A","thumb":"https://example.com/a.jpg"}"#.to_string(),
+                r#"{"name":"演员B","thumb":"https://example.com/b.jpg","order":"2"}"#.to_string(),
+                "演员C".to_string(),
+            ],
+        );
+
+        let crawler = <MovieNfoCrawler as crawler_template::CrawlerData>::parse(&map).unwrap();
+
+        assert_eq!(crawler.actors.len(), 3);
+        assert_eq!(crawler.actors[0].name, "演员A");
+        assert_eq!(crawler.actors[0].thumb, "https://example.com/a.jpg");
+        assert_eq!(crawler.actors[1].name, "演员B");
+        assert_eq!(crawler.actors[1].thumb, "https://example.com/b.jpg");
+        assert_eq!(crawler.actors[1].order, Some(2));
+        assert_eq!(crawler.actors[2].name, "演员C");
+        assert!(crawler.actors[2].thumb.is_empty());
+    }
+
+    #[test]
+    fn test_apply_adult_markers() {
+        let markers = strings(&["成人", "R18"]);
+
+        // 类型命中标记时标记为成人内容
+        let mut nfo = MovieNfo::from(MovieNfoCrawler {
+            genres: strings(&["剧情", " r18 "]),
+            is_adult: Some(false),
+            ..Default::default()
+        });
+        nfo.apply_adult_markers(&markers);
+        assert!(nfo.is_adult);
+
+        // 标签同样参与匹配
+        let mut nfo = MovieNfo::from(MovieNfoCrawler {
+            tags: strings(&["成人"]),
+            ..Default::default()
+        });
+        nfo.apply_adult_markers(&markers);
+        assert!(nfo.is_adult);
+
+        // 没有命中时保留爬取到的值
+        let crawled = |is_adult| {
+            let mut nfo = MovieNfo::from(MovieNfoCrawler {
+                genres: strings(&["剧情"]),
+                is_adult,
+                ..Default::default()
+            });
+            nfo.apply_adult_markers(&markers);
+            nfo.is_adult
+        };
+        assert!(crawled(Some(true)));
+        assert!(!crawled(Some(false)));
+        assert!(!crawled(None));
+    }
+
+    #[test]
+    fn test_apply_label_series() {
+        let map = HashMap::from([("S1 No.1 Style".to_string(), "S1 系列".to_string())]);
+
+        // 制作商命中映射时补全系列
+        let mut nfo = MovieNfo::from(MovieNfoCrawler {
+            studios: strings(&["其他", " s1 no.1 style "]),
+            ..Default::default()
+        });
+        nfo.apply_label_series(&map);
+        assert_eq!(nfo.set.as_ref().unwrap().name, "S1 系列");
+        assert!(nfo.format_to_xml().contains("<set><name>S1 系列</name></set>"));
+
+        // 已有系列时保留爬取到的系列
+        let mut nfo = MovieNfo::from(MovieNfoCrawler {
+            studios: strings(&["S1 No.1 Style"]),
+            series_name: "爬取的系列".to_string(),
+            ..Default::default()
+        });
+        nfo.apply_label_series(&map);
+        assert_eq!(nfo.set.unwrap().name, "爬取的系列");
+
+        // 没有命中时不生成系列
+        let mut nfo = MovieNfo::from(MovieNfoCrawler {
+            studios: strings(&["其他"]),
+            ..Default::default()
+        });
+        nfo.apply_label_series(&map);
+        assert!(nfo.set.is_none());
+    }
+
+    #[test]
+    fn test_plot_outline() {
+        // 模板提供的简介原样保留，不被截取覆盖
+        let mut nfo = MovieNfo::from(MovieNfoCrawler {
+            plot: "完整的剧情介绍，内容很长。".to_string(),
+            plot_outline: "模板提供的简介".to_string(),
+            ..Default::default()
+        });
+        nfo.fill_plot_outline(5);
+        assert_eq!(nfo.plot_outline, "模板提供的简介");
+        let xml = nfo.format_to_xml();
+        assert!(xml.contains("<outline>模板提供的简介</outline>"), "{}", xml);
+
+        // 没有简介时按字符数截取剧情
+        let mut nfo = MovieNfo::from(MovieNfoCrawler {
+            plot: " 完整的剧情介绍，内容很长。 ".to_string(),
+            ..Default::default()
+        });
+        nfo.fill_plot_outline(6);
+        assert_eq!(nfo.plot_outline, "完整的剧情介…");
+
+        // 剧情不超过长度时使用完整剧情，长度为 0 时不生成
+        let mut nfo = MovieNfo::from(MovieNfoCrawler {
+            plot: "短剧情".to_string(),
+            ..Default::default()
+        });
+        nfo.fill_plot_outline(0);
+        assert!(nfo.plot_outline.is_empty());
+        assert!(!nfo.format_to_xml().contains("<outline>"));
+        nfo.fill_plot_outline(10);
+        assert_eq!(nfo.plot_outline, "短剧情");
+
+        // 读取已有NFO时同样接受 <plotoutline>
+        let nfo: MovieNfo =
+            quick_xml::de::from_str("<movie><plotoutline>旧格式简介</plotoutline></movie>").unwrap();
+        assert_eq!(nfo.plot_outline, "旧格式简介");
+    }
+
+    fn redaction_sample() -> MovieNfo {
+        MovieNfo::for_universal(MovieNfoCrawler {
+            title: "测试电影".to_string(),
+            original_title: Some("Test Movie".to_string()),
+            plot: "剧情介绍".to_string(),
+            year: Some(2023),
+            premiered: "2023-05-12".to_string(),
+            rating: Some(8.5),
+            genres: vec!["剧情".to_string()],
+            studios: vec!["测试制作商".to_string()],
+            actors: vec![Actor {
+                name: "演员1".to_string(),
+                ..Default::default()
+            }],
+            series_name: "测试系列".to_string(),
+            posters: vec!["https://example.com/poster.jpg".to_string()],
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_redact_fields() {
+        let fields = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+
+        let mut nfo = redaction_sample();
+        nfo.redact(&[]);
+        let xml = nfo.format_to_xml();
+        for element in ["<plot>", "<actor>", "<studio>", "<set>", "<art>", "<rating>"] {
+            assert!(xml.contains(element), "{} 缺失: {}", element, xml);
+        }
+
+        let mut nfo = redaction_sample();
+        nfo.redact(&fields(&["plot", "actors", "studios"]));
+        let xml = nfo.format_to_xml();
+        for element in ["<plot>", "<actor>", "<studio>"] {
+            assert!(!xml.contains(element), "{} 未省略: {}", element, xml);
+        }
+        assert!(xml.contains("<title>测试电影</title>"), "{}", xml);
+        assert!(xml.contains("<genre>剧情</genre>"), "{}", xml);
+
+        let mut nfo = redaction_sample();
+        nfo.redact(&fields(&["year", "premiered", "rating", "ratings", "set", "art"]));
+        let xml = nfo.format_to_xml();
+        for element in ["<year>", "<premiered>", "<rating>", "<ratings>", "<set>", "<art>"] {
+            assert!(!xml.contains(element), "{} 未省略: {}", element, xml);
+        }
+        assert!(xml.contains("<actor>"), "{}", xml);
+        assert!(xml.contains("<isadult>"), "{}", xml);
+    }
+
+    #[test]
+    fn test_stream_details_xml() {
+        let details = StreamDetails {
+            video: vec![VideoStream {
+                codec: "hevc".to_string(),
+                aspect: "1.78".to_string(),
+                width: Some(3840),
+                height: Some(2160),
+                duration_in_seconds: Some(7170),
+                hdr_type: "hdr10".to_string(),
+            }],
+            audio: vec![AudioStream {
+                codec: "aac".to_string(),
+                channels: Some(2),
+            }],
+        };
+
+        // 没有时长时用视频时长补全
+        let mut nfo = redaction_sample();
+        nfo.runtime = None;
+        nfo.apply_stream_details(details.clone());
+        assert_eq!(nfo.runtime, Some(120));
+        let xml = nfo.format_to_xml();
+        assert!(xml.contains("<runtime>120</runtime>"), "{}", xml);
+        assert!(
+            xml.contains(
+                "<fileinfo><streamdetails><video><codec>hevc</codec><aspect>1.78</aspect><width>3840</width>\
+                 <height>2160</height><durationinseconds>7170</durationinseconds><hdrtype>hdr10</hdrtype></video>\
+                 <audio><codec>aac</codec><channels>2</channels></audio></streamdetails></fileinfo>"
+            ),
+            "{}",
+            xml
+        );
+
+        // 已有时长时保留爬取到的值
+        let mut nfo = redaction_sample();
+        nfo.runtime = Some(118);
+        nfo.apply_stream_details(details);
+        assert_eq!(nfo.runtime, Some(118));
+
+        nfo.redact(&["file_info".to_string()]);
+        assert!(!nfo.format_to_xml().contains("<fileinfo>"));
+    }
+
+    #[test]
+    fn test_validate_omit_fields() {
+        assert!(validate_omit_fields(&[]).is_ok());
+        assert!(validate_omit_fields(&["plot".to_string(), " actors ".to_string()]).is_ok());
+
+        let err = validate_omit_fields(&["plot".to_string(), "title".to_string(), "cast".to_string()])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("title, cast"), "{}", err);
+    }
+
+    #[test]
+    fn test_apply_title_style() {
+        let mut nfo = redaction_sample();
+        nfo.apply_title_style(NfoTitleStyle::Title, "ABC-123");
+        assert_eq!(nfo.title, "测试电影");
+        nfo.apply_title_style(NfoTitleStyle::IdTitle, "ABC-123");
+        assert_eq!(nfo.title, "ABC-123 - 测试电影");
+        nfo.apply_title_style(NfoTitleStyle::Id, "ABC-123");
+        assert_eq!(nfo.title, "ABC-123");
+
+        let mut nfo = MovieNfo::default();
+        nfo.apply_title_style(NfoTitleStyle::IdTitle, "ABC-123");
+        assert_eq!(nfo.title, "ABC-123");
+    }
 }
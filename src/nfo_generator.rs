@@ -1,5 +1,8 @@
 use crate::config::AppConfig;
+use crate::file::{safe_write::safe_write, sanitize_file_name};
 use crate::nfo::{MediaCenterType, MovieNfo, MovieNfoCrawler, NfoFormatter};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -10,6 +13,135 @@ pub struct NfoGenerator {
     media_center_type: MediaCenterType,
 }
 
+/// NFO 验证警告的严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WarningSeverity {
+    /// 推荐补充的信息
+    Info,
+    /// 缺失会影响媒体中心展示的信息
+    Warning,
+    /// 缺失后 NFO 基本不可用
+    Error,
+}
+
+/// NFO 验证警告，每个变体表示对应字段缺失
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NfoWarning {
+    Title,
+    Plot,
+    Year,
+    Runtime,
+    Actors,
+    Genres,
+    ImdbId,
+    Rating,
+}
+
+impl NfoWarning {
+    /// 警告对应的字段名，用于 `fail_on_missing` 配置及统计
+    pub fn field(&self) -> &'static str {
+        match self {
+            Self::Title => "title",
+            Self::Plot => "plot",
+            Self::Year => "year",
+            Self::Runtime => "runtime",
+            Self::Actors => "actors",
+            Self::Genres => "genres",
+            Self::ImdbId => "imdb_id",
+            Self::Rating => "rating",
+        }
+    }
+
+    pub fn severity(&self) -> WarningSeverity {
+        match self {
+            Self::Title => WarningSeverity::Error,
+            Self::Plot
+            | Self::Year
+            | Self::Runtime
+            | Self::Actors
+            | Self::Genres => WarningSeverity::Warning,
+            Self::ImdbId | Self::Rating => WarningSeverity::Info,
+        }
+    }
+}
+
+impl fmt::Display for NfoWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            Self::Title => "标题为空",
+            Self::Plot => "剧情简介为空",
+            Self::Year => "发行年份未设置",
+            Self::Runtime => "运行时长未设置",
+            Self::Actors => "演员列表为空",
+            Self::Genres => "类型标签为空",
+            Self::ImdbId => "推荐设置 IMDB ID",
+            Self::Rating => "推荐设置评分信息",
+        };
+        f.write_str(message)
+    }
+}
+
+/// 单次运行中 NFO 验证警告的统计
+///
+/// 逐文件的警告只在 debug 级别记录，汇总信息在队列空闲时统一输出，输出后重新统计
+#[derive(Debug, Default)]
+pub struct NfoValidationStats {
+    files_checked: usize,
+    files_with_warnings: usize,
+    counts: BTreeMap<NfoWarning, usize>,
+}
+
+impl NfoValidationStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一个文件的验证结果
+    pub fn record(&mut self, warnings: &[NfoWarning]) {
+        self.files_checked += 1;
+
+        if warnings.is_empty() {
+            return;
+        }
+
+        self.files_with_warnings += 1;
+        for warning in warnings {
+            *self.counts.entry(*warning).or_default() += 1;
+        }
+    }
+
+    /// 生成汇总信息，按严重程度从高到低排列
+    pub fn summary(&self) -> String {
+        let mut counts: Vec<_> = self.counts.iter().collect();
+        counts.sort_by(|(a, a_count), (b, b_count)| {
+            b.severity().cmp(&a.severity()).then(b_count.cmp(a_count))
+        });
+
+        let details = counts
+            .iter()
+            .map(|(warning, count)| format!("{} {} 个", warning, count))
+            .collect::<Vec<_>>()
+            .join("，");
+
+        if details.is_empty() {
+            format!("已验证 {} 个NFO，均无警告", self.files_checked)
+        } else {
+            format!(
+                "已验证 {} 个NFO，其中 {} 个存在警告：{}",
+                self.files_checked, self.files_with_warnings, details
+            )
+        }
+    }
+
+    /// 有新记录时输出本轮汇总信息并清空统计
+    pub fn log_summary(&mut self) {
+        if self.files_checked > 0 {
+            log::info!("NFO数据验证汇总: {}", self.summary());
+            *self = Self::default();
+        }
+    }
+}
+
 /// NFO 生成配置
 #[derive(Debug, Clone)]
 #[allow(dead_code)] // 预留给未来的配置功能
@@ -42,7 +174,13 @@ impl NfoGenerator {
         original_file_path: &Path,
         config: &AppConfig,
     ) -> anyhow::Result<Vec<PathBuf>> {
-        let nfo = MovieNfo::for_universal(crawler_data);
+        let mut nfo = MovieNfo::for_universal(crawler_data);
+        nfo.apply_adult_markers(&config.adult_genre_markers);
+        nfo.fill_plot_outline(config.plot_outline_length);
+        nfo.apply_label_series(&config.label_series_map);
+        if config.deterministic_nfo {
+            nfo.normalize();
+        }
         self.generate_and_save(&nfo, original_file_path, config)
     }
 
@@ -94,7 +232,7 @@ impl NfoGenerator {
         }
 
         // 保存文件
-        fs::write(&nfo_path, xml_content)?;
+        safe_write(&nfo_path, xml_content)?;
 
         log::info!(
             "NFO文件已保存到: {} (格式: {:?})",
@@ -121,15 +259,14 @@ impl NfoGenerator {
         // 获取文件名基础部分（不包含扩展名）
         let file_stem = original_file_path
             .file_stem()
-            .and_then(|s| s.to_str())
             .ok_or_else(|| anyhow::anyhow!("无法获取文件名"))?;
 
-        // 生成通用格式的文件名
-        let new_filename = if !nfo.title.is_empty() {
-            format!("{} [{}].nfo", file_stem, self.sanitize_filename(&nfo.title))
-        } else {
-            format!("{}.nfo", file_stem)
-        };
+        // 生成通用格式的文件名，保留原始文件名的字节
+        let mut new_filename = file_stem.to_os_string();
+        if !nfo.title.is_empty() {
+            new_filename.push(format!(" [{}]", self.sanitize_filename(&nfo.title)));
+        }
+        new_filename.push(".nfo");
 
         let nfo_path = output_dir.join(new_filename);
 
@@ -168,24 +305,7 @@ impl NfoGenerator {
     /// 清理文件名中的非法字符
     #[allow(dead_code)] // 预留给未来的文件名清理功能
     fn sanitize_filename(&self, filename: &str) -> String {
-        // 移除或替换文件名中的非法字符
-        let illegal_chars = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
-        let mut sanitized = filename.to_string();
-
-        for char in illegal_chars {
-            sanitized = sanitized.replace(char, "");
-        }
-
-        // 移除多余的空格
-        sanitized = sanitized.split_whitespace().collect::<Vec<_>>().join(" ");
-
-        // 限制长度以避免路径过长
-        if sanitized.len() > 100 {
-            sanitized.truncate(100);
-            sanitized = sanitized.trim_end().to_string();
-        }
-
-        sanitized
+        sanitize_file_name(filename)
     }
 
     /// 预览NFO内容（不保存文件）
@@ -195,7 +315,7 @@ impl NfoGenerator {
     }
 
     /// 验证NFO数据的完整性
-    pub fn validate_nfo(&self, nfo: &MovieNfo) -> Vec<String> {
+    pub fn validate_nfo(&self, nfo: &MovieNfo) -> Vec<NfoWarning> {
         self.validate_nfo_for_type(nfo, &self.media_center_type)
     }
 
@@ -204,40 +324,40 @@ impl NfoGenerator {
         &self,
         nfo: &MovieNfo,
         _format_type: &MediaCenterType,
-    ) -> Vec<String> {
+    ) -> Vec<NfoWarning> {
         let mut warnings = Vec::new();
 
         if nfo.title.is_empty() {
-            warnings.push("标题为空".to_string());
+            warnings.push(NfoWarning::Title);
         }
 
         if nfo.plot.is_empty() {
-            warnings.push("剧情简介为空".to_string());
+            warnings.push(NfoWarning::Plot);
         }
 
         if nfo.year.is_none() {
-            warnings.push("发行年份未设置".to_string());
+            warnings.push(NfoWarning::Year);
         }
 
         if nfo.runtime.is_none() {
-            warnings.push("运行时长未设置".to_string());
+            warnings.push(NfoWarning::Runtime);
         }
 
         if nfo.actors.is_empty() {
-            warnings.push("演员列表为空".to_string());
+            warnings.push(NfoWarning::Actors);
         }
 
         if nfo.genres.is_empty() {
-            warnings.push("类型标签为空".to_string());
+            warnings.push(NfoWarning::Genres);
         }
 
         // 通用验证
         if nfo.imdb_id.is_empty() {
-            warnings.push("推荐设置 IMDB ID".to_string());
+            warnings.push(NfoWarning::ImdbId);
         }
 
         if nfo.rating.is_none() && nfo.ratings.is_none() {
-            warnings.push("推荐设置评分信息".to_string());
+            warnings.push(NfoWarning::Rating);
         }
 
         warnings
@@ -402,7 +522,33 @@ maximum_fetch_count = 3
         // 测试不完整的NFO
         let incomplete_nfo = MovieNfo::default();
         let warnings = generator.validate_nfo(&incomplete_nfo);
-        assert!(!warnings.is_empty());
+        assert!(warnings.contains(&NfoWarning::Title));
+        assert!(warnings.contains(&NfoWarning::Actors));
+        assert_eq!(NfoWarning::Title.severity(), WarningSeverity::Error);
+        assert_eq!(NfoWarning::Title.field(), "title");
+    }
+
+    #[test]
+    fn test_validation_stats_summary() {
+        let generator = NfoGenerator::new();
+        let mut stats = NfoValidationStats::new();
+
+        stats.record(&generator.validate_nfo(&create_test_nfo()));
+        stats.record(&generator.validate_nfo(&MovieNfo::default()));
+
+        assert_eq!(stats.files_checked, 2);
+        assert_eq!(stats.counts[&NfoWarning::ImdbId], 2);
+        assert_eq!(stats.counts[&NfoWarning::Plot], 1);
+
+        // 汇总按严重程度排序，标题缺失排在最前
+        let summary = stats.summary();
+        assert!(summary.starts_with("已验证 2 个NFO，其中 2 个存在警告：标题为空 1 个"));
+        assert!(summary.contains("推荐设置 IMDB ID 2 个"));
+
+        // 输出汇总后重新统计
+        stats.log_summary();
+        assert_eq!(stats.files_checked, 0);
+        assert!(stats.counts.is_empty());
     }
 
     #[test]
@@ -423,6 +569,7 @@ maximum_fetch_count = 3
 
         // 测试通用格式验证
         let universal_warnings = generator.validate_nfo_for_type(&nfo, &MediaCenterType::Universal);
-        assert!(universal_warnings.iter().any(|w| w.contains("IMDB ID")));
+        assert!(universal_warnings.contains(&NfoWarning::ImdbId));
+        assert!(!universal_warnings.contains(&NfoWarning::Title));
     }
 }
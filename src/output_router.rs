@@ -0,0 +1,228 @@
+use crate::classifier::categories_from_tags;
+use crate::config::{AppConfig, OutputRoute};
+use crate::nfo::MovieNfo;
+use regex::Regex;
+use std::path::PathBuf;
+
+/// 输出路由支持匹配的字段
+const ROUTE_FIELDS: &[&str] = &[
+    "genre", "tag", "studio", "director", "actor", "series", "title", "category",
+];
+
+/// 正则匹配条件的前缀
+const REGEX_PREFIX: &str = "re:";
+
+/// 影片的整理目标：输出目录与命名模板
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputTarget {
+    pub output_dir: PathBuf,
+    pub naming_template: String,
+}
+
+impl OutputTarget {
+    /// 全局配置的输出目录与命名模板
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            output_dir: config.get_output_dir().to_path_buf(),
+            naming_template: config.get_file_naming_template().to_string(),
+        }
+    }
+}
+
+/// 按配置的输出路由规则为影片选择整理目标
+///
+/// 规则按顺序匹配，第一条命中的规则生效；都不命中时使用全局输出目录
+pub fn route_output(nfo: &MovieNfo, config: &AppConfig) -> anyhow::Result<OutputTarget> {
+    for (index, route) in config.output_routes.iter().enumerate() {
+        if route_matches(route, nfo, config)? {
+            log::info!(
+                "影片 '{}' 命中输出路由规则 #{}，输出目录: {}",
+                nfo.title,
+                index + 1,
+                route.output_dir.display()
+            );
+            return Ok(OutputTarget {
+                output_dir: route.output_dir.clone(),
+                naming_template: route
+                    .naming_template
+                    .clone()
+                    .unwrap_or_else(|| config.get_file_naming_template().to_string()),
+            });
+        }
+    }
+
+    Ok(OutputTarget::from_config(config))
+}
+
+/// 检查输出路由规则的字段名和正则表达式
+pub fn validate_routes(routes: &[OutputRoute]) -> anyhow::Result<()> {
+    for (index, route) in routes.iter().enumerate() {
+        if route.conditions.is_empty() {
+            anyhow::bail!("输出路由规则 #{} 没有匹配条件", index + 1);
+        }
+        for (field, pattern) in &route.conditions {
+            if !ROUTE_FIELDS.contains(&field.as_str()) {
+                anyhow::bail!(
+                    "输出路由规则 #{} 的字段 '{}' 不受支持，可选: {}",
+                    index + 1,
+                    field,
+                    ROUTE_FIELDS.join(", ")
+                );
+            }
+            if let Some(regex) = pattern.strip_prefix(REGEX_PREFIX) {
+                Regex::new(regex).map_err(|e| {
+                    anyhow::anyhow!("输出路由规则 #{} 的正则 '{}' 无效: {}", index + 1, regex, e)
+                })?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 所有条件都有至少一个字段值匹配时规则命中
+fn route_matches(route: &OutputRoute, nfo: &MovieNfo, config: &AppConfig) -> anyhow::Result<bool> {
+    for (field, pattern) in &route.conditions {
+        let values = field_values(nfo, field, config);
+        let matched = match pattern.strip_prefix(REGEX_PREFIX) {
+            Some(regex) => {
+                let re = Regex::new(regex)?;
+                values.iter().any(|value| re.is_match(value))
+            }
+            None => values.iter().any(|value| value.eq_ignore_ascii_case(pattern)),
+        };
+        if !matched {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// 取出NFO中用于路由匹配的字段值
+///
+/// `category` 为NFO标签对应的影片分类（censored/uncensored/western）
+fn field_values<'a>(nfo: &'a MovieNfo, field: &str, config: &AppConfig) -> Vec<&'a str> {
+    match field {
+        "genre" => nfo.genres.iter().map(String::as_str).collect(),
+        "tag" => nfo.tags.iter().map(String::as_str).collect(),
+        "studio" => nfo.studios.iter().map(String::as_str).collect(),
+        "director" => nfo.directors.iter().map(String::as_str).collect(),
+        "actor" => nfo.actors.iter().map(|actor| actor.name.as_str()).collect(),
+        "series" => nfo.set.iter().map(|set| set.name.as_str()).collect(),
+        "title" => vec![nfo.title.as_str()],
+        "category" => categories_from_tags(&nfo.tags, &config.classification)
+            .into_iter()
+            .map(|category| category.as_str())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn create_routing_config() -> AppConfig {
+        let test_config_content = r#"
+migrate_files = ["mp4"]
+migrate_subtitles = false
+ignored_id_pattern = []
+capital = false
+input_dir = "./input"
+output_dir = "./output"
+thread_limit = 4
+template_priority = ["javdb.yaml"]
+
+[[output_routes]]
+match = { genre = "VR" }
+output_dir = "./vr"
+
+[[output_routes]]
+match = { studio = "re:^(?i)blacked" }
+output_dir = "./western"
+naming_template = "$studio$/$title$"
+
+[[output_routes]]
+match = { category = "uncensored" }
+output_dir = "./uncensored"
+"#;
+
+        let config_path = std::env::temp_dir().join("test_output_router_config.toml");
+        std::fs::write(&config_path, test_config_content).unwrap();
+
+        AppConfig::new(&config_path).unwrap()
+    }
+
+    #[test]
+    fn test_route_output_genre_match() {
+        let config = create_routing_config();
+        let nfo = MovieNfo {
+            title: "测试影片".to_string(),
+            genres: vec!["剧情".to_string(), "vr".to_string()],
+            studios: vec!["Blacked".to_string()],
+            ..Default::default()
+        };
+
+        // 两条规则都满足时第一条生效
+        let target = route_output(&nfo, &config).unwrap();
+        assert_eq!(target.output_dir, Path::new("./vr"));
+        assert_eq!(target.naming_template, config.get_file_naming_template());
+    }
+
+    #[test]
+    fn test_route_output_regex_studio_match() {
+        let config = create_routing_config();
+        let nfo = MovieNfo {
+            title: "测试影片".to_string(),
+            studios: vec!["BLACKED RAW".to_string()],
+            ..Default::default()
+        };
+
+        let target = route_output(&nfo, &config).unwrap();
+        assert_eq!(target.output_dir, Path::new("./western"));
+        assert_eq!(target.naming_template, "$studio$/$title$");
+    }
+
+    #[test]
+    fn test_route_output_category_match() {
+        let config = create_routing_config();
+        let nfo = MovieNfo {
+            title: "测试影片".to_string(),
+            tags: vec!["剧情".to_string(), "Uncensored".to_string()],
+            studios: vec!["一本道".to_string()],
+            ..Default::default()
+        };
+
+        let target = route_output(&nfo, &config).unwrap();
+        assert_eq!(target.output_dir, Path::new("./uncensored"));
+    }
+
+    #[test]
+    fn test_route_output_fallback() {
+        let config = create_routing_config();
+        let nfo = MovieNfo {
+            title: "测试影片".to_string(),
+            genres: vec!["剧情".to_string()],
+            studios: vec!["S1".to_string()],
+            ..Default::default()
+        };
+
+        let target = route_output(&nfo, &config).unwrap();
+        assert_eq!(target, OutputTarget::from_config(&config));
+        assert_eq!(target.output_dir, Path::new("./output"));
+    }
+
+    #[test]
+    fn test_validate_routes() {
+        let route = |field: &str, pattern: &str| OutputRoute {
+            conditions: [(field.to_string(), pattern.to_string())].into_iter().collect(),
+            output_dir: PathBuf::from("./vr"),
+            naming_template: None,
+        };
+
+        assert!(validate_routes(&[route("genre", "VR")]).is_ok());
+        assert!(validate_routes(&[route("category", "western")]).is_ok());
+        assert!(validate_routes(&[route("rating", "5")]).is_err());
+        assert!(validate_routes(&[route("studio", "re:([")]).is_err());
+    }
+}
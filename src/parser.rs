@@ -1,7 +1,17 @@
 use crate::config::AppConfig;
 use regex::Regex;
+use std::borrow::Cow;
 use std::path::Path;
 
+/// 文件名解析结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedFileName {
+    /// 标准化后的影片ID（不包含后缀标记）
+    pub movie_id: String,
+    /// 由ID之后的后缀标记得到的标签，如 `-C` -> `chinese-sub`
+    pub suffix_tags: Vec<String>,
+}
+
 /// 文件名解析器
 ///
 /// 负责从文件路径中提取影片ID，用于后续的网络搜索
@@ -31,20 +41,20 @@ impl FileNameParser {
         Ok(Self { movie_id_regexes })
     }
 
-    /// 从文件路径中提取影片ID
-    ///
-    /// # 参数
-    /// - `file_path`: 文件路径
-    /// - `config`: 应用配置，包含清理规则
+    /// 从文件路径中提取影片ID及后缀标签
     ///
-    /// # 返回
-    /// 成功时返回影片ID，失败时返回None
-    pub fn extract_movie_id(&self, file_path: &Path, config: &AppConfig) -> Option<String> {
-        // 获取文件名（不包含扩展名）
-        let file_stem = file_path.file_stem()?.to_str()?;
+    /// 后缀标记（如 `ABC-123-C` 中的 `C`）不会进入影片ID，而是按配置的
+    /// `suffix_tags` 映射为标签返回
+    pub fn extract_movie_info(&self, file_path: &Path, config: &AppConfig) -> Option<ParsedFileName> {
+        // 获取文件名（不包含扩展名），非 UTF-8 文件名替换无效字符后用于匹配，
+        // 文件系统操作仍使用原始路径
+        let file_stem = file_path.file_stem()?.to_string_lossy();
+        if let Cow::Owned(_) = file_stem {
+            log::warn!("文件名不是有效的 UTF-8，替换无效字符后匹配影片ID: {}", file_path.display());
+        }
 
         // 清理文件名
-        let cleaned_name = self.clean_filename(file_stem, config);
+        let cleaned_name = self.clean_filename(&file_stem, config);
 
         log::debug!("原始文件名: {}", file_stem);
         log::debug!("清理后文件名: {}", cleaned_name);
@@ -54,7 +64,45 @@ impl FileNameParser {
 
         log::info!("从文件 {} 提取到影片ID: {}", file_path.display(), movie_id);
 
-        Some(movie_id)
+        // 后缀标记从原始文件名中识别，避免被忽略模式提前移除
+        let suffix_tags = self.extract_suffix_tags(&file_stem, &movie_id, config);
+        if !suffix_tags.is_empty() {
+            log::info!("从文件名后缀识别到标签: {:?}", suffix_tags);
+        }
+
+        Some(ParsedFileName {
+            movie_id,
+            suffix_tags,
+        })
+    }
+
+    /// 识别紧跟在影片ID之后的后缀标记并转换为标签
+    fn extract_suffix_tags(&self, file_stem: &str, movie_id: &str, config: &AppConfig) -> Vec<String> {
+        let upper_stem = file_stem.to_ascii_uppercase();
+        let Some(id_start) = upper_stem.find(&movie_id.to_ascii_uppercase()) else {
+            log::debug!("原始文件名中找不到影片ID '{}'，跳过后缀识别", movie_id);
+            return Vec::new();
+        };
+        let remainder = &upper_stem[id_start + movie_id.len()..];
+
+        let mut tags = Vec::new();
+        for token in remainder
+            .split(|c: char| matches!(c, '-' | '_' | '.') || c.is_whitespace())
+            .filter(|token| !token.is_empty())
+        {
+            // 只识别连续的后缀标记，遇到未配置的片段即停止
+            match config.get_suffix_tag(token) {
+                Some(tag) => {
+                    log::debug!("后缀标记 '{}' -> 标签 '{}'", token, tag);
+                    if !tags.iter().any(|t: &String| t == tag) {
+                        tags.push(tag.to_string());
+                    }
+                }
+                None => break,
+            }
+        }
+
+        tags
     }
 
     /// 清理文件名，移除配置中指定的模式
@@ -200,7 +248,7 @@ maximum_fetch_count = 3
 
         for (filename, expected) in test_cases {
             let path = Path::new(filename);
-            let result = parser.extract_movie_id(path, &config);
+            let result = parser.extract_movie_info(path, &config).map(|parsed| parsed.movie_id);
             assert_eq!(
                 result.as_deref(),
                 expected,
@@ -210,6 +258,49 @@ maximum_fetch_count = 3
         }
     }
 
+    fn create_suffix_test_config() -> AppConfig {
+        let test_config_content = r#"
+migrate_files = ["mp4"]
+migrate_subtitles = false
+ignored_id_pattern = ["-HD", "-4K", "_"]
+capital = false
+input_dir = "./input"
+output_dir = "./output"
+thread_limit = 4
+template_priority = ["javdb.yaml"]
+maximum_fetch_count = 3
+suffix_tags = { "C" = "chinese-sub", "UC" = "uncensored", "4K" = "2160p" }
+"#;
+
+        let config_path = std::env::temp_dir().join("test_suffix_config.toml");
+        std::fs::write(&config_path, test_config_content).unwrap();
+
+        AppConfig::new(&config_path).unwrap()
+    }
+
+    #[test]
+    fn test_extract_movie_info_suffix_tags() {
+        let parser = FileNameParser::new().unwrap();
+        let config = create_suffix_test_config();
+
+        let test_cases = vec![
+            ("ABC-123-C.mp4", "ABC-123", vec!["chinese-sub"]),
+            ("ABC-123-UC.mp4", "ABC-123", vec!["uncensored"]),
+            ("abc-123-4k.mp4", "ABC-123", vec!["2160p"]),
+            ("ABC-123-C-4K.mp4", "ABC-123", vec!["chinese-sub", "2160p"]),
+            ("ABC-123.mp4", "ABC-123", vec![]),
+            ("ABC-123-HD.mp4", "ABC-123", vec![]),
+        ];
+
+        for (filename, expected_id, expected_tags) in test_cases {
+            let parsed = parser
+                .extract_movie_info(Path::new(filename), &config)
+                .unwrap_or_else(|| panic!("Failed to parse filename: {}", filename));
+            assert_eq!(parsed.movie_id, expected_id, "Failed for filename: {}", filename);
+            assert_eq!(parsed.suffix_tags, expected_tags, "Failed for filename: {}", filename);
+        }
+    }
+
     #[test]
     fn test_is_valid_movie_id() {
         let parser = FileNameParser::new().unwrap();
@@ -220,4 +311,24 @@ maximum_fetch_count = 3
         assert!(!parser.is_valid_movie_id("invalid"));
         assert!(!parser.is_valid_movie_id("123-456"));
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_extract_movie_id_non_utf8() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let parser = FileNameParser::new().unwrap();
+        let config = create_test_config();
+
+        // 无效字节替换后仍能匹配影片ID
+        let path = Path::new(OsStr::from_bytes(b"IPX-001 \xff\xfe.mp4"));
+        assert_eq!(
+            parser.extract_movie_info(path, &config).map(|parsed| parsed.movie_id),
+            Some("IPX-001".to_string())
+        );
+
+        let path = Path::new(OsStr::from_bytes(b"\xff\xfe.mp4"));
+        assert!(parser.extract_movie_info(path, &config).is_none());
+    }
 }
@@ -0,0 +1,636 @@
+// 影片整理流程的对外接口，便于在其他程序（如 TUI）中嵌入，不依赖 indicatif 进度条
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use anyhow::Context;
+use indicatif::ProgressBar;
+
+use crate::{
+    config::AppConfig,
+    crawler::{
+        crawler, get_templates, preview_file, process_single_file, ProcessingDependencies, SourceParams, Templates,
+    },
+    error::AppError,
+    file_organizer::FileOrganizer,
+    ignored_ids::IgnoredIds,
+    image_manager::ImageManager,
+    nfo::{MediaCenterType, MovieNfo, MovieNfoCrawler},
+    nfo_generator::{NfoGenerator, NfoValidationStats, NfoWarning},
+    parser::FileNameParser,
+    taxonomy::Taxonomy,
+    translator::Translator,
+};
+
+/// 处理进度回调，用于展示当前的处理阶段
+pub trait ProgressReporter: Send + Sync {
+    /// 更新当前阶段的描述
+    fn set_message(&self, message: &str);
+}
+
+/// 不展示进度
+impl ProgressReporter for () {
+    fn set_message(&self, _message: &str) {}
+}
+
+impl ProgressReporter for ProgressBar {
+    fn set_message(&self, message: &str) {
+        ProgressBar::set_message(self, message.to_string());
+    }
+}
+
+/// 单个文件的处理结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProcessOutcome {
+    /// 已生成NFO并整理到输出目录
+    Organized {
+        movie_id: String,
+        /// 整理后的视频文件路径，文件已在输出目录中时为原路径
+        video_path: PathBuf,
+        /// 写入的NFO文件，第一个为主NFO
+        nfo_paths: Vec<PathBuf>,
+    },
+    /// 按规则跳过（如影片ID在忽略列表中、影片数据不存在、数据质量过低），文件保留在输入目录
+    Skipped { reason: String },
+}
+
+/// 文件处理的预览结果
+#[derive(Debug)]
+pub struct PreviewPlan {
+    pub movie_id: String,
+    /// 将要写入的NFO内容
+    pub nfo: MovieNfo,
+    /// 整理后的视频文件路径，文件已在输出目录中时为原路径
+    pub video_path: PathBuf,
+    /// 将要写入的NFO文件，第一个为主NFO
+    pub nfo_paths: Vec<PathBuf>,
+    /// NFO数据验证警告
+    pub warnings: Vec<NfoWarning>,
+}
+
+/// 影片整理流程：按模板爬取影片数据、生成NFO并整理文件
+pub struct Pipeline {
+    config: AppConfig,
+    templates: Templates,
+    parser: FileNameParser,
+    nfo_generator: NfoGenerator,
+    file_organizer: FileOrganizer,
+    image_manager: ImageManager,
+    translator: Option<Translator>,
+    ignored_ids: IgnoredIds,
+    genre_taxonomy: Option<Taxonomy>,
+    progress: Box<dyn ProgressReporter>,
+    validation_stats: Mutex<NfoValidationStats>,
+}
+
+impl Pipeline {
+    /// 从模板目录加载配置中使用的模板并创建处理流程
+    pub fn new(config: AppConfig, template_dir: &Path) -> anyhow::Result<Self> {
+        let templates = Arc::new(
+            get_templates(template_dir, &config)
+                .with_context(|| format!("get template from {}", template_dir.display()))?,
+        );
+        log::info!("成功加载 {} 个模板", templates.len());
+
+        let parser = FileNameParser::new().context("创建文件名解析器失败")?;
+        let image_manager = ImageManager::from_config(&config);
+        let translator = create_translator(&config);
+        let genre_taxonomy = config
+            .genre_taxonomy_file
+            .as_deref()
+            .map(Taxonomy::load)
+            .transpose()?;
+
+        Ok(Self {
+            templates,
+            parser,
+            nfo_generator: NfoGenerator::for_media_center(MediaCenterType::Universal),
+            file_organizer: FileOrganizer::new(),
+            image_manager,
+            translator,
+            ignored_ids: IgnoredIds::from_config(&config),
+            genre_taxonomy,
+            progress: Box::new(()),
+            validation_stats: Mutex::new(NfoValidationStats::new()),
+            config,
+        })
+    }
+
+    /// 设置处理进度回调
+    pub fn with_progress(mut self, progress: impl ProgressReporter + 'static) -> Self {
+        self.progress = Box::new(progress);
+        self
+    }
+
+    pub fn config(&self) -> &AppConfig {
+        &self.config
+    }
+
+    /// 从文件名提取影片ID，无法提取时返回 None
+    pub fn movie_id(&self, file_path: &Path) -> Option<String> {
+        self.parser
+            .extract_movie_info(file_path, &self.config)
+            .map(|parsed| parsed.movie_id)
+    }
+
+    /// 按模板优先级爬取影片数据，启用爬取缓存时优先使用缓存
+    pub async fn lookup(&self, movie_id: &str) -> Result<MovieNfoCrawler, AppError> {
+        crawler(
+            movie_id,
+            movie_id,
+            &SourceParams::default(),
+            self.progress.as_ref(),
+            &self.templates,
+            &self.config,
+        )
+        .await
+    }
+
+    /// 处理单个文件：爬取数据、生成NFO并整理到输出目录
+    pub async fn process_file(&self, file_path: &Path) -> anyhow::Result<ProcessOutcome> {
+        self.process_file_with_progress(file_path, self.progress.as_ref())
+            .await
+    }
+
+    /// 预览单个文件的处理结果，不移动文件也不写入NFO
+    pub async fn preview(&self, file_path: &Path) -> anyhow::Result<PreviewPlan> {
+        preview_file(file_path, &self.dependencies(), self.progress.as_ref()).await
+    }
+
+    /// 使用指定的进度回调处理单个文件，按规则跳过的文件返回 `ProcessOutcome::Skipped`
+    pub(crate) async fn process_file_with_progress(
+        &self,
+        file_path: &Path,
+        progress: &dyn ProgressReporter,
+    ) -> anyhow::Result<ProcessOutcome> {
+        match process_single_file(file_path, &self.dependencies(), progress, &self.validation_stats).await {
+            Err(e) => match e.downcast_ref::<AppError>() {
+                Some(app_error) if app_error.should_skip_processing() => Ok(ProcessOutcome::Skipped {
+                    reason: app_error.skip_reason().unwrap_or("未知原因").to_string(),
+                }),
+                _ => Err(e),
+            },
+            outcome => outcome,
+        }
+    }
+
+    /// 测试翻译服务连接，未启用翻译时不做任何操作
+    pub(crate) async fn test_translation_connection(&self) {
+        if let Some(translator) = &self.translator {
+            match translator.test_connection().await {
+                Ok(_) => {
+                    log::info!("翻译服务连接测试成功");
+                }
+                Err(e) => {
+                    log::warn!("翻译服务连接测试失败: {}，翻译功能可能无法正常工作", e);
+                }
+            }
+        }
+    }
+
+    /// 输出NFO验证汇总
+    pub(crate) fn log_validation_summary(&self) {
+        self.validation_stats
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .log_summary();
+    }
+
+    fn dependencies(&self) -> ProcessingDependencies<'_> {
+        ProcessingDependencies {
+            parser: &self.parser,
+            nfo_generator: &self.nfo_generator,
+            file_organizer: &self.file_organizer,
+            image_manager: &self.image_manager,
+            translator: self.translator.as_ref(),
+            ignored_ids: &self.ignored_ids,
+            genre_taxonomy: self.genre_taxonomy.as_ref(),
+            templates: &self.templates,
+            config: &self.config,
+        }
+    }
+}
+
+/// 创建翻译器（如果启用），初始化失败时跳过翻译功能
+fn create_translator(config: &AppConfig) -> Option<Translator> {
+    if !config.is_translation_enabled() {
+        log::info!("翻译功能已禁用");
+        return None;
+    }
+
+    match Translator::from_app_config(config) {
+        Ok(translator) => {
+            log::info!("翻译器初始化成功，提供商: {}", config.get_translation_provider());
+            Some(translator)
+        }
+        Err(e) => {
+            log::warn!("翻译器初始化失败: {}，将跳过翻译功能", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::harness::create_site_pipeline;
+
+    #[tokio::test]
+    async fn test_pipeline_lookup() {
+        let mut server = mockito::Server::new_async().await;
+        let _movie = server
+            .mock("GET", "/movie/PIPE-001")
+            .with_status(200)
+            .with_body(r#"<h1>PIPE-001 流程影片</h1><div class="actor">演员A</div>"#)
+            .create_async()
+            .await;
+        let _missing = server
+            .mock("GET", "/movie/MISS-001")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let (pipeline, test_dir) = create_site_pipeline("test_pipeline_lookup", &server.url(), "");
+
+        let nfo = pipeline.lookup("PIPE-001").await.unwrap();
+        assert_eq!(nfo.title, "PIPE-001 流程影片");
+        assert_eq!(nfo.actors[0].name, "演员A");
+
+        // 模板中不存在的影片
+        let result = pipeline.lookup("MISS-001").await;
+        assert!(matches!(result, Err(AppError::MovieDataNotFound(_))));
+
+        std::fs::remove_dir_all(&test_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_preview_does_not_modify_files() {
+        let mut server = mockito::Server::new_async().await;
+        let _movie = server
+            .mock("GET", "/movie/PIPE-002")
+            .with_status(200)
+            .with_body(r#"<h1>PIPE-002 预览影片</h1><div class="actor">演员B</div>"#)
+            .create_async()
+            .await;
+
+        let (pipeline, test_dir) = create_site_pipeline("test_pipeline_preview", &server.url(), "");
+        let video_file = test_dir.join("input").join("PIPE-002-C.mp4");
+        std::fs::write(&video_file, b"video content").unwrap();
+
+        let plan = pipeline.preview(&video_file).await.unwrap();
+
+        assert_eq!(plan.movie_id, "PIPE-002");
+        assert_eq!(plan.nfo.title, "PIPE-002 预览影片");
+        assert!(plan.video_path.starts_with(test_dir.join("output")));
+        assert_eq!(plan.video_path.extension().unwrap(), "mp4");
+        assert_eq!(plan.nfo_paths[0].extension().unwrap(), "nfo");
+        assert_eq!(plan.nfo_paths[0].parent(), plan.video_path.parent());
+
+        // 预览不移动文件也不创建输出目录
+        assert!(video_file.exists());
+        assert!(!test_dir.join("output").exists());
+
+        std::fs::remove_dir_all(&test_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_process_bdmv_folder() {
+        let mut server = mockito::Server::new_async().await;
+        let _movie = server
+            .mock("GET", "/movie/PIPE-003")
+            .with_status(200)
+            .with_body(r#"<h1>PIPE-003 原盘影片</h1><div class="actor">演员C</div>"#)
+            .create_async()
+            .await;
+
+        let (pipeline, test_dir) = create_site_pipeline("test_process_bdmv_folder", &server.url(), "");
+        let folder = test_dir.join("input").join("PIPE-003");
+        let stream_dir = folder.join("BDMV").join("STREAM");
+        std::fs::create_dir_all(&stream_dir).unwrap();
+        std::fs::write(folder.join("BDMV").join("index.bdmv"), b"index").unwrap();
+        std::fs::write(stream_dir.join("00000.m2ts"), b"stream").unwrap();
+
+        let outcome = pipeline.process_file(&folder).await.unwrap();
+        let ProcessOutcome::Organized { video_path, nfo_paths, .. } = outcome else {
+            panic!("Expected organized outcome, got {:?}", outcome);
+        };
+
+        // 整个目录移动到输出目录，NFO 位于目录内并以目录名命名
+        assert!(!folder.exists());
+        assert!(video_path.starts_with(test_dir.join("output")));
+        assert!(video_path.join("BDMV").join("STREAM").join("00000.m2ts").is_file());
+        let dir_name = video_path.file_name().unwrap().to_str().unwrap();
+        assert_eq!(nfo_paths, vec![video_path.join(format!("{}.nfo", dir_name))]);
+        let nfo = std::fs::read_to_string(&nfo_paths[0]).unwrap();
+        assert!(nfo.contains("PIPE-003 原盘影片"));
+
+        std::fs::remove_dir_all(&test_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_process_strm_in_place() {
+        let mut server = mockito::Server::new_async().await;
+        let _movie = server
+            .mock("GET", "/movie/PIPE-004")
+            .with_status(200)
+            .with_body(r#"<h1>PIPE-004 网络影片</h1><div class="actor">演员D</div>"#)
+            .create_async()
+            .await;
+
+        let (pipeline, test_dir) = create_site_pipeline("test_process_strm_in_place", &server.url(), "");
+        let strm = test_dir.join("input").join("PIPE-004.strm");
+        std::fs::write(&strm, b"http://example.com/PIPE-004.mp4").unwrap();
+
+        let outcome = pipeline.process_file(&strm).await.unwrap();
+
+        // .strm 文件保持原位，只在旁边生成 NFO
+        assert_eq!(
+            outcome,
+            ProcessOutcome::Organized {
+                movie_id: "PIPE-004".to_string(),
+                video_path: strm.clone(),
+                nfo_paths: vec![test_dir.join("input").join("PIPE-004.nfo")],
+            }
+        );
+        assert!(strm.exists());
+        assert!(test_dir.join("input").join("PIPE-004.nfo").is_file());
+        assert!(!test_dir.join("output").exists());
+
+        std::fs::remove_dir_all(&test_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_process_ignored_id_skips_crawl() {
+        let mut server = mockito::Server::new_async().await;
+        let movie = server
+            .mock("GET", mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(r#"<h1>PIPE-005 忽略影片</h1>"#)
+            .expect(0)
+            .create_async()
+            .await;
+
+        let (pipeline, test_dir) = create_site_pipeline(
+            "test_process_ignored_id_skips_crawl",
+            &server.url(),
+            r#"ignored_ids = ["pipe005"]"#,
+        );
+        let video_file = test_dir.join("input").join("PIPE-005.mp4");
+        std::fs::write(&video_file, b"video content").unwrap();
+
+        let outcome = pipeline.process_file(&video_file).await.unwrap();
+
+        // 忽略列表中的影片不发起请求，文件保留在输入目录
+        assert!(matches!(outcome, ProcessOutcome::Skipped { .. }));
+        movie.assert_async().await;
+        assert!(video_file.exists());
+        assert!(!test_dir.join("output").exists());
+
+        std::fs::remove_dir_all(&test_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_process_fail_on_missing_skips_file() {
+        let mut server = mockito::Server::new_async().await;
+        let _movie = server
+            .mock("GET", "/movie/PIPE-011")
+            .with_status(200)
+            .with_body(r#"<h1>PIPE-011 缺少简介</h1><div class="actor">演员K</div>"#)
+            .create_async()
+            .await;
+
+        let (pipeline, test_dir) = create_site_pipeline(
+            "test_process_fail_on_missing_skips_file",
+            &server.url(),
+            r#"fail_on_missing = ["plot"]"#,
+        );
+        let video_file = test_dir.join("input").join("PIPE-011.mp4");
+        std::fs::write(&video_file, b"video content").unwrap();
+
+        let outcome = pipeline.process_file(&video_file).await.unwrap();
+
+        // 缺少必需字段时跳过，文件保留在输入目录且不写入NFO
+        let ProcessOutcome::Skipped { reason } = outcome else {
+            panic!("Expected skipped outcome, got {:?}", outcome);
+        };
+        assert_eq!(reason, "缺少必需的NFO字段");
+        assert!(video_file.exists());
+        assert!(!test_dir.join("output").exists());
+
+        std::fs::remove_dir_all(&test_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_process_crawl_errors_are_not_skipped() {
+        let mut server = mockito::Server::new_async().await;
+        let _movie = server
+            .mock("GET", "/movie/PIPE-012")
+            .with_status(503)
+            .create_async()
+            .await;
+
+        let (pipeline, test_dir) = create_site_pipeline("test_process_crawl_errors_are_not_skipped", &server.url(), "");
+        let video_file = test_dir.join("input").join("PIPE-012.mp4");
+        std::fs::write(&video_file, b"video content").unwrap();
+
+        // 站点出错与影片不存在不同，返回错误以便重试，而不是跳过
+        let err = pipeline.process_file(&video_file).await.unwrap_err();
+        let app_error = err.downcast_ref::<AppError>();
+        assert!(matches!(app_error, Some(AppError::MovieDataUnavailable(_))), "{:?}", err);
+        assert!(app_error.unwrap().is_retryable());
+        assert!(video_file.exists());
+
+        std::fs::remove_dir_all(&test_dir).unwrap();
+    }
+
+    /// 在输出目录中写入属于 `movie_id` 的NFO
+    fn write_existing_nfo(path: &Path, movie_id: &str, title: &str) {
+        let nfo = MovieNfo {
+            title: title.to_string(),
+            unique_ids: vec![crate::nfo::UniqueId {
+                id_type: "javdb".to_string(),
+                default: Some(true),
+                value: movie_id.to_string(),
+            }],
+            ..Default::default()
+        };
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, crate::nfo::NfoFormatter::format_to_xml(&nfo)).unwrap();
+    }
+
+    fn dir_entries(dir: &Path) -> Vec<String> {
+        let mut entries: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        entries.sort();
+        entries
+    }
+
+    #[tokio::test]
+    async fn test_process_resumes_partial_movie_dir() {
+        let mut server = mockito::Server::new_async().await;
+        let _movie = server
+            .mock("GET", "/movie/PIPE-006")
+            .with_status(200)
+            .with_body(r#"<h1>续传影片</h1><div class="actor">演员F</div>"#)
+            .create_async()
+            .await;
+
+        let (pipeline, test_dir) = create_site_pipeline(
+            "test_process_resumes_partial_movie_dir",
+            &server.url(),
+            r#"file_naming_template = "$title$""#,
+        );
+        // 上次处理已写入NFO与图片，但在移动视频前中断
+        let movie_dir = test_dir.join("output").join("续传影片");
+        write_existing_nfo(&movie_dir.join("续传影片.nfo"), "PIPE-006", "旧标题");
+        std::fs::write(movie_dir.join("poster.jpg"), b"existing poster").unwrap();
+        let video_file = test_dir.join("input").join("PIPE-006.mp4");
+        std::fs::write(&video_file, b"video content").unwrap();
+
+        let outcome = pipeline.process_file(&video_file).await.unwrap();
+
+        // 继续使用该目录，视频使用规范文件名，NFO 被覆盖，已有图片保留
+        assert_eq!(
+            outcome,
+            ProcessOutcome::Organized {
+                movie_id: "PIPE-006".to_string(),
+                video_path: movie_dir.join("续传影片.mp4"),
+                nfo_paths: vec![movie_dir.join("续传影片.nfo")],
+            }
+        );
+        assert_eq!(dir_entries(&movie_dir), ["poster.jpg", "续传影片.mp4", "续传影片.nfo"]);
+        assert_eq!(std::fs::read(movie_dir.join("poster.jpg")).unwrap(), b"existing poster");
+        let nfo = MovieNfo::read_from(&movie_dir.join("续传影片.nfo")).unwrap();
+        assert_eq!(nfo.title, "续传影片");
+
+        std::fs::remove_dir_all(&test_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_process_keeps_existing_video_in_movie_dir() {
+        let mut server = mockito::Server::new_async().await;
+        let _movie = server
+            .mock("GET", "/movie/PIPE-007")
+            .with_status(200)
+            .with_body(r#"<h1>重复影片</h1><div class="actor">演员G</div>"#)
+            .create_async()
+            .await;
+
+        let (pipeline, test_dir) = create_site_pipeline(
+            "test_process_keeps_existing_video_in_movie_dir",
+            &server.url(),
+            r#"file_naming_template = "$title$""#,
+        );
+        // 同一影片已完整整理过，规范文件名已被另一个视频占用
+        let movie_dir = test_dir.join("output").join("重复影片");
+        write_existing_nfo(&movie_dir.join("重复影片.nfo"), "PIPE-007", "重复影片");
+        std::fs::write(movie_dir.join("重复影片.mp4"), b"first copy").unwrap();
+        let video_file = test_dir.join("input").join("PIPE-007.mp4");
+        std::fs::write(&video_file, b"second copy").unwrap();
+
+        let outcome = pipeline.process_file(&video_file).await.unwrap();
+
+        // 视频追加序号而不是覆盖已有视频，NFO 文件名跟随视频
+        let ProcessOutcome::Organized { video_path, nfo_paths, .. } = outcome else {
+            panic!("Expected organized outcome, got {:?}", outcome);
+        };
+        assert_eq!(video_path, movie_dir.join("重复影片 (1).mp4"));
+        assert_eq!(nfo_paths, vec![movie_dir.join("重复影片 (1).nfo")]);
+        assert_eq!(std::fs::read(movie_dir.join("重复影片.mp4")).unwrap(), b"first copy");
+        assert_eq!(std::fs::read(&video_path).unwrap(), b"second copy");
+        assert!(movie_dir.join("重复影片.nfo").is_file());
+
+        std::fs::remove_dir_all(&test_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_process_writes_redacted_nfo() {
+        let mut server = mockito::Server::new_async().await;
+        let _movie = server
+            .mock("GET", "/movie/PIPE-008")
+            .with_status(200)
+            .with_body(r#"<h1>隐私影片</h1><div class="actor">演员H</div>"#)
+            .create_async()
+            .await;
+
+        let (pipeline, test_dir) = create_site_pipeline(
+            "test_process_writes_redacted_nfo",
+            &server.url(),
+            r#"file_naming_template = "$actor$/$title$"
+
+[nfo]
+omit_fields = ["actors"]
+title_style = "id - title"
+"#,
+        );
+        let video_file = test_dir.join("input").join("PIPE-008.mp4");
+        std::fs::write(&video_file, b"video content").unwrap();
+
+        let outcome = pipeline.process_file(&video_file).await.unwrap();
+
+        // 命名仍使用完整数据，写入的NFO省略演员并改写标题
+        let ProcessOutcome::Organized { video_path, nfo_paths, .. } = outcome else {
+            panic!("Expected organized outcome, got {:?}", outcome);
+        };
+        assert_eq!(video_path, test_dir.join("output").join("演员H").join("隐私影片").join("隐私影片.mp4"));
+        let xml = std::fs::read_to_string(&nfo_paths[0]).unwrap();
+        assert!(!xml.contains("<actor>"), "{}", xml);
+        assert!(xml.contains("<title>PIPE-008 - 隐私影片</title>"), "{}", xml);
+
+        std::fs::remove_dir_all(&test_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_process_probes_media_info() {
+        let mut server = mockito::Server::new_async().await;
+        let _movie = server
+            .mock("GET", "/movie/PIPE-009")
+            .with_status(200)
+            .with_body(r#"<h1>高清影片</h1><div class="actor">演员I</div>"#)
+            .create_async()
+            .await;
+
+        let (pipeline, test_dir) =
+            create_site_pipeline("test_process_probes_media_info", &server.url(), "probe_media = true\n");
+        let video_file = test_dir.join("input").join("PIPE-009.mp4");
+        std::fs::write(&video_file, include_bytes!("tests/fixtures/sample.mp4")).unwrap();
+
+        let outcome = pipeline.process_file(&video_file).await.unwrap();
+
+        // 写入流信息，站点没有时长时用视频时长补全
+        let ProcessOutcome::Organized { nfo_paths, .. } = outcome else {
+            panic!("Expected organized outcome, got {:?}", outcome);
+        };
+        let xml = std::fs::read_to_string(&nfo_paths[0]).unwrap();
+        assert!(xml.contains("<runtime>90</runtime>"), "{}", xml);
+        assert!(
+            xml.contains("<streamdetails><video><codec>hevc</codec><aspect>1.78</aspect><width>1920</width><height>1080</height>"),
+            "{}",
+            xml
+        );
+        assert!(xml.contains("<audio><codec>aac</codec><channels>2</channels></audio>"), "{}", xml);
+
+        // 无法解析的文件照常整理，不写入流信息
+        let _broken = server
+            .mock("GET", "/movie/PIPE-010")
+            .with_status(200)
+            .with_body(r#"<h1>损坏影片</h1><div class="actor">演员I</div>"#)
+            .create_async()
+            .await;
+        let broken_file = test_dir.join("input").join("PIPE-010.mp4");
+        std::fs::write(&broken_file, b"video content").unwrap();
+        let outcome = pipeline.process_file(&broken_file).await.unwrap();
+        let ProcessOutcome::Organized { nfo_paths, .. } = outcome else {
+            panic!("Expected organized outcome, got {:?}", outcome);
+        };
+        let xml = std::fs::read_to_string(&nfo_paths[0]).unwrap();
+        assert!(!xml.contains("<fileinfo>"), "{}", xml);
+        assert!(!xml.contains("<runtime>"), "{}", xml);
+
+        std::fs::remove_dir_all(&test_dir).unwrap();
+    }
+}
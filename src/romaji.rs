@@ -0,0 +1,155 @@
+// 假名转罗马字（赫本式，不加长音符号），用于统一演员名的书写形式
+
+/// 将只由假名组成的名字转为罗马字，每个词首字母大写，如 `みかみ ゆあ` -> `Mikami Yua`
+///
+/// 空格与 `・` 作为分词符；包含汉字、拉丁字母等非假名字符时返回 None
+pub fn kana_to_romaji(text: &str) -> Option<String> {
+    let words = text
+        .split(|c: char| c.is_whitespace() || c == '・')
+        .filter(|word| !word.is_empty())
+        .map(word_to_romaji)
+        .collect::<Option<Vec<_>>>()?;
+    if words.is_empty() {
+        return None;
+    }
+    Some(words.join(" "))
+}
+
+fn word_to_romaji(word: &str) -> Option<String> {
+    let mut syllables: Vec<String> = Vec::new();
+    let mut double_next = false;
+
+    for c in word.chars().map(to_hiragana) {
+        match c {
+            // 促音重复下一个音节的辅音
+            'っ' => double_next = true,
+            // 长音重复前一个元音
+            'ー' => {
+                let vowel = syllables.last()?.chars().last()?;
+                syllables.push(vowel.to_string());
+            }
+            'ゃ' | 'ゅ' | 'ょ' => {
+                let vowel = match c {
+                    'ゃ' => 'a',
+                    'ゅ' => 'u',
+                    _ => 'o',
+                };
+                let stem = syllables
+                    .last()
+                    .and_then(|last| last.strip_suffix('i'))
+                    .filter(|stem| !stem.is_empty())
+                    .map(str::to_string);
+                match (stem, syllables.last_mut()) {
+                    (Some(stem), Some(last)) => {
+                        // しゃ -> sha、じゃ -> ja、きゃ -> kya
+                        let stem = if stem.ends_with("sh") || stem.ends_with("ch") || stem == "j" {
+                            stem
+                        } else {
+                            format!("{}y", stem)
+                        };
+                        *last = format!("{}{}", stem, vowel);
+                    }
+                    _ => syllables.push(format!("y{}", vowel)),
+                }
+            }
+            'ぁ' | 'ぃ' | 'ぅ' | 'ぇ' | 'ぉ' => {
+                let vowel = small_vowel(c);
+                match syllables.last_mut() {
+                    // ふぁ -> fa、しぇ -> she、てぃ -> ti
+                    Some(last) if last.len() > 1 && last.ends_with(is_vowel) => {
+                        last.pop();
+                        last.push(vowel);
+                    }
+                    _ => syllables.push(vowel.to_string()),
+                }
+            }
+            c => {
+                let mut syllable = base_romaji(c)?.to_string();
+                if std::mem::take(&mut double_next) {
+                    if syllable.starts_with("ch") {
+                        syllable.insert(0, 't');
+                    } else if let Some(first) = syllable.chars().next().filter(|c| !is_vowel(*c) && *c != 'n') {
+                        syllable.insert(0, first);
+                    }
+                }
+                syllables.push(syllable);
+            }
+        }
+    }
+
+    let romaji = syllables.concat();
+    let mut chars = romaji.chars();
+    let first = chars.next()?;
+    Some(first.to_uppercase().chain(chars).collect())
+}
+
+/// 片假名转为对应的平假名，其余字符不变
+fn to_hiragana(c: char) -> char {
+    match c {
+        'ァ'..='ヶ' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+        _ => c,
+    }
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'i' | 'u' | 'e' | 'o')
+}
+
+fn small_vowel(c: char) -> char {
+    match c {
+        'ぁ' => 'a',
+        'ぃ' => 'i',
+        'ぅ' => 'u',
+        'ぇ' => 'e',
+        _ => 'o',
+    }
+}
+
+#[rustfmt::skip]
+fn base_romaji(c: char) -> Option<&'static str> {
+    let romaji = match c {
+        'あ' => "a", 'い' => "i", 'う' => "u", 'え' => "e", 'お' => "o",
+        'か' => "ka", 'き' => "ki", 'く' => "ku", 'け' => "ke", 'こ' => "ko",
+        'が' => "ga", 'ぎ' => "gi", 'ぐ' => "gu", 'げ' => "ge", 'ご' => "go",
+        'さ' => "sa", 'し' => "shi", 'す' => "su", 'せ' => "se", 'そ' => "so",
+        'ざ' => "za", 'じ' => "ji", 'ず' => "zu", 'ぜ' => "ze", 'ぞ' => "zo",
+        'た' => "ta", 'ち' => "chi", 'つ' => "tsu", 'て' => "te", 'と' => "to",
+        'だ' => "da", 'ぢ' => "ji", 'づ' => "zu", 'で' => "de", 'ど' => "do",
+        'な' => "na", 'に' => "ni", 'ぬ' => "nu", 'ね' => "ne", 'の' => "no",
+        'は' => "ha", 'ひ' => "hi", 'ふ' => "fu", 'へ' => "he", 'ほ' => "ho",
+        'ば' => "ba", 'び' => "bi", 'ぶ' => "bu", 'べ' => "be", 'ぼ' => "bo",
+        'ぱ' => "pa", 'ぴ' => "pi", 'ぷ' => "pu", 'ぺ' => "pe", 'ぽ' => "po",
+        'ま' => "ma", 'み' => "mi", 'む' => "mu", 'め' => "me", 'も' => "mo",
+        'や' => "ya", 'ゆ' => "yu", 'よ' => "yo",
+        'ら' => "ra", 'り' => "ri", 'る' => "ru", 'れ' => "re", 'ろ' => "ro",
+        'わ' => "wa", 'ゐ' => "i", 'ゑ' => "e", 'を' => "o", 'ん' => "n",
+        'ゔ' => "vu",
+        _ => return None,
+    };
+    Some(romaji)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kana_to_romaji() {
+        assert_eq!(kana_to_romaji("みかみ ゆあ").as_deref(), Some("Mikami Yua"));
+        assert_eq!(kana_to_romaji("ミカミ・ユア").as_deref(), Some("Mikami Yua"));
+        assert_eq!(kana_to_romaji("きょうこ").as_deref(), Some("Kyouko"));
+        assert_eq!(kana_to_romaji("シェリー").as_deref(), Some("Sherii"));
+        assert_eq!(kana_to_romaji("まっち").as_deref(), Some("Matchi"));
+        assert_eq!(kana_to_romaji("あっこ").as_deref(), Some("Akko"));
+        assert_eq!(kana_to_romaji("ジュン").as_deref(), Some("Jun"));
+        assert_eq!(kana_to_romaji("ファン").as_deref(), Some("Fan"));
+
+        // 同一输入总是得到相同结果
+        assert_eq!(kana_to_romaji("さくら まな"), kana_to_romaji("さくら まな"));
+
+        // 包含汉字或拉丁字母时无法转换
+        assert_eq!(kana_to_romaji("三上悠亜"), None);
+        assert_eq!(kana_to_romaji("Yua Mikami"), None);
+        assert_eq!(kana_to_romaji(" "), None);
+    }
+}
@@ -0,0 +1,209 @@
+// 规范分类表：把爬取到的类型等取值映射为固定的一组规范名称
+
+use std::{collections::BTreeMap, collections::HashMap, path::Path};
+
+use anyhow::Context;
+use regex::Regex;
+
+use crate::{config::UnmappedGenres, nfo::MovieNfoCrawler};
+
+/// 正则别名的前缀，与输出路由规则一致
+const REGEX_PREFIX: &str = "re:";
+
+/// 规范名称到别名的映射表
+///
+/// 文件为 TOML 表，键为规范名称，值为别名列表，如 `"巨乳" = ["Big Tits", "re:(?i)^busty"]`。
+/// 别名与规范名称本身不区分大小写精确匹配，`re:` 前缀表示正则匹配；
+/// 精确匹配优先，多个正则命中时取规范名称排序靠前的一个
+#[derive(Debug, Default)]
+pub struct Taxonomy {
+    /// 规范化后的别名到规范名称
+    aliases: HashMap<String, String>,
+    /// 按规范名称排序的正则别名
+    patterns: Vec<(Regex, String)>,
+}
+
+impl Taxonomy {
+    /// 从 TOML 文件加载分类表
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("读取分类表失败: {}", path.display()))?;
+        let entries: BTreeMap<String, Vec<String>> = toml::from_str(&content)
+            .with_context(|| format!("解析分类表失败: {}", path.display()))?;
+        Self::new(entries).with_context(|| format!("分类表无效: {}", path.display()))
+    }
+
+    /// 由规范名称与别名列表创建分类表，同一别名对应多个规范名称或正则无效时返回错误
+    pub fn new(entries: BTreeMap<String, Vec<String>>) -> anyhow::Result<Self> {
+        let mut taxonomy = Self::default();
+        let mut seen_patterns: HashMap<String, String> = HashMap::new();
+
+        for (canonical, aliases) in entries {
+            let canonical_name = canonical.trim();
+            if canonical_name.is_empty() {
+                anyhow::bail!("规范名称不能为空");
+            }
+            for alias in std::iter::once(canonical_name).chain(aliases.iter().map(|alias| alias.trim())) {
+                if let Some(pattern) = alias.strip_prefix(REGEX_PREFIX) {
+                    let regex = Regex::new(pattern)
+                        .map_err(|e| anyhow::anyhow!("'{}' 的正则别名 '{}' 无效: {}", canonical_name, pattern, e))?;
+                    match seen_patterns.insert(pattern.to_string(), canonical_name.to_string()) {
+                        Some(other) if other != canonical_name => {
+                            anyhow::bail!("正则别名 '{}' 同时属于 '{}' 与 '{}'", pattern, other, canonical_name)
+                        }
+                        Some(_) => {}
+                        None => taxonomy.patterns.push((regex, canonical_name.to_string())),
+                    }
+                } else if !alias.is_empty() {
+                    let key = alias.to_lowercase();
+                    match taxonomy.aliases.get(&key) {
+                        Some(other) if other != canonical_name => {
+                            anyhow::bail!("别名 '{}' 同时属于 '{}' 与 '{}'", alias, other, canonical_name)
+                        }
+                        _ => {
+                            taxonomy.aliases.insert(key, canonical_name.to_string());
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(taxonomy)
+    }
+
+    /// 查找取值对应的规范名称，没有匹配的别名时返回 None
+    pub fn lookup(&self, value: &str) -> Option<&str> {
+        let value = value.trim();
+        if value.is_empty() {
+            return None;
+        }
+        self.aliases
+            .get(&value.to_lowercase())
+            .or_else(|| {
+                self.patterns
+                    .iter()
+                    .find(|(regex, _)| regex.is_match(value))
+                    .map(|(_, canonical)| canonical)
+            })
+            .map(String::as_str)
+    }
+
+    /// 把取值映射为规范名称，返回去重后的规范名称与没有匹配的取值，均保持原有顺序
+    pub fn map_values(&self, values: &[String]) -> (Vec<String>, Vec<String>) {
+        let mut mapped: Vec<String> = Vec::new();
+        let mut unmapped: Vec<String> = Vec::new();
+        for value in values {
+            match self.lookup(value) {
+                Some(canonical) => {
+                    if !mapped.iter().any(|existing| existing == canonical) {
+                        mapped.push(canonical.to_string());
+                    }
+                }
+                None => {
+                    if !value.trim().is_empty() && !unmapped.contains(value) {
+                        unmapped.push(value.clone());
+                    }
+                }
+            }
+        }
+        (mapped, unmapped)
+    }
+}
+
+/// 按分类表映射影片类型，没有匹配的类型按 `unmapped` 策略转为标签、丢弃或保留
+pub fn apply_genre_taxonomy(movie: &mut MovieNfoCrawler, taxonomy: &Taxonomy, unmapped: UnmappedGenres) {
+    let (mut genres, others) = taxonomy.map_values(&movie.genres);
+    match unmapped {
+        UnmappedGenres::Tag => {
+            for genre in others {
+                if !movie.tags.contains(&genre) {
+                    movie.tags.push(genre);
+                }
+            }
+        }
+        UnmappedGenres::Drop => {
+            if !others.is_empty() {
+                log::debug!("丢弃分类表之外的类型: {:?}", others);
+            }
+        }
+        UnmappedGenres::Keep => genres.extend(others),
+    }
+    movie.genres = genres;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn taxonomy() -> Taxonomy {
+        let entries = toml::from_str(
+            r#"
+"巨乳" = ["Big Tits", "re:(?i)^busty"]
+"剧情" = ["Drama", "ドラマ"]
+"中出" = ["re:中出し"]
+"#,
+        )
+        .unwrap();
+        Taxonomy::new(entries).unwrap()
+    }
+
+    #[test]
+    fn test_taxonomy_lookup() {
+        let taxonomy = taxonomy();
+        assert_eq!(taxonomy.lookup("big tits"), Some("巨乳"));
+        assert_eq!(taxonomy.lookup(" DRAMA "), Some("剧情"));
+        assert_eq!(taxonomy.lookup("剧情"), Some("剧情"));
+        assert_eq!(taxonomy.lookup("Busty Girl"), Some("巨乳"));
+        assert_eq!(taxonomy.lookup("素人中出し"), Some("中出"));
+        assert_eq!(taxonomy.lookup("Cosplay"), None);
+        assert_eq!(taxonomy.lookup(""), None);
+    }
+
+    #[test]
+    fn test_taxonomy_rejects_conflicting_aliases() {
+        let entries = |entries: &[(&str, &[&str])]| {
+            entries
+                .iter()
+                .map(|(canonical, aliases)| (canonical.to_string(), strings(aliases)))
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        assert!(Taxonomy::new(entries(&[("巨乳", &["Big Tits"]), ("爆乳", &["big tits"])])).is_err());
+        assert!(Taxonomy::new(entries(&[("巨乳", &["re:^busty"]), ("爆乳", &["re:^busty"])])).is_err());
+        assert!(Taxonomy::new(entries(&[("巨乳", &[]), ("爆乳", &["巨乳"])])).is_err());
+        assert!(Taxonomy::new(entries(&[("巨乳", &["re:(["])])).is_err());
+
+        // 同一规范名称下重复的别名不算冲突
+        assert!(Taxonomy::new(entries(&[("巨乳", &["巨乳", "Big Tits", "big tits"])])).is_ok());
+    }
+
+    #[test]
+    fn test_apply_genre_taxonomy() {
+        let taxonomy = taxonomy();
+        let movie = MovieNfoCrawler {
+            genres: strings(&["Drama", "Cosplay", "Big Tits", "ドラマ", "Busty", "单体作品"]),
+            tags: strings(&["中文字幕", "Cosplay"]),
+            ..Default::default()
+        };
+
+        // 映射后的类型按首次出现的顺序去重
+        let mut tagged = movie.clone();
+        apply_genre_taxonomy(&mut tagged, &taxonomy, UnmappedGenres::Tag);
+        assert_eq!(tagged.genres, strings(&["剧情", "巨乳"]));
+        assert_eq!(tagged.tags, strings(&["中文字幕", "Cosplay", "单体作品"]));
+
+        let mut dropped = movie.clone();
+        apply_genre_taxonomy(&mut dropped, &taxonomy, UnmappedGenres::Drop);
+        assert_eq!(dropped.genres, strings(&["剧情", "巨乳"]));
+        assert_eq!(dropped.tags, strings(&["中文字幕", "Cosplay"]));
+
+        let mut kept = movie;
+        apply_genre_taxonomy(&mut kept, &taxonomy, UnmappedGenres::Keep);
+        assert_eq!(kept.genres, strings(&["剧情", "巨乳", "Cosplay", "单体作品"]));
+        assert_eq!(kept.tags, strings(&["中文字幕", "Cosplay"]));
+    }
+}
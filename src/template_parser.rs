@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, path::Path};
 use crate::nfo::MovieNfo;
 use anyhow::{anyhow, Result};
 use regex::Regex;
@@ -68,8 +68,9 @@ impl TemplateParser {
                 nfo.original_title.clone() 
             });
         
+        // 没有年份时由首映或发行日期得出
         self.variables.insert("year".to_string(), 
-            nfo.year.map(|y| y.to_string()).unwrap_or_else(|| "Unknown".to_string()));
+            nfo.release_year().map(|y| y.to_string()).unwrap_or_else(|| "Unknown".to_string()));
         
         // 系列信息
         if let Some(set) = &nfo.set {
@@ -119,9 +120,37 @@ impl TemplateParser {
             self.variables.insert("id".to_string(), "Unknown".to_string());
         }
 
+        // 文件名后缀标签默认为空，由 populate_suffix_tags 填充
+        self.variables.insert("suffix_tags".to_string(), "".to_string());
+
+        // 原始文件所在目录与文件名默认为空（解析为 Unknown），由 populate_source 填充
+        self.variables.insert("source_dir".to_string(), "".to_string());
+        self.variables.insert("source_name".to_string(), "".to_string());
+
         Ok(())
     }
 
+    /// 填充文件名后缀得到的标签
+    pub fn populate_suffix_tags(&mut self, suffix_tags: &[String]) {
+        self.variables.insert("suffix_tags".to_string(), suffix_tags.join(" "));
+    }
+
+    /// 填充整理前文件所在目录的名称与不含扩展名的文件名
+    pub fn populate_source(&mut self, original_file_path: &Path) {
+        let name = |path: Option<&Path>, stem: bool| {
+            path.and_then(|path| if stem { path.file_stem() } else { path.file_name() })
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        };
+        self.variables.insert("source_dir".to_string(), name(original_file_path.parent(), false));
+        self.variables.insert("source_name".to_string(), name(Some(original_file_path), true));
+    }
+
+    /// 模板是否使用了整理前的文件路径，这类路径无法仅由NFO重新计算
+    pub fn uses_source_variables(template: &str) -> bool {
+        template.contains("$source_dir$") || template.contains("$source_name$")
+    }
+
     /// 解析模板字符串，返回解析结果
     pub fn parse_template(&self, template: &str, strategy: MultiActorStrategy) -> Result<ParseResult> {
         // 创建正则表达式来匹配 $variable$ 格式的变量
@@ -174,8 +203,12 @@ impl TemplateParser {
                 })?.clone()
             };
             
-            // 清理文件名中的非法字符
-            let clean_replacement = self.sanitize_filename(&replacement);
+            // 清理文件名中的非法字符，没有后缀标签时直接留空而不是填充 Unknown
+            let clean_replacement = if var_name == "suffix_tags" && replacement.is_empty() {
+                replacement
+            } else {
+                self.sanitize_filename(&replacement)
+            };
             result = result.replace(placeholder, &clean_replacement);
         }
         
@@ -256,6 +289,9 @@ impl TemplateParser {
             "studio",        // 制片厂
             "genre",         // 类型（第一个）
             "id",            // 影片ID
+            "suffix_tags",   // 文件名后缀标签
+            "source_dir",    // 整理前文件所在目录的名称
+            "source_name",   // 整理前的文件名（不含扩展名）
         ]
     }
 }
@@ -372,6 +408,64 @@ mod tests {
         assert!(result.additional_paths.is_empty());
     }
 
+    #[test]
+    fn test_template_parser_suffix_tags() {
+        let mut parser = TemplateParser::new();
+        let nfo = create_test_nfo();
+
+        parser.populate_from_nfo(&nfo).unwrap();
+        let result = parser.parse_template(
+            "$title$ $suffix_tags$",
+            MultiActorStrategy::FirstOnly
+        ).unwrap();
+        assert_eq!(result.primary_path, "测试电影 ");
+
+        parser.populate_suffix_tags(&["chinese-sub".to_string(), "2160p".to_string()]);
+        let result = parser.parse_template(
+            "$title$ [$suffix_tags$]",
+            MultiActorStrategy::FirstOnly
+        ).unwrap();
+        assert_eq!(result.primary_path, "测试电影 [chinese-sub 2160p]");
+    }
+
+    #[test]
+    fn test_template_parser_source_variables() {
+        let mut parser = TemplateParser::new();
+        let nfo = create_test_nfo();
+
+        parser.populate_from_nfo(&nfo).unwrap();
+        let result = parser.parse_template(
+            "$source_dir$/$title$",
+            MultiActorStrategy::FirstOnly
+        ).unwrap();
+        assert_eq!(result.primary_path, "Unknown/测试电影");
+
+        parser.populate_source(Path::new("/downloads/Yua Mikami/ABC-123.mp4"));
+        let result = parser.parse_template(
+            "$source_dir$/$title$ [$source_name$]",
+            MultiActorStrategy::FirstOnly
+        ).unwrap();
+        assert_eq!(result.primary_path, "Yua Mikami/测试电影 [ABC-123]");
+        assert!(TemplateParser::uses_source_variables("$source_dir$/$title$"));
+        assert!(!TemplateParser::uses_source_variables("$series$/$title$"));
+    }
+
+    #[test]
+    fn test_template_parser_year_from_date() {
+        let mut parser = TemplateParser::new();
+        let mut nfo = create_test_nfo();
+        nfo.year = None;
+        nfo.premiered = "2024/07/10".to_string();
+
+        parser.populate_from_nfo(&nfo).unwrap();
+
+        let result = parser.parse_template(
+            "$title$ ($year$)",
+            MultiActorStrategy::FirstOnly
+        ).unwrap();
+        assert_eq!(result.primary_path, "测试电影 (2024)");
+    }
+
     #[test]
     fn test_available_variables() {
         let vars = TemplateParser::get_available_variables();
@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        pipeline::ProcessOutcome,
+        tests::harness::{create_pipeline, normalize_nfo, start_mock_site},
+        webhook::ProcessEvent,
+    };
+
+    /// 整理后NFO的基准文件
+    const GOLDEN_NFO: &str = include_str!("fixtures/tidy_001.nfo");
+
+    #[tokio::test]
+    async fn test_end_to_end_with_mock_site() {
+        let (mut server, pages) = start_mock_site().await;
+        // 未启用图片下载，不请求封面与预览图
+        let images = server
+            .mock("GET", mockito::Matcher::Regex("^/(covers|samples)/".to_string()))
+            .expect(0)
+            .create_async()
+            .await;
+
+        let (pipeline, root) = create_pipeline("test_end_to_end_with_mock_site", &server.url(), "");
+        let video_file = root.join("input").join("TIDY-001.mp4");
+        std::fs::write(&video_file, b"video content").unwrap();
+
+        let result = pipeline.process_file(&video_file).await;
+        for page in &pages {
+            page.assert_async().await;
+        }
+        images.assert_async().await;
+
+        // 目录结构
+        let movie_dir = root.join("output").join("测试系列").join("端到端测试影片 (2023)");
+        let video_path = movie_dir.join("端到端测试影片 (2023).mp4");
+        let nfo_path = movie_dir.join("端到端测试影片 (2023).nfo");
+        assert_eq!(
+            result.as_ref().unwrap(),
+            &ProcessOutcome::Organized {
+                movie_id: "TIDY-001".to_string(),
+                video_path: video_path.clone(),
+                nfo_paths: vec![nfo_path.clone()],
+            }
+        );
+        assert!(!video_file.exists());
+        assert_eq!(std::fs::read(&video_path).unwrap(), b"video content");
+        let mut files: Vec<_> = std::fs::read_dir(&movie_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        assert_eq!(files, ["端到端测试影片 (2023).mp4", "端到端测试影片 (2023).nfo"]);
+
+        // NFO 与基准文件一致
+        let nfo = std::fs::read_to_string(&nfo_path).unwrap();
+        assert_eq!(normalize_nfo(&nfo, &server.url()), normalize_nfo(GOLDEN_NFO, &server.url()));
+
+        // 处理结果通知
+        let event = ProcessEvent::new(&video_file, "TIDY-001", &result);
+        assert_eq!(event.movie_id, "TIDY-001");
+        assert_eq!(event.outcome, "organized");
+        assert_eq!(event.message, video_path.display().to_string());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,145 @@
+//! 端到端测试环境
+//!
+//! 使用 `template/javdb.yaml` 与临时的输入输出目录创建处理流程，模板的 `base_url`
+//! 通过 `template_overrides` 指向模拟站点（或真实站点），不下载图片、不翻译。
+
+use std::path::{Path, PathBuf};
+
+use crate::{config::AppConfig, pipeline::Pipeline};
+
+/// 模拟站点的搜索页与详情页
+pub const SEARCH_PAGE: &str = include_str!("fixtures/javdb_search.html");
+pub const DETAIL_PAGE: &str = include_str!("fixtures/javdb_detail.html");
+
+/// 创建临时目录并返回使用 `base_url` 的处理流程与目录路径，`extra_config` 追加到配置文件末尾
+pub fn create_pipeline(name: &str, base_url: &str, extra_config: &str) -> (Pipeline, PathBuf) {
+    let root = std::env::temp_dir().join(name);
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("input")).unwrap();
+
+    let config_path = root.join("config.toml");
+    std::fs::write(
+        &config_path,
+        format!(
+            r#"
+migrate_files = ["mp4"]
+ignored_id_pattern = []
+input_dir = "{root}/input"
+output_dir = "{root}/output"
+thread_limit = 1
+template_priority = ["javdb.yaml"]
+maximum_fetch_count = 1
+{extra_config}
+
+[image]
+download_images = false
+
+[translation]
+enabled = false
+
+[naming]
+template = "$series$/$title$ ($year$)"
+capital = false
+
+[template_overrides."javdb.yaml"]
+base_url = "{base_url}"
+"#,
+            root = root.display().to_string().replace('\\', "/"),
+        ),
+    )
+    .unwrap();
+
+    let config = AppConfig::new(&config_path).unwrap();
+    let pipeline = Pipeline::new(config, Path::new("template")).unwrap();
+    (pipeline, root)
+}
+
+/// 在 `dir` 中写入只启用 `template` 模板的最小配置并加载，输入输出目录位于 `dir` 下，
+/// `extra_config` 追加到顶层配置之后
+pub fn create_config(dir: &Path, template: &str, extra_config: &str) -> AppConfig {
+    std::fs::create_dir_all(dir).unwrap();
+    let config_path = dir.join("config.toml");
+    std::fs::write(
+        &config_path,
+        format!(
+            r#"
+migrate_files = ["mp4"]
+migrate_subtitles = false
+ignored_id_pattern = []
+capital = false
+input_dir = "{dir}/input"
+output_dir = "{dir}/output"
+thread_limit = 4
+template_priority = ["{template}"]
+maximum_fetch_count = 1
+{extra_config}
+"#,
+            dir = dir.display().to_string().replace('\\', "/"),
+        ),
+    )
+    .unwrap();
+    AppConfig::new(&config_path).unwrap()
+}
+
+/// 创建临时目录与从 `server_url` 爬取标题和演员的 `site.yaml` 模板，返回处理流程与目录路径，
+/// `extra_config` 追加到配置文件的顶层配置之后
+pub fn create_site_pipeline(name: &str, server_url: &str, extra_config: &str) -> (Pipeline, PathBuf) {
+    let test_dir = std::env::temp_dir().join(name);
+    let _ = std::fs::remove_dir_all(&test_dir);
+    let template_dir = test_dir.join("template");
+    std::fs::create_dir_all(&template_dir).unwrap();
+    std::fs::create_dir_all(test_dir.join("input")).unwrap();
+
+    std::fs::write(
+        template_dir.join("site.yaml"),
+        format!(
+            r#"
+entrypoint: "{server_url}/movie/${{crawl_name}}"
+nodes:
+  title: selector("h1").val()
+  actors: selector(".actor").val()
+"#
+        ),
+    )
+    .unwrap();
+
+    let config = create_config(&test_dir, "site.yaml", extra_config);
+    (Pipeline::new(config, &template_dir).unwrap(), test_dir)
+}
+
+/// 启动提供搜索页与详情页的模拟站点，返回站点与必须各请求一次的页面
+pub async fn start_mock_site() -> (mockito::ServerGuard, Vec<mockito::Mock>) {
+    let mut server = mockito::Server::new_async().await;
+    let search = server
+        .mock("GET", "/search")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("q".into(), "TIDY-001".into()),
+            mockito::Matcher::UrlEncoded("f".into(), "all".into()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "text/html; charset=utf-8")
+        .with_body(SEARCH_PAGE)
+        .expect(1)
+        .create_async()
+        .await;
+    let detail = server
+        .mock("GET", "/v/tidy001")
+        .with_status(200)
+        .with_header("content-type", "text/html; charset=utf-8")
+        .with_body(DETAIL_PAGE)
+        .expect(1)
+        .create_async()
+        .await;
+    (server, vec![search, detail])
+}
+
+/// 规范化NFO以便与基准文件比较：每个标签一行、去掉空行，站点地址替换为 `{base_url}`
+pub fn normalize_nfo(nfo: &str, base_url: &str) -> String {
+    nfo.replace(base_url, "{base_url}")
+        .replace("><", ">\n<")
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
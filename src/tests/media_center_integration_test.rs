@@ -4,6 +4,7 @@
 use crate::config::AppConfig;
 use crate::file_organizer::FileOrganizer;
 use crate::nfo::{MovieNfo, MovieSet};
+use crate::output_router::OutputTarget;
 use std::path::Path;
 
 #[cfg(test)]
@@ -75,7 +76,7 @@ subtitle_language = "zh-CN"
         let nfo = create_sample_nfo_with_series();
 
         let original_path = Path::new("./test_input/IPZZ-315.mp4");
-        let result = organizer.preview_media_center_structure(original_path, &nfo, &config);
+        let result = organizer.preview_media_center_structure(original_path, &nfo, &config, &OutputTarget::from_config(&config), &[]);
 
         assert!(result.is_ok());
         let (video_path, nfo_path) = result.unwrap();
@@ -111,7 +112,7 @@ subtitle_language = "zh-CN"
         let nfo = create_sample_nfo_without_series();
 
         let original_path = Path::new("./test_input/TEST-001.mp4");
-        let result = organizer.preview_media_center_structure(original_path, &nfo, &config);
+        let result = organizer.preview_media_center_structure(original_path, &nfo, &config, &OutputTarget::from_config(&config), &[]);
 
         assert!(result.is_ok());
         let (video_path, nfo_path) = result.unwrap();
@@ -141,7 +142,7 @@ subtitle_language = "zh-CN"
 
         let original_path = Path::new("./test_input/IPZZ-315.mp4");
         let (video_path, nfo_path) = organizer
-            .preview_media_center_structure(original_path, &nfo, &config)
+            .preview_media_center_structure(original_path, &nfo, &config, &OutputTarget::from_config(&config), &[])
             .unwrap();
 
         // 验证符合媒体中心扫描标准
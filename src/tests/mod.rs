@@ -5,6 +5,12 @@
 #[cfg(test)]
 mod config_test;
 
+#[cfg(test)]
+mod end_to_end_test;
+
+#[cfg(test)]
+pub(crate) mod harness;
+
 #[cfg(test)]
 mod media_center_integration_test;
 
@@ -4,7 +4,7 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use tokio::time::{timeout, Duration};
 
-use crate::config::AppConfig;
+use crate::config::{ActorNamePolicy, AppConfig};
 use crate::nfo::MovieNfoCrawler;
 
 /// OpenAI API 兼容的请求结构
@@ -39,6 +39,37 @@ struct OpenAiMessage2 {
     content: String,
 }
 
+/// OpenAI API 兼容的流式响应分片 (SSE `data:` 行)
+#[derive(Debug, Deserialize)]
+struct OpenAiStreamChunk {
+    choices: Vec<OpenAiStreamChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiStreamChoice {
+    #[serde(default)]
+    delta: OpenAiStreamDelta,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct OpenAiStreamDelta {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+/// API 错误响应：OpenAI 为 `{"error": {"message": ...}}`，Ollama 为 `{"error": "..."}`
+#[derive(Debug, Deserialize)]
+struct ApiErrorResponse {
+    error: ApiErrorBody,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ApiErrorBody {
+    Message(String),
+    Object { message: String },
+}
+
 /// 翻译服务提供商
 #[derive(Debug, Clone)]
 pub enum TranslationProvider {
@@ -68,6 +99,107 @@ impl TranslationProvider {
             TranslationProvider::Custom(url) => url,
         }
     }
+
+    /// 未配置模型时使用的默认模型，Ollama 的模型取决于本地拉取的模型，没有默认值
+    pub fn default_model(&self) -> Option<&'static str> {
+        match self {
+            TranslationProvider::OpenAI | TranslationProvider::Custom(_) => Some("gpt-3.5-turbo"),
+            TranslationProvider::Ollama => None,
+        }
+    }
+
+    /// 连接测试的探测地址：OpenAI 兼容接口为 `/models`，Ollama 为原生的 `/api/tags`
+    fn probe_url(&self, base_url: &str) -> String {
+        let base_url = base_url.trim_end_matches('/');
+        match self {
+            TranslationProvider::Ollama => {
+                format!("{}/api/tags", base_url.trim_end_matches("/v1"))
+            }
+            _ => format!("{}/models", base_url),
+        }
+    }
+}
+
+/// 可识别的文本语言，用于判断文本是否已是目标语言
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextLanguage {
+    Chinese,
+    Japanese,
+    Korean,
+    English,
+}
+
+impl TextLanguage {
+    /// 根据配置中的语言名称识别语言，无法识别时返回 None
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        match name.as_str() {
+            "中文" | "汉语" | "漢語" | "简体中文" | "繁体中文" | "繁體中文" | "chinese" => Some(Self::Chinese),
+            "日语" | "日語" | "日文" | "日本語" | "japanese" | "ja" => Some(Self::Japanese),
+            "韩语" | "韓語" | "韩文" | "한국어" | "korean" | "ko" => Some(Self::Korean),
+            "英语" | "英語" | "英文" | "english" | "en" => Some(Self::English),
+            _ if name.starts_with("zh") => Some(Self::Chinese),
+            _ if name.starts_with("en-") => Some(Self::English),
+            _ => None,
+        }
+    }
+
+    /// 检测文本是否主要由该语言组成
+    ///
+    /// 汉字、假名、谚文每个字符计为一个单位，连续的拉丁字母计为一个单位，
+    /// 数字、空白与标点不参与统计；超过一半的单位属于该语言即视为该语言文本
+    pub fn matches(&self, text: &str) -> bool {
+        let mut han = 0usize;
+        let mut kana = 0usize;
+        let mut hangul = 0usize;
+        let mut latin_words = 0usize;
+        let mut in_latin_word = false;
+
+        for c in text.chars() {
+            let code = c as u32;
+            let is_latin = c.is_alphabetic() && code < 0x0250;
+
+            if is_latin {
+                if !in_latin_word {
+                    latin_words += 1;
+                }
+            } else if (0x4E00..=0x9FFF).contains(&code) || // CJK 统一表意文字
+                (0x3400..=0x4DBF).contains(&code) || // CJK 扩展 A
+                (0x20000..=0x2CEAF).contains(&code) // CJK 扩展 B-E
+            {
+                han += 1;
+            } else if (0x3040..=0x30FF).contains(&code) || // 平假名、片假名
+                (0x31F0..=0x31FF).contains(&code) || // 片假名音标扩展
+                (0xFF66..=0xFF9F).contains(&code) // 半角片假名
+            {
+                kana += 1;
+            } else if (0xAC00..=0xD7AF).contains(&code) || // 谚文音节
+                (0x1100..=0x11FF).contains(&code) || // 谚文字母
+                (0x3130..=0x318F).contains(&code) // 谚文兼容字母
+            {
+                hangul += 1;
+            }
+
+            in_latin_word = is_latin;
+        }
+
+        let total = han + kana + hangul + latin_words;
+        if total == 0 {
+            return false;
+        }
+
+        let matched = match self {
+            // 含有假名的文本视为日语，避免把汉字较多的日语标题误判为中文
+            Self::Chinese if kana > 0 => 0,
+            Self::Chinese => han,
+            Self::Japanese if kana == 0 => 0,
+            Self::Japanese => han + kana,
+            Self::Korean => hangul,
+            Self::English => latin_words,
+        };
+
+        matched as f64 / total as f64 > 0.5
+    }
 }
 
 /// 翻译配置
@@ -75,13 +207,28 @@ impl TranslationProvider {
 pub struct TranslationConfig {
     pub provider: TranslationProvider,
     pub api_key: Option<String>,
+    /// 模型名称，留空时使用提供商的默认模型
     pub model: String,
+    /// 覆盖提供商的 API 地址
+    pub base_url: Option<String>,
+    /// 每个请求附加的 HTTP 头
+    pub extra_headers: HashMap<String, String>,
     pub target_language: String,
     pub source_language: Option<String>,
     pub max_tokens: u32,
     pub temperature: f32,
     pub timeout_seconds: u64,
     pub retry_count: u32,
+    /// 重试退避的基础延迟（毫秒）
+    pub retry_base_delay_ms: u64,
+    /// 重试退避的最大延迟（毫秒）
+    pub retry_max_delay_ms: u64,
+    /// 重试延迟的随机抖动比例 (0.0-1.0)
+    pub retry_jitter: f32,
+    /// 是否使用流式响应 (stream=true)
+    pub stream: bool,
+    /// 不翻译的术语表，翻译时以占位符保护
+    pub no_translate_terms: Vec<String>,
 }
 
 impl Default for TranslationConfig {
@@ -89,13 +236,20 @@ impl Default for TranslationConfig {
         Self {
             provider: TranslationProvider::OpenAI,
             api_key: None,
-            model: "gpt-3.5-turbo".to_string(),
+            model: String::new(),
+            base_url: None,
+            extra_headers: HashMap::new(),
             target_language: "中文".to_string(),
             source_language: Some("日语".to_string()),
             max_tokens: 1000,
             temperature: 0.3,
             timeout_seconds: 30,
             retry_count: 3,
+            retry_base_delay_ms: 1000,
+            retry_max_delay_ms: 30_000,
+            retry_jitter: 0.2,
+            stream: false,
+            no_translate_terms: Vec::new(),
         }
     }
 }
@@ -108,10 +262,18 @@ pub struct Translator {
 }
 
 impl Translator {
-    pub fn new(config: TranslationConfig) -> Result<Self> {
+    pub fn new(mut config: TranslationConfig) -> Result<Self> {
+        if config.model.trim().is_empty() {
+            config.model = config
+                .provider
+                .default_model()
+                .context("使用 Ollama 翻译时必须在 translation.model 中指定已拉取的模型名称")?
+                .to_string();
+        }
+
         let mut headers = reqwest::header::HeaderMap::new();
         
-        // 添加认证头
+        // 添加认证头（未配置 API Key 时不发送，避免空的 Bearer 头被代理拒绝）
         if let Some(api_key) = &config.api_key {
             if !api_key.is_empty() {
                 let auth_value = format!("Bearer {}", api_key);
@@ -127,9 +289,25 @@ impl Translator {
             "application/json".parse().unwrap(),
         );
 
-        let client = Client::builder()
-            .default_headers(headers)
-            .timeout(Duration::from_secs(config.timeout_seconds))
+        for (name, value) in &config.extra_headers {
+            headers.insert(
+                reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                    .with_context(|| format!("无效的请求头名称: {}", name))?,
+                value
+                    .parse()
+                    .with_context(|| format!("请求头 '{}' 的值无效", name))?,
+            );
+        }
+
+        // 流式响应只限制两次数据之间的间隔，避免慢速模型的长文本整体超时
+        let client_builder = Client::builder().default_headers(headers);
+        let client_builder = if config.stream {
+            client_builder.read_timeout(Duration::from_secs(config.timeout_seconds))
+        } else {
+            client_builder.timeout(Duration::from_secs(config.timeout_seconds))
+        };
+
+        let client = client_builder
             .user_agent("jav-tidy-rs/1.0")
             .build()
             .context("创建 HTTP 客户端失败")?;
@@ -149,12 +327,19 @@ impl Translator {
             provider: app_config.get_translation_provider().parse().unwrap_or(TranslationProvider::OpenAI),
             api_key: app_config.get_translation_api_key().clone(),
             model: app_config.get_translation_model().to_string(),
+            base_url: app_config.get_translation_base_url().clone(),
+            extra_headers: app_config.get_translation_extra_headers().clone(),
             target_language: app_config.get_translation_target_language().to_string(),
             source_language: app_config.get_translation_source_language().clone(),
             max_tokens: app_config.get_translation_max_tokens(),
             temperature: app_config.get_translation_temperature(),
             timeout_seconds: app_config.get_translation_timeout(),
             retry_count: app_config.get_translation_retry_count(),
+            retry_base_delay_ms: app_config.get_translation_retry_base_delay_ms(),
+            retry_max_delay_ms: app_config.get_translation_retry_max_delay_ms(),
+            retry_jitter: app_config.get_translation_retry_jitter(),
+            stream: app_config.is_translation_stream_enabled(),
+            no_translate_terms: app_config.get_translation_no_translate_terms().clone(),
         };
 
         Self::new(translation_config)
@@ -216,13 +401,36 @@ impl Translator {
 
     /// 翻译文本
     pub async fn translate_text(&self, text: &str) -> Result<String> {
+        self.translate_text_with_terms(text, &[]).await
+    }
+
+    /// 翻译文本，`extra_terms` 与配置中的术语表一起原样保留
+    pub async fn translate_text_with_terms(&self, text: &str, extra_terms: &[String]) -> Result<String> {
         if text.is_empty() {
             return Ok(String::new());
         }
 
+        if self.is_target_language_text(text) {
+            log::debug!("文本已是目标语言，跳过翻译: {}", text);
+            return Ok(text.to_string());
+        }
+
         log::debug!("开始翻译文本: {}", text);
 
-        let prompt = self.build_translation_prompt(text);
+        let (masked, placeholders) = self.mask_terms(text, extra_terms);
+
+        // 文本全部由术语组成时无需翻译
+        if !placeholders.is_empty()
+            && placeholders
+                .iter()
+                .fold(masked.clone(), |rest, (placeholder, _)| rest.replace(placeholder, ""))
+                .trim()
+                .is_empty()
+        {
+            return Ok(text.to_string());
+        }
+
+        let prompt = self.build_translation_prompt(&masked, !placeholders.is_empty());
         
         let mut last_error = None;
         
@@ -230,6 +438,7 @@ impl Translator {
         for attempt in 1..=self.config.retry_count {
             match self.call_api(&prompt).await {
                 Ok(translated) => {
+                    let translated = Self::unmask_terms(&translated, &placeholders);
                     log::info!("翻译成功 (第{}次尝试): {} -> {}", attempt, text, translated);
                     return Ok(translated);
                 }
@@ -238,7 +447,9 @@ impl Translator {
                     last_error = Some(e);
                     
                     if attempt < self.config.retry_count {
-                        tokio::time::sleep(Duration::from_secs(2u64.pow(attempt))).await;
+                        let delay = self.retry_delay(attempt, random_unit());
+                        log::debug!("等待 {:?} 后重试翻译", delay);
+                        tokio::time::sleep(delay).await;
                     }
                 }
             }
@@ -247,17 +458,101 @@ impl Translator {
         Err(last_error.unwrap_or_else(|| anyhow::anyhow!("翻译失败")))
     }
 
+    /// 将文本中的不翻译术语替换为占位符，返回替换后的文本及占位符与术语的对应关系
+    ///
+    /// 术语按长度从长到短匹配，避免较短的术语截断较长的术语
+    fn mask_terms(&self, text: &str, extra_terms: &[String]) -> (String, Vec<(String, String)>) {
+        let mut terms: Vec<&str> = self
+            .config
+            .no_translate_terms
+            .iter()
+            .chain(extra_terms.iter())
+            .map(|term| term.trim())
+            .filter(|term| !term.is_empty() && text.contains(term))
+            .collect();
+
+        if terms.is_empty() {
+            return (text.to_string(), Vec::new());
+        }
+
+        terms.sort_by_key(|term| std::cmp::Reverse(term.chars().count()));
+        terms.dedup();
+
+        let pattern = terms
+            .iter()
+            .map(|term| regex::escape(term))
+            .collect::<Vec<_>>()
+            .join("|");
+        let Ok(re) = regex::Regex::new(&pattern) else {
+            return (text.to_string(), Vec::new());
+        };
+
+        let mut placeholders: Vec<(String, String)> = Vec::new();
+        let masked = re.replace_all(text, |caps: &regex::Captures| {
+            let term = &caps[0];
+            match placeholders.iter().find(|(_, t)| t == term) {
+                Some((placeholder, _)) => placeholder.clone(),
+                None => {
+                    let placeholder = format!("[[T{}]]", placeholders.len());
+                    placeholders.push((placeholder.clone(), term.to_string()));
+                    placeholder
+                }
+            }
+        });
+
+        (masked.into_owned(), placeholders)
+    }
+
+    /// 将占位符还原为原始术语
+    fn unmask_terms(text: &str, placeholders: &[(String, String)]) -> String {
+        placeholders
+            .iter()
+            .fold(text.to_string(), |text, (placeholder, term)| text.replace(placeholder, term))
+    }
+
+    /// 计算第 `attempt` 次失败后的退避延迟
+    ///
+    /// 基础延迟按 2 的幂增长并受最大延迟限制，再按 `random` (取值 0.0-1.0)
+    /// 在 ±`retry_jitter` 比例内随机抖动，避免多个请求同时重试
+    fn retry_delay(&self, attempt: u32, random: f64) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let backoff = self
+            .config
+            .retry_base_delay_ms
+            .saturating_mul(1u64 << exponent)
+            .min(self.config.retry_max_delay_ms);
+
+        let jitter = (self.config.retry_jitter as f64).clamp(0.0, 1.0);
+        let factor = 1.0 + jitter * (random.clamp(0.0, 1.0) * 2.0 - 1.0);
+
+        Duration::from_millis((backoff as f64 * factor).round() as u64)
+    }
+
     /// 构建翻译提示词
-    fn build_translation_prompt(&self, text: &str) -> String {
+    fn build_translation_prompt(&self, text: &str, has_placeholders: bool) -> String {
         let source_lang = self.config.source_language.as_deref().unwrap_or("自动检测");
         let target_lang = &self.config.target_language;
+        let placeholder_hint = if has_placeholders {
+            "文本中形如 [[T0]] 的占位符必须原样保留。"
+        } else {
+            ""
+        };
 
         format!(
-            "请将以下{}文本翻译为{}，保持原意的同时使其更易读懂。只返回翻译结果，不要任何解释：\n\n{}",
-            source_lang, target_lang, text
+            "请将以下{}文本翻译为{}，保持原意的同时使其更易读懂。{}只返回翻译结果，不要任何解释：\n\n{}",
+            source_lang, target_lang, placeholder_hint, text
         )
     }
 
+    /// API 地址，配置了 `base_url` 时覆盖提供商的默认地址
+    fn base_url(&self) -> &str {
+        self.config
+            .base_url
+            .as_deref()
+            .unwrap_or_else(|| self.config.provider.get_base_url())
+            .trim_end_matches('/')
+    }
+
     /// 调用 API
     async fn call_api(&self, prompt: &str) -> Result<String> {
         let request = OpenAiRequest {
@@ -268,10 +563,10 @@ impl Translator {
             }],
             max_tokens: Some(self.config.max_tokens),
             temperature: Some(self.config.temperature),
-            stream: false,
+            stream: self.config.stream,
         };
 
-        let url = format!("{}/chat/completions", self.config.provider.get_base_url());
+        let url = format!("{}/chat/completions", self.base_url());
         
         log::debug!("调用翻译 API: {}", url);
 
@@ -286,13 +581,22 @@ impl Translator {
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
-            return Err(anyhow::anyhow!("API 错误 {}: {}", status, body));
+            return Err(anyhow::anyhow!("API 错误 {}: {}", status, Self::error_message(&body)));
         }
 
-        let api_response: OpenAiResponse = response
-            .json()
-            .await
-            .context("解析 API 响应失败")?;
+        if self.config.stream {
+            return Self::read_stream_response(response).await;
+        }
+
+        let body = response.text().await.context("读取 API 响应失败")?;
+        let api_response: OpenAiResponse = match serde_json::from_str(&body) {
+            Ok(api_response) => api_response,
+            // Ollama 出错时也可能返回 200 和 `{"error": ...}`
+            Err(e) => match Self::parse_api_error(&body) {
+                Some(message) => return Err(anyhow::anyhow!("API 错误: {}", message)),
+                None => return Err(anyhow::Error::new(e).context("解析 API 响应失败")),
+            },
+        };
 
         if api_response.choices.is_empty() {
             return Err(anyhow::anyhow!("API 响应为空"));
@@ -307,13 +611,99 @@ impl Translator {
         Ok(translated)
     }
 
+    /// 读取流式响应，拼接所有 `delta.content` 分片
+    async fn read_stream_response(mut response: reqwest::Response) -> Result<String> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut translated = String::new();
+
+        'read: while let Some(chunk) = response.chunk().await.context("读取流式响应失败")? {
+            buffer.extend_from_slice(&chunk);
+
+            // 只解码完整的行，剩余部分留到下一个分片，避免多字节字符被分片截断
+            while let Some(line_end) = buffer.iter().position(|&byte| byte == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=line_end).collect();
+                let line = String::from_utf8_lossy(&line);
+
+                match Self::parse_stream_line(line.trim())? {
+                    StreamLine::Delta(delta) => {
+                        log::debug!("流式翻译片段: {}", delta);
+                        translated.push_str(&delta);
+                    }
+                    StreamLine::Done => break 'read,
+                    StreamLine::Skip => {}
+                }
+            }
+        }
+
+        if let StreamLine::Delta(delta) = Self::parse_stream_line(String::from_utf8_lossy(&buffer).trim())? {
+            translated.push_str(&delta);
+        }
+
+        let translated = translated.trim().to_string();
+
+        if translated.is_empty() {
+            return Err(anyhow::anyhow!("翻译结果为空"));
+        }
+
+        Ok(translated)
+    }
+
+    /// 解析单行 SSE 数据
+    fn parse_stream_line(line: &str) -> Result<StreamLine> {
+        let Some(data) = line.strip_prefix("data:") else {
+            return Ok(StreamLine::Skip);
+        };
+
+        let data = data.trim();
+        if data == "[DONE]" {
+            return Ok(StreamLine::Done);
+        }
+
+        let chunk: OpenAiStreamChunk = match serde_json::from_str(data) {
+            Ok(chunk) => chunk,
+            Err(e) => match Self::parse_api_error(data) {
+                Some(message) => return Err(anyhow::anyhow!("API 错误: {}", message)),
+                None => return Err(anyhow::Error::new(e).context("解析流式响应分片失败")),
+            },
+        };
+
+        Ok(chunk
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|choice| choice.delta.content)
+            .map_or(StreamLine::Skip, StreamLine::Delta))
+    }
+
+    /// 从 API 错误响应中提取错误信息
+    fn parse_api_error(body: &str) -> Option<String> {
+        let response: ApiErrorResponse = serde_json::from_str(body).ok()?;
+        Some(match response.error {
+            ApiErrorBody::Message(message) | ApiErrorBody::Object { message } => message,
+        })
+    }
+
+    /// 可读的错误信息，无法识别错误格式时使用原始响应
+    fn error_message(body: &str) -> String {
+        Self::parse_api_error(body).unwrap_or_else(|| body.trim().to_string())
+    }
+
     /// 翻译影片数据
     pub async fn translate_movie_data(&self, movie_data: &mut MovieNfoCrawler, config: &crate::config::AppConfig) -> Result<()> {
         log::info!("开始翻译影片数据: {}", movie_data.title);
 
-        // 翻译标题
-        if !movie_data.title.is_empty() {
-            match self.translate_text(&movie_data.title).await {
+        // 演员名自动加入不翻译术语
+        let actor_terms: Vec<String> = movie_data.actors.iter().map(|actor| actor.name.clone()).collect();
+
+        // 翻译标题（翻译前将原文保留到原始标题中）
+        if !movie_data.title.is_empty() && config.should_translate_field("title") {
+            if config.should_preserve_original_title()
+                && movie_data.original_title.as_ref().is_none_or(|t| t.is_empty())
+            {
+                movie_data.original_title = Some(movie_data.title.clone());
+            }
+
+            match self.translate_text_with_terms(&movie_data.title, &actor_terms).await {
                 Ok(translated) => {
                     log::info!("标题翻译: {} -> {}", movie_data.title, translated);
                     movie_data.title = translated;
@@ -324,24 +714,9 @@ impl Translator {
             }
         }
 
-        // 翻译原始标题（如果与标题不同）
-        if let Some(original_title) = &movie_data.original_title {
-            if !original_title.is_empty() && original_title != &movie_data.title {
-                match self.translate_text(original_title).await {
-                    Ok(translated) => {
-                        log::info!("原始标题翻译: {} -> {}", original_title, translated);
-                        movie_data.original_title = Some(translated);
-                    }
-                    Err(e) => {
-                        log::warn!("原始标题翻译失败: {}", e);
-                    }
-                }
-            }
-        }
-
         // 翻译剧情简介
-        if !movie_data.plot.is_empty() && movie_data.plot.len() > 10 {
-            match self.translate_text(&movie_data.plot).await {
+        if !movie_data.plot.is_empty() && movie_data.plot.len() > 10 && config.should_translate_field("plot") {
+            match self.translate_text_with_terms(&movie_data.plot, &actor_terms).await {
                 Ok(translated) => {
                     log::info!("剧情简介翻译完成 ({} -> {} 字符)", movie_data.plot.len(), translated.len());
                     movie_data.plot = translated;
@@ -353,8 +728,8 @@ impl Translator {
         }
 
         // 翻译标语
-        if !movie_data.tagline.is_empty() {
-            match self.translate_text(&movie_data.tagline).await {
+        if !movie_data.tagline.is_empty() && config.should_translate_field("tagline") {
+            match self.translate_text_with_terms(&movie_data.tagline, &actor_terms).await {
                 Ok(translated) => {
                     log::info!("标语翻译: {} -> {}", movie_data.tagline, translated);
                     movie_data.tagline = translated;
@@ -366,8 +741,8 @@ impl Translator {
         }
 
         // 翻译系列名称
-        if !movie_data.series_name.is_empty() {
-            match self.translate_text(&movie_data.series_name).await {
+        if !movie_data.series_name.is_empty() && config.should_translate_field("series_name") {
+            match self.translate_text_with_terms(&movie_data.series_name, &actor_terms).await {
                 Ok(translated) => {
                     log::info!("系列名称翻译: {} -> {}", movie_data.series_name, translated);
                     movie_data.series_name = translated;
@@ -379,8 +754,8 @@ impl Translator {
         }
 
         // 翻译系列描述
-        if !movie_data.series_overview.is_empty() {
-            match self.translate_text(&movie_data.series_overview).await {
+        if !movie_data.series_overview.is_empty() && config.should_translate_field("series_overview") {
+            match self.translate_text_with_terms(&movie_data.series_overview, &actor_terms).await {
                 Ok(translated) => {
                     log::info!("系列描述翻译完成");
                     movie_data.series_overview = translated;
@@ -393,7 +768,7 @@ impl Translator {
 
         // 翻译标签（如果启用）
         if config.should_translate_tags() {
-            if !movie_data.tags.is_empty() {
+            if !movie_data.tags.is_empty() && config.should_translate_field("tags") {
                 match self.translate_tags(&mut movie_data.tags).await {
                     Ok(_) => {
                         log::info!("标签翻译完成: {:?}", movie_data.tags);
@@ -405,7 +780,7 @@ impl Translator {
             }
 
             // 翻译类型（如果启用）
-            if !movie_data.genres.is_empty() {
+            if !movie_data.genres.is_empty() && config.should_translate_field("genres") {
                 match self.translate_tags(&mut movie_data.genres).await {
                     Ok(_) => {
                         log::info!("类型翻译完成: {:?}", movie_data.genres);
@@ -417,6 +792,10 @@ impl Translator {
             }
         }
 
+        // 统一演员名的书写形式后再合并，不同写法的同一演员可以去重
+        self.apply_actor_name_policy(&mut movie_data.actors, config.get_actor_name_policy())
+            .await;
+
         // 处理演员名称合并（始终开启）
         self.merge_actors(&mut movie_data.actors);
 
@@ -449,6 +828,44 @@ impl Translator {
         Ok(())
     }
 
+    /// 按配置改写演员名：`romaji` 只转换由假名组成的名字，`target_language` 逐个翻译，失败时保留原名
+    pub async fn apply_actor_name_policy(
+        &self,
+        actors: &mut [crate::nfo::Actor],
+        policy: ActorNamePolicy,
+    ) {
+        let translate = match policy {
+            ActorNamePolicy::Keep => return,
+            ActorNamePolicy::Romaji => false,
+            ActorNamePolicy::TargetLanguage => true,
+        };
+
+        for actor in actors.iter_mut() {
+            let renamed = if !translate {
+                crate::romaji::kana_to_romaji(&actor.name)
+            } else {
+                match self.translate_text(&actor.name).await {
+                    // 名字翻译结果只应是一行短文本
+                    Ok(translated) if !translated.trim().is_empty() && !translated.contains('\n') => {
+                        Some(translated.trim().to_string())
+                    }
+                    Ok(translated) => {
+                        log::warn!("演员名翻译结果无效，保留原名 {}: {:?}", actor.name, translated);
+                        None
+                    }
+                    Err(e) => {
+                        log::warn!("演员名翻译失败，保留原名 {}: {}", actor.name, e);
+                        None
+                    }
+                }
+            };
+            if let Some(renamed) = renamed.filter(|renamed| *renamed != actor.name) {
+                log::info!("演员名: {} -> {}", actor.name, renamed);
+                actor.name = renamed;
+            }
+        }
+    }
+
     /// 合并相同演员名称（基础合并，始终开启）
     pub fn merge_actors(&self, actors: &mut Vec<crate::nfo::Actor>) {
         if actors.is_empty() {
@@ -531,8 +948,8 @@ impl Translator {
                 continue;
             }
 
-            // 如果标签很短或已经是中文，可能不需要翻译
-            if tag.chars().count() <= 2 || self.is_chinese_text(tag) {
+            // 如果标签很短或已经是目标语言，可能不需要翻译
+            if tag.chars().count() <= 2 || self.is_target_language_text(tag) {
                 translated_tags.push(tag.clone());
                 continue;
             }
@@ -646,35 +1063,21 @@ impl Translator {
         Ok(merged_tags)
     }
 
-    /// 检测是否为中文文本
-    fn is_chinese_text(&self, text: &str) -> bool {
-        let chinese_chars = text.chars()
-            .filter(|c| {
-                let code = *c as u32;
-                // Unicode 中文字符范围
-                (0x4E00..=0x9FFF).contains(&code) || // CJK 统一表意文字
-                (0x3400..=0x4DBF).contains(&code) || // CJK 扩展 A
-                (0x20000..=0x2A6DF).contains(&code) || // CJK 扩展 B
-                (0x2A700..=0x2B73F).contains(&code) || // CJK 扩展 C
-                (0x2B740..=0x2B81F).contains(&code) || // CJK 扩展 D
-                (0x2B820..=0x2CEAF).contains(&code) // CJK 扩展 E
-            })
-            .count();
-
-        let total_chars = text.chars().count();
-        
-        // 如果超过50%是中文字符，则认为是中文文本
-        total_chars > 0 && (chinese_chars as f64 / total_chars as f64) > 0.5
+    /// 检测文本是否已是目标语言，目标语言无法识别时返回 false
+    fn is_target_language_text(&self, text: &str) -> bool {
+        TextLanguage::from_name(&self.config.target_language)
+            .is_some_and(|language| language.matches(text))
     }
 
     /// 测试翻译服务连接
+    ///
+    /// 请求模型列表接口探测连通性和认证，不消耗翻译额度
     pub async fn test_connection(&self) -> Result<()> {
         log::info!("测试翻译服务连接...");
-        
-        let test_text = "テスト";
-        match self.translate_text(test_text).await {
-            Ok(result) => {
-                log::info!("翻译服务连接正常，测试翻译: {} -> {}", test_text, result);
+
+        match self.probe().await {
+            Ok(()) => {
+                log::info!("翻译服务连接正常，模型: {}", self.config.model);
                 Ok(())
             }
             Err(e) => {
@@ -683,6 +1086,47 @@ impl Translator {
             }
         }
     }
+
+    async fn probe(&self) -> Result<()> {
+        let url = self.config.provider.probe_url(self.base_url());
+        log::debug!("探测翻译服务: {}", url);
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("发送探测请求失败")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow::anyhow!("API 错误 {}: {}", status, Self::error_message(&body)));
+        }
+
+        Ok(())
+    }
+}
+
+/// SSE 单行的解析结果
+enum StreamLine {
+    Delta(String),
+    Done,
+    Skip,
+}
+
+/// 生成 0.0-1.0 之间的随机数，用于重试抖动
+fn random_unit() -> f64 {
+    use std::hash::{BuildHasher, Hasher};
+
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
 }
 
 impl Default for Translator {
@@ -721,11 +1165,507 @@ mod tests {
     fn test_chinese_detection() {
         let translator = Translator::default();
         
-        assert!(translator.is_chinese_text("这是中文"));
-        assert!(translator.is_chinese_text("中文测试"));
-        assert!(!translator.is_chinese_text("english text"));
-        assert!(!translator.is_chinese_text("テスト"));
-        assert!(translator.is_chinese_text("中英混合 mixed"));
+        assert!(translator.is_target_language_text("这是中文"));
+        assert!(translator.is_target_language_text("中文测试"));
+        assert!(!translator.is_target_language_text("english text"));
+        assert!(!translator.is_target_language_text("テスト"));
+        assert!(translator.is_target_language_text("中英混合 mixed"));
+        assert!(!translator.is_target_language_text("人妻自宅エステサロン"));
+    }
+
+    #[test]
+    fn test_text_language_detection() {
+        assert_eq!(TextLanguage::from_name("简体中文"), Some(TextLanguage::Chinese));
+        assert_eq!(TextLanguage::from_name("zh-CN"), Some(TextLanguage::Chinese));
+        assert_eq!(TextLanguage::from_name("English"), Some(TextLanguage::English));
+        assert_eq!(TextLanguage::from_name("Klingon"), None);
+
+        assert!(TextLanguage::Japanese.matches("人妻自宅エステサロン"));
+        assert!(!TextLanguage::Japanese.matches("人妻自宅美容院"));
+        assert!(TextLanguage::English.matches("A beautiful wife runs a salon"));
+        assert!(!TextLanguage::English.matches("美人妻が自宅で"));
+        assert!(TextLanguage::Korean.matches("한국어 제목"));
+        assert!(!TextLanguage::Chinese.matches("123 !?"));
+    }
+
+    #[tokio::test]
+    async fn test_skip_translation_for_target_language_text() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/chat/completions")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices":[{"message":{"content":"人妻自宅美容院"}}]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let translator = Translator::new(TranslationConfig {
+            provider: TranslationProvider::Custom(server.url()),
+            target_language: "中文".to_string(),
+            retry_count: 1,
+            ..Default::default()
+        })
+        .unwrap();
+
+        // 中文输入不调用 API
+        let translated = translator.translate_text("人妻自宅美容院").await.unwrap();
+        assert_eq!(translated, "人妻自宅美容院");
+
+        // 日语输入正常翻译
+        let translated = translator.translate_text("人妻自宅エステサロン").await.unwrap();
+        assert_eq!(translated, "人妻自宅美容院");
+
+        mock.assert_async().await;
+    }
+
+    #[test]
+    fn test_retry_delay_backoff_with_jitter() {
+        let translator = Translator::new(TranslationConfig {
+            retry_base_delay_ms: 1000,
+            retry_max_delay_ms: 5000,
+            retry_jitter: 0.2,
+            ..Default::default()
+        })
+        .unwrap();
+
+        // 无抖动时（随机数取中值）为纯指数退避，并受最大延迟限制
+        assert_eq!(translator.retry_delay(1, 0.5), Duration::from_millis(1000));
+        assert_eq!(translator.retry_delay(2, 0.5), Duration::from_millis(2000));
+        assert_eq!(translator.retry_delay(3, 0.5), Duration::from_millis(4000));
+        assert_eq!(translator.retry_delay(4, 0.5), Duration::from_millis(5000));
+        assert_eq!(translator.retry_delay(40, 0.5), Duration::from_millis(5000));
+
+        // 抖动边界
+        assert_eq!(translator.retry_delay(2, 0.0), Duration::from_millis(1600));
+        assert_eq!(translator.retry_delay(2, 1.0), Duration::from_millis(2400));
+
+        // 随机抖动始终落在 ±20% 范围内
+        for attempt in 1..=5 {
+            let base = translator.retry_delay(attempt, 0.5).as_millis();
+            let delay = translator.retry_delay(attempt, random_unit()).as_millis();
+            assert!(delay >= base * 8 / 10 && delay <= base * 12 / 10);
+        }
+    }
+
+    fn create_test_config(name: &str, translation_section: &str) -> AppConfig {
+        let test_config_content = format!(
+            r#"
+migrate_files = ["mp4"]
+ignored_id_pattern = []
+input_dir = "./input"
+output_dir = "./output"
+thread_limit = 1
+template_priority = ["javdb.yaml"]
+
+[translation]
+enabled = true
+{}
+"#,
+            translation_section
+        );
+
+        let config_path = std::env::temp_dir().join(name);
+        std::fs::write(&config_path, test_config_content).unwrap();
+
+        AppConfig::new(&config_path).unwrap()
+    }
+
+    async fn mock_translation_server(reply: &str) -> (mockito::ServerGuard, Translator) {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/chat/completions")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(format!(r#"{{"choices":[{{"message":{{"content":"{}"}}}}]}}"#, reply))
+            .create_async()
+            .await;
+
+        let translator = Translator::new(TranslationConfig {
+            provider: TranslationProvider::Custom(server.url()),
+            retry_count: 1,
+            ..Default::default()
+        })
+        .unwrap();
+
+        (server, translator)
+    }
+
+    #[tokio::test]
+    async fn test_translation_preserves_original_title() {
+        let (_server, translator) = mock_translation_server("翻译结果").await;
+        let config = create_test_config("test_translation_preserve.toml", "");
+
+        let mut movie_data = MovieNfoCrawler {
+            title: "人妻自宅エステサロン".to_string(),
+            ..Default::default()
+        };
+
+        translator.translate_movie_data(&mut movie_data, &config).await.unwrap();
+
+        assert_eq!(movie_data.title, "翻译结果");
+        assert_eq!(movie_data.original_title.as_deref(), Some("人妻自宅エステサロン"));
+
+        // 原始标题已存在时保持不变，也不会被翻译
+        let mut movie_data = MovieNfoCrawler {
+            title: "人妻自宅エステサロン".to_string(),
+            original_title: Some("Married Woman Home Salon".to_string()),
+            ..Default::default()
+        };
+
+        translator.translate_movie_data(&mut movie_data, &config).await.unwrap();
+
+        assert_eq!(movie_data.title, "翻译结果");
+        assert_eq!(movie_data.original_title.as_deref(), Some("Married Woman Home Salon"));
+
+        let xml = crate::nfo::NfoFormatter::format_to_xml(&crate::nfo::MovieNfo::from(movie_data));
+        assert!(xml.contains("<originaltitle>Married Woman Home Salon</originaltitle>"));
+    }
+
+    #[tokio::test]
+    async fn test_translation_preserve_original_title_option() {
+        let (_server, translator) = mock_translation_server("翻译结果").await;
+
+        let config = create_test_config("test_translation_preserve_on.toml", "preserve_original_title = true");
+        let mut movie_data = MovieNfoCrawler {
+            title: "人妻自宅エステサロン".to_string(),
+            original_title: Some(String::new()),
+            ..Default::default()
+        };
+
+        translator.translate_movie_data(&mut movie_data, &config).await.unwrap();
+
+        let xml = crate::nfo::NfoFormatter::format_to_xml(&crate::nfo::MovieNfo::from(movie_data));
+        assert!(xml.contains("<title>翻译结果</title>"));
+        assert!(xml.contains("<originaltitle>人妻自宅エステサロン</originaltitle>"));
+
+        let config = create_test_config("test_translation_preserve_off.toml", "preserve_original_title = false");
+        let mut movie_data = MovieNfoCrawler {
+            title: "人妻自宅エステサロン".to_string(),
+            ..Default::default()
+        };
+
+        translator.translate_movie_data(&mut movie_data, &config).await.unwrap();
+
+        assert_eq!(movie_data.title, "翻译结果");
+        assert_eq!(movie_data.original_title, None);
+    }
+
+    #[tokio::test]
+    async fn test_translation_fields_whitelist() {
+        let (_server, translator) = mock_translation_server("翻译结果").await;
+        let config = create_test_config("test_translation_fields.toml", r#"fields = ["plot"]"#);
+
+        let mut movie_data = MovieNfoCrawler {
+            title: "人妻自宅エステサロン".to_string(),
+            plot: "美人妻が自宅でエステサロンを営んでいる".to_string(),
+            tagline: "キャッチコピー".to_string(),
+            ..Default::default()
+        };
+
+        translator.translate_movie_data(&mut movie_data, &config).await.unwrap();
+
+        assert_eq!(movie_data.title, "人妻自宅エステサロン");
+        assert_eq!(movie_data.original_title, None);
+        assert_eq!(movie_data.plot, "翻译结果");
+        assert_eq!(movie_data.tagline, "キャッチコピー");
+    }
+
+    #[tokio::test]
+    async fn test_actor_name_policy_romaji() {
+        let translator = Translator::default();
+        let config = create_test_config(
+            "test_actor_name_policy_romaji.toml",
+            r#"actor_name_policy = "romaji""#,
+        );
+
+        let mut movie_data = MovieNfoCrawler {
+            actors: ["ミカミ ユア", "mikami yua", "三上悠亜", "さくら・まな"]
+                .map(|name| crate::nfo::Actor {
+                    name: name.to_string(),
+                    ..Default::default()
+                })
+                .to_vec(),
+            ..Default::default()
+        };
+
+        translator.translate_movie_data(&mut movie_data, &config).await.unwrap();
+
+        // 假名转为罗马字后与已有的罗马字写法合并，汉字名保持不变
+        let names: Vec<&str> = movie_data.actors.iter().map(|actor| actor.name.as_str()).collect();
+        assert_eq!(names, ["Mikami Yua", "三上悠亜", "Sakura Mana"]);
+    }
+
+    #[tokio::test]
+    async fn test_actor_name_policy_target_language() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/chat/completions")
+            .match_body(mockito::Matcher::Regex("三上悠亜".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices":[{"message":{"content":"Yua Mikami"}}]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let translator = Translator::new(TranslationConfig {
+            provider: TranslationProvider::Custom(server.url()),
+            target_language: "English".to_string(),
+            retry_count: 1,
+            no_translate_terms: vec!["河北彩花".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+        let config = create_test_config(
+            "test_actor_name_policy_target_language.toml",
+            r#"actor_name_policy = "target_language""#,
+        );
+
+        let mut movie_data = MovieNfoCrawler {
+            actors: ["三上悠亜", "Yua Mikami", "河北彩花"]
+                .map(|name| crate::nfo::Actor {
+                    name: name.to_string(),
+                    ..Default::default()
+                })
+                .to_vec(),
+            ..Default::default()
+        };
+
+        translator.translate_movie_data(&mut movie_data, &config).await.unwrap();
+
+        // 已是目标语言的名字与术语表中的名字不请求翻译，翻译后的同一演员合并
+        mock.assert_async().await;
+        let names: Vec<&str> = movie_data.actors.iter().map(|actor| actor.name.as_str()).collect();
+        assert_eq!(names, ["Yua Mikami", "河北彩花"]);
+
+        // 默认保持原名
+        let config = create_test_config("test_actor_name_policy_keep.toml", "");
+        let mut actors = vec![crate::nfo::Actor {
+            name: "三上悠亜".to_string(),
+            ..Default::default()
+        }];
+        translator
+            .apply_actor_name_policy(&mut actors, config.get_actor_name_policy())
+            .await;
+        assert_eq!(actors[0].name, "三上悠亜");
+    }
+
+    #[tokio::test]
+    async fn test_no_translate_terms_preserved() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/chat/completions")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::Regex(r"\[\[T0\]\].*\[\[T1\]\]".to_string()),
+                mockito::Matcher::Regex("^[^S]*$".to_string()),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices":[{"message":{"content":"[[T0]] 出品，[[T1]] 主演的新作"}}]}"#)
+            .create_async()
+            .await;
+
+        let translator = Translator::new(TranslationConfig {
+            provider: TranslationProvider::Custom(server.url()),
+            retry_count: 1,
+            no_translate_terms: vec!["S1 NO.1 STYLE".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+
+        let translated = translator
+            .translate_text_with_terms("S1 NO.1 STYLE の新作、三上悠亜 主演", &["三上悠亜".to_string()])
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(translated, "S1 NO.1 STYLE 出品，三上悠亜 主演的新作");
+
+        // 全部由术语组成的文本不调用 API
+        let translated = translator.translate_text("S1 NO.1 STYLE").await.unwrap();
+        assert_eq!(translated, "S1 NO.1 STYLE");
+    }
+
+    #[tokio::test]
+    async fn test_streaming_response() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/chat/completions")
+            .match_body(mockito::Matcher::PartialJsonString(r#"{"stream":true}"#.to_string()))
+            .with_status(200)
+            .with_header("content-type", "text/event-stream")
+            .with_body(concat!(
+                "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n",
+                "data: {\"choices\":[{\"delta\":{\"content\":\"人妻\"}}]}\n\n",
+                ": keep-alive\n\n",
+                "data: {\"choices\":[{\"delta\":{\"content\":\"自宅\"}}]}\n\n",
+                "data: {\"choices\":[{\"delta\":{\"content\":\"美容沙龙\"}}]}\n\n",
+                "data: [DONE]\n\n",
+            ))
+            .create_async()
+            .await;
+
+        let translator = Translator::new(TranslationConfig {
+            provider: TranslationProvider::Custom(server.url()),
+            retry_count: 1,
+            stream: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let translated = translator.translate_text("人妻自宅エステサロン").await.unwrap();
+        assert_eq!(translated, "人妻自宅美容沙龙");
+    }
+
+    #[tokio::test]
+    async fn test_streaming_response_split_character() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/chat/completions")
+            .with_status(200)
+            .with_header("content-type", "text/event-stream")
+            .with_chunked_body(|writer| {
+                let body = concat!(
+                    "data: {\"choices\":[{\"delta\":{\"content\":\"人妻\"}}]}\n\n",
+                    "data: [DONE]\n\n",
+                )
+                .as_bytes();
+                // 在 `人` 的三个字节中间切开，分两个分片发送
+                let split = body.iter().position(|&byte| byte >= 0x80).unwrap() + 1;
+                writer.write_all(&body[..split])?;
+                writer.flush()?;
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                writer.write_all(&body[split..])
+            })
+            .create_async()
+            .await;
+
+        let translator = Translator::new(TranslationConfig {
+            provider: TranslationProvider::Custom(server.url()),
+            retry_count: 1,
+            stream: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let translated = translator.translate_text("ひとづま").await.unwrap();
+        mock.assert_async().await;
+        assert_eq!(translated, "人妻");
+    }
+
+    #[tokio::test]
+    async fn test_ollama_request_shaping() {
+        let mut server = mockito::Server::new_async().await;
+        let completion = server
+            .mock("POST", "/v1/chat/completions")
+            .match_header("authorization", mockito::Matcher::Missing)
+            .match_header("x-proxy-token", "secret")
+            .match_body(mockito::Matcher::PartialJsonString(r#"{"model":"qwen2.5:7b"}"#.to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices":[{"message":{"content":"人妻自宅美容院"}}]}"#)
+            .create_async()
+            .await;
+        let tags = server
+            .mock("GET", "/api/tags")
+            .match_header("authorization", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_body(r#"{"models":[{"name":"qwen2.5:7b"}]}"#)
+            .create_async()
+            .await;
+
+        let translator = Translator::new(TranslationConfig {
+            provider: TranslationProvider::Ollama,
+            model: "qwen2.5:7b".to_string(),
+            base_url: Some(format!("{}/v1", server.url())),
+            extra_headers: [("X-Proxy-Token".to_string(), "secret".to_string())].into_iter().collect(),
+            retry_count: 1,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let translated = translator.translate_text("人妻自宅エステサロン").await.unwrap();
+        assert_eq!(translated, "人妻自宅美容院");
+        translator.test_connection().await.unwrap();
+
+        completion.assert_async().await;
+        tags.assert_async().await;
+
+        // Ollama 没有默认模型
+        let result = Translator::new(TranslationConfig {
+            provider: TranslationProvider::Ollama,
+            ..Default::default()
+        });
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ollama_error_body() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/v1/chat/completions")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error":"model \"llama3\" not found, try pulling it first"}"#)
+            .create_async()
+            .await;
+
+        let translator = Translator::new(TranslationConfig {
+            provider: TranslationProvider::Ollama,
+            model: "llama3".to_string(),
+            base_url: Some(format!("{}/v1", server.url())),
+            retry_count: 1,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let error = translator.translate_text("人妻自宅エステサロン").await.unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            r#"API 错误: model "llama3" not found, try pulling it first"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_openai_request_shaping() {
+        let mut server = mockito::Server::new_async().await;
+        let completion = server
+            .mock("POST", "/chat/completions")
+            .match_header("authorization", "Bearer sk-test")
+            .match_body(mockito::Matcher::PartialJsonString(r#"{"model":"gpt-3.5-turbo"}"#.to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices":[{"message":{"content":"人妻自宅美容院"}}]}"#)
+            .create_async()
+            .await;
+        let models = server
+            .mock("GET", "/models")
+            .with_status(401)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error":{"message":"Incorrect API key provided","type":"invalid_request_error"}}"#)
+            .create_async()
+            .await;
+
+        let translator = Translator::new(TranslationConfig {
+            provider: TranslationProvider::OpenAI,
+            api_key: Some("sk-test".to_string()),
+            base_url: Some(server.url()),
+            retry_count: 1,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let translated = translator.translate_text("人妻自宅エステサロン").await.unwrap();
+        assert_eq!(translated, "人妻自宅美容院");
+
+        let error = translator.test_connection().await.unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "API 错误 401 Unauthorized: Incorrect API key provided"
+        );
+
+        completion.assert_async().await;
+        models.assert_async().await;
     }
 
     #[test]
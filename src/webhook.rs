@@ -0,0 +1,183 @@
+// 处理结果通知：每个文件整理完成、跳过或失败后向配置的地址发送 JSON
+
+use std::{path::Path, time::Duration};
+
+use crate::{config::WebhookConfig, pipeline::ProcessOutcome};
+
+/// 单个文件的处理结果通知
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessEvent {
+    /// 影片ID，无法从文件名提取时为空
+    pub movie_id: String,
+    /// 处理的文件
+    pub file: String,
+    /// `organized`、`skipped` 或 `failed`
+    pub outcome: &'static str,
+    /// 整理后的视频文件、跳过原因或错误信息
+    pub message: String,
+}
+
+impl ProcessEvent {
+    /// 由处理结果生成通知，`movie_id` 用于跳过和失败时没有影片ID的情况
+    pub fn new(file_path: &Path, movie_id: &str, result: &anyhow::Result<ProcessOutcome>) -> Self {
+        let (movie_id, outcome, message) = match result {
+            Ok(ProcessOutcome::Organized { movie_id, video_path, .. }) => (
+                movie_id.clone(),
+                "organized",
+                video_path.display().to_string(),
+            ),
+            Ok(ProcessOutcome::Skipped { reason }) => (movie_id.to_string(), "skipped", reason.clone()),
+            Err(e) => (movie_id.to_string(), "failed", format!("{:#}", e)),
+        };
+        Self {
+            movie_id,
+            file: file_path.display().to_string(),
+            outcome,
+            message,
+        }
+    }
+
+    /// 按模板生成请求体，模板中的 `{id}`、`{file}`、`{outcome}`、`{message}` 替换为 JSON 转义后的值；
+    /// 未配置模板时发送包含这些字段的 JSON 对象
+    pub fn render(&self, template: Option<&str>) -> String {
+        let Some(template) = template else {
+            return serde_json::json!({
+                "id": self.movie_id,
+                "file": self.file,
+                "outcome": self.outcome,
+                "message": self.message,
+            })
+            .to_string();
+        };
+
+        [
+            ("{id}", self.movie_id.as_str()),
+            ("{file}", self.file.as_str()),
+            ("{outcome}", self.outcome),
+            ("{message}", self.message.as_str()),
+        ]
+        .into_iter()
+        .fold(template.to_string(), |body, (placeholder, value)| {
+            body.replace(placeholder, &json_escape(value))
+        })
+    }
+}
+
+/// 向配置的地址发送处理结果
+#[derive(Debug)]
+pub struct Webhook {
+    client: reqwest::Client,
+    url: String,
+    body: Option<String>,
+}
+
+impl Webhook {
+    /// 根据配置创建通知，未配置地址或创建客户端失败时返回 None
+    pub fn from_config(config: &WebhookConfig) -> Option<Self> {
+        let url = config.url.clone().filter(|url| !url.trim().is_empty())?;
+        let client = match reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout))
+            .build()
+        {
+            Ok(client) => client,
+            Err(e) => {
+                log::warn!("创建通知客户端失败: {}，不发送处理结果通知", e);
+                return None;
+            }
+        };
+        Some(Self {
+            client,
+            url,
+            body: config.body.clone(),
+        })
+    }
+
+    /// 发送通知，失败时只记录警告
+    pub async fn notify(&self, event: &ProcessEvent) {
+        let result = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(event.render(self.body.as_deref()))
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status);
+        match result {
+            Ok(_) => log::debug!("已发送文件 {} 的处理结果通知", event.file),
+            Err(e) => log::warn!("发送文件 {} 的处理结果通知失败: {}", event.file, e),
+        }
+    }
+}
+
+/// JSON 字符串转义，不含两侧引号
+fn json_escape(value: &str) -> String {
+    let quoted = serde_json::Value::from(value).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn organized_event() -> ProcessEvent {
+        let result = Ok(ProcessOutcome::Organized {
+            movie_id: "HOOK-001".to_string(),
+            video_path: PathBuf::from("/output/HOOK-001/HOOK-001.mp4"),
+            nfo_paths: vec![PathBuf::from("/output/HOOK-001/HOOK-001.nfo")],
+        });
+        ProcessEvent::new(Path::new("/input/HOOK-001.mp4"), "HOOK-001", &result)
+    }
+
+    #[test]
+    fn test_render_body() {
+        let event = organized_event();
+        let body: serde_json::Value = serde_json::from_str(&event.render(None)).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "id": "HOOK-001",
+                "file": "/input/HOOK-001.mp4",
+                "outcome": "organized",
+                "message": "/output/HOOK-001/HOOK-001.mp4",
+            })
+        );
+
+        // 模板中的值按 JSON 转义
+        let failed = ProcessEvent::new(
+            Path::new("/input/HOOK-002.mp4"),
+            "HOOK-002",
+            &Err(anyhow::anyhow!("文件 \"HOOK-002\" 不存在")),
+        );
+        let body = failed.render(Some(r#"{"text": "{id} {outcome}: {message}"}"#));
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["text"], "HOOK-002 failed: 文件 \"HOOK-002\" 不存在");
+    }
+
+    #[tokio::test]
+    async fn test_webhook_posts_event() {
+        let mut server = mockito::Server::new_async().await;
+        let hook = server
+            .mock("POST", "/hook")
+            .match_header("content-type", "application/json")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "id": "HOOK-001",
+                "file": "/input/HOOK-001.mp4",
+                "outcome": "organized",
+                "message": "/output/HOOK-001/HOOK-001.mp4",
+            })))
+            .with_status(200)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let webhook = Webhook::from_config(&WebhookConfig {
+            url: Some(format!("{}/hook", server.url())),
+            ..Default::default()
+        })
+        .unwrap();
+        webhook.notify(&organized_event()).await;
+
+        hook.assert_async().await;
+    }
+}